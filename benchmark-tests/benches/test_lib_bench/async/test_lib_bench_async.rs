@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::hint::black_box;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use iai_callgrind::{library_benchmark, library_benchmark_group, main, Executor, LocalExecutor};
+
+/// A future which is pending until it was woken up by another thread
+struct WokenByThread(Option<Arc<AtomicBool>>);
+
+struct WrappingExecutor;
+
+impl Future for WokenByThread {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &self.0 {
+            Some(ready) if ready.load(Ordering::Acquire) => Poll::Ready(()),
+            Some(_) => Poll::Pending,
+            None => {
+                let ready = Arc::new(AtomicBool::new(false));
+                let waker = cx.waker().clone();
+                let thread_ready = Arc::clone(&ready);
+                std::thread::spawn(move || {
+                    thread_ready.store(true, Ordering::Release);
+                    waker.wake();
+                });
+                self.0 = Some(ready);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Executor for WrappingExecutor {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        LocalExecutor.block_on(future)
+    }
+}
+
+async fn fibonacci(n: u64) -> u64 {
+    match n {
+        0 | 1 => 1,
+        n => Box::pin(fibonacci(n - 1)).await + Box::pin(fibonacci(n - 2)).await,
+    }
+}
+
+fn setup_len(len: u64) -> Vec<u64> {
+    (0..len).collect()
+}
+
+#[library_benchmark(async)]
+async fn bench_standalone() -> u64 {
+    black_box(fibonacci(black_box(10)).await)
+}
+
+#[library_benchmark(async)]
+#[bench::short(10)]
+#[bench::long(20)]
+async fn bench_fibonacci(n: u64) -> u64 {
+    black_box(fibonacci(n).await)
+}
+
+#[library_benchmark(async, executor = WrappingExecutor, setup = setup_len)]
+#[benches::with_setup(10, 100)]
+async fn bench_with_executor(values: Vec<u64>) -> u64 {
+    black_box(values.iter().sum())
+}
+
+#[library_benchmark(async)]
+#[bench::woken()]
+async fn bench_pending() {
+    WokenByThread(None).await;
+}
+
+library_benchmark_group!(
+    name = async_group;
+    benchmarks = bench_standalone, bench_fibonacci, bench_with_executor, bench_pending
+);
+
+main!(library_benchmark_groups = async_group);
@@ -7,7 +7,7 @@ use proc_macro_error2::abort;
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 use syn::parse::Parse;
 use syn::punctuated::Punctuated;
-use syn::{parse2, parse_quote, Attribute, Expr, Ident, ItemFn, MetaNameValue, Token};
+use syn::{parse2, parse_quote, Attribute, Expr, Ident, ItemFn, MetaNameValue, Signature, Token};
 
 use crate::common::{self, format_ident, truncate_str_utf8, BenchesArgs, File};
 use crate::{defaults, CargoMetadata};
@@ -317,7 +317,10 @@ impl Bench {
         }
     }
 
-    fn render_as_member(&self) -> TokenStream {
+    /// Render the `InternalMacroBinBench` of this benchmark
+    ///
+    /// The names of the parameters are taken from the `signature` of the benchmark function.
+    fn render_as_member(&self, signature: &Signature) -> TokenStream {
         let id = &self.id;
         let id_display = self.id.to_string();
         let config = self.config.render_as_member(Some(id));
@@ -334,6 +337,7 @@ impl Bench {
                         args_display: Some(#args_display),
                         func: iai_callgrind::__internal::InternalBinFunctionKind::Iter(#id),
                         config: #config,
+                        parameters: &[],
                         setup: #setup,
                         teardown: #teardown,
                     }
@@ -342,6 +346,7 @@ impl Bench {
             BenchMode::Args(args) => {
                 let args_string = self.setup.to_string_with_args(args);
                 let args_display = truncate_str_utf8(&args_string, defaults::MAX_BYTES_ARGS);
+                let parameters = args.render_as_parameters(Some(signature));
                 let setup = self.setup.render_as_member(Some(id), None);
                 let teardown = self.teardown.render_as_member(Some(id), None);
                 quote! {
@@ -350,6 +355,7 @@ impl Bench {
                         args_display: Some(#args_display),
                         func: iai_callgrind::__internal::InternalBinFunctionKind::Default(#id),
                         config: #config,
+                        parameters: #parameters,
                         setup: #setup,
                         teardown: #teardown,
                     }
@@ -510,6 +516,7 @@ impl BinaryBenchmark {
                         id_display: None,
                         args_display: None,
                         func: iai_callgrind::__internal::InternalBinFunctionKind::Default(#ident),
+                        parameters: &[],
                         setup: #setup_member,
                         teardown: #teardown_member,
                         config: None
@@ -537,7 +544,7 @@ impl BinaryBenchmark {
         let mut bin_benches = vec![];
         for bench in self.benches {
             funcs.append_all(bench.render_as_code(callee));
-            bin_benches.push(bench.render_as_member());
+            bin_benches.push(bench.render_as_member(&item_fn.sig));
         }
 
         let config = self.config.render_as_code();
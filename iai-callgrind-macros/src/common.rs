@@ -6,12 +6,13 @@ use std::path::PathBuf;
 
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error2::{abort, emit_error};
-use quote::{format_ident, quote_spanned, ToTokens, TokenStreamExt};
+use quote::{format_ident, quote, quote_spanned, ToTokens, TokenStreamExt};
+use syn::ext::IdentExt;
 use syn::parse::Parse;
 use syn::spanned::Spanned;
 use syn::{
-    parse2, parse_quote_spanned, Expr, ExprArray, ExprPath, Ident, LitStr, MetaList, MetaNameValue,
-    Pat, Token,
+    parse2, parse_quote_spanned, Expr, ExprArray, ExprPath, FnArg, Ident, Lit, LitStr, MetaList,
+    MetaNameValue, Pat, Signature, Token, UnOp,
 };
 
 use crate::CargoMetadata;
@@ -124,6 +125,31 @@ impl Args {
         Ok(())
     }
 
+    /// Render the `args` as the structured parameters of the benchmark
+    ///
+    /// The names are the names of the parameters in the `signature` of the benchmark function. If
+    /// there's no `signature` (for example if the `args` are passed to a `setup` function) or the
+    /// parameter is not a simple identifier, the positional name `argN` is used instead.
+    pub fn render_as_parameters(&self, signature: Option<&Signature>) -> TokenStream {
+        let Some((_, exprs)) = self.0.as_ref() else {
+            return quote! { &[] };
+        };
+
+        let parameters = exprs.iter().enumerate().map(|(index, expr)| {
+            let name = signature
+                .and_then(|signature| signature.inputs.iter().nth(index))
+                .and_then(|fn_arg| match fn_arg {
+                    FnArg::Typed(pat_type) => pattern_to_parameter_name(&pat_type.pat),
+                    FnArg::Receiver(_) => None,
+                })
+                .unwrap_or_else(|| format!("arg{index}"));
+            let value = expression_to_parameter_value(expr);
+            quote! { (#name, iai_callgrind::__internal::InternalMacroParameterValue::#value) }
+        });
+
+        quote! { &[#(#parameters),*] }
+    }
+
     pub fn to_tokens_without_black_box(&self) -> TokenStream {
         if let Some((span, exprs)) = self.0.as_ref() {
             quote_spanned! { *span => #(#exprs),* }
@@ -533,6 +559,66 @@ pub fn format_ident(prefix: &str, ident: Option<&Ident>) -> Ident {
     }
 }
 
+/// Render the variant of the `InternalMacroParameterValue` for an expression of the `args`
+///
+/// Literal booleans, integers and floats keep their type. String and character literals are
+/// unquoted and all other expressions are stored as they appear in the source code.
+pub fn expression_to_parameter_value(expr: &Expr) -> TokenStream {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Bool(lit) => {
+                let value = lit.value;
+                return quote! { Bool(#value) };
+            }
+            Lit::Int(lit) => {
+                if let Ok(value) = lit.base10_parse::<i64>() {
+                    return quote! { Int(#value) };
+                }
+            }
+            Lit::Float(lit) => {
+                if let Some(value) = lit.base10_parse::<f64>().ok().filter(|f| f.is_finite()) {
+                    return quote! { Float(#value) };
+                }
+            }
+            Lit::Str(lit) => {
+                let value = lit.value();
+                return quote! { String(#value) };
+            }
+            Lit::Char(lit) => {
+                let value = lit.value().to_string();
+                return quote! { String(#value) };
+            }
+            _ => {}
+        },
+        Expr::Unary(expr_unary) if matches!(expr_unary.op, UnOp::Neg(_)) => {
+            if let Expr::Lit(expr_lit) = &*expr_unary.expr {
+                match &expr_lit.lit {
+                    Lit::Int(lit) => {
+                        if let Ok(value) = format!("-{}", lit.base10_digits()).parse::<i64>() {
+                            return quote! { Int(#value) };
+                        }
+                    }
+                    Lit::Float(lit) => {
+                        if let Some(value) =
+                            lit.base10_parse::<f64>().ok().filter(|f| f.is_finite())
+                        {
+                            let value = -value;
+                            return quote! { Float(#value) };
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Expr::Group(expr_group) => return expression_to_parameter_value(&expr_group.expr),
+        Expr::Paren(expr_paren) => return expression_to_parameter_value(&expr_paren.expr),
+        _ => {}
+    }
+
+    let value = expr.to_token_stream().to_string();
+    quote! { String(#value) }
+}
+
 pub fn format_indexed_ident(ident: &Ident, index: usize) -> Ident {
     format_ident!("{ident}_{index}")
 }
@@ -550,6 +636,17 @@ pub fn truncate_str_utf8(string: &str, len: usize) -> &str {
     }
 }
 
+/// Return the name of a parameter in the signature of the benchmark function if it is a simple
+/// identifier like `value`, `mut value` or `&value`
+pub fn pattern_to_parameter_name(pat: &Pat) -> Option<String> {
+    match pat {
+        Pat::Ident(pat_ident) => Some(pat_ident.ident.unraw().to_string()),
+        Pat::Paren(pat_paren) => pattern_to_parameter_name(&pat_paren.pat),
+        Pat::Reference(pat_reference) => pattern_to_parameter_name(&pat_reference.pat),
+        _ => None,
+    }
+}
+
 pub fn pattern_to_single_function_ident(
     pat: &Pat,
     elem_ident: &Ident,
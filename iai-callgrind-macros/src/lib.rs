@@ -34,7 +34,11 @@ impl CargoMetadata {
 /// use in the `library_benchmark_groups!` macro.
 ///
 /// This attribute accepts the following parameters:
+/// * `async`: Required if the benchmark function is an `async fn`. The future is run to completion
+///   by the `executor` and only the polls of the future are attributed to the benchmark.
 /// * `config`: Accepts a `LibraryBenchmarkConfig`
+/// * `executor`: Accepts an expression evaluating to an `iai_callgrind::Executor`. Only valid
+///   together with `async`. The default is the single-threaded `iai_callgrind::LocalExecutor`.
 /// * `setup`: A global setup function which is applied to all following [`#[bench]`][bench] and
 ///   [`#[benches]`][benches] attributes if not overwritten by a `setup` parameter of these
 ///   attributes.
@@ -51,12 +55,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -118,12 +129,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -145,9 +163,15 @@ impl CargoMetadata {
 ///
 /// The `#[benches]` attribute lets you define multiple benchmarks in one go. This attribute accepts
 /// the same parameters as the [`#[bench]`][bench] attribute: `args`, `config`, `setup` and
-/// `teardown` and additionally the `file` parameter. In contrast to the `args` parameter in
-/// [`#[bench]`][bench], `args` takes an array of arguments. The id (`#[benches::id(*/ parameters
-/// */)]`) is getting suffixed with the index of the current element of the `args` array.
+/// `teardown` and additionally the `file`, `iter` and `size` parameters. In contrast to the `args`
+/// parameter in [`#[bench]`][bench], `args` takes an array of arguments. The id
+/// (`#[benches::id(*/ parameters */)]`) is getting suffixed with the index of the current element
+/// of the `args` array.
+///
+/// The `size` parameter takes a closure which receives the same arguments as the benchmark function
+/// (or the `setup` function if present) and returns the size of the input. The runner fits the
+/// metrics of all benchmarks of the benchmark function with a size to a complexity class like
+/// `O(n)`.
 ///
 /// ```rust
 /// # use iai_callgrind_macros::library_benchmark;
@@ -159,12 +183,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -202,12 +233,19 @@ impl CargoMetadata {
 /// # pub fn stop_instrumentation() {}
 /// # }}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -252,12 +290,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -279,12 +324,19 @@ impl CargoMetadata {
 /// # mod iai_callgrind {
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -312,12 +364,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -349,12 +408,19 @@ impl CargoMetadata {
 /// # }}
 /// # pub struct LibraryBenchmarkConfig {}
 /// # pub mod __internal {
+/// # pub mod lib_bench {
+/// # pub fn start_instrumentation() {}
+/// # pub fn stop_instrumentation() {}
+/// # }
 /// # pub enum InternalLibFunctionKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroLibBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalLibFunctionKind,
-/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>
+/// #   pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+/// #   pub size: Option<fn(Option<usize>) -> u64>,
 /// # }
 /// # pub struct InternalLibraryBenchmarkConfig {}
 /// # }
@@ -471,11 +537,13 @@ pub fn library_benchmark(args: TokenStream, input: TokenStream) -> TokenStream {
 /// # use crate::iai_callgrind;
 /// # pub enum InternalBinFunctionKind { None, Default(fn() -> iai_callgrind::Command) }
 /// # pub enum InternalBinAssistantKind { None, Default(fn()) }
+/// # pub enum InternalMacroParameterValue { Bool(bool), Int(i64), Float(f64), String(&'static str) }
 /// # pub struct InternalMacroBinBench {
 /// #   pub id_display: Option<&'static str>,
 /// #   pub args_display: Option<&'static str>,
 /// #   pub func: InternalBinFunctionKind,
 /// #   pub config: Option<fn() -> InternalBinaryBenchmarkConfig>,
+/// #   pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
 /// #   pub setup: InternalBinAssistantKind,
 /// #   pub teardown: InternalBinAssistantKind,
 /// # }
@@ -2,7 +2,7 @@ use std::ops::Deref;
 
 use derive_more::{Deref as DerefDerive, DerefMut as DerefMutDerive};
 use proc_macro2::TokenStream;
-use proc_macro_error2::abort;
+use proc_macro_error2::{abort, emit_error};
 use quote::{format_ident, quote, quote_spanned, ToTokens, TokenStreamExt};
 use syn::parse::Parse;
 use syn::punctuated::Punctuated;
@@ -17,6 +17,15 @@ use crate::common::{
 };
 use crate::{defaults, CargoMetadata};
 
+/// The `async` and `executor` parameters of the `#[library_benchmark]` attribute
+///
+/// If not `async`, the `executor` is always `None`.
+#[derive(Debug, Default, Clone)]
+struct Async {
+    enabled: bool,
+    executor: Option<Expr>,
+}
+
 /// The benchmark mode for `iter` and any another option in the bench attributes
 #[derive(Debug)]
 enum BenchMode {
@@ -37,6 +46,7 @@ struct Bench {
     id: Ident,
     mode: BenchMode,
     setup: Setup,
+    size: Size,
     teardown: Teardown,
 }
 
@@ -52,6 +62,7 @@ struct Iter(Expr);
 /// This is the counterpart to the `#[library_benchmark]` attribute.
 #[derive(Debug, Default)]
 struct LibraryBenchmark {
+    asyncness: Async,
     benches: Vec<Bench>,
     config: LibraryBenchmarkConfig,
     setup: Setup,
@@ -71,6 +82,14 @@ struct LibraryBenchmarkConfig(common::BenchConfig);
 #[derive(Debug, Default, Clone, DerefDerive, DerefMutDerive)]
 struct Setup(common::Setup);
 
+/// The `size` parameter of the `#[benches]` attribute
+///
+/// A closure which takes the arguments of a benchmark (or the element of the `iter` parameter) and
+/// returns the size of the input. The runner uses the sizes to fit the complexity of the benchmark
+/// function.
+#[derive(Debug, Default, Clone)]
+struct Size(Option<Expr>);
+
 #[derive(Debug, Default, Clone, DerefDerive, DerefMutDerive)]
 struct Teardown(common::Teardown);
 
@@ -80,6 +99,85 @@ impl ToTokens for Args {
     }
 }
 
+impl Async {
+    fn parse_pair(&mut self, pair: &MetaNameValue) {
+        if self.executor.is_none() {
+            self.executor = Some(pair.value.clone());
+        } else {
+            abort!(
+                pair, "Duplicate argument: `executor`";
+                help = "`executor` is allowed only once"
+            );
+        }
+    }
+
+    /// Verify the `async` parameters against the asyncness of the benchmark function
+    fn verify(&self, item_fn: &ItemFn) {
+        match (self.enabled, &item_fn.sig.asyncness) {
+            (true, None) => abort!(
+                item_fn.sig, "The `async` argument requires an `async fn`";
+                help = "Declare the benchmark function as `async fn`"
+            ),
+            (false, Some(asyncness)) => abort!(
+                asyncness, "An `async fn` requires the `async` argument";
+                help = "Use `#[library_benchmark(async)]`"
+            ),
+            _ => {}
+        }
+        if let (false, Some(executor)) = (self.enabled, &self.executor) {
+            abort!(
+                executor, "The `executor` argument requires the `async` argument";
+                help = "Use `#[library_benchmark(async, executor = ...)]`"
+            );
+        }
+    }
+
+    /// Render the call of the benchmark function (the `Callee`) with the `pats` as arguments
+    ///
+    /// If `async`, the future returned by the benchmark function is driven to completion by the
+    /// executor. The future is polled by the poll function in the `__iai_callgrind_wrapper_mod`,
+    /// so the costs of the executor itself are not attributed to the benchmark.
+    fn render_call(&self, callee_ident: &Ident, pats: &[Pat]) -> TokenStream {
+        if self.enabled {
+            let executor = self.executor.as_ref().map_or_else(
+                || quote! { iai_callgrind::LocalExecutor },
+                ToTokens::to_token_stream,
+            );
+            quote_spanned! { callee_ident.span() =>
+                std::hint::black_box(
+                    iai_callgrind::__internal::block_on(
+                        &#executor,
+                        __iai_callgrind_wrapper_mod::#callee_ident(#(#pats),*),
+                        __iai_callgrind_wrapper_mod::__iai_callgrind_poll
+                    )
+                )
+            }
+        } else {
+            quote_spanned! { callee_ident.span() =>
+                std::hint::black_box(
+                    __iai_callgrind_wrapper_mod::#callee_ident(#(#pats),*)
+                )
+            }
+        }
+    }
+
+    /// Render the poll function of the `__iai_callgrind_wrapper_mod` if `async`
+    fn render_poll_fn(&self) -> TokenStream {
+        if self.enabled {
+            let block = render_poll_block();
+            quote! {
+                #[inline(never)]
+                pub(super) fn __iai_callgrind_poll<F: std::future::Future>(
+                    __future: std::pin::Pin<&mut F>,
+                    __cx: &mut std::task::Context<'_>
+                ) -> std::task::Poll<F::Output> #block
+            }
+        } else {
+            TokenStream::new()
+        }
+    }
+}
+
 impl Bench {
     fn parse_bench_attribute(
         item_fn: &ItemFn,
@@ -129,6 +227,7 @@ impl Bench {
             mode: BenchMode::Args(args),
             config,
             setup,
+            size: Size::default(),
             teardown,
         })
     }
@@ -150,6 +249,7 @@ impl Bench {
         let mut args = BenchesArgs::default();
         let mut file = File::default();
         let mut iter = common::Iter::default();
+        let mut size = Size::default();
 
         if let Ok(pairs) =
             meta.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
@@ -167,10 +267,12 @@ impl Bench {
                     file.parse_pair(&pair)?;
                 } else if pair.path.is_ident("iter") {
                     iter.parse_pair(&pair);
+                } else if pair.path.is_ident("size") {
+                    size.parse_pair(&pair);
                 } else {
                     abort!(
                         pair, "Invalid argument: {}", pair.path.require_ident()?;
-                        help = "Valid arguments are: `args`, `file`, `iter`, `config`, `setup`, `teardown`"
+                        help = "Valid arguments are: `args`, `file`, `iter`, `size`, `config`, `setup`, `teardown`"
                     );
                 }
             }
@@ -197,6 +299,7 @@ impl Bench {
             mode: b.mode.into(),
             config: config.clone(),
             setup: setup.clone(),
+            size: size.clone(),
             teardown: teardown.clone(),
         })
         .collect();
@@ -205,7 +308,7 @@ impl Bench {
     }
 
     #[allow(clippy::too_many_lines)]
-    fn render_as_code(&self, callee: &Callee) -> TokenStream {
+    fn render_as_code(&self, callee: &Callee, asyncness: &Async) -> TokenStream {
         let bench_id = &self.id;
         let elem_ident = format_ident!("__elem");
         let run_func_id = format_ident("__run", Some(bench_id));
@@ -224,11 +327,8 @@ impl Bench {
                 let (iter_count, iter_elem) = iter.render_as_code(&self.setup);
 
                 let (bench_id_func, pats) = callee.to_caller_signature(&elem_ident, bench_id);
-                let call_bench_func = quote_spanned! { callee_ident.span() =>
-                    std::hint::black_box(
-                        __iai_callgrind_wrapper_mod::#callee_ident(#(#pats),*)
-                    )
-                };
+                let call_bench_func =
+                    render_instrumented_call(&asyncness.render_call(callee_ident, &pats));
 
                 let call_bench_id = self
                     .teardown
@@ -276,11 +376,8 @@ impl Bench {
                 };
 
                 let (bench_id_func, pats) = callee.to_caller_signature(&elem_ident, bench_id);
-                let call_bench_func = quote_spanned! { callee_ident.span() =>
-                        std::hint::black_box(
-                            __iai_callgrind_wrapper_mod::#callee_ident(#(#pats),*)
-                        )
-                };
+                let call_bench_func =
+                    render_instrumented_call(&asyncness.render_call(callee_ident, &pats));
 
                 quote!(
                    #[inline(never)]
@@ -298,16 +395,23 @@ impl Bench {
         };
 
         let config = self.config.render_as_code(bench_id);
+        let size = self.size.render_as_code(bench_id, &self.mode);
         quote! {
             #config
+            #size
             #func
         }
     }
 
-    fn render_as_member(&self) -> TokenStream {
+    /// Render the `InternalMacroLibBench` of this benchmark
+    ///
+    /// The `args` are passed to the `setup` function if present, so the names of the parameters
+    /// are taken from the `callee` only if there's no `setup` function.
+    fn render_as_member(&self, callee: &Callee) -> TokenStream {
         let id = &self.id;
         let id_display = self.id.to_string();
         let config = self.config.render_as_member(id);
+        let size = self.size.render_as_member(id);
         let run_id = format_ident("__run", Some(id));
 
         match &self.mode {
@@ -319,19 +423,25 @@ impl Bench {
                         id_display: Some(#id_display),
                         args_display: Some(#args_display),
                         func: iai_callgrind::__internal::InternalLibFunctionKind::Iter(#run_id),
-                        config: #config
+                        config: #config,
+                        parameters: &[],
+                        size: #size
                     }
                 }
             }
             BenchMode::Args(args) => {
                 let args_string = self.setup.to_string_with_args(args);
                 let args_display = truncate_str_utf8(&args_string, defaults::MAX_BYTES_ARGS);
+                let parameters =
+                    args.render_as_parameters((!self.setup.is_some()).then_some(callee.0));
                 quote! {
                     iai_callgrind::__internal::InternalMacroLibBench {
                         id_display: Some(#id_display),
                         args_display: Some(#args_display),
                         func: iai_callgrind::__internal::InternalLibFunctionKind::Default(#run_id),
-                        config: #config
+                        config: #config,
+                        parameters: #parameters,
+                        size: #size
                     }
                 }
             }
@@ -383,7 +493,8 @@ impl Callee<'_> {
     ///
     /// All elements with multiple inputs like tuples, structs, tuple structs, ... have a single
     /// ident in the signature. The returned patterns contain the correctly named identifiers, so
-    /// they can be used as inputs for a function call to the `Callee` function.
+    /// they can be used as inputs for a function call to the `Callee` function. The caller is
+    /// always synchronous, even if the `Callee` is an `async fn`.
     fn to_caller_signature(&self, elem_ident: &Ident, bench_id: &Ident) -> (Signature, Vec<Pat>) {
         let inputs = self
             .0
@@ -418,6 +529,7 @@ impl Callee<'_> {
 
         (
             Signature {
+                asyncness: None,
                 ident: bench_id.clone(),
                 inputs: inputs.1,
                 ..self.0.clone()
@@ -583,11 +695,9 @@ impl LibraryBenchmark {
         };
 
         let (wrapper_func, pats) = callee.to_caller_signature(&elem_ident, &wrapper_ident);
-        let call_bench_func = quote_spanned! { callee_ident.span() =>
-                std::hint::black_box(
-                    __iai_callgrind_wrapper_mod::#callee_ident(#(#pats),*)
-                )
-        };
+        let call_bench_func =
+            render_instrumented_call(&self.asyncness.render_call(callee_ident, &pats));
+        let poll_fn = self.asyncness.render_poll_fn();
 
         let export = generate_export_name(&callee, &run_func_id);
         let func = quote! {
@@ -603,6 +713,8 @@ impl LibraryBenchmark {
 
                     #[inline(never)]
                     #new_item_fn
+
+                    #poll_fn
                 }
 
                 pub const __BENCHES: &[iai_callgrind::__internal::InternalMacroLibBench]= &[
@@ -610,7 +722,9 @@ impl LibraryBenchmark {
                         id_display: None,
                         args_display: None,
                         func: #func,
-                        config: None
+                        config: None,
+                        parameters: &[],
+                        size: None
                     },
                 ];
 
@@ -681,11 +795,13 @@ impl LibraryBenchmark {
         let new_item_fn = create_item_fn(item_fn);
 
         let mod_name = &item_fn.sig.ident;
+        let poll_fn = self.asyncness.render_poll_fn();
         let mut funcs = TokenStream::new();
         let mut lib_benches = vec![];
         for bench in self.benches {
-            funcs.append_all(bench.render_as_code(&Callee(&item_fn.sig)));
-            lib_benches.push(bench.render_as_member());
+            let callee = Callee(&item_fn.sig);
+            funcs.append_all(bench.render_as_code(&callee, &self.asyncness));
+            lib_benches.push(bench.render_as_member(&callee));
         }
 
         let config = self.config.render_as_code();
@@ -698,6 +814,8 @@ impl LibraryBenchmark {
 
                     #[inline(never)]
                     #new_item_fn
+
+                    #poll_fn
                 }
 
                 pub const __BENCHES: &[iai_callgrind::__internal::InternalMacroLibBench] = &[
@@ -712,32 +830,127 @@ impl LibraryBenchmark {
     }
 }
 
+impl Size {
+    fn ident(id: &Ident) -> Ident {
+        format_ident("__size", Some(id))
+    }
+
+    fn parse_pair(&mut self, pair: &MetaNameValue) {
+        if self.0.is_none() {
+            self.0 = Some(pair.value.clone());
+        } else {
+            emit_error!(
+                pair, "Duplicate argument: `size`";
+                help = "`size` is allowed only once"
+            );
+        }
+    }
+
+    /// Render the function returning the size of the input of the benchmark
+    ///
+    /// In `iter` mode, the size closure is applied to the element of the iterator at the given
+    /// index. In `args` mode, the closure takes the same arguments as the benchmark function (or
+    /// the `setup` function if present) and the index is ignored.
+    fn render_as_code(&self, id: &Ident, mode: &BenchMode) -> TokenStream {
+        let Some(size) = &self.0 else {
+            return TokenStream::new();
+        };
+
+        let ident = Self::ident(id);
+        let index_ident = Iter::index_ident();
+        let body = match mode {
+            BenchMode::Iter(iter) => {
+                let iter_expr = iter.expr();
+                let iter_ident = Iter::iter_ident();
+                quote_spanned! { size.span() =>
+                    let #iter_ident = #iter_expr;
+                    #iter_ident
+                        .into_iter()
+                        .nth(#index_ident.expect("The iterator index should be present"))
+                        .map(#size)
+                        .expect("The iterator index should be within bounds") as u64
+                }
+            }
+            BenchMode::Args(args) => {
+                let args = args.to_tokens_without_black_box();
+                quote_spanned! { size.span() =>
+                    let _ = #index_ident;
+                    (#size)(#args) as u64
+                }
+            }
+        };
+
+        quote! {
+            #[inline(never)]
+            #[allow(clippy::cast_possible_truncation)]
+            #[allow(clippy::cast_sign_loss)]
+            #[allow(clippy::cast_lossless)]
+            #[allow(clippy::redundant_closure_call)]
+            #[allow(clippy::unnecessary_cast)]
+            #[allow(clippy::useless_conversion)]
+            pub fn #ident(#index_ident: Option<usize>) -> u64 {
+                #body
+            }
+        }
+    }
+
+    fn render_as_member(&self, id: &Ident) -> TokenStream {
+        if self.0.is_some() {
+            let ident = Self::ident(id);
+            quote! { Some(#ident) }
+        } else {
+            quote! { None }
+        }
+    }
+}
+
 impl Parse for LibraryBenchmark {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         if input.is_empty() {
             Ok(Self::default())
         } else {
+            let mut asyncness = Async::default();
             let mut config = LibraryBenchmarkConfig::default();
             let mut setup = Setup::default();
             let mut teardown = Teardown::default();
 
-            let pairs = input.parse_terminated(MetaNameValue::parse, Token![,])?;
-            for pair in pairs {
-                if pair.path.is_ident("config") {
-                    config.parse_pair(&pair);
-                } else if pair.path.is_ident("setup") {
-                    setup.parse_pair(&pair);
-                } else if pair.path.is_ident("teardown") {
-                    teardown.parse_pair(&pair);
+            // The `async` argument is a keyword and can't be parsed as `MetaNameValue`
+            while !input.is_empty() {
+                if input.peek(Token![async]) {
+                    let token = input.parse::<Token![async]>()?;
+                    if asyncness.enabled {
+                        abort!(
+                            token, "Duplicate argument: `async`";
+                            help = "`async` is allowed only once"
+                        );
+                    }
+                    asyncness.enabled = true;
                 } else {
-                    abort!(
-                        pair, "Invalid argument: {}", pair.path.require_ident()?;
-                        help = "Valid arguments are: `config`, `setup`, `teardown`"
-                    );
+                    let pair = input.parse::<MetaNameValue>()?;
+                    if pair.path.is_ident("config") {
+                        config.parse_pair(&pair);
+                    } else if pair.path.is_ident("executor") {
+                        asyncness.parse_pair(&pair);
+                    } else if pair.path.is_ident("setup") {
+                        setup.parse_pair(&pair);
+                    } else if pair.path.is_ident("teardown") {
+                        teardown.parse_pair(&pair);
+                    } else {
+                        abort!(
+                            pair, "Invalid argument: {}", pair.path.require_ident()?;
+                            help = "Valid arguments are: `async`, `config`, `executor`, `setup`, \
+                                    `teardown`"
+                        );
+                    }
+                }
+
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
                 }
             }
 
             let library_benchmark = Self {
+                asyncness,
                 config,
                 setup,
                 teardown,
@@ -813,6 +1026,16 @@ impl Teardown {
 fn create_item_fn(item_fn: &ItemFn) -> ItemFn {
     let vis = parse_quote_spanned! { item_fn.span() => pub(super) };
     let item_fn_block = item_fn.block.clone();
+    // The instrumentation of an `async fn` is started and stopped in the poll function
+    if item_fn.sig.asyncness.is_some() {
+        return ItemFn {
+            attrs: vec![],
+            vis,
+            sig: item_fn.sig.clone(),
+            block: item_fn_block,
+        };
+    }
+
     let block = parse_quote_spanned!( item_fn_block.span() =>
         {
             iai_callgrind::client_requests::cachegrind::start_instrumentation();
@@ -841,6 +1064,20 @@ fn create_item_fn(item_fn: &ItemFn) -> ItemFn {
     }
 }
 
+/// Render the call of the benchmark function between the start and stop of the instrumentation
+///
+/// The instrumentation is only started and stopped with client requests if the benchmark runs in
+/// the `ClientRequests` instrumentation mode. Otherwise, these functions do nothing.
+fn render_instrumented_call(call_bench_func: &TokenStream) -> TokenStream {
+    quote! {
+        iai_callgrind::__internal::lib_bench::start_instrumentation();
+        #[allow(clippy::let_unit_value)]
+        let __r = #call_bench_func;
+        iai_callgrind::__internal::lib_bench::stop_instrumentation();
+        __r
+    }
+}
+
 fn generate_export_name(callee: &Callee, run_func_id: &Ident) -> TokenStream {
     let export_name = format!("__iai_callgrind::{}::{run_func_id}", &callee.ident);
     if cfg!(unsafe_keyword_needed) {
@@ -850,12 +1087,34 @@ fn generate_export_name(callee: &Callee, run_func_id: &Ident) -> TokenStream {
     }
 }
 
+#[cfg(feature = "cachegrind")]
+fn render_poll_block() -> TokenStream {
+    quote! {
+        {
+            iai_callgrind::client_requests::cachegrind::start_instrumentation();
+            let __r = std::future::Future::poll(__future, __cx);
+            iai_callgrind::client_requests::cachegrind::stop_instrumentation();
+            __r
+        }
+    }
+}
+
+#[cfg(not(feature = "cachegrind"))]
+fn render_poll_block() -> TokenStream {
+    quote! {
+        {
+            std::future::Future::poll(__future, __cx)
+        }
+    }
+}
+
 pub fn render(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     let mut library_benchmark = parse2::<LibraryBenchmark>(args)?;
     let item_fn = parse2::<ItemFn>(input)?;
 
     let cargo_meta = CargoMetadata::try_new();
 
+    library_benchmark.asyncness.verify(&item_fn);
     library_benchmark.extract_benches(&item_fn, cargo_meta.as_ref())?;
     if library_benchmark.benches.is_empty() {
         Ok(library_benchmark.render_standalone(&item_fn))
@@ -31,6 +31,36 @@ use crate::runner::metrics::Summarize;
 #[cfg(feature = "runner")]
 use crate::runner::metrics::TypeChecker;
 
+/// A named preset of the cache hierarchy used in the cache simulation of Callgrind and Cachegrind
+///
+/// Each preset sets the `--I1`, `--D1` and `--LL` command-line arguments of the tools to the
+/// cache sizes, associativity and line sizes of a typical machine of its class. The values are
+/// written as `<size>,<associativity>,<line size>` (all in bytes) as expected by Valgrind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "runner", derive(EnumIter))]
+pub enum CacheModel {
+    /// A typical desktop x86 machine. These are the cache sizes used if no cache model is
+    /// selected.
+    ///
+    /// * I1: 32 KiB, 8-way, 64 byte lines (`32768,8,64`)
+    /// * D1: 32 KiB, 8-way, 64 byte lines (`32768,8,64`)
+    /// * LL: 8 MiB, 16-way, 64 byte lines (`8388608,16,64`)
+    Desktop,
+    /// A server machine with a large last-level cache
+    ///
+    /// * I1: 32 KiB, 8-way, 64 byte lines (`32768,8,64`)
+    /// * D1: 48 KiB, 12-way, 64 byte lines (`49152,12,64`)
+    /// * LL: 32 MiB, 16-way, 64 byte lines (`33554432,16,64`)
+    Server,
+    /// A small embedded core
+    ///
+    /// * I1: 16 KiB, 4-way, 32 byte lines (`16384,4,32`)
+    /// * D1: 16 KiB, 4-way, 32 byte lines (`16384,4,32`)
+    /// * LL: 256 KiB, 8-way, 32 byte lines (`262144,8,32`)
+    Embedded,
+}
+
 /// All metrics which cachegrind produces and additionally some derived events
 ///
 /// Depending on the options passed to Cachegrind, these are the events that Cachegrind can produce.
@@ -300,6 +330,7 @@ pub enum CallgrindMetrics {
     ///     CallgrindMetrics::CacheHits,
     ///     EventKind::TotalRW.into(),
     ///     EventKind::EstimatedCycles.into(),
+    ///     EventKind::CustomCycles.into(),
     ///     CallgrindMetrics::SystemCalls,
     ///     EventKind::Ge.into(),
     ///     CallgrindMetrics::BranchSim,
@@ -384,7 +415,8 @@ pub enum CallgrindMetrics {
 
     /// All metrics produced by `--cache-sim=yes` including the iai-callgrind specific metrics
     /// [`EventKind::L1hits`], [`EventKind::LLhits`], [`EventKind::RamHits`],
-    /// [`EventKind::TotalRW`], [`EventKind::EstimatedCycles`] and miss/hit rates in this order:
+    /// [`EventKind::TotalRW`], [`EventKind::EstimatedCycles`], [`EventKind::CustomCycles`] and
+    /// miss/hit rates in this order:
     ///
     /// ```rust
     /// # pub mod iai_callgrind {
@@ -401,6 +433,7 @@ pub enum CallgrindMetrics {
     ///     EventKind::TotalRW.into(),
     ///     CallgrindMetrics::CacheHitRates,
     ///     EventKind::EstimatedCycles.into(),
+    ///     EventKind::CustomCycles.into(),
     /// ];
     /// ```
     CacheSim,
@@ -726,6 +759,9 @@ pub enum EventKind {
     TotalRW,
     /// Derived event showing estimated CPU cycles (--cache-sim=yes)
     EstimatedCycles,
+    /// Derived event showing the CPU cycles estimated with a custom [`CyclesModel`]
+    /// (--cache-sim=yes)
+    CustomCycles,
     /// The number of system calls done (--collect-systime=yes)
     SysCount,
     /// The elapsed time spent in system calls (--collect-systime=yes)
@@ -800,6 +836,21 @@ pub enum FlamegraphKind {
     None,
 }
 
+/// The mode how the instrumentation of the benchmark function is controlled in library benchmarks
+///
+/// The default is [`InstrumentationMode::Default`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstrumentationMode {
+    /// The instrumentation is active from the start of the benchmark binary and callgrind
+    /// collects the metrics of the benchmark function only with the help of the `EntryPoint`
+    #[default]
+    Default,
+    /// The instrumentation is switched off at the start of the benchmark binary and switched on
+    /// with client requests just before the call of the benchmark function and switched off
+    /// immediately after it (currently only callgrind and cachegrind)
+    ClientRequests,
+}
+
 /// A `Limit` which can be either an integer or a float
 ///
 /// Depending on the metric the type of the hard limit is a float or an integer. For example
@@ -816,6 +867,26 @@ pub enum Limit {
     Float(f64),
 }
 
+/// The value of a benchmark [`Parameter`]
+///
+/// The values are captured at compile time from the arguments of the `#[bench]` or `#[benches]`
+/// attributes. Literal booleans, integers and floats keep their type. String and character
+/// literals are stored without quotes and any other expression is stored as it appears in the
+/// source code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(untagged)]
+pub enum ParameterValue {
+    /// A boolean literal
+    Bool(bool),
+    /// An integer literal
+    Int(i64),
+    /// A float literal
+    Float(f64),
+    /// A string or character literal or the source code of any other expression
+    String(String),
+}
+
 /// Configure the `Stream` which should be used as pipe in [`Stdin::Setup`]
 ///
 /// The default is [`Pipe::Stdout`]
@@ -828,6 +899,21 @@ pub enum Pipe {
     Stderr,
 }
 
+/// The policy how failed regression checks of a benchmark are treated
+///
+/// The default is [`RegressionPolicy::Enforce`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum RegressionPolicy {
+    /// Regressions are reported and fail the benchmark run
+    #[default]
+    Enforce,
+    /// Regressions are reported but don't fail the benchmark run
+    WarnOnly,
+    /// No regression checks are performed
+    Ignore,
+}
+
 /// This is a special `Stdio` for the stdin method of [`Command`]
 ///
 /// Contains all the standard [`Stdio`] options and the [`Stdin::Setup`] option
@@ -875,6 +961,18 @@ pub(crate) enum Stream {
     Stdout,
 }
 
+/// The amount of bytes or elements processed by a single run of a benchmark
+///
+/// Used to normalize the callgrind metrics per byte or per element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum Throughput {
+    /// The amount of bytes processed
+    Bytes(u64),
+    /// The amount of elements processed
+    Elements(u64),
+}
+
 /// The tool specific flamegraph configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ToolFlamegraphConfig {
@@ -980,6 +1078,8 @@ pub struct BinaryBenchmarkBench {
     pub has_teardown: bool,
     /// The `id` of the benchmark as in `#[bench::id]`
     pub id: Option<String>,
+    /// The parameters of the benchmark function with the values of `args`
+    pub parameters: Vec<Parameter>,
 }
 
 /// The model for the configuration in binary benchmarks
@@ -1000,10 +1100,14 @@ pub struct BinaryBenchmarkConfig {
     pub exit_with: Option<ExitWith>,
     /// The configuration of the output format
     pub output_format: Option<OutputFormat>,
+    /// The policy how failed regression checks are treated
+    pub regression_policy: Option<RegressionPolicy>,
     /// Run the benchmarked binary in a [`Sandbox`] or not
     pub sandbox: Option<Sandbox>,
     /// Run the `setup` function parallel to the benchmarked binary
     pub setup_parallel: Option<bool>,
+    /// The amount of bytes or elements processed by the benchmark
+    pub throughput: Option<Throughput>,
     /// The valgrind tools to run in addition to the default tool
     pub tools: Tools,
     /// The tool override at this configuration level
@@ -1019,6 +1123,8 @@ pub struct BinaryBenchmarkGroup {
     pub binary_benchmarks: Vec<BinaryBenchmark>,
     /// If true compare the benchmarks in this group
     pub compare_by_id: Option<bool>,
+    /// The limits of the benchmarks relative to other benchmarks with the same id
+    pub compare_limits: Vec<CompareLimit>,
     /// The configuration at this level
     pub config: Option<BinaryBenchmarkConfig>,
     /// True if there is a `setup` function
@@ -1053,10 +1159,23 @@ pub struct CachegrindRegressionConfig {
     pub fail_fast: Option<bool>,
     /// The hard limits
     pub hard_limits: Vec<(CachegrindMetrics, Limit)>,
+    /// The expected improvements in percent
+    pub improvements: Vec<(CachegrindMetrics, f64)>,
     /// The soft limits
     pub soft_limits: Vec<(CachegrindMetrics, f64)>,
 }
 
+/// The model for the configuration of the call graph export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallGraphConfig {
+    /// The event kind used to calculate the costs of the nodes and edges
+    pub event_kind: Option<EventKind>,
+    /// Nodes and edges below this percentage of the total inclusive costs are not shown
+    pub node_threshold: Option<f64>,
+    /// If true, write callgrind output files pruned to the entry point subtree
+    pub prune: Option<bool>,
+}
+
 /// The model for the regression check configuration of Callgrind
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CallgrindRegressionConfig {
@@ -1064,6 +1183,8 @@ pub struct CallgrindRegressionConfig {
     pub fail_fast: Option<bool>,
     /// The hard limits
     pub hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    /// The expected improvements in percent
+    pub improvements: Vec<(CallgrindMetrics, f64)>,
     /// The soft limits
     pub soft_limits: Vec<(CallgrindMetrics, f64)>,
 }
@@ -1087,6 +1208,53 @@ pub struct Command {
     pub stdout: Option<Stdio>,
 }
 
+/// The model for a limit of the metrics of benchmarks relative to other benchmarks with the same id
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompareLimit {
+    /// The glob pattern matching the `function::id` of the benchmarks to compare against
+    pub baseline: String,
+    /// The glob pattern matching the `function::id` of the benchmarks which are limited
+    pub benchmark: String,
+    /// The maximum percentage of the metrics of the `baseline` benchmark
+    pub limits: Vec<(CallgrindMetrics, f64)>,
+}
+
+/// The model for the configuration of the user-defined metrics of callgrind
+///
+/// A custom metric is a named arithmetic expression over the [`EventKind`]s like `Ir /
+/// EstimatedCycles` or `(Bcm + Bim) / (Bc + Bi) * 100`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomMetricsConfig {
+    /// The hard limits applied to the custom metrics by name
+    pub hard_limits: Vec<(String, Limit)>,
+    /// The custom metrics as pairs of name and expression
+    pub metrics: Vec<(String, String)>,
+    /// The soft limits applied to the custom metrics by name
+    pub soft_limits: Vec<(String, f64)>,
+}
+
+/// The model of the custom estimation of the cpu cycles ([`EventKind::CustomCycles`])
+///
+/// The estimated cycles are the sum of the [`EventKind::L1hits`], [`EventKind::LLhits`] and
+/// [`EventKind::RamHits`] multiplied with their weight (the latency in cycles) plus the
+/// mispredicted conditional branches ([`EventKind::Bcm`]) and indirect branches
+/// ([`EventKind::Bim`]) multiplied with their penalty. The default weights are the same as in the
+/// formula of the [`EventKind::EstimatedCycles`] without any penalties for branch mispredictions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CyclesModel {
+    /// The penalty of a mispredicted conditional branch (--branch-sim=yes)
+    pub bcm: u64,
+    /// The penalty of a mispredicted indirect branch (--branch-sim=yes)
+    pub bim: u64,
+    /// The weight of a L1 hit
+    pub l1_hits: u64,
+    /// The weight of a last-level cache hit
+    pub ll_hits: u64,
+    /// The weight of a RAM hit
+    pub ram_hits: u64,
+}
+
 /// The delay of the [`Command`]
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Delay {
@@ -1105,6 +1273,8 @@ pub struct DhatRegressionConfig {
     pub fail_fast: Option<bool>,
     /// The hard limits
     pub hard_limits: Vec<(DhatMetrics, Limit)>,
+    /// The expected improvements in percent
+    pub improvements: Vec<(DhatMetrics, f64)>,
     /// The soft limits
     pub soft_limits: Vec<(DhatMetrics, f64)>,
 }
@@ -1121,10 +1291,16 @@ pub struct Fixtures {
 /// The model for the configuration of flamegraphs
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct FlamegraphConfig {
+    /// If true, collapse the generic arguments of the function names
+    pub collapse_generics: Option<bool>,
     /// The direction of the flamegraph. Top to bottom or vice versa
     pub direction: Option<Direction>,
     /// The event kinds for which a flamegraph should be generated
     pub event_kinds: Option<Vec<EventKind>>,
+    /// The crates which are folded into a single node
+    pub fold_crates: Vec<String>,
+    /// If true, fold the frames of `std`, `core` and `alloc`
+    pub fold_std: Option<bool>,
     /// The flamegraph kind
     pub kind: Option<FlamegraphKind>,
     /// The minimum width which should be displayed
@@ -1133,12 +1309,25 @@ pub struct FlamegraphConfig {
     pub negate_differential: Option<bool>,
     /// If true, normalize a differential flamegraph
     pub normalize_differential: Option<bool>,
+    /// If true, strip the hashes of the function names
+    pub strip_hashes: Option<bool>,
     /// The subtitle to use for the flamegraphs
     pub subtitle: Option<String>,
     /// The title to use for the flamegraphs
     pub title: Option<String>,
 }
 
+/// The model for the configuration of the inclusive metrics of functions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionMetricsConfig {
+    /// The glob patterns matching the functions
+    pub functions: Vec<String>,
+    /// The hard limits applied to the metrics of each glob pattern
+    pub hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    /// The soft limits applied to the metrics of each glob pattern
+    pub soft_limits: Vec<(CallgrindMetrics, f64)>,
+}
+
 /// The model for the `#[library_benchmark]` attribute
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct LibraryBenchmark {
@@ -1161,6 +1350,12 @@ pub struct LibraryBenchmarkBench {
     pub id: Option<String>,
     /// The amount of elements in the iterator of the `#[benches::id(iter = ITERATOR)]` if present
     pub iter_count: Option<usize>,
+    /// The parameters of the benchmark function with the values of `args`
+    pub parameters: Vec<Parameter>,
+    /// The input sizes of the `#[benches::id(size = ...)]` attribute if present
+    ///
+    /// There's one size per element of the iterator or a single size for an element of `args`.
+    pub sizes: Vec<u64>,
 }
 
 /// The model for the configuration in library benchmarks
@@ -1175,14 +1370,25 @@ pub struct LibraryBenchmarkConfig {
     pub env_clear: Option<bool>,
     /// The environment variables to set or pass through to the binary
     pub envs: Vec<(OsString, Option<OsString>)>,
+    /// The mode how the instrumentation of the benchmark function is controlled
+    pub instrumentation_mode: Option<InstrumentationMode>,
+    /// The amount of measured iterations of the benchmark function
+    pub iterations: Option<usize>,
     /// The configuration of the output format
     pub output_format: Option<OutputFormat>,
+    /// The policy how failed regression checks are treated
+    pub regression_policy: Option<RegressionPolicy>,
+    /// The amount of bytes or elements processed by the benchmark
+    pub throughput: Option<Throughput>,
     /// The valgrind tools to run in addition to the default tool
     pub tools: Tools,
     /// The tool override at this configuration level
     pub tools_override: Option<Tools>,
     /// The arguments to pass to all tools
     pub valgrind_args: RawArgs,
+    /// The amount of unmeasured iterations of the benchmark function before the measured
+    /// iterations
+    pub warmup: Option<usize>,
 }
 
 /// The model for the `library_benchmark_group` macro
@@ -1190,6 +1396,8 @@ pub struct LibraryBenchmarkConfig {
 pub struct LibraryBenchmarkGroup {
     /// If true compare the benchmarks in this group
     pub compare_by_id: Option<bool>,
+    /// The limits of the benchmarks relative to other benchmarks with the same id
+    pub compare_limits: Vec<CompareLimit>,
     /// The configuration at this level
     pub config: Option<LibraryBenchmarkConfig>,
     /// True if there is a `setup` function
@@ -1232,10 +1440,36 @@ pub struct OutputFormat {
     pub truncate_description: Option<Option<usize>>,
 }
 
+/// A parameter of a benchmark
+///
+/// The name is the name of the parameter in the signature of the benchmark function or the
+/// positional name `argN` if the name is not available.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Parameter {
+    /// The name of the parameter
+    pub name: String,
+    /// The value of the parameter
+    pub value: ParameterValue,
+}
+
 /// The raw arguments to pass to a valgrind tool
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RawArgs(pub Vec<String>);
 
+/// The model for a named region of a callgrind benchmark
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    /// The glob pattern matching the function which defines the region
+    pub function: String,
+    /// The hard limits applied to the metrics of this region
+    pub hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    /// The name of the region
+    pub name: String,
+    /// The soft limits applied to the metrics of this region
+    pub soft_limits: Vec<(CallgrindMetrics, f64)>,
+}
+
 /// The sandbox to run the benchmarks in
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Sandbox {
@@ -1247,9 +1481,47 @@ pub struct Sandbox {
     pub follow_symlinks: Option<bool>,
 }
 
+/// The model for the selection and breakdown of the threads and subprocesses of a benchmark
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadsConfig {
+    /// The glob patterns matching the commands of the processes which are included. If empty,
+    /// all processes are included
+    pub commands: Vec<String>,
+    /// The hard limits applied to the metrics of each thread
+    pub hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    /// The amount of the most expensive threads to show in the terminal output
+    pub show: Option<usize>,
+    /// The soft limits applied to the metrics of each thread
+    pub soft_limits: Vec<(CallgrindMetrics, f64)>,
+    /// The thread numbers which are included. If empty, all threads are included
+    pub threads: Vec<usize>,
+}
+
+/// The model for the configuration of the metrics normalized by the [`Throughput`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThroughputConfig {
+    /// The hard limits applied to the normalized metrics
+    pub hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    /// The metrics to normalize. If empty, the default metrics are used
+    pub metrics: Vec<CallgrindMetrics>,
+    /// The soft limits applied to the normalized metrics
+    pub soft_limits: Vec<(CallgrindMetrics, f64)>,
+}
+
 /// The tool configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tool {
+    /// The cache models to run the cache simulation with (currently only callgrind and
+    /// cachegrind)
+    pub cache_models: Option<Vec<CacheModel>>,
+    /// The configuration of the call graph export
+    pub call_graph_config: Option<CallGraphConfig>,
+    /// The metric to fit the complexity of benchmarks with input sizes
+    pub complexity_metric: Option<EventKind>,
+    /// The user-defined metrics (currently only callgrind)
+    pub custom_metrics: Option<CustomMetricsConfig>,
+    /// The model of the custom cycles estimation (currently only callgrind)
+    pub cycles_model: Option<CyclesModel>,
     /// If true the tool is run. Ignored for the default tool which always runs
     pub enable: Option<bool>,
     /// The entry point for the tool
@@ -1258,16 +1530,30 @@ pub struct Tool {
     pub flamegraph_config: Option<ToolFlamegraphConfig>,
     /// Any frames in the call stack which should be considered in addition to the entry point
     pub frames: Option<Vec<String>>,
+    /// The configuration of the inclusive metrics of functions
+    pub function_metrics: Option<FunctionMetricsConfig>,
+    /// The amount of the hottest instructions to show in the terminal output
+    pub hot_instructions: Option<usize>,
+    /// The amount of the hottest source lines to show in the terminal output
+    pub hot_lines: Option<usize>,
     /// The valgrind tool this configuration is for
     pub kind: ValgrindTool,
     /// The configuration of the output format
     pub output_format: Option<ToolOutputFormat>,
     /// The arguments to pass to the tool
     pub raw_args: RawArgs,
+    /// The named regions which are reported separately
+    pub regions: Option<Vec<Region>>,
     /// The configuration for regression checks of tools which perform regression checks
     pub regression_config: Option<ToolRegressionConfig>,
+    /// The amount of runs to measure the noise of the metrics
+    pub repeat: Option<usize>,
     /// If true show the logging output of Valgrind (not Iai-Callgrind)
     pub show_log: Option<bool>,
+    /// The selection and breakdown of the threads and subprocesses (currently only callgrind)
+    pub threads_config: Option<ThreadsConfig>,
+    /// The configuration of the metrics normalized by the [`Throughput`]
+    pub throughput_config: Option<ThroughputConfig>,
 }
 
 /// The configurations of all tools to run in addition to the default tool
@@ -1303,6 +1589,9 @@ impl BinaryBenchmarkConfig {
             self.sandbox = update_option(&self.sandbox, &other.sandbox);
             self.setup_parallel = update_option(&self.setup_parallel, &other.setup_parallel);
             self.output_format = update_option(&self.output_format, &other.output_format);
+            self.regression_policy =
+                update_option(&self.regression_policy, &other.regression_policy);
+            self.throughput = update_option(&self.throughput, &other.throughput);
         }
         self
     }
@@ -1334,6 +1623,65 @@ impl BinaryBenchmarkConfig {
     }
 }
 
+impl CacheModel {
+    /// Return the id of this cache model as used on the command-line
+    pub fn id(&self) -> String {
+        match self {
+            Self::Desktop => "desktop".to_owned(),
+            Self::Server => "server".to_owned(),
+            Self::Embedded => "embedded".to_owned(),
+        }
+    }
+
+    /// Return the value of the `--I1` command-line argument
+    pub fn i1(&self) -> &'static str {
+        match self {
+            Self::Desktop | Self::Server => "32768,8,64",
+            Self::Embedded => "16384,4,32",
+        }
+    }
+
+    /// Return the value of the `--D1` command-line argument
+    pub fn d1(&self) -> &'static str {
+        match self {
+            Self::Desktop => "32768,8,64",
+            Self::Server => "49152,12,64",
+            Self::Embedded => "16384,4,32",
+        }
+    }
+
+    /// Return the value of the `--LL` command-line argument
+    pub fn ll(&self) -> &'static str {
+        match self {
+            Self::Desktop => "8388608,16,64",
+            Self::Server => "33554432,16,64",
+            Self::Embedded => "262144,8,32",
+        }
+    }
+}
+
+impl Display for CacheModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id())
+    }
+}
+
+#[cfg(feature = "runner")]
+impl FromStr for CacheModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "desktop" => Ok(Self::Desktop),
+            "server" => Ok(Self::Server),
+            "embedded" => Ok(Self::Embedded),
+            v => Err(anyhow!(
+                "Unknown cache model '{v}': Possible values are desktop, server and embedded"
+            )),
+        }
+    }
+}
+
 impl CachegrindMetric {
     /// Return true if this `EventKind` is a derived event
     ///
@@ -1542,6 +1890,60 @@ impl FromStr for CallgrindMetrics {
     }
 }
 
+impl CyclesModel {
+    /// Return true if this model has penalties for branch mispredictions
+    ///
+    /// The branch mispredictions are only available if callgrind runs with `--branch-sim=yes`.
+    pub fn has_branch_penalties(&self) -> bool {
+        self.bcm > 0 || self.bim > 0
+    }
+}
+
+impl Default for CyclesModel {
+    fn default() -> Self {
+        Self {
+            bcm: 0,
+            bim: 0,
+            l1_hits: 1,
+            ll_hits: 5,
+            ram_hits: 35,
+        }
+    }
+}
+
+#[cfg(feature = "runner")]
+impl FromStr for CyclesModel {
+    type Err = anyhow::Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut model = Self::default();
+        for item in string.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid format of key=value pair: '{item}'"))?;
+            let (key, value) = (key.trim(), value.trim());
+            let value = value
+                .parse::<u64>()
+                .map_err(|error| anyhow!("Invalid weight '{value}' of '{key}': {error}"))?;
+            match key.to_lowercase().as_str() {
+                "l1hits" => model.l1_hits = value,
+                "llhits" => model.ll_hits = value,
+                "ramhits" => model.ram_hits = value,
+                "bcm" => model.bcm = value,
+                "bim" => model.bim = value,
+                _ => {
+                    return Err(anyhow!(
+                        "Unknown metric '{key}' of the cycles model: Possible values are L1hits, \
+                         LLhits, RamHits, Bcm and Bim"
+                    ))
+                }
+            }
+        }
+
+        Ok(model)
+    }
+}
+
 impl Default for DelayKind {
     fn default() -> Self {
         Self::DurationElapse(Duration::from_secs(60))
@@ -1692,6 +2094,7 @@ impl EventKind {
     /// * [`EventKind::RamHits`]
     /// * [`EventKind::TotalRW`]
     /// * [`EventKind::EstimatedCycles`]
+    /// * [`EventKind::CustomCycles`]
     /// * [`EventKind::I1MissRate`]
     /// * [`EventKind::D1MissRate`]
     /// * [`EventKind::LLiMissRate`]
@@ -1708,6 +2111,7 @@ impl EventKind {
                 | Self::RamHits
                 | Self::TotalRW
                 | Self::EstimatedCycles
+                | Self::CustomCycles
                 | Self::I1MissRate
                 | Self::D1MissRate
                 | Self::LLiMissRate
@@ -1734,6 +2138,7 @@ impl Display for EventKind {
             Self::RamHits => f.write_str("RAM Hits"),
             Self::TotalRW => f.write_str("Total read+write"),
             Self::EstimatedCycles => f.write_str("Estimated Cycles"),
+            Self::CustomCycles => f.write_str("Custom Cycles"),
             Self::I1MissRate => f.write_str("I1 Miss Rate"),
             Self::D1MissRate => f.write_str("D1 Miss Rate"),
             Self::LLiMissRate => f.write_str("LLi Miss Rate"),
@@ -1816,6 +2221,7 @@ impl FromStr for EventKind {
             "ramhits" => Self::RamHits,
             "totalrw" => Self::TotalRW,
             "estimatedcycles" => Self::EstimatedCycles,
+            "customcycles" => Self::CustomCycles,
             "i1missrate" => Self::I1MissRate,
             "d1missrate" => Self::D1MissRate,
             "llimissrate" => Self::LLiMissRate,
@@ -1849,6 +2255,7 @@ impl TypeChecker for EventKind {
             | Self::RamHits
             | Self::TotalRW
             | Self::EstimatedCycles
+            | Self::CustomCycles
             | Self::SysCount
             | Self::SysTime
             | Self::SysCpuTime
@@ -1980,7 +2387,11 @@ impl From<CallgrindMetrics> for IndexSet<EventKind> {
             CallgrindMetrics::Default => {
                 event_kinds.insert(EventKind::Ir);
                 event_kinds.extend(Self::from(CallgrindMetrics::CacheHits));
-                event_kinds.extend([EventKind::TotalRW, EventKind::EstimatedCycles]);
+                event_kinds.extend([
+                    EventKind::TotalRW,
+                    EventKind::EstimatedCycles,
+                    EventKind::CustomCycles,
+                ]);
                 event_kinds.extend(Self::from(CallgrindMetrics::SystemCalls));
                 event_kinds.insert(EventKind::Ge);
                 event_kinds.extend(Self::from(CallgrindMetrics::BranchSim));
@@ -2020,6 +2431,7 @@ impl From<CallgrindMetrics> for IndexSet<EventKind> {
                 event_kinds.extend(Self::from(CallgrindMetrics::CacheHitRates));
                 event_kinds.insert(EventKind::TotalRW);
                 event_kinds.insert(EventKind::EstimatedCycles);
+                event_kinds.insert(EventKind::CustomCycles);
             }
             CallgrindMetrics::CacheUse => event_kinds.extend([
                 EventKind::AcCost1,
@@ -2072,7 +2484,14 @@ impl LibraryBenchmarkConfig {
                 // do nothing
             }
 
+            self.instrumentation_mode =
+                update_option(&self.instrumentation_mode, &other.instrumentation_mode);
+            self.iterations = update_option(&self.iterations, &other.iterations);
             self.output_format = update_option(&self.output_format, &other.output_format);
+            self.regression_policy =
+                update_option(&self.regression_policy, &other.regression_policy);
+            self.throughput = update_option(&self.throughput, &other.throughput);
+            self.warmup = update_option(&self.warmup, &other.warmup);
         }
         self
     }
@@ -2123,6 +2542,17 @@ impl From<u64> for Limit {
     }
 }
 
+impl Display for ParameterValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::String(value) => f.write_str(value),
+        }
+    }
+}
+
 impl RawArgs {
     /// Create new arguments for a valgrind tool
     pub fn new<I, T>(args: T) -> Self
@@ -2184,6 +2614,33 @@ where
     }
 }
 
+impl Display for RegressionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Enforce => f.write_str("enforce"),
+            Self::WarnOnly => f.write_str("warn-only"),
+            Self::Ignore => f.write_str("ignore"),
+        }
+    }
+}
+
+#[cfg(feature = "runner")]
+impl FromStr for RegressionPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "enforce" => Ok(Self::Enforce),
+            "warn-only" | "warn_only" | "warnonly" => Ok(Self::WarnOnly),
+            "ignore" => Ok(Self::Ignore),
+            v => Err(anyhow!(
+                "Unknown regression policy '{v}': Possible values are enforce, warn-only and \
+                 ignore"
+            )),
+        }
+    }
+}
+
 impl Stdin {
     #[cfg(feature = "runner")]
     pub(crate) fn apply(
@@ -2321,14 +2778,26 @@ impl Tool {
     pub fn new(kind: ValgrindTool) -> Self {
         Self {
             kind,
+            cache_models: None,
+            call_graph_config: None,
+            complexity_metric: None,
+            custom_metrics: None,
+            cycles_model: None,
             enable: None,
             raw_args: RawArgs::default(),
+            regions: None,
+            repeat: None,
             show_log: None,
             regression_config: None,
             flamegraph_config: None,
             output_format: None,
             entry_point: None,
             frames: None,
+            function_metrics: None,
+            hot_instructions: None,
+            hot_lines: None,
+            threads_config: None,
+            throughput_config: None,
         }
     }
 
@@ -2346,6 +2815,13 @@ impl Tool {
     /// Update this tool configuration with another configuration
     pub fn update(&mut self, other: &Self) {
         if self.kind == other.kind {
+            self.cache_models = update_option(&self.cache_models, &other.cache_models);
+            self.call_graph_config =
+                update_option(&self.call_graph_config, &other.call_graph_config);
+            self.complexity_metric =
+                update_option(&self.complexity_metric, &other.complexity_metric);
+            self.custom_metrics = update_option(&self.custom_metrics, &other.custom_metrics);
+            self.cycles_model = update_option(&self.cycles_model, &other.cycles_model);
             self.enable = update_option(&self.enable, &other.enable);
             self.show_log = update_option(&self.show_log, &other.show_log);
             self.regression_config =
@@ -2355,6 +2831,14 @@ impl Tool {
             self.output_format = update_option(&self.output_format, &other.output_format);
             self.entry_point = update_option(&self.entry_point, &other.entry_point);
             self.frames = update_option(&self.frames, &other.frames);
+            self.function_metrics = update_option(&self.function_metrics, &other.function_metrics);
+            self.regions = update_option(&self.regions, &other.regions);
+            self.hot_instructions = update_option(&self.hot_instructions, &other.hot_instructions);
+            self.hot_lines = update_option(&self.hot_lines, &other.hot_lines);
+            self.repeat = update_option(&self.repeat, &other.repeat);
+            self.threads_config = update_option(&self.threads_config, &other.threads_config);
+            self.throughput_config =
+                update_option(&self.throughput_config, &other.throughput_config);
 
             self.raw_args.extend_ignore_flag(other.raw_args.0.iter());
         }
@@ -2491,6 +2975,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cache_model_from_str_ignore_case() {
+        for cache_model in CacheModel::iter() {
+            let string = format!("{cache_model:?}");
+            let actual = CacheModel::from_str(&string);
+            assert_eq!(actual.unwrap(), cache_model);
+        }
+    }
+
     #[test]
     fn test_event_kind_from_str_ignore_case() {
         for event_kind in EventKind::iter() {
@@ -2518,6 +3011,9 @@ mod tests {
             envs: vec![(OsString::from("MY_ENV"), Some(OsString::from("value")))],
             tools: Tools(vec![Tool {
                 kind: ValgrindTool::DHAT,
+                cache_models: None,
+                custom_metrics: None,
+                cycles_model: None,
                 enable: None,
                 raw_args: RawArgs(vec![]),
                 show_log: None,
@@ -2530,10 +3026,24 @@ mod tests {
                 entry_point: Some(EntryPoint::default()),
                 output_format: Some(ToolOutputFormat::None),
                 frames: Some(vec!["some::frame".to_owned()]),
+                hot_instructions: None,
+                hot_lines: None,
+                call_graph_config: None,
+                complexity_metric: None,
+                function_metrics: None,
+                regions: None,
+                repeat: None,
+                threads_config: None,
+                throughput_config: None,
             }]),
             tools_override: None,
             output_format: None,
             default_tool: Some(ValgrindTool::BBV),
+            instrumentation_mode: Some(InstrumentationMode::ClientRequests),
+            iterations: Some(10),
+            regression_policy: Some(RegressionPolicy::WarnOnly),
+            throughput: Some(Throughput::Bytes(1024)),
+            warmup: Some(2),
         };
 
         assert_eq!(base.update_from_all([Some(&other.clone())]), other);
@@ -2548,6 +3058,9 @@ mod tests {
             envs: vec![(OsString::from("MY_ENV"), Some(OsString::from("value")))],
             tools: Tools(vec![Tool {
                 kind: ValgrindTool::DHAT,
+                cache_models: None,
+                custom_metrics: None,
+                cycles_model: None,
                 enable: None,
                 raw_args: RawArgs(vec![]),
                 show_log: None,
@@ -2560,10 +3073,24 @@ mod tests {
                 entry_point: Some(EntryPoint::default()),
                 output_format: Some(ToolOutputFormat::None),
                 frames: Some(vec!["some::frame".to_owned()]),
+                hot_instructions: None,
+                hot_lines: None,
+                call_graph_config: None,
+                complexity_metric: None,
+                function_metrics: None,
+                regions: None,
+                repeat: None,
+                threads_config: None,
+                throughput_config: None,
             }]),
             tools_override: Some(Tools(vec![])),
             output_format: Some(OutputFormat::default()),
             default_tool: Some(ValgrindTool::BBV),
+            instrumentation_mode: Some(InstrumentationMode::ClientRequests),
+            iterations: Some(3),
+            regression_policy: Some(RegressionPolicy::Ignore),
+            throughput: Some(Throughput::Elements(10)),
+            warmup: Some(1),
         };
         let expected = LibraryBenchmarkConfig {
             tools: other.tools_override.as_ref().unwrap().clone(),
@@ -2588,6 +3115,24 @@ mod tests {
         assert_eq!(actual, config);
     }
 
+    #[rstest]
+    #[case::enforce("enforce", RegressionPolicy::Enforce)]
+    #[case::warn_only("warn-only", RegressionPolicy::WarnOnly)]
+    #[case::warn_only_underscore("warn_only", RegressionPolicy::WarnOnly)]
+    #[case::ignore_upper_case("IGNORE", RegressionPolicy::Ignore)]
+    fn test_regression_policy_from_str(#[case] value: &str, #[case] expected: RegressionPolicy) {
+        assert_eq!(RegressionPolicy::from_str(value).unwrap(), expected);
+        assert_eq!(
+            RegressionPolicy::from_str(&expected.to_string()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_regression_policy_from_str_when_unknown() {
+        RegressionPolicy::from_str("warn").unwrap_err();
+    }
+
     #[rstest]
     #[case::all_none(None, None, None)]
     #[case::some_and_none(Some(true), None, Some(true))]
@@ -2623,11 +3168,11 @@ mod tests {
     #[case::none(CallgrindMetrics::None, indexset![])]
     #[case::all(CallgrindMetrics::All, indexset![Ir, Dr, Dw, I1mr, D1mr, D1mw, ILmr, DLmr,
         DLmw, I1MissRate, LLiMissRate, D1MissRate, LLdMissRate, LLMissRate, L1hits, LLhits, RamHits,
-        TotalRW, L1HitRate, LLHitRate, RamHitRate, EstimatedCycles, SysCount, SysTime, SysCpuTime,
-        Ge, Bc, Bcm, Bi, Bim, ILdmr, DLdmr, DLdmw, AcCost1, AcCost2, SpLoss1, SpLoss2]
+        TotalRW, L1HitRate, LLHitRate, RamHitRate, EstimatedCycles, CustomCycles, SysCount, SysTime,
+        SysCpuTime, Ge, Bc, Bcm, Bi, Bim, ILdmr, DLdmr, DLdmw, AcCost1, AcCost2, SpLoss1, SpLoss2]
     )]
     #[case::default(CallgrindMetrics::Default, indexset![Ir, L1hits, LLhits, RamHits, TotalRW,
-        EstimatedCycles, SysCount, SysTime, SysCpuTime, Ge, Bc,
+        EstimatedCycles, CustomCycles, SysCount, SysTime, SysCpuTime, Ge, Bc,
         Bcm, Bi, Bim, ILdmr, DLdmr, DLdmw, AcCost1, AcCost2, SpLoss1, SpLoss2]
     )]
     #[case::cache_misses(CallgrindMetrics::CacheMisses, indexset![I1mr, D1mr, D1mw, ILmr,
@@ -2642,7 +3187,7 @@ mod tests {
     ])]
     #[case::cache_sim(CallgrindMetrics::CacheSim, indexset![Dr, Dw, I1mr, D1mr, D1mw, ILmr, DLmr,
         DLmw, I1MissRate, LLiMissRate, D1MissRate, LLdMissRate, LLMissRate, L1hits, LLhits, RamHits,
-        TotalRW, L1HitRate, LLHitRate, RamHitRate, EstimatedCycles]
+        TotalRW, L1HitRate, LLHitRate, RamHitRate, EstimatedCycles, CustomCycles]
     )]
     #[case::cache_use(CallgrindMetrics::CacheUse, indexset![AcCost1, AcCost2, SpLoss1, SpLoss2])]
     #[case::system_calls(CallgrindMetrics::SystemCalls, indexset![SysCount, SysTime, SysCpuTime])]
@@ -2719,6 +3264,7 @@ mod tests {
         let mut base = Tool::new(ValgrindTool::Callgrind);
         let other = Tool {
             kind: ValgrindTool::Callgrind,
+            cache_models: Some(vec![CacheModel::Server]),
             enable: Some(true),
             raw_args: RawArgs::new(["--some"]),
             show_log: Some(false),
@@ -2727,6 +3273,17 @@ mod tests {
             output_format: Some(ToolOutputFormat::None),
             entry_point: Some(EntryPoint::Default),
             frames: Some(vec!["some::frame".to_owned()]),
+            hot_instructions: Some(5),
+            hot_lines: Some(10),
+            call_graph_config: Some(CallGraphConfig::default()),
+            complexity_metric: Some(EventKind::EstimatedCycles),
+            custom_metrics: Some(CustomMetricsConfig::default()),
+            cycles_model: Some(CyclesModel::default()),
+            function_metrics: Some(FunctionMetricsConfig::default()),
+            regions: Some(vec![Region::default()]),
+            repeat: Some(5),
+            threads_config: Some(ThreadsConfig::default()),
+            throughput_config: Some(ThroughputConfig::default()),
         };
         let expected = other.clone();
         base.update(&other);
@@ -2738,6 +3295,7 @@ mod tests {
         let mut base = Tool::new(ValgrindTool::Callgrind);
         let other = Tool {
             kind: ValgrindTool::DRD,
+            cache_models: Some(vec![CacheModel::Server]),
             enable: Some(true),
             raw_args: RawArgs::new(["--some"]),
             show_log: Some(false),
@@ -2746,6 +3304,17 @@ mod tests {
             output_format: Some(ToolOutputFormat::None),
             entry_point: Some(EntryPoint::Default),
             frames: Some(vec!["some::frame".to_owned()]),
+            hot_instructions: Some(5),
+            hot_lines: Some(10),
+            call_graph_config: Some(CallGraphConfig::default()),
+            complexity_metric: Some(EventKind::EstimatedCycles),
+            custom_metrics: Some(CustomMetricsConfig::default()),
+            cycles_model: Some(CyclesModel::default()),
+            function_metrics: Some(FunctionMetricsConfig::default()),
+            regions: Some(vec![Region::default()]),
+            repeat: Some(5),
+            threads_config: Some(ThreadsConfig::default()),
+            throughput_config: Some(ThroughputConfig::default()),
         };
 
         let expected = base.clone();
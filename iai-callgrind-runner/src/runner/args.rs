@@ -13,31 +13,47 @@ use indexmap::{indexset, IndexMap, IndexSet};
 use strum::IntoEnumIterator;
 
 use super::cachegrind::regression::CachegrindRegressionConfig;
+use super::callgrind::custom_metrics;
 use super::callgrind::regression::CallgrindRegressionConfig;
 use super::dhat::regression::DhatRegressionConfig;
 use super::format::OutputFormatKind;
+use super::git;
 use super::metrics::{Metric, TypeChecker};
 use super::summary::{BaselineName, SummaryFormat};
 use super::tool::regression::ToolRegressionConfig;
 use crate::api::{
-    CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DhatMetric, DhatMetrics, ErrorMetric,
-    EventKind, RawArgs, ValgrindTool,
+    CacheModel, CachegrindMetric, CachegrindMetrics, CallgrindMetrics, CyclesModel, DhatMetric,
+    DhatMetrics, ErrorMetric, EventKind, Parameter, RawArgs, RegressionPolicy, ValgrindTool,
 };
 
 // Utility for complex types intended to be used during the parsing of the command-line arguments
 type Limits<T> = (IndexMap<T, f64>, IndexMap<T, Metric>);
 type ParsedMetrics<T> = Result<Vec<(T, Option<Metric>)>, String>;
 
+/// What to do if a baseline was created in a different environment than the new benchmark run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BaselineMismatch {
+    /// Refuse to compare against the baseline and fail the benchmark
+    Error,
+    /// Compare against the baseline without printing anything
+    Ignore,
+    /// Print a warning listing the differences and compare against the baseline
+    #[default]
+    Warn,
+}
+
 /// A filter for benchmarks
 ///
 /// # Developer Notes
 ///
 /// This enum is used instead of a plain `String` for possible future usages to filter by benchmark
 /// ids, group name, file name etc.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BenchmarkFilter {
     /// The name of the benchmark
     Name(String),
+    /// The name and value of a benchmark parameter as in `param:<name>=<value>`
+    Parameter(String, String),
 }
 
 /// The `NoCapture` options for the command-line argument --nocapture
@@ -174,8 +190,14 @@ pub struct CommandLineArgs {
 
     #[rustfmt::skip]
     /// Compare against this baseline if present but do not overwrite it
+    ///
+    /// Instead of a name, a git reference can be given as `git:<ref>` (like `git:main`) or
+    /// `git:merge-base` for the merge-base of `HEAD` and the main branch. The reference is
+    /// resolved to a commit with `git` and the baseline is named `git_<commit>` after the
+    /// abbreviated commit hash. See also --git-baseline-create.
     #[arg(
         long = "baseline",
+        value_parser = parse_baseline_name,
         default_missing_value = "default",
         num_args = 0..=1,
         require_equals = true,
@@ -184,6 +206,26 @@ pub struct CommandLineArgs {
     )]
     pub baseline: Option<BaselineName>,
 
+    #[rustfmt::skip]
+    /// What to do if the baseline was created in a different environment
+    ///
+    /// Together with the output files of a benchmark, the effective valgrind arguments of the
+    /// tool, the valgrind and rustc versions and the target triple are recorded. If these differ
+    /// between the new benchmark run and the baseline (the `old` run or a named baseline), the
+    /// comparison is usually meaningless. With `warn`, the differences are listed in a warning,
+    /// `error` refuses to compare against the baseline and fails the benchmark, and `ignore`
+    /// silently compares nonetheless.
+    #[arg(
+        long = "baseline-mismatch",
+        value_enum,
+        required = false,
+        default_value = "warn",
+        num_args = 1,
+        env = "IAI_CALLGRIND_BASELINE_MISMATCH",
+        display_order = 200
+    )]
+    pub baseline_mismatch: BaselineMismatch,
+
     #[rustfmt::skip]
     /// The command-line arguments to pass through to the experimental BBV
     ///
@@ -203,6 +245,34 @@ pub struct CommandLineArgs {
     )]
     pub bbv_args: Option<RawArgs>,
 
+    #[rustfmt::skip]
+    /// A comma separated list of cache models to run the cache simulation of Callgrind and
+    /// Cachegrind with
+    ///
+    /// A cache model is a named preset of the cache hierarchy which sets the --I1, --D1 and --LL
+    /// command-line arguments of the tools. If more than one cache model is given, Callgrind and
+    /// Cachegrind run the benchmark once per cache model and the metrics of each run are reported
+    /// side by side. The cache models specified here take precedence over the cache models in the
+    /// benchmarks.
+    ///
+    /// Possible values are:
+    ///   * desktop: A typical desktop x86 machine (I1/D1: 32KiB, 8-way, LL: 8MiB, 16-way)
+    ///   * server: A server with a large LL cache (I1: 32KiB, D1: 48KiB, LL: 32MiB)
+    ///   * embedded: A small embedded core (I1/D1: 16KiB, 4-way, LL: 256KiB, 8-way)
+    ///
+    /// Examples
+    ///   * --cache-model=server
+    ///   * --cache-model=desktop,server,embedded
+    #[arg(
+        long = "cache-model",
+        num_args = 1..,
+        value_delimiter = ',',
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CACHE_MODEL",
+        display_order = 500
+    )]
+    pub cache_models: Vec<CacheModel>,
+
     #[rustfmt::skip]
     /// The command-line arguments to pass through to Cachegrind
     ///
@@ -313,6 +383,82 @@ pub struct CommandLineArgs {
     )]
     pub callgrind_args: Option<RawArgs>,
 
+    #[rustfmt::skip]
+    #[allow(clippy::doc_markdown)]
+    /// Define custom metrics as arithmetic expressions over the callgrind metrics
+    ///
+    /// This is a `,`-separated list of name=expression pairs. The name may consist of ascii
+    /// alphanumeric characters and `_`. An expression consists of EventKinds (with the same
+    /// names and abbreviations as in `--callgrind-limits`), integer or float numbers, the
+    /// operators `+`, `-`, `*`, `/` and parentheses. A division by zero results in `0.0`. The
+    /// custom metrics are calculated from the metrics of the total and shown below them. A custom
+    /// metric is not shown if one of its EventKinds was not collected. A custom metric specified
+    /// here takes precedence over a custom metric with the same name in the benchmarks.
+    ///
+    /// Examples:
+    ///   * --callgrind-custom-metrics='ipc_proxy=Ir / EstimatedCycles'
+    ///   * --callgrind-custom-metrics='branch_miss_rate=(Bcm + Bim) / (Bc + Bi) * 100,rw=Dr+Dw'
+    #[arg(
+        long = "callgrind-custom-metrics",
+        num_args = 1,
+        verbatim_doc_comment,
+        value_parser = parse_callgrind_custom_metrics,
+        env = "IAI_CALLGRIND_CALLGRIND_CUSTOM_METRICS",
+        display_order = 700
+    )]
+    pub callgrind_custom_metrics: Option<IndexMap<String, String>>,
+
+    #[rustfmt::skip]
+    /// Show the N hottest instructions of the benchmark executable with their disassembly
+    ///
+    /// The instructions are sorted by their exclusive costs in descending order. Hot instructions
+    /// which are close to each other in the same function are shown together with the
+    /// surrounding instructions, so for example the whole inner loop is visible. This option
+    /// implies `--dump-instr=yes` and requires `objdump` to show the disassembly. Only the
+    /// instructions of the benchmark executable itself are considered.
+    ///
+    /// If no value is specified, the 10 hottest instructions are shown. A value of `0` disables
+    /// the hot instructions even if configured in the benchmark file.
+    ///
+    /// Examples:
+    ///   * --callgrind-hot-instructions (show the 10 hottest instructions)
+    ///   * --callgrind-hot-instructions=20 (show the 20 hottest instructions)
+    #[arg(
+        long = "callgrind-hot-instructions",
+        default_missing_value = "10",
+        num_args = 0..=1,
+        require_equals = true,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CALLGRIND_HOT_INSTRUCTIONS",
+        display_order = 700
+    )]
+    pub callgrind_hot_instructions: Option<usize>,
+
+    #[rustfmt::skip]
+    /// Show the N hottest source lines below the callgrind metrics
+    ///
+    /// The source lines are sorted by their exclusive costs (the costs of the line itself
+    /// without the costs of any called functions) in descending order. Only source lines within
+    /// the project root are shown. The costs of all source lines outside the project root, for
+    /// example of dependencies and the standard library, are folded into a single entry.
+    ///
+    /// If no value is specified, the 10 hottest source lines are shown. A value of `0` disables
+    /// the hot lines even if configured in the benchmark file.
+    ///
+    /// Examples:
+    ///   * --callgrind-hot-lines (show the 10 hottest lines)
+    ///   * --callgrind-hot-lines=20 (show the 20 hottest lines)
+    #[arg(
+        long = "callgrind-hot-lines",
+        default_missing_value = "10",
+        num_args = 0..=1,
+        require_equals = true,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CALLGRIND_HOT_LINES",
+        display_order = 700
+    )]
+    pub callgrind_hot_lines: Option<usize>,
+
     #[rustfmt::skip]
     #[allow(clippy::doc_markdown)]
     /// Set performance regression limits for specific `EventKinds`
@@ -397,6 +543,71 @@ pub struct CommandLineArgs {
     )]
     pub callgrind_metrics: Option<IndexSet<EventKind>>,
 
+    #[rustfmt::skip]
+    /// Fail the benchmarks if their metrics differ from the committed snapshot files
+    ///
+    /// The metrics of each benchmark are compared with the metrics recorded in the snapshot file
+    /// `benches/snapshots/<bench file>/<group>/<function>[.<id>].toml` in the package directory.
+    /// A benchmark fails if a metric differs by more than the --snapshot-tolerance, if the
+    /// recorded metrics differ from the metrics of the benchmark run or if the snapshot file does
+    /// not exist. Snapshot files are created and updated with --update-snapshots.
+    #[arg(
+        long = "check-snapshots",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        conflicts_with = "update_snapshots",
+        env = "IAI_CALLGRIND_CHECK_SNAPSHOTS",
+        display_order = 200
+    )]
+    pub check_snapshots: bool,
+
+    #[rustfmt::skip]
+    /// Select a profile of the `iai-callgrind.toml` configuration files
+    ///
+    /// The configuration files are searched in the workspace root and in the package directory of
+    /// the benchmark. The settings of the `[profile.<NAME>]` table are applied on top of the
+    /// top-level settings of these files. It is an error if no configuration file contains the
+    /// profile. See the guide for all details about the configuration file.
+    ///
+    /// Examples:
+    ///   * --config-profile=ci
+    #[arg(
+        long = "config-profile",
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CONFIG_PROFILE",
+        display_order = 100
+    )]
+    pub config_profile: Option<String>,
+
+    #[rustfmt::skip]
+    /// The weights of the custom cycles estimation of callgrind
+    ///
+    /// The `Custom Cycles` (`CustomCycles`) are calculated like the `Estimated Cycles` but with
+    /// the latencies of the target hardware: The `L1hits`, `LLhits` and `RamHits` are multiplied
+    /// with their weight and the mispredicted conditional branches (`Bcm`) and indirect branches
+    /// (`Bim`) with their penalty. This is a `,`-separated list of `metric=weight` pairs. Missing
+    /// weights default to the weights of the `Estimated Cycles` (L1hits=1, LLhits=5, RamHits=35)
+    /// and no penalties (Bcm=0, Bim=0). Setting a cycles model switches on the cache simulation
+    /// of callgrind and the branch simulation if a penalty is set. The cycles model specified
+    /// here takes precedence over the cycles model in the benchmarks.
+    ///
+    /// Examples:
+    ///   * --cycles-model='LLhits=12,RamHits=200'
+    ///   * --cycles-model='L1hits=1,LLhits=10,RamHits=100,Bcm=15,Bim=20'
+    #[arg(
+        long = "cycles-model",
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CYCLES_MODEL",
+        display_order = 500
+    )]
+    pub cycles_model: Option<CyclesModel>,
+
     #[rustfmt::skip]
     /// The default tool used to run the benchmarks
     ///
@@ -567,10 +778,32 @@ pub struct CommandLineArgs {
     #[rustfmt::skip]
     /// If specified, only run benches containing this string in their names
     ///
-    /// Note that a benchmark name might differ from the benchmark file name.
+    /// Note that a benchmark name might differ from the benchmark file name. Use
+    /// `param:<name>=<value>` instead to run only the benchmarks with a parameter <name> of the
+    /// benchmark function which has the value <value>, for example `param:size=1000`.
     #[arg(name = "BENCHNAME", num_args = 0..=1, env = "IAI_CALLGRIND_FILTER")]
     pub filter: Option<BenchmarkFilter>,
 
+    #[rustfmt::skip]
+    /// Create a missing baseline of a git commit before comparing against it
+    ///
+    /// If the baseline of --baseline=git:<ref> doesn't exist for the benchmark file, the commit is
+    /// checked out in a git worktree and the same benchmark file is run there with `cargo bench`
+    /// and --save-baseline to create it. The worktree and its own cargo target directory are kept
+    /// in the target directory of iai-callgrind, so each commit is checked out and built only once.
+    #[arg(
+        long = "git-baseline-create",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_GIT_BASELINE_CREATE",
+        display_order = 200
+    )]
+    pub git_baseline_create: bool,
+
     #[rustfmt::skip]
     /// The command-line arguments to pass through to Helgrind
     ///
@@ -653,6 +886,7 @@ pub struct CommandLineArgs {
     #[clap(
         id = "LOAD_BASELINE",
         long = "load-baseline",
+        value_parser = parse_baseline_name,
         requires = "baseline",
         num_args = 0..=1,
         require_equals = true,
@@ -822,10 +1056,51 @@ pub struct CommandLineArgs {
     )]
     pub regression_fail_fast: Option<bool>,
 
+    #[rustfmt::skip]
+    /// Overwrite the regression policy of all benchmarks
+    ///
+    /// The regression policy is usually configured per benchmark or group in the benchmark file.
+    /// Possible values are:
+    ///
+    /// - enforce:   Regressions are reported and fail the benchmark run (the default)
+    /// - warn-only: Regressions are reported but don't fail the benchmark run
+    /// - ignore:    No regression checks are performed
+    #[arg(
+        long = "regression-policy",
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_REGRESSION_POLICY",
+        display_order = 600
+    )]
+    pub regression_policy: Option<RegressionPolicy>,
+
+    #[rustfmt::skip]
+    /// Run each callgrind benchmark N times to measure the noise of the metrics
+    ///
+    /// The spread of each metric over all runs is stored next to the output files and with a
+    /// saved baseline. The soft limits of the callgrind regression checks are widened to the
+    /// larger noise of the new run and the baseline, so only changes exceeding the observed
+    /// noise are reported as regression. The metrics of the last run are shown. Values smaller
+    /// than `2` disable the repeated runs even if configured in the benchmark file.
+    ///
+    /// Examples:
+    ///   * --repeat=5
+    #[arg(
+        long = "repeat",
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_REPEAT",
+        display_order = 700
+    )]
+    pub repeat: Option<usize>,
+
     #[rustfmt::skip]
     /// Compare against this baseline if present and then overwrite it
+    ///
+    /// Like --baseline, this argument accepts a git reference as `git:<ref>`
     #[arg(
         long = "save-baseline",
+        value_parser = parse_baseline_name,
         default_missing_value = "default",
         num_args = 0..=1,
         require_equals = true,
@@ -938,6 +1213,22 @@ pub struct CommandLineArgs {
     )]
     pub show_only_comparison: Option<bool>,
 
+    #[rustfmt::skip]
+    /// The tolerance in percent within which metrics match the snapshot with --check-snapshots
+    ///
+    /// Negative tolerance values are converted to their absolute value. (Default: 0)
+    ///
+    /// Examples:
+    /// * --snapshot-tolerance=0.5 (metrics may differ by up to `0.5%`)
+    #[arg(
+        long = "snapshot-tolerance",
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_SNAPSHOT_TOLERANCE",
+        display_order = 200
+    )]
+    pub snapshot_tolerance: Option<f64>,
+
     #[rustfmt::skip]
     /// Show changes only when they are above the `tolerance` level
     ///
@@ -1012,6 +1303,25 @@ pub struct CommandLineArgs {
     )]
     pub truncate_description: Option<TruncateDescription>,
 
+    #[rustfmt::skip]
+    /// Create or update the snapshot files with the metrics of the benchmarks
+    ///
+    /// Only the metrics shown in the terminal output (see for example --callgrind-metrics) are
+    /// recorded in the snapshot files. The snapshot files are meant to be committed and are
+    /// checked with --check-snapshots.
+    #[arg(
+        long = "update-snapshots",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_UPDATE_SNAPSHOTS",
+        display_order = 200
+    )]
+    pub update_snapshots: bool,
+
     #[rustfmt::skip]
     /// The command-line arguments to pass through to all tools
     ///
@@ -1036,9 +1346,25 @@ pub struct CommandLineArgs {
 
 impl BenchmarkFilter {
     /// Return true if the haystack contains the filter
+    ///
+    /// A [`BenchmarkFilter::Parameter`] doesn't filter by name and always returns true.
     pub fn apply(&self, haystack: &str) -> bool {
-        let Self::Name(name) = self;
-        haystack.contains(name)
+        match self {
+            Self::Name(name) => haystack.contains(name),
+            Self::Parameter(..) => true,
+        }
+    }
+
+    /// Return true if any of the `parameters` matches the filter
+    ///
+    /// A [`BenchmarkFilter::Name`] doesn't filter by parameters and always returns true.
+    pub fn apply_parameters(&self, parameters: &[Parameter]) -> bool {
+        match self {
+            Self::Name(_) => true,
+            Self::Parameter(name, value) => parameters
+                .iter()
+                .any(|p| p.name == *name && p.value.to_string() == *value),
+        }
     }
 }
 
@@ -1046,7 +1372,19 @@ impl FromStr for BenchmarkFilter {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::Name(s.to_owned()))
+        if let Some(parameter) = s.strip_prefix("param:") {
+            match parameter.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => Ok(Self::Parameter(
+                    name.trim().to_owned(),
+                    value.trim().to_owned(),
+                )),
+                _ => Err(format!(
+                    "Invalid parameter filter '{s}': Expected the format 'param:<name>=<value>'"
+                )),
+            }
+        } else {
+            Ok(Self::Name(s.to_owned()))
+        }
     }
 }
 
@@ -1098,14 +1436,25 @@ fn convert_metric<T: Display + TypeChecker + Copy>(
 }
 
 /// This function parses a space separated list of raw argument strings into [`crate::api::RawArgs`]
-fn parse_args(value: &str) -> Result<RawArgs, String> {
+pub fn parse_args(value: &str) -> Result<RawArgs, String> {
     shlex::split(value)
         .ok_or_else(|| "Failed to split args".to_owned())
         .map(RawArgs::new)
 }
 
+/// Parse the baseline name resolving git references (`git:<ref>`) to a commit baseline
+fn parse_baseline_name(value: &str) -> Result<BaselineName, String> {
+    if let Some(reference) = value.strip_prefix(git::REF_PREFIX) {
+        git::resolve(reference)
+            .map(|commit| git::baseline_name(&commit))
+            .map_err(|error| error.to_string())
+    } else {
+        value.parse()
+    }
+}
+
 /// Same as `parse_callgrind_limits` but for cachegrind
-fn parse_cachegrind_limits(value: &str) -> Result<ToolRegressionConfig, String> {
+pub fn parse_cachegrind_limits(value: &str) -> Result<ToolRegressionConfig, String> {
     let (soft_limits, hard_limits) = parse_limits(value, |key, metric| {
         let metrics = key
             .parse::<CachegrindMetrics>()
@@ -1126,7 +1475,7 @@ fn parse_cachegrind_limits(value: &str) -> Result<ToolRegressionConfig, String>
 }
 
 /// Parse the cachegrind metrics
-fn parse_cachegrind_metrics(value: &str) -> Result<IndexSet<CachegrindMetric>, String> {
+pub fn parse_cachegrind_metrics(value: &str) -> Result<IndexSet<CachegrindMetric>, String> {
     parse_tool_metrics(value, |item| {
         item.parse::<CachegrindMetrics>()
             .map(IndexSet::from)
@@ -1134,6 +1483,25 @@ fn parse_cachegrind_metrics(value: &str) -> Result<IndexSet<CachegrindMetric>, S
     })
 }
 
+/// Parse and verify the `name=expression` pairs of the custom metrics of callgrind
+///
+/// The expressions are verified but stored as strings, so they can be merged with the custom
+/// metrics of the benchmarks.
+pub fn parse_callgrind_custom_metrics(value: &str) -> Result<IndexMap<String, String>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (name, expression) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid format of name=expression pair: '{item}'"))?;
+            custom_metrics::parse(name, expression)
+                .map(|(name, _)| (name, expression.trim().to_owned()))
+        })
+        .collect()
+}
+
 /// Parse the callgrind limits from the command-line
 ///
 /// This method (and the other `parse_dhat_limits`, ...) parses soft and hard limits in one go. The
@@ -1141,7 +1509,7 @@ fn parse_cachegrind_metrics(value: &str) -> Result<IndexSet<CachegrindMetric>, S
 ///
 /// In order to avoid back and forth conversions between `api::ToolRegressionConfig` and
 /// `tool::ToolRegressionConfig` we parse the `tool::ToolRegressionConfig` directly.
-fn parse_callgrind_limits(value: &str) -> Result<ToolRegressionConfig, String> {
+pub fn parse_callgrind_limits(value: &str) -> Result<ToolRegressionConfig, String> {
     let (soft_limits, hard_limits) = parse_limits(value, |key, metric| {
         let metrics = key
             .parse::<CallgrindMetrics>()
@@ -1162,7 +1530,7 @@ fn parse_callgrind_limits(value: &str) -> Result<ToolRegressionConfig, String> {
 }
 
 /// Parse the callgrind metrics
-fn parse_callgrind_metrics(value: &str) -> Result<IndexSet<EventKind>, String> {
+pub fn parse_callgrind_metrics(value: &str) -> Result<IndexSet<EventKind>, String> {
     parse_tool_metrics(value, |item| {
         item.parse::<CallgrindMetrics>()
             .map(IndexSet::from)
@@ -1171,7 +1539,7 @@ fn parse_callgrind_metrics(value: &str) -> Result<IndexSet<EventKind>, String> {
 }
 
 /// Same as `parse_callgrind_limits` but for dhat
-fn parse_dhat_limits(value: &str) -> Result<ToolRegressionConfig, String> {
+pub fn parse_dhat_limits(value: &str) -> Result<ToolRegressionConfig, String> {
     let (soft_limits, hard_limits) = parse_limits(value, |key, metric| {
         let metrics = key
             .parse::<DhatMetrics>()
@@ -1192,7 +1560,7 @@ fn parse_dhat_limits(value: &str) -> Result<ToolRegressionConfig, String> {
 }
 
 /// Parse the DHAT metrics
-fn parse_dhat_metrics(value: &str) -> Result<IndexSet<DhatMetric>, String> {
+pub fn parse_dhat_metrics(value: &str) -> Result<IndexSet<DhatMetric>, String> {
     parse_tool_metrics(value, |item| {
         item.parse::<DhatMetrics>()
             .map(IndexSet::from)
@@ -1201,7 +1569,7 @@ fn parse_dhat_metrics(value: &str) -> Result<IndexSet<DhatMetric>, String> {
 }
 
 /// Parse the DRD metrics as error metrics
-fn parse_drd_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
+pub fn parse_drd_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
     parse_tool_metrics(value, parse_error_metrics)
 }
 
@@ -1224,7 +1592,7 @@ fn parse_error_metrics(item: &str) -> Result<IndexSet<ErrorMetric>, String> {
 }
 
 /// Parse the helgrind metrics as error metrics
-fn parse_helgrind_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
+pub fn parse_helgrind_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
     parse_tool_metrics(value, parse_error_metrics)
 }
 
@@ -1279,7 +1647,7 @@ fn parse_limits<T: Eq + Hash>(
 }
 
 /// Parse the memcheck metrics as error metrics
-fn parse_memcheck_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
+pub fn parse_memcheck_metrics(value: &str) -> Result<IndexSet<ErrorMetric>, String> {
     parse_tool_metrics(value, parse_error_metrics)
 }
 
@@ -1325,7 +1693,8 @@ fn parse_tool_metrics<T: Eq + Hash>(
     Ok(format)
 }
 
-fn parse_truncate_description(value: &str) -> Result<TruncateDescription, String> {
+/// Parse the value of --truncate-description
+pub fn parse_truncate_description(value: &str) -> Result<TruncateDescription, String> {
     // Almost the same as the BoolishValueParser but without `1` and `0` which are interpreted as
     // values. The FALSE_LITERALS also contain `none` as special value.
     const TRUE_LITERALS: [&str; 5] = ["y", "yes", "t", "true", "on"];
@@ -1350,7 +1719,7 @@ mod tests {
 
     use super::*;
     use crate::api::EventKind::*;
-    use crate::api::RawArgs;
+    use crate::api::{ParameterValue, RawArgs};
 
     #[rstest]
     #[case::empty("", &[])]
@@ -1513,6 +1882,49 @@ mod tests {
         assert_eq!(result.save_summary, Some(expected));
     }
 
+    #[rstest]
+    #[case::enforce("enforce", RegressionPolicy::Enforce)]
+    #[case::warn_only("warn-only", RegressionPolicy::WarnOnly)]
+    #[case::ignore("ignore", RegressionPolicy::Ignore)]
+    fn test_regression_policy_cli(#[case] value: &str, #[case] expected: RegressionPolicy) {
+        let result = CommandLineArgs::parse_from([format!("--regression-policy={value}")]);
+        assert_eq!(result.regression_policy, Some(expected));
+    }
+
+    #[rstest]
+    #[case::default("", 10)]
+    #[case::zero("0", 0)]
+    #[case::some_value("20", 20)]
+    fn test_callgrind_hot_instructions_cli(#[case] value: &str, #[case] expected: usize) {
+        let result = if value.is_empty() {
+            CommandLineArgs::parse_from(["--callgrind-hot-instructions".to_owned()])
+        } else {
+            CommandLineArgs::parse_from([format!("--callgrind-hot-instructions={value}")])
+        };
+        assert_eq!(result.callgrind_hot_instructions, Some(expected));
+    }
+
+    #[rstest]
+    #[case::default("", 10)]
+    #[case::zero("0", 0)]
+    #[case::some_value("20", 20)]
+    fn test_callgrind_hot_lines_cli(#[case] value: &str, #[case] expected: usize) {
+        let result = if value.is_empty() {
+            CommandLineArgs::parse_from(["--callgrind-hot-lines".to_owned()])
+        } else {
+            CommandLineArgs::parse_from([format!("--callgrind-hot-lines={value}")])
+        };
+        assert_eq!(result.callgrind_hot_lines, Some(expected));
+    }
+
+    #[rstest]
+    #[case::zero("0", 0)]
+    #[case::some_value("5", 5)]
+    fn test_repeat_cli(#[case] value: &str, #[case] expected: usize) {
+        let result = CommandLineArgs::parse_from([format!("--repeat={value}")]);
+        assert_eq!(result.repeat, Some(expected));
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_allow_aslr_env() {
@@ -1555,6 +1967,33 @@ mod tests {
         assert_eq!(result.separate_targets, expected);
     }
 
+    #[rstest]
+    #[case::check(&["--check-snapshots"], true, false, None)]
+    #[case::check_no(&["--check-snapshots=no"], false, false, None)]
+    #[case::check_with_tolerance(
+        &["--check-snapshots", "--snapshot-tolerance=0.5"],
+        true,
+        false,
+        Some(0.5)
+    )]
+    #[case::update(&["--update-snapshots"], false, true, None)]
+    fn test_snapshots_cli(
+        #[case] args: &[&str],
+        #[case] check: bool,
+        #[case] update: bool,
+        #[case] tolerance: Option<f64>,
+    ) {
+        let result = CommandLineArgs::parse_from(args);
+        assert_eq!(result.check_snapshots, check);
+        assert_eq!(result.update_snapshots, update);
+        assert_eq!(result.snapshot_tolerance, tolerance);
+    }
+
+    #[test]
+    fn test_snapshots_cli_when_check_and_update() {
+        CommandLineArgs::try_parse_from(["--check-snapshots", "--update-snapshots"]).unwrap_err();
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_home_env() {
@@ -1598,6 +2037,74 @@ mod tests {
         assert_eq!(result.nocapture, NoCapture::True);
     }
 
+    #[rstest]
+    #[case::single("server", &[CacheModel::Server])]
+    #[case::ignore_case("Embedded", &[CacheModel::Embedded])]
+    #[case::multiple(
+        "desktop,server,embedded",
+        &[CacheModel::Desktop, CacheModel::Server, CacheModel::Embedded]
+    )]
+    fn test_cache_model_cli(#[case] value: &str, #[case] expected: &[CacheModel]) {
+        let actual = CommandLineArgs::parse_from([format!("--cache-model={value}")]);
+        assert_eq!(actual.cache_models, expected);
+    }
+
+    #[test]
+    fn test_cache_model_cli_when_unknown_then_error() {
+        CommandLineArgs::try_parse_from(["--cache-model=laptop"]).unwrap_err();
+    }
+
+    #[rstest]
+    #[case::single("ipc=Ir / EstimatedCycles", &[("ipc", "Ir / EstimatedCycles")])]
+    #[case::multiple(
+        " a = (Bcm + Bim) / (Bc + Bi) * 100 ,b=Dr+Dw",
+        &[("a", "(Bcm + Bim) / (Bc + Bi) * 100"), ("b", "Dr+Dw")]
+    )]
+    #[case::duplicate("a=Ir,a=Dr", &[("a", "Dr")])]
+    fn test_callgrind_custom_metrics_cli(#[case] value: &str, #[case] expected: &[(&str, &str)]) {
+        let expected = expected
+            .iter()
+            .map(|(n, e)| ((*n).to_owned(), (*e).to_owned()))
+            .collect::<IndexMap<_, _>>();
+
+        let actual = CommandLineArgs::parse_from([format!("--callgrind-custom-metrics={value}")]);
+        assert_eq!(actual.callgrind_custom_metrics, Some(expected));
+    }
+
+    #[rstest]
+    #[case::missing_expression("ipc")]
+    #[case::invalid_name("i-p-c=Ir")]
+    #[case::invalid_expression("ipc=Ir /")]
+    #[case::unknown_metric("ipc=Ir / Unknown")]
+    fn test_callgrind_custom_metrics_cli_when_invalid_then_error(#[case] value: &str) {
+        CommandLineArgs::try_parse_from([format!("--callgrind-custom-metrics={value}")])
+            .unwrap_err();
+    }
+
+    #[rstest]
+    #[case::default("", CyclesModel::default())]
+    #[case::single("RamHits=100", CyclesModel { ram_hits: 100, ..Default::default() })]
+    #[case::ignore_case_and_whitespace(
+        " llhits = 12 , BCM=15",
+        CyclesModel { ll_hits: 12, bcm: 15, ..Default::default() }
+    )]
+    #[case::all(
+        "L1hits=2,LLhits=10,RamHits=100,Bcm=15,Bim=20",
+        CyclesModel { bcm: 15, bim: 20, l1_hits: 2, ll_hits: 10, ram_hits: 100 }
+    )]
+    fn test_cycles_model_cli(#[case] value: &str, #[case] expected: CyclesModel) {
+        let actual = CommandLineArgs::parse_from([format!("--cycles-model={value}")]);
+        assert_eq!(actual.cycles_model, Some(expected));
+    }
+
+    #[rstest]
+    #[case::unknown_metric("Ir=1")]
+    #[case::invalid_weight("RamHits=1.5")]
+    #[case::missing_weight("RamHits")]
+    fn test_cycles_model_cli_when_invalid_then_error(#[case] value: &str) {
+        CommandLineArgs::try_parse_from([format!("--cycles-model={value}")]).unwrap_err();
+    }
+
     #[rstest]
     #[case::single("drd", &[ValgrindTool::DRD])]
     #[case::two("drd,callgrind", &[ValgrindTool::DRD, ValgrindTool::Callgrind])]
@@ -1919,6 +2426,67 @@ mod tests {
         assert_eq!(result.show_grid, Some(true));
     }
 
+    #[rstest]
+    #[case::name("bench", BenchmarkFilter::Name("bench".to_owned()))]
+    #[case::name_with_colon("some:bench", BenchmarkFilter::Name("some:bench".to_owned()))]
+    #[case::parameter(
+        "param:size=10",
+        BenchmarkFilter::Parameter("size".to_owned(), "10".to_owned())
+    )]
+    #[case::parameter_with_whitespace(
+        "param: size = 10 ",
+        BenchmarkFilter::Parameter("size".to_owned(), "10".to_owned())
+    )]
+    #[case::parameter_empty_value(
+        "param:name=",
+        BenchmarkFilter::Parameter("name".to_owned(), String::new())
+    )]
+    fn test_benchmark_filter_from_str(#[case] input: &str, #[case] expected: BenchmarkFilter) {
+        assert_eq!(input.parse::<BenchmarkFilter>().unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::missing_equals("param:size")]
+    #[case::missing_name("param:=10")]
+    fn test_benchmark_filter_from_str_when_invalid(#[case] input: &str) {
+        input.parse::<BenchmarkFilter>().unwrap_err();
+    }
+
+    #[rstest]
+    #[case::name_always_matches("bench", &[], true)]
+    #[case::int("param:size=10", &[("size", ParameterValue::Int(10))], true)]
+    #[case::float("param:factor=1.5", &[("factor", ParameterValue::Float(1.5))], true)]
+    #[case::bool("param:flag=true", &[("flag", ParameterValue::Bool(true))], true)]
+    #[case::string(
+        "param:name=some",
+        &[("name", ParameterValue::String("some".to_owned()))],
+        true
+    )]
+    #[case::any_parameter(
+        "param:size=10",
+        &[("other", ParameterValue::Int(10)), ("size", ParameterValue::Int(10))],
+        true
+    )]
+    #[case::different_value("param:size=10", &[("size", ParameterValue::Int(100))], false)]
+    #[case::different_name("param:size=10", &[("other", ParameterValue::Int(10))], false)]
+    #[case::no_parameters("param:size=10", &[], false)]
+    fn test_benchmark_filter_apply_parameters(
+        #[case] filter: &str,
+        #[case] parameters: &[(&str, ParameterValue)],
+        #[case] expected: bool,
+    ) {
+        let filter = filter.parse::<BenchmarkFilter>().unwrap();
+        let parameters = parameters
+            .iter()
+            .map(|(name, value)| Parameter {
+                name: (*name).to_owned(),
+                value: value.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(filter.apply_parameters(&parameters), expected);
+    }
+
     #[rstest]
     #[case::missing_value("--truncate-description", TruncateDescription::To(50))]
     #[case::some_value("--truncate-description=20", TruncateDescription::To(20))]
@@ -0,0 +1,1053 @@
+//! Module containing the management, export and import of named baselines
+//!
+//! The output files of a named baseline are stored below the iai-callgrind home directory (per
+//! default `target/iai`) and have the baseline name in their file name (`*.base@<name>*`) as
+//! described in [`crate::runner::tool::path::ToolOutputPath`] and the flamegraph output. An export
+//! packages all these files into a single tar archive together with a [`Manifest`]. The import
+//! unpacks the archive into the home directory again, so a baseline survives a `cargo clean` or
+//! can be cached and restored on a fresh CI runner.
+//!
+//! Each benchmark run records the benchmarks of the benchmark file in an [`INDEX_FILE`], so
+//! baselines of benchmarks which were removed from the benchmark groups can be pruned.
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+use super::common::ModulePath;
+use super::envs;
+use super::summary::BaselineName;
+use super::tool::fingerprint::Fingerprint;
+use crate::util::command_output;
+
+/// The prefix of the baseline name in the file names of baseline output files
+const BASELINE_PREFIX: &str = "base@";
+
+/// The name of the file listing the benchmarks of a benchmark file
+pub const INDEX_FILE: &str = "benchmarks.json";
+
+/// The name of the manifest file in the archive
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The version of the manifest format
+pub const MANIFEST_VERSION: &str = "1";
+
+/// The output files of a named baseline of a single benchmark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    /// The module path of the benchmark directory relative to the home directory
+    pub benchmark: String,
+    /// The time of the last modification of any of the files
+    pub created: SystemTime,
+    /// The paths of the files relative to the home directory
+    pub files: Vec<PathBuf>,
+    /// The name of the baseline
+    pub name: String,
+}
+
+/// The command-line arguments of `iai-callgrind-runner baseline`
+#[derive(Debug, Parser)]
+#[command(
+    name = "baseline",
+    bin_name = "iai-callgrind-runner baseline",
+    about = "Manage, export and import named baselines",
+    version
+)]
+pub struct BaselineArgs {
+    /// The baseline subcommand
+    #[command(subcommand)]
+    pub command: BaselineCommand,
+}
+
+/// The subcommands of `iai-callgrind-runner baseline`
+#[derive(Debug, Subcommand)]
+pub enum BaselineCommand {
+    /// Delete all output files of a baseline
+    Delete {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The name of the baseline
+        name: BaselineName,
+    },
+    /// Export all output files of a baseline into a tar archive
+    Export {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The name of the baseline
+        name: BaselineName,
+        /// The path of the archive
+        #[arg(short = 'o', long = "output")]
+        output: PathBuf,
+    },
+    /// Import the output files of a baseline from a tar archive
+    Import {
+        /// The path of the archive
+        archive: PathBuf,
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Replace the files of an existing baseline with the same name
+        #[arg(long = "force")]
+        force: bool,
+    },
+    /// List all baselines with the number of benchmarks and the time of the last update
+    List {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+    /// Delete the baselines of benchmarks which don't exist anymore
+    ///
+    /// The benchmarks of a benchmark file are recorded each time the benchmark file is run. Only
+    /// the baselines of benchmark files which were run at least once with this version of
+    /// iai-callgrind are pruned.
+    Prune {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Only print the baselines which would be deleted
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Rename a baseline
+    Rename {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The name of the baseline
+        name: BaselineName,
+        /// The new name of the baseline
+        new_name: BaselineName,
+    },
+    /// Show the benchmarks of a baseline with their creation time and fingerprint
+    Show {
+        /// The options shared by all subcommands
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The name of the baseline
+        name: BaselineName,
+    },
+}
+
+/// The command-line arguments shared by all subcommands of `iai-callgrind-runner baseline`
+#[derive(Debug, Args)]
+pub struct CommonArgs {
+    /// The home directory of the benchmark output files (Default: `$CARGO_TARGET_DIR/iai`)
+    #[arg(long = "home", env = "IAI_CALLGRIND_HOME")]
+    pub home: Option<PathBuf>,
+}
+
+/// The benchmarks of a benchmark file as recorded in the [`INDEX_FILE`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    /// The directories of the benchmarks relative to the directory of the index file
+    pub benchmarks: Vec<PathBuf>,
+}
+
+/// The manifest describing the content and origin of a baseline archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The name of the baseline
+    pub baseline: String,
+    /// The module paths of the benchmarks (relative to the home directory) with output files
+    pub benchmarks: Vec<String>,
+    /// The git commit of the project at the time of the export if available
+    pub git_commit: Option<String>,
+    /// The output of `rustc --version` if available
+    pub rustc_version: Option<String>,
+    /// The output of `valgrind --version` if available
+    pub valgrind_version: Option<String>,
+    /// The version of the manifest format
+    pub version: String,
+}
+
+impl Manifest {
+    /// Create a new `Manifest` of the baseline `name` with the `files` relative to the home
+    /// directory
+    ///
+    /// The git commit and the versions of rustc and valgrind are queried from the environment.
+    pub fn new(name: &BaselineName, files: &[PathBuf]) -> Self {
+        let mut benchmarks = files
+            .iter()
+            .filter_map(|file| file.parent())
+            .map(to_module_path)
+            .collect::<Vec<_>>();
+        benchmarks.sort();
+        benchmarks.dedup();
+
+        let rustc = std::env::var_os(envs::RUSTC).unwrap_or_else(|| OsString::from("rustc"));
+        Self {
+            baseline: name.to_string(),
+            benchmarks,
+            git_commit: command_output(OsStr::new("git"), &["rev-parse", "HEAD"]),
+            rustc_version: command_output(&rustc, &["--version"]),
+            valgrind_version: command_output(OsStr::new("valgrind"), &["--version"]),
+            version: MANIFEST_VERSION.to_owned(),
+        }
+    }
+}
+
+/// Return the relative paths of all files of the baseline `name` in the `home` directory
+///
+/// The paths are sorted and relative to the `home` directory.
+pub fn baseline_files(home: &Path, name: &BaselineName) -> Result<Vec<PathBuf>> {
+    let name = name.to_string();
+    let mut files = baselines(home)?
+        .into_iter()
+        .filter(|baseline| baseline.name == name)
+        .flat_map(|baseline| baseline.files)
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
+}
+
+/// Return all [`Baseline`]s in the `home` directory sorted by name and benchmark
+pub fn baselines(home: &Path) -> Result<Vec<Baseline>> {
+    let mut files = vec![];
+    if home.is_dir() {
+        walk(home, &mut files)?;
+    }
+
+    let mut grouped: BTreeMap<(String, String), Vec<PathBuf>> = BTreeMap::new();
+    for path in files {
+        let Some(name) = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .and_then(baseline_name)
+            .map(ToOwned::to_owned)
+        else {
+            continue;
+        };
+        let path = path
+            .strip_prefix(home)
+            .map(Path::to_path_buf)
+            .expect("The file should be below the home directory");
+        let benchmark = path.parent().map_or_else(String::new, to_module_path);
+        grouped.entry((name, benchmark)).or_default().push(path);
+    }
+
+    grouped
+        .into_iter()
+        .map(|((name, benchmark), mut files)| {
+            files.sort();
+            let mut created = SystemTime::UNIX_EPOCH;
+            for file in &files {
+                let modified = std::fs::metadata(home.join(file))
+                    .and_then(|metadata| metadata.modified())
+                    .with_context(|| format!("Failed to query metadata of '{}'", file.display()))?;
+                created = created.max(modified);
+            }
+            Ok(Baseline {
+                benchmark,
+                created,
+                files,
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Delete all files of the baseline `name` in the `home` directory
+///
+/// Returns the deleted [`Baseline`]s of each benchmark.
+pub fn delete(home: &Path, name: &BaselineName) -> Result<Vec<Baseline>> {
+    let name = name.to_string();
+    let deleted = baselines(home)?
+        .into_iter()
+        .filter(|baseline| baseline.name == name)
+        .collect::<Vec<_>>();
+    if deleted.is_empty() {
+        return Err(anyhow!(
+            "No output files of baseline '{name}' found in '{}'",
+            home.display()
+        ));
+    }
+
+    for baseline in &deleted {
+        remove(home, baseline)?;
+    }
+
+    Ok(deleted)
+}
+
+/// Export all files of the baseline `name` in the `home` directory to the tar archive `output`
+///
+/// The [`Manifest`] is the first entry of the archive.
+pub fn export(home: &Path, name: &BaselineName, output: &Path) -> Result<Manifest> {
+    let files = baseline_files(home, name)?;
+    if files.is_empty() {
+        return Err(anyhow!(
+            "No output files of baseline '{name}' found in '{}'",
+            home.display()
+        ));
+    }
+
+    let manifest = Manifest::new(name, &files);
+    let bytes =
+        serde_json::to_vec_pretty(&manifest).with_context(|| "Failed to serialize manifest")?;
+
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create archive '{}'", output.display()))?;
+    let mut builder = tar::Builder::new(file);
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_FILE, bytes.as_slice())?;
+
+    for file in &files {
+        builder
+            .append_path_with_name(home.join(file), file)
+            .with_context(|| format!("Failed to add '{}' to the archive", file.display()))?;
+    }
+    builder.into_inner()?.flush()?;
+
+    Ok(manifest)
+}
+
+/// Import the baseline of the tar `archive` into the `home` directory
+///
+/// Fails if a baseline with the same name already exists unless `force` is true, in which case all
+/// files of the existing baseline are removed first. Returns the [`Manifest`] of the archive.
+pub fn import(home: &Path, archive: &Path, force: bool) -> Result<Manifest> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive '{}'", archive.display()))?;
+    let mut archive = tar::Archive::new(file);
+    let mut entries = archive.entries()?;
+
+    let manifest: Manifest = match entries.next() {
+        Some(entry) => {
+            let mut entry = entry?;
+            if entry.path()?.as_ref() != Path::new(MANIFEST_FILE) {
+                return Err(anyhow!(
+                    "Invalid baseline archive: Expected '{MANIFEST_FILE}' as first entry"
+                ));
+            }
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            serde_json::from_str(&content).with_context(|| "Failed to parse manifest")?
+        }
+        None => return Err(anyhow!("Invalid baseline archive: The archive is empty")),
+    };
+    if manifest.version != MANIFEST_VERSION {
+        return Err(anyhow!(
+            "Unsupported manifest version '{}': Requires version '{MANIFEST_VERSION}'",
+            manifest.version
+        ));
+    }
+
+    let existing = baselines(home)?
+        .into_iter()
+        .filter(|baseline| baseline.name == manifest.baseline)
+        .collect::<Vec<_>>();
+    if !existing.is_empty() {
+        if !force {
+            return Err(anyhow!(
+                "A baseline with the name '{}' already exists in '{}'. Use --force to replace it",
+                manifest.baseline,
+                home.display()
+            ));
+        }
+        for baseline in &existing {
+            remove(home, baseline)?;
+        }
+    }
+
+    std::fs::create_dir_all(home)
+        .with_context(|| format!("Failed to create directory '{}'", home.display()))?;
+    for entry in entries {
+        let mut entry = entry?;
+        // `unpack_in` refuses to write outside of the home directory
+        if !entry.unpack_in(home)? {
+            return Err(anyhow!(
+                "Invalid baseline archive: Refusing to unpack '{}' outside of '{}'",
+                entry.path()?.display(),
+                home.display()
+            ));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Delete the baselines of benchmarks which are not listed in the [`INDEX_FILE`] of their
+/// benchmark file
+///
+/// Baselines of benchmark files without an index file are kept. If `dry_run` is true, nothing is
+/// deleted. Returns the stale [`Baseline`]s.
+pub fn prune(home: &Path, dry_run: bool) -> Result<Vec<Baseline>> {
+    let mut files = vec![];
+    if home.is_dir() {
+        walk(home, &mut files)?;
+    }
+
+    let mut indexed = vec![];
+    let mut benchmarks = vec![];
+    for path in files
+        .iter()
+        .filter(|path| path.file_name() == Some(OsStr::new(INDEX_FILE)))
+    {
+        let dir = path
+            .parent()
+            .expect("An index file should have a parent directory");
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read index file '{}'", path.display()))?;
+        let index: Index = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse index file '{}'", path.display()))?;
+
+        indexed.push(dir.to_path_buf());
+        benchmarks.extend(index.benchmarks.iter().map(|benchmark| dir.join(benchmark)));
+    }
+
+    let stale = baselines(home)?
+        .into_iter()
+        .filter(|baseline| {
+            let dir = baseline
+                .files
+                .first()
+                .and_then(|file| file.parent())
+                .map(|dir| home.join(dir))
+                .expect("A baseline should have at least one file");
+            indexed.iter().any(|index_dir| dir.starts_with(index_dir)) && !benchmarks.contains(&dir)
+        })
+        .collect::<Vec<_>>();
+
+    if !dry_run {
+        for baseline in &stale {
+            remove(home, baseline)?;
+        }
+    }
+
+    Ok(stale)
+}
+
+/// Rename the baseline `name` in the `home` directory to `new_name`
+///
+/// Fails if there are no files of the baseline `name` or if the baseline `new_name` already
+/// exists. Returns the renamed [`Baseline`]s with their old names.
+pub fn rename(home: &Path, name: &BaselineName, new_name: &BaselineName) -> Result<Vec<Baseline>> {
+    let name = name.to_string();
+    let new_name = new_name.to_string();
+
+    let all = baselines(home)?;
+    if all.iter().any(|baseline| baseline.name == new_name) {
+        return Err(anyhow!(
+            "A baseline with the name '{new_name}' already exists in '{}'",
+            home.display()
+        ));
+    }
+    let renamed = all
+        .into_iter()
+        .filter(|baseline| baseline.name == name)
+        .collect::<Vec<_>>();
+    if renamed.is_empty() {
+        return Err(anyhow!(
+            "No output files of baseline '{name}' found in '{}'",
+            home.display()
+        ));
+    }
+
+    for file in renamed.iter().flat_map(|baseline| &baseline.files) {
+        let file_name = file
+            .file_name()
+            .and_then(OsStr::to_str)
+            .expect("A baseline file should have a valid file name");
+        let index = file_name
+            .rfind(BASELINE_PREFIX)
+            .expect("A baseline file name should contain the baseline prefix")
+            + BASELINE_PREFIX.len();
+        let new_file_name = format!(
+            "{}{new_name}{}",
+            &file_name[..index],
+            &file_name[index + name.len()..]
+        );
+
+        let source = home.join(file);
+        let dest = source.with_file_name(new_file_name);
+        std::fs::rename(&source, &dest).with_context(|| {
+            format!(
+                "Failed to move '{}' to '{}'",
+                source.display(),
+                dest.display()
+            )
+        })?;
+    }
+
+    Ok(renamed)
+}
+
+/// Run the `iai-callgrind-runner baseline` subcommand with the command-line `args`
+///
+/// The first argument is expected to be `baseline`.
+#[allow(clippy::too_many_lines)]
+pub fn run<I, T>(args: I) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    match BaselineArgs::parse_from(args).command {
+        BaselineCommand::Delete { common, name } => {
+            let deleted = delete(&resolve_home(common.home)?, &name)?;
+            println!("Deleted baseline '{name}' of {} benchmarks", deleted.len());
+        }
+        BaselineCommand::Export {
+            common,
+            name,
+            output,
+        } => {
+            let home = resolve_home(common.home)?;
+            let manifest = export(&home, &name, &output)?;
+            println!(
+                "Exported baseline '{name}' of {} benchmarks to '{}'",
+                manifest.benchmarks.len(),
+                output.display()
+            );
+        }
+        BaselineCommand::Import {
+            archive,
+            common,
+            force,
+        } => {
+            let home = resolve_home(common.home)?;
+            let manifest = import(&home, &archive, force)?;
+            println!(
+                "Imported baseline '{}' of {} benchmarks into '{}'",
+                manifest.baseline,
+                manifest.benchmarks.len(),
+                home.display()
+            );
+        }
+        BaselineCommand::List { common } => {
+            let now = SystemTime::now();
+            let mut summaries: BTreeMap<String, (usize, SystemTime)> = BTreeMap::new();
+            for baseline in baselines(&resolve_home(common.home)?)? {
+                let (benchmarks, created) = summaries
+                    .entry(baseline.name)
+                    .or_insert((0, SystemTime::UNIX_EPOCH));
+                *benchmarks += 1;
+                *created = (*created).max(baseline.created);
+            }
+
+            for (name, (benchmarks, created)) in summaries {
+                println!(
+                    "{name}: {benchmarks} benchmarks, last updated {}",
+                    format_age(now, created)
+                );
+            }
+        }
+        BaselineCommand::Prune { common, dry_run } => {
+            let stale = prune(&resolve_home(common.home)?, dry_run)?;
+            for baseline in &stale {
+                println!("{}: {}", baseline.name, baseline.benchmark);
+            }
+            println!(
+                "{} {} stale baselines",
+                if dry_run { "Would prune" } else { "Pruned" },
+                stale.len()
+            );
+        }
+        BaselineCommand::Rename {
+            common,
+            name,
+            new_name,
+        } => {
+            let renamed = rename(&resolve_home(common.home)?, &name, &new_name)?;
+            println!(
+                "Renamed baseline '{name}' of {} benchmarks to '{new_name}'",
+                renamed.len()
+            );
+        }
+        BaselineCommand::Show { common, name } => {
+            let home = resolve_home(common.home)?;
+            let now = SystemTime::now();
+            let baselines = baselines(&home)?
+                .into_iter()
+                .filter(|baseline| baseline.name == name.to_string())
+                .collect::<Vec<_>>();
+            if baselines.is_empty() {
+                return Err(anyhow!(
+                    "No output files of baseline '{name}' found in '{}'",
+                    home.display()
+                ));
+            }
+
+            println!("Baseline '{name}': {} benchmarks", baselines.len());
+            for baseline in baselines {
+                println!(
+                    "{}: {} files, created {}",
+                    baseline.benchmark,
+                    baseline.files.len(),
+                    format_age(now, baseline.created)
+                );
+                for (tool, fingerprint) in fingerprints(&home, &baseline)? {
+                    let unknown = || "unknown".to_owned();
+                    println!(
+                        "  {tool}: {}, {}, {}",
+                        fingerprint.valgrind_version.unwrap_or_else(unknown),
+                        fingerprint.rustc_version.unwrap_or_else(unknown),
+                        fingerprint.target
+                    );
+                    println!("  {tool} arguments: {}", fingerprint.tool_args.join(" "));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Record the directories of all benchmarks of the benchmark file with the `module_path` in its
+/// [`INDEX_FILE`]
+///
+/// The `target_dir` is supposed to be the same as [`crate::runner::meta::Metadata::target_dir`]
+/// and the `benchmark_dirs` the directories of the
+/// [`crate::runner::tool::path::ToolOutputPath`]s.
+pub fn write_index(
+    target_dir: &Path,
+    module_path: &ModulePath,
+    benchmark_dirs: &[PathBuf],
+) -> Result<()> {
+    let dir = target_dir.join(module_path.components().iter().collect::<PathBuf>());
+    let index = Index {
+        benchmarks: benchmark_dirs
+            .iter()
+            .filter_map(|benchmark_dir| benchmark_dir.strip_prefix(&dir).ok())
+            .map(Path::to_path_buf)
+            .collect(),
+    };
+
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+    let path = dir.join(INDEX_FILE);
+    let content = serde_json::to_string_pretty(&index)?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write index file '{}'", path.display()))
+}
+
+/// Return the name of the baseline if the `file_name` is the name of an output file of a baseline
+///
+/// Baseline names consist of ascii alphanumeric characters and `_` only, so the name ends at the
+/// first other character like in `callgrind.bench.out.base@name.#1234` or
+/// `callgrind.bench.Ir.flamegraph.base@name.svg`.
+fn baseline_name(file_name: &str) -> Option<&str> {
+    let rest = &file_name[file_name.rfind(BASELINE_PREFIX)? + BASELINE_PREFIX.len()..];
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+/// Return the [`Fingerprint`]s of the tools of the `baseline`
+fn fingerprints(home: &Path, baseline: &Baseline) -> Result<Vec<(String, Fingerprint)>> {
+    let mut fingerprints = vec![];
+    for file in &baseline.files {
+        let Some((tool, _)) = file
+            .file_name()
+            .and_then(OsStr::to_str)
+            .filter(|file_name| file_name.contains(".fingerprint.base@"))
+            .and_then(|file_name| file_name.split_once('.'))
+        else {
+            continue;
+        };
+        fingerprints.push((tool.to_owned(), Fingerprint::read(&home.join(file))?));
+    }
+
+    Ok(fingerprints)
+}
+
+/// Format the age of the `time` relative to `now` like `5 minutes ago`
+fn format_age(now: SystemTime, time: SystemTime) -> String {
+    let seconds = now.duration_since(time).unwrap_or(Duration::ZERO).as_secs();
+    let (amount, unit) = match seconds {
+        0..=59 => return "just now".to_owned(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+
+    let mut age = format!("{amount} {unit}");
+    if amount > 1 {
+        age.push('s');
+    }
+    write!(age, " ago").unwrap();
+    age
+}
+
+/// Remove all files of the `baseline` and the benchmark directory if it is empty afterwards
+fn remove(home: &Path, baseline: &Baseline) -> Result<()> {
+    for file in &baseline.files {
+        let path = home.join(file);
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove '{}'", path.display()))?;
+    }
+
+    if let Some(dir) = baseline.files.first().and_then(|file| file.parent()) {
+        let dir = home.join(dir);
+        if std::fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_none()) {
+            std::fs::remove_dir(&dir)
+                .with_context(|| format!("Failed to remove directory '{}'", dir.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the `home` directory if present or the default home directory `$CARGO_TARGET_DIR/iai`
+fn resolve_home(home: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(home) = home {
+        return Ok(home);
+    }
+
+    let target_dir = if let Some(target_dir) = std::env::var_os(envs::CARGO_TARGET_DIR) {
+        PathBuf::from(target_dir)
+    } else {
+        cargo_metadata::MetadataCommand::new()
+            .no_deps()
+            .exec()
+            .with_context(|| "Failed to query the target directory of the cargo workspace")?
+            .target_directory
+            .into_std_path_buf()
+    };
+
+    Ok(target_dir.join("iai"))
+}
+
+/// Convert a `dir` relative to the home directory into a module path like `pkg::bench::group`
+fn to_module_path(dir: &Path) -> String {
+    dir.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Collect all files in the `dir` recursively
+///
+/// Hidden directories like the [`crate::runner::git::WORKTREES_DIR`] don't contain any benchmark
+/// output files and are skipped.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory '{}'", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            if !path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|name| name.starts_with('.'))
+            {
+                walk(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn write(home: &Path, path: &str) {
+        let path = home.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "content").unwrap();
+    }
+
+    #[rstest]
+    #[case::out("callgrind.bench.out.base@main", Some("main"))]
+    #[case::out_pid("callgrind.bench.out.base@main.#1234", Some("main"))]
+    #[case::log("callgrind.bench.log.base@main", Some("main"))]
+    #[case::bbv("exp-bbv.bench.bb.out.base@main.#1234.1", Some("main"))]
+    #[case::flamegraph("callgrind.bench.Ir.flamegraph.base@main.svg", Some("main"))]
+    #[case::fingerprint("callgrind.bench.fingerprint.base@main", Some("main"))]
+    #[case::longer_name("callgrind.bench.out.base@main_2", Some("main_2"))]
+    #[case::old("callgrind.bench.out.old", None)]
+    #[case::new("callgrind.bench.out", None)]
+    #[case::index("benchmarks.json", None)]
+    fn test_baseline_name(#[case] file_name: &str, #[case] expected: Option<&str>) {
+        assert_eq!(baseline_name(file_name), expected);
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        let name: BaselineName = "main".parse().unwrap();
+
+        write(
+            source.path(),
+            "pkg/bench/group/func/callgrind.func.out.base@main",
+        );
+        write(
+            source.path(),
+            "pkg/bench/group/func/callgrind.func.log.base@main",
+        );
+        write(source.path(), "pkg/bench/group/func/callgrind.func.out");
+        write(
+            source.path(),
+            "pkg/bench/group/other/callgrind.other.out.base@dev",
+        );
+
+        let manifest = export(source.path(), &name, archive.path()).unwrap();
+        assert_eq!(manifest.baseline, "main");
+        assert_eq!(
+            manifest.benchmarks,
+            vec!["pkg::bench::group::func".to_owned()]
+        );
+
+        let imported = import(dest.path(), archive.path(), false).unwrap();
+        assert_eq!(imported, manifest);
+        assert_eq!(
+            baseline_files(dest.path(), &name).unwrap(),
+            baseline_files(source.path(), &name).unwrap()
+        );
+        assert!(!dest
+            .path()
+            .join("pkg/bench/group/func/callgrind.func.out")
+            .exists());
+    }
+
+    #[test]
+    fn test_import_when_baseline_exists() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        let name: BaselineName = "main".parse().unwrap();
+
+        write(
+            source.path(),
+            "pkg/bench/group/func/callgrind.func.out.base@main",
+        );
+        export(source.path(), &name, archive.path()).unwrap();
+
+        write(
+            dest.path(),
+            "pkg/bench/group/func/callgrind.func.out.base@main",
+        );
+        write(
+            dest.path(),
+            "pkg/bench/group/removed/callgrind.removed.out.base@main",
+        );
+        write(
+            dest.path(),
+            "pkg/bench/group/func/callgrind.func.out.base@dev",
+        );
+
+        import(dest.path(), archive.path(), false).unwrap_err();
+        assert!(dest
+            .path()
+            .join("pkg/bench/group/removed/callgrind.removed.out.base@main")
+            .exists());
+
+        import(dest.path(), archive.path(), true).unwrap();
+        assert_eq!(
+            baseline_files(dest.path(), &name).unwrap(),
+            baseline_files(source.path(), &name).unwrap()
+        );
+        assert!(!dest.path().join("pkg/bench/group/removed").exists());
+        assert!(dest
+            .path()
+            .join("pkg/bench/group/func/callgrind.func.out.base@dev")
+            .exists());
+    }
+
+    #[rstest]
+    #[case::unsupported_version("2", "pkg/callgrind.func.out.base@main")]
+    #[case::outside_of_home("1", "../callgrind.func.out.base@main")]
+    fn test_import_when_invalid_archive(#[case] version: &str, #[case] path: &str) {
+        let home = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+
+        let manifest = Manifest {
+            version: version.to_owned(),
+            ..Manifest::new(&"main".parse().unwrap(), &[PathBuf::from(path)])
+        };
+        let bytes = serde_json::to_vec(&manifest).unwrap();
+
+        let mut builder = tar::Builder::new(File::create(archive.path()).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, MANIFEST_FILE, bytes.as_slice())
+            .unwrap();
+
+        // `set_path` rejects `..`, so the name is written into the header directly
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(7);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, b"content".as_slice()).unwrap();
+        builder.into_inner().unwrap().flush().unwrap();
+
+        import(home.path(), archive.path(), false).unwrap_err();
+        assert!(!home
+            .path()
+            .parent()
+            .unwrap()
+            .join("callgrind.func.out.base@main")
+            .exists());
+    }
+
+    #[rstest]
+    #[case::just_now(59, "just now")]
+    #[case::minute(60, "1 minute ago")]
+    #[case::minutes(3599, "59 minutes ago")]
+    #[case::hours(7200, "2 hours ago")]
+    #[case::days(86400 * 3, "3 days ago")]
+    fn test_format_age(#[case] seconds: u64, #[case] expected: &str) {
+        let now = SystemTime::now();
+        assert_eq!(
+            format_age(now, now - Duration::from_secs(seconds)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_baselines() {
+        let home = tempfile::tempdir().unwrap();
+        write(home.path(), "pkg/bench/group/b/callgrind.b.out.base@main");
+        write(home.path(), "pkg/bench/group/a/callgrind.a.log.base@main");
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@main");
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@dev");
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out");
+
+        let actual = baselines(home.path())
+            .unwrap()
+            .into_iter()
+            .map(|baseline| (baseline.name, baseline.benchmark, baseline.files.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                ("dev".to_owned(), "pkg::bench::group::a".to_owned(), 1),
+                ("main".to_owned(), "pkg::bench::group::a".to_owned(), 2),
+                ("main".to_owned(), "pkg::bench::group::b".to_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_baselines_skips_hidden_directories() {
+        let home = tempfile::tempdir().unwrap();
+        write(
+            home.path(),
+            "pkg/bench/group/func/callgrind.func.out.base@main",
+        );
+        write(
+            home.path(),
+            "pkg/.git-worktrees/0123456789ab/worktree/callgrind.func.out.base@main",
+        );
+
+        let baselines = baselines(home.path()).unwrap();
+        assert_eq!(baselines.len(), 1);
+        assert_eq!(baselines[0].benchmark, "pkg::bench::group::func");
+    }
+
+    #[test]
+    fn test_delete() {
+        let home = tempfile::tempdir().unwrap();
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@main");
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@dev");
+        write(home.path(), "pkg/bench/group/b/callgrind.b.out.base@main");
+
+        let deleted = delete(home.path(), &"main".parse().unwrap()).unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert!(!home.path().join("pkg/bench/group/b").exists());
+        assert_eq!(
+            baseline_files(home.path(), &"dev".parse().unwrap()).unwrap(),
+            vec![PathBuf::from("pkg/bench/group/a/callgrind.a.out.base@dev")]
+        );
+        delete(home.path(), &"main".parse().unwrap()).unwrap_err();
+    }
+
+    #[test]
+    fn test_rename() {
+        let home = tempfile::tempdir().unwrap();
+        write(
+            home.path(),
+            "pkg/bench/group/a/callgrind.a.out.base@main.#12",
+        );
+        write(
+            home.path(),
+            "pkg/bench/group/a/callgrind.a.Ir.flamegraph.base@main.svg",
+        );
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@dev");
+
+        let main = "main".parse().unwrap();
+        rename(home.path(), &main, &"dev".parse().unwrap()).unwrap_err();
+        rename(home.path(), &main, &"feature".parse().unwrap()).unwrap();
+
+        assert_eq!(
+            baseline_files(home.path(), &"feature".parse().unwrap()).unwrap(),
+            vec![
+                PathBuf::from("pkg/bench/group/a/callgrind.a.Ir.flamegraph.base@feature.svg"),
+                PathBuf::from("pkg/bench/group/a/callgrind.a.out.base@feature.#12"),
+            ]
+        );
+        assert!(baseline_files(home.path(), &main).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune() {
+        let home = tempfile::tempdir().unwrap();
+        write(home.path(), "pkg/bench/group/a/callgrind.a.out.base@main");
+        write(
+            home.path(),
+            "pkg/bench/group/removed/callgrind.removed.out.base@main",
+        );
+        write(home.path(), "pkg/bench/group/removed/callgrind.removed.out");
+        write(home.path(), "pkg/other/group/c/callgrind.c.out.base@main");
+        write_index(
+            &home.path().join("pkg"),
+            &ModulePath::new("bench"),
+            &[home.path().join("pkg/bench/group/a")],
+        )
+        .unwrap();
+
+        let stale = prune(home.path(), true).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].benchmark, "pkg::bench::group::removed");
+        assert!(home.path().join("pkg/bench/group/removed").exists());
+
+        prune(home.path(), false).unwrap();
+        assert_eq!(
+            baseline_files(home.path(), &"main".parse().unwrap()).unwrap(),
+            vec![
+                PathBuf::from("pkg/bench/group/a/callgrind.a.out.base@main"),
+                PathBuf::from("pkg/other/group/c/callgrind.c.out.base@main"),
+            ]
+        );
+        assert!(home
+            .path()
+            .join("pkg/bench/group/removed/callgrind.removed.out")
+            .exists());
+    }
+
+    #[test]
+    fn test_export_when_no_files() {
+        let source = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+
+        export(source.path(), &"main".parse().unwrap(), archive.path()).unwrap_err();
+    }
+}
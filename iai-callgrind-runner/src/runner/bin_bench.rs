@@ -21,15 +21,22 @@ use std::{panic, thread};
 use anyhow::{anyhow, Context, Result};
 use log::{debug, warn};
 
+use super::baseline;
+use super::callgrind::noise;
 use super::common::{Assistant, AssistantKind, Baselines, BenchmarkSummaries, Config, ModulePath};
+use super::compare::{self, CompareLimit};
+use super::config_file::Settings;
 use super::format::{BinaryBenchmarkHeader, OutputFormat};
 use super::meta::Metadata;
+use super::snapshot::{self, SnapshotMode};
 use super::summary::{BaselineKind, BaselineName, BenchmarkKind, BenchmarkSummary, SummaryOutput};
 use super::tool::config::ToolConfigs;
+use super::tool::fingerprint;
 use super::tool::path::{ToolOutputPath, ToolOutputPathKind};
 use super::tool::run::RunOptions;
 use crate::api::{
-    self, BinaryBenchmarkConfig, BinaryBenchmarkGroups, DelayKind, EntryPoint, Stdin, ValgrindTool,
+    self, BinaryBenchmarkConfig, BinaryBenchmarkGroups, DelayKind, EntryPoint, Parameter,
+    RegressionPolicy, Stdin, Throughput, ValgrindTool,
 };
 use crate::error::Error;
 use crate::runner::format;
@@ -56,8 +63,14 @@ pub struct BinBench {
     pub module_path: ModulePath,
     /// The [`OutputFormat`]
     pub output_format: OutputFormat,
+    /// The structured [`Parameter`]s of the benchmark function
+    pub parameters: Vec<Parameter>,
+    /// The [`RegressionPolicy`] of this benchmark
+    pub regression_policy: RegressionPolicy,
     /// The [`RunOptions`]
     pub run_options: RunOptions,
+    /// The amount of bytes or elements processed by the benchmark if declared
+    pub throughput: Option<Throughput>,
     /// The tool configurations for this benchmark run
     pub tools: ToolConfigs,
 }
@@ -90,6 +103,7 @@ pub struct Delay {
 struct Group {
     benches: Vec<BinBench>,
     compare_by_id: bool,
+    compare_limits: Vec<CompareLimit>,
     /// The module path so far which should be `file_name::group_name`
     module_path: ModulePath,
     /// This name is the name from the `library_benchmark_group!` macro
@@ -168,7 +182,10 @@ impl Benchmark for BaselineBenchmark {
         out_path.init()?;
 
         for path in bin_bench.tools.output_paths(&out_path) {
+            path.init()?;
             path.shift()?;
+            fingerprint::shift(&path)?;
+            noise::shift(&path)?;
             if path.kind == ToolOutputPathKind::Out {
                 path.to_log_output().shift()?;
             }
@@ -210,6 +227,7 @@ impl BinBench {
     fn new(
         id: Option<String>,
         display: Option<String>,
+        parameters: Vec<Parameter>,
         module_path: ModulePath,
         function_name: String,
         has_setup: bool,
@@ -217,6 +235,7 @@ impl BinBench {
         meta: &Metadata,
         group: &Group,
         config: BinaryBenchmarkConfig,
+        settings: &Settings,
         group_index: usize,
         bench_index: usize,
         iter_index: Option<usize>,
@@ -261,7 +280,7 @@ impl BinBench {
             .map_or_else(OutputFormat::default, Into::into);
         output_format.kind = meta.args.output_format;
 
-        let tool_configs = ToolConfigs::new(
+        let mut tool_configs = ToolConfigs::new(
             &mut output_format,
             config.tools,
             &module_path,
@@ -271,11 +290,21 @@ impl BinBench {
             &EntryPoint::None,
             &config.valgrind_args,
             &HashMap::default(),
+            settings,
         )
         .map_err(|error| {
             Error::ConfigurationError(module_path.clone(), id.clone(), error.to_string())
         })?;
 
+        let regression_policy = meta
+            .args
+            .regression_policy
+            .or(config.regression_policy)
+            .unwrap_or_default();
+        if regression_policy == RegressionPolicy::Ignore {
+            tool_configs.clear_regression_configs();
+        }
+
         let setup = has_setup.then_some(Assistant::new_bench_assistant(
             AssistantKind::Setup,
             &group.name,
@@ -302,7 +331,10 @@ impl BinBench {
         Ok(Self {
             id,
             display,
+            parameters,
             function_name,
+            regression_policy,
+            throughput: config.throughput,
             tools: tool_configs,
             run_options: RunOptions {
                 env_clear: config.env_clear.unwrap_or(defaults::ENV_CLEAR),
@@ -348,7 +380,7 @@ impl BinBench {
             None
         };
 
-        Ok(BenchmarkSummary::new(
+        let mut benchmark_summary = BenchmarkSummary::new(
             BenchmarkKind::BinaryBenchmark,
             config.meta.project_root.clone(),
             config.package_dir.clone(),
@@ -360,7 +392,12 @@ impl BinBench {
             description,
             summary_output,
             baselines,
-        ))
+        );
+        benchmark_summary.throughput = self.throughput;
+        benchmark_summary.regression_policy = self.regression_policy;
+        benchmark_summary.parameters.clone_from(&self.parameters);
+
+        Ok(benchmark_summary)
     }
 }
 
@@ -521,6 +558,7 @@ impl Group {
 
         let mut summaries: HashMap<String, Vec<BenchmarkSummary>> =
             HashMap::with_capacity(self.benches.len());
+        let snapshot_mode = SnapshotMode::from_args(&config.meta.args);
         for bench in &self.benches {
             let fail_fast = bench
                 .tools
@@ -528,21 +566,38 @@ impl Group {
                 .iter()
                 .any(|c| c.regression_config.is_fail_fast());
 
-            let summary = benchmark.run(bench, config, self)?;
+            let is_compared = self.compare_by_id && bench.output_format.is_default();
+
+            let mut summary = benchmark.run(bench, config, self)?;
+            if let Some(id) = summary.id.clone() {
+                if let Some(sums) = summaries.get(&id) {
+                    if is_compared {
+                        for sum in sums {
+                            sum.compare_and_print(&id, &summary, &bench.output_format)?;
+                        }
+                    }
+                    if bench.regression_policy != RegressionPolicy::Ignore {
+                        compare::check_and_print(&self.compare_limits, &mut summary, sums);
+                    }
+                }
+            }
+            if let Some(mode) = snapshot_mode {
+                snapshot::check_or_update(
+                    mode,
+                    &config.package_dir,
+                    &self.module_path,
+                    &bench.name(),
+                    &bench.output_format,
+                    &mut summary,
+                )?;
+            }
             summary.print_and_save(&config.meta.args.output_format)?;
             summary.check_regression(fail_fast)?;
 
             benchmark_summaries.add_summary(summary.clone());
-            if self.compare_by_id && bench.output_format.is_default() {
-                if let Some(id) = &summary.id {
-                    if let Some(sums) = summaries.get_mut(id) {
-                        for sum in sums.iter() {
-                            sum.compare_and_print(id, &summary, &bench.output_format)?;
-                        }
-                        sums.push(summary);
-                    } else {
-                        summaries.insert(id.clone(), vec![summary]);
-                    }
+            if is_compared || !self.compare_limits.is_empty() {
+                if let Some(id) = summary.id.clone() {
+                    summaries.entry(id).or_default().push(summary);
                 }
             }
         }
@@ -595,6 +650,11 @@ impl Groups {
                 compare_by_id: binary_benchmark_group
                     .compare_by_id
                     .unwrap_or(defaults::COMPARE_BY_ID),
+                compare_limits: binary_benchmark_group
+                    .compare_limits
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
             };
 
             for (group_index, binary_benchmark_benches) in binary_benchmark_group
@@ -609,9 +669,23 @@ impl Groups {
                         .module_path
                         .join(&binary_benchmark_bench.function_name);
 
+                    if meta.args.filter.as_ref().is_some_and(|filter| {
+                        !filter.apply_parameters(&binary_benchmark_bench.parameters)
+                    }) {
+                        continue;
+                    }
+
+                    // The configuration files are the lowest level of the configuration
+                    let settings = meta
+                        .config_file
+                        .resolve(&module_path, binary_benchmark_bench.id.as_deref());
+                    let file_config = settings.to_binary_benchmark_config();
+
                     match &binary_benchmark_bench.command {
                         api::CommandKind::Default(command) => {
-                            let config = group_config.clone().update_from_all([
+                            let config = file_config.update_from_all([
+                                Some(&global_config),
+                                binary_benchmark_group.config.as_ref(),
                                 binary_benchmark_benches.config.as_ref(),
                                 binary_benchmark_bench.config.as_ref(),
                                 Some(&command.config),
@@ -620,6 +694,7 @@ impl Groups {
                             let bin_bench = BinBench::new(
                                 binary_benchmark_bench.id,
                                 binary_benchmark_bench.args,
+                                binary_benchmark_bench.parameters,
                                 module_path,
                                 binary_benchmark_bench.function_name,
                                 binary_benchmark_bench.has_setup,
@@ -627,6 +702,7 @@ impl Groups {
                                 meta,
                                 &group,
                                 config,
+                                &settings,
                                 group_index,
                                 bench_index,
                                 None,
@@ -647,7 +723,9 @@ impl Groups {
                                 }
                                 _ => {
                                     for (iter_index, command) in commands.iter().enumerate() {
-                                        let config = group_config.clone().update_from_all([
+                                        let config = file_config.clone().update_from_all([
+                                            Some(&global_config),
+                                            binary_benchmark_group.config.as_ref(),
                                             binary_benchmark_benches.config.as_ref(),
                                             binary_benchmark_bench.config.as_ref(),
                                             Some(&command.config),
@@ -656,6 +734,7 @@ impl Groups {
                                         let bin_bench = BinBench::new(
                                             binary_benchmark_bench.id.clone(),
                                             binary_benchmark_bench.args.clone(),
+                                            binary_benchmark_bench.parameters.clone(),
                                             module_path.clone(),
                                             binary_benchmark_bench.function_name.clone(),
                                             binary_benchmark_bench.has_setup,
@@ -663,6 +742,7 @@ impl Groups {
                                             meta,
                                             &group,
                                             config,
+                                            &settings,
                                             group_index,
                                             bench_index,
                                             Some(iter_index),
@@ -822,6 +902,24 @@ impl Runner {
     }
 
     fn run(&self) -> Result<BenchmarkSummaries> {
+        let benchmark_dirs = self
+            .groups
+            .0
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(move |bin_bench| {
+                    self.benchmark
+                        .output_path(bin_bench, &self.config, group)
+                        .dir
+                })
+            })
+            .collect::<Vec<_>>();
+        baseline::write_index(
+            &self.config.meta.target_dir,
+            &self.config.module_path,
+            &benchmark_dirs,
+        )?;
+
         if let Some(setup) = &self.setup {
             setup.run(&self.config, &self.config.module_path)?;
         }
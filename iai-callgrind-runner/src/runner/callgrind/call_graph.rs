@@ -0,0 +1,638 @@
+//! Module containing the export of the callgrind call graph to DOT and pruned callgrind files
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+
+use super::hashmap_parser::{make_path, CallgrindMap, HashMapParser, Id, SourcePath};
+use super::model::Metrics;
+use super::parser::{CallgrindParser, Sentinel};
+use crate::api::{self, EventKind};
+use crate::runner::tool::path::{ToolOutputPath, ToolOutputPathKind};
+use crate::util::percentage_diff;
+
+/// The exported callgrind `CallGraph`
+#[derive(Debug, Clone)]
+pub struct CallGraph {
+    /// The [`Config`]
+    pub config: Config,
+}
+
+/// The main configuration for the call graph export
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The event kind used to calculate the costs of the nodes and edges
+    pub event_kind: EventKind,
+    /// Nodes and edges below this percentage of the total inclusive costs are not shown
+    pub node_threshold: f64,
+    /// If true, write callgrind output files pruned to the entry point subtree
+    pub prune: bool,
+}
+
+/// The files created by the [`CallGraph`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallGraphFiles {
+    /// The path to the DOT file
+    pub dot: Option<PathBuf>,
+    /// The paths to the pruned callgrind output files
+    pub pruned: Vec<PathBuf>,
+}
+
+impl CallGraph {
+    /// Create a new `CallGraph`
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Create the DOT file and the pruned callgrind output files if configured
+    ///
+    /// The nodes in the DOT file are colored by the difference of their inclusive costs to the
+    /// baseline if a baseline is present. Otherwise, the nodes are colored by their inclusive
+    /// costs.
+    pub fn create(
+        &self,
+        tool_output_path: &ToolOutputPath,
+        sentinel: Option<&Sentinel>,
+        project_root: &Path,
+    ) -> Result<CallGraphFiles> {
+        let mut files = CallGraphFiles::default();
+        if !tool_output_path.exists() {
+            return Ok(files);
+        }
+
+        let parser = HashMapParser {
+            project_root: project_root.to_owned(),
+            sentinel: sentinel.cloned(),
+        };
+
+        let maps = parser.parse(tool_output_path)?;
+        let base_path = tool_output_path.to_base_path();
+        // With `--save-baseline` the base path is the output path itself
+        let base_map = if base_path != *tool_output_path && base_path.exists() {
+            total_map(parser.parse(&base_path)?.into_iter().map(|(_, _, map)| map))
+        } else {
+            None
+        };
+
+        if self.config.prune && sentinel.is_some() {
+            for (path, _, map) in &maps {
+                if let Some(reachable) = reachable(map) {
+                    let dest = pruned_path(path);
+                    prune(path, &dest, &reachable, project_root)?;
+                    debug!("Pruned callgrind output written to '{}'", dest.display());
+                    files.pruned.push(dest);
+                }
+            }
+        }
+
+        if let Some(map) = total_map(maps.into_iter().map(|(_, _, map)| map)) {
+            let dot = self.to_dot(&map, base_map.as_ref());
+            let path = self.dot_path(tool_output_path);
+            let mut writer = BufWriter::new(
+                File::create(&path)
+                    .with_context(|| format!("Failed creating dot file '{}'", path.display()))?,
+            );
+            writer
+                .write_all(dot.as_bytes())
+                .and_then(|()| writer.flush())
+                .with_context(|| format!("Failed writing dot file '{}'", path.display()))?;
+
+            info!("Call graph written to '{}'", path.display());
+            files.dot = Some(path);
+        }
+
+        Ok(files)
+    }
+
+    /// Return the path to the DOT file of this `ToolOutputPath`
+    pub fn dot_path(&self, tool_output_path: &ToolOutputPath) -> PathBuf {
+        let suffix = match &tool_output_path.kind {
+            ToolOutputPathKind::BaseOut(name) => format!(".base@{name}"),
+            _ => String::new(),
+        };
+        tool_output_path.dir.join(format!(
+            "{}.total.{}.callgraph{suffix}.dot",
+            tool_output_path.prefix(),
+            self.config.event_kind.to_name()
+        ))
+    }
+
+    /// Convert the `map` to the DOT format
+    ///
+    /// If present, only the subtree of the entry point is shown. The total costs are the
+    /// inclusive costs of the entry point or, without entry point, the highest inclusive costs
+    /// of all functions.
+    #[allow(clippy::too_many_lines)]
+    pub fn to_dot(&self, map: &CallgrindMap, base: Option<&CallgrindMap>) -> String {
+        let event_kind = &self.config.event_kind;
+        let cost_of = |metrics: &Metrics| metrics.metric_by_kind(event_kind).map(f64::from);
+
+        let reachable = reachable(map);
+        let is_included = |id: &Id| {
+            reachable
+                .as_ref()
+                .map_or(true, |r| r.contains(&(id.obj.clone(), id.func.clone())))
+        };
+
+        let total = map
+            .sentinel_key
+            .as_ref()
+            .and_then(|key| map.map.get(key))
+            .and_then(|value| cost_of(&value.metrics))
+            .or_else(|| {
+                map.map
+                    .values()
+                    .filter_map(|value| cost_of(&value.metrics))
+                    .max_by(f64::total_cmp)
+            })
+            .filter(|total| *total > 0f64);
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph {{").unwrap();
+        writeln!(
+            dot,
+            "  graph [fontname=\"Arial\", nodesep=0.125, ranksep=0.25];"
+        )
+        .unwrap();
+        writeln!(
+            dot,
+            "  node [fontcolor=black, fontname=\"Arial\", shape=box, style=\"filled,rounded\"];"
+        )
+        .unwrap();
+        writeln!(dot, "  edge [fontname=\"Arial\"];").unwrap();
+
+        let Some(total) = total else {
+            writeln!(dot, "}}").unwrap();
+            return dot;
+        };
+
+        let percentage = |cost: f64| cost / total * 100f64;
+        let threshold = self.config.node_threshold;
+
+        let mut nodes = map
+            .map
+            .iter()
+            .filter(|(id, _)| is_included(id))
+            .filter_map(|(id, value)| value.metrics.metric_by_kind(event_kind).map(|m| (id, m)))
+            .filter(|(_, metric)| percentage(f64::from(*metric)) >= threshold)
+            .collect::<Vec<_>>();
+        nodes.sort_by(|(a_id, a_metric), (b_id, b_metric)| {
+            b_metric
+                .cmp(a_metric)
+                .then_with(|| a_id.func.cmp(&b_id.func))
+        });
+
+        let mut indices = HashMap::new();
+        for (index, (id, metric)) in nodes.iter().enumerate() {
+            indices.insert(*id, index);
+
+            let pct = percentage(f64::from(*metric));
+            let mut label = format!("{}\\n{pct:.2}%\\n({metric})", escape(&id.func));
+            let color = match base.map(|base| {
+                base.map
+                    .get(id)
+                    .map(|value| value.metrics.metric_by_kind(event_kind))
+            }) {
+                Some(Some(Some(old))) => {
+                    let diff = percentage_diff(*metric, old);
+                    write!(label, "\\n{diff:+.2}%").unwrap();
+                    diff_color(diff)
+                }
+                Some(None) => {
+                    write!(label, "\\n(new)").unwrap();
+                    diff_color(f64::INFINITY)
+                }
+                Some(Some(None)) | None => cost_color(pct),
+            };
+
+            writeln!(
+                dot,
+                "  n{index} [label=\"{label}\", fillcolor=\"{color}\", tooltip=\"{}\"];",
+                escape(&source_of(id))
+            )
+            .unwrap();
+        }
+
+        let mut edges = map
+            .calls
+            .iter()
+            .filter_map(|((caller, callee), call)| {
+                let from = indices.get(caller)?;
+                let to = indices.get(callee)?;
+                let cost = cost_of(&call.metrics)?;
+                (percentage(cost) >= threshold).then_some((*from, *to, cost, call.amount))
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by_key(|(from, to, _, _)| (*from, *to));
+
+        for (from, to, cost, amount) in edges {
+            let pct = percentage(cost);
+            writeln!(
+                dot,
+                "  n{from} -> n{to} [label=\"{pct:.2}%\\n{amount}x\", penwidth={:.2}];",
+                (pct / 10f64).clamp(1f64, 8f64)
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+impl From<api::CallGraphConfig> for Config {
+    fn from(value: api::CallGraphConfig) -> Self {
+        Self {
+            event_kind: value.event_kind.unwrap_or(EventKind::Ir),
+            node_threshold: value.node_threshold.unwrap_or(0.5f64),
+            prune: value.prune.unwrap_or(true),
+        }
+    }
+}
+
+/// The fill color of a node by its percentage of the total costs (from white to blue)
+fn cost_color(percentage: f64) -> String {
+    format!("0.600 {:.3} 1.000", (percentage / 100f64).clamp(0f64, 1f64))
+}
+
+/// The fill color of a node by the difference to the baseline
+///
+/// Regressions are colored red, improvements green. The saturation increases with the
+/// difference and reaches its maximum at a difference of 50%.
+fn diff_color(diff: f64) -> String {
+    let hue = if diff > 0f64 { 0f64 } else { 0.333f64 };
+    format!(
+        "{hue:.3} {:.3} 1.000",
+        (diff.abs() / 50f64).clamp(0f64, 1f64)
+    )
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Return true if the trimmed `line` is an empty line, a comment or a `key: value` line
+///
+/// The header of a callgrind output file consists only of these lines. Values like the `cmd:` of
+/// the header can contain a `=`, so the header can't be told apart from the body by a `=` alone.
+fn is_header_line(line: &str) -> bool {
+    line.is_empty()
+        || line.starts_with('#')
+        || line.split_once(':').is_some_and(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// Prune the callgrind output file at `path` to the `reachable` functions and write it to `dest`
+///
+/// The `totals:` of the pruned file are the summed up self costs of the remaining functions.
+fn prune(
+    path: &Path,
+    dest: &Path,
+    reachable: &HashSet<(Option<SourcePath>, String)>,
+    project_root: &Path,
+) -> Result<()> {
+    let reader = BufReader::new(
+        File::open(path).with_context(|| format!("Failed opening '{}'", path.display()))?,
+    );
+    let mut writer = BufWriter::new(
+        File::create(dest).with_context(|| format!("Failed creating '{}'", dest.display()))?,
+    );
+
+    let mut is_header = true;
+    let mut totals: Option<Metrics> = None;
+    let mut num_positions = 1;
+
+    let mut current_obj: Option<(String, SourcePath)> = None;
+    let mut current_file: Option<String> = None;
+    let mut keep = false;
+    let mut is_call_cost = false;
+
+    for line in reader.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("totals:") || trimmed.starts_with("summary:") {
+            continue;
+        }
+
+        if is_header {
+            if let Some(positions) = trimmed.strip_prefix("positions:") {
+                num_positions = positions.split_ascii_whitespace().count();
+            }
+            if let Some(events) = trimmed.strip_prefix("events:") {
+                totals = Some(Metrics::with_metric_kinds(
+                    events
+                        .split_ascii_whitespace()
+                        .filter_map(|e| e.parse::<EventKind>().ok())
+                        .map(|e| (e, 0)),
+                ));
+            }
+            if is_header_line(trimmed) {
+                writeln!(writer, "{line}")?;
+                continue;
+            }
+            is_header = false;
+        }
+
+        match trimmed.split_once('=') {
+            Some(("ob", obj)) => {
+                current_obj = Some((obj.to_owned(), make_path(project_root, obj)));
+            }
+            Some(("fl", file)) => current_file = Some(file.to_owned()),
+            Some(("fn", func)) => {
+                keep = reachable.contains(&(
+                    current_obj.as_ref().map(|(_, path)| path.clone()),
+                    func.to_owned(),
+                ));
+                if keep {
+                    if let Some((obj, _)) = &current_obj {
+                        writeln!(writer, "ob={obj}")?;
+                    }
+                    if let Some(file) = &current_file {
+                        writeln!(writer, "fl={file}")?;
+                    }
+                    writeln!(writer, "{line}")?;
+                }
+            }
+            Some(("calls", _)) if keep => {
+                is_call_cost = true;
+                writeln!(writer, "{line}")?;
+            }
+            None if keep && trimmed.starts_with(|c: char| c.is_ascii_digit()) => {
+                if is_call_cost {
+                    is_call_cost = false;
+                } else if let Some(totals) = totals.as_mut() {
+                    totals.add_iter_str(trimmed.split_ascii_whitespace().skip(num_positions))?;
+                } else {
+                    // do nothing
+                }
+                writeln!(writer, "{line}")?;
+            }
+            _ if keep => writeln!(writer, "{line}")?,
+            _ => {}
+        }
+    }
+
+    if let Some(totals) = totals {
+        let values = totals
+            .0
+            .values()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        writeln!(writer, "totals: {}", values.join(" "))?;
+    }
+
+    writer
+        .flush()
+        .with_context(|| format!("Failed writing '{}'", dest.display()))
+}
+
+/// The pruned output file is stored next to the original output file with a `.pruned` suffix
+fn pruned_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".pruned");
+    path.with_file_name(file_name)
+}
+
+/// Return the functions (object, function name) reachable from the entry point
+///
+/// Returns `None` if there is no entry point in this map.
+fn reachable(map: &CallgrindMap) -> Option<HashSet<(Option<SourcePath>, String)>> {
+    let root = map.sentinel_key.as_ref()?;
+
+    let mut callees = HashMap::<&Id, Vec<&Id>>::new();
+    for (caller, callee) in map.calls.keys() {
+        callees.entry(caller).or_default().push(callee);
+    }
+
+    // Functions with inlined code can have multiple ids with different files. To follow all
+    // calls, the ids are grouped by object and function name
+    let mut ids = HashMap::<(&Option<SourcePath>, &str), Vec<&Id>>::new();
+    for id in callees.keys() {
+        ids.entry((&id.obj, id.func.as_str())).or_default().push(id);
+    }
+
+    let mut reachable = HashSet::new();
+    let mut queue = VecDeque::from([root]);
+    while let Some(id) = queue.pop_front() {
+        if reachable.insert((id.obj.clone(), id.func.clone())) {
+            for same in ids.get(&(&id.obj, id.func.as_str())).into_iter().flatten() {
+                queue.extend(callees.get(same).into_iter().flatten());
+            }
+        }
+    }
+
+    Some(reachable)
+}
+
+fn source_of(id: &Id) -> String {
+    match &id.file {
+        Some(SourcePath::Rust(path) | SourcePath::Relative(path) | SourcePath::Absolute(path)) => {
+            format!("{}:{}", path.display(), id.func)
+        }
+        Some(SourcePath::Unknown) | None => id.func.clone(),
+    }
+}
+
+fn total_map<I>(maps: I) -> Option<CallgrindMap>
+where
+    I: IntoIterator<Item = CallgrindMap>,
+{
+    let mut total: Option<CallgrindMap> = None;
+    for map in maps {
+        if let Some(total) = total.as_mut() {
+            if total.sentinel_key.is_none() {
+                total.sentinel_key.clone_from(&map.sentinel_key);
+            }
+            total.add_mut(&map);
+        } else {
+            total = Some(map);
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const HEADER: &str = "# callgrind format
+version: 1
+creator: callgrind-3.23.0
+pid: 1234
+cmd: benchmark
+part: 1
+
+positions: line
+events: Ir
+";
+
+    const BODY: &str = "ob=/project/target/release/bench
+fl=/project/src/main.rs
+fn=main
+1 5
+cfn=harness::setup
+calls=1 1
+2 100
+cfn=bench::some
+calls=1 1
+3 50
+
+fn=harness::setup
+1 100
+
+fn=bench::some
+1 10
+cfn=lib::work
+calls=2 1
+2 40
+
+fn=lib::work
+1 40
+totals: 195
+";
+
+    fn write_fixture() -> tempfile::NamedTempFile {
+        write_fixture_with_header(HEADER)
+    }
+
+    fn write_fixture_with_header(header: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(header.as_bytes()).unwrap();
+        file.write_all(BODY.as_bytes()).unwrap();
+        file
+    }
+
+    fn parse(path: &Path) -> CallgrindMap {
+        HashMapParser {
+            project_root: PathBuf::from("/project"),
+            sentinel: Some(Sentinel::new("bench::some")),
+        }
+        .parse_single(path)
+        .unwrap()
+        .1
+    }
+
+    fn call_graph() -> CallGraph {
+        CallGraph::new(Config {
+            event_kind: EventKind::Ir,
+            node_threshold: 0.5,
+            prune: true,
+        })
+    }
+
+    #[test]
+    fn test_reachable_from_sentinel() {
+        let file = write_fixture();
+        let map = parse(file.path());
+        let obj = Some(make_path(
+            Path::new("/project"),
+            "/project/target/release/bench",
+        ));
+
+        assert_eq!(
+            reachable(&map),
+            Some(HashSet::from([
+                (obj.clone(), "bench::some".to_owned()),
+                (obj, "lib::work".to_owned())
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_prune_to_entry_point_subtree() {
+        let file = write_fixture();
+        let map = parse(file.path());
+        let dest = tempfile::NamedTempFile::new().unwrap();
+
+        prune(
+            file.path(),
+            dest.path(),
+            &reachable(&map).unwrap(),
+            Path::new("/project"),
+        )
+        .unwrap();
+
+        let expected = format!(
+            "{HEADER}ob=/project/target/release/bench
+fl=/project/src/main.rs
+fn=bench::some
+1 10
+cfn=lib::work
+calls=2 1
+2 40
+
+ob=/project/target/release/bench
+fl=/project/src/main.rs
+fn=lib::work
+1 40
+totals: 50
+"
+        );
+        assert_eq!(std::fs::read_to_string(dest.path()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_prune_when_cmd_contains_equals_sign() {
+        let header = HEADER.replace("cmd: benchmark", "cmd: benchmark --flag=value");
+        let file = write_fixture_with_header(&header);
+        let map = parse(file.path());
+        let dest = tempfile::NamedTempFile::new().unwrap();
+
+        prune(
+            file.path(),
+            dest.path(),
+            &reachable(&map).unwrap(),
+            Path::new("/project"),
+        )
+        .unwrap();
+
+        let actual = std::fs::read_to_string(dest.path()).unwrap();
+        assert!(actual.starts_with(&header));
+        assert!(actual.contains("\nfn=bench::some\n"));
+        assert!(!actual.contains("fn=main"));
+        assert!(actual.ends_with("totals: 50\n"));
+    }
+
+    #[test]
+    fn test_to_dot_with_entry_point() {
+        let file = write_fixture();
+        let map = parse(file.path());
+
+        let dot = call_graph().to_dot(&map, None);
+
+        assert!(dot.contains("n0 [label=\"bench::some\\n100.00%\\n(50)\""));
+        assert!(dot.contains("n1 [label=\"lib::work\\n80.00%\\n(40)\""));
+        assert!(dot.contains("n0 -> n1 [label=\"80.00%\\n2x\""));
+        assert!(!dot.contains("label=\"main"));
+        assert!(!dot.contains("label=\"harness::setup"));
+    }
+
+    #[test]
+    fn test_to_dot_with_base() {
+        let file = write_fixture();
+        let map = parse(file.path());
+        let mut base = map.clone();
+        for value in base.map.values_mut() {
+            value.metrics = Metrics::with_metric_kinds([(EventKind::Ir, 25)]);
+        }
+
+        let dot = call_graph().to_dot(&map, Some(&base));
+
+        assert!(dot.contains(
+            "bench::some\\n100.00%\\n(50)\\n+100.00%\", fillcolor=\"0.000 1.000 1.000\""
+        ));
+        assert!(
+            dot.contains("lib::work\\n80.00%\\n(40)\\n+60.00%\", fillcolor=\"0.000 1.000 1.000\"")
+        );
+    }
+}
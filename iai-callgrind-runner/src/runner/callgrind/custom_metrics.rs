@@ -0,0 +1,553 @@
+//! Module containing the user-defined metrics of callgrind
+//!
+//! A custom metric is a named arithmetic expression over the [`EventKind`]s of callgrind, for
+//! example `ipc_proxy = Ir / EstimatedCycles` or `branch_miss_rate = (Bcm + Bim) / (Bc + Bi) *
+//! 100`. The expressions support the operators `+`, `-`, `*`, `/` with the usual precedence,
+//! parentheses, integer and float numbers. Like the derived [`EventKind`]s, a division by zero
+//! results in `0.0` and the subtraction of integers saturates at `0`. The custom metrics are
+//! calculated from the metrics of the total. A custom metric is not calculated if one of its
+//! [`EventKind`]s was not collected.
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use either_or_both::EitherOrBoth;
+use indexmap::IndexMap;
+
+use crate::api::{self, EventKind};
+use crate::runner::format::print_custom_metrics_regressions;
+use crate::runner::metrics::{Metric, MetricKind, Metrics, MetricsSummary, Summarize};
+use crate::runner::summary::{CustomMetricsSummary, ToolRegression};
+use crate::runner::tool::regression::RegressionConfig;
+
+/// The arithmetic expression of a custom metric
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// The binary operation of two expressions
+    Binary(Box<Self>, Operator, Box<Self>),
+    /// A constant integer or float number
+    Constant(Metric),
+    /// The value of the [`EventKind`]
+    Metric(EventKind),
+}
+
+/// The binary operators of an [`Expression`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// The addition `+`
+    Add,
+    /// The division `/`
+    Div,
+    /// The multiplication `*`
+    Mul,
+    /// The subtraction `-`
+    Sub,
+}
+
+/// A single token of an [`Expression`]
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Close,
+    Metric(EventKind),
+    Number(Metric),
+    Open,
+    Operator(Operator),
+}
+
+/// The configuration of the custom metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The custom metrics with their name in the order of their declaration
+    pub metrics: Vec<(String, Expression)>,
+    /// The regression check configuration applied to the custom metrics
+    pub regression_config: Option<CustomRegressionConfig>,
+}
+
+/// The regression check configuration of the custom metrics
+///
+/// The custom metrics can be integers or floats depending on their expression, so the hard limits
+/// are not type checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomRegressionConfig {
+    /// The hard limits
+    pub hard_limits: Vec<(String, Metric)>,
+    /// The soft limits
+    pub soft_limits: Vec<(String, f64)>,
+}
+
+/// A recursive descent parser of the [`Token`]s of an [`Expression`]
+struct Parser {
+    tokens: Peekable<IntoIter<Token>>,
+}
+
+impl Config {
+    /// Calculate the custom metrics from the metrics of the `metrics_summary`
+    ///
+    /// Returns `None` if none of the custom metrics could be calculated.
+    pub fn summarize(&self, metrics_summary: &MetricsSummary) -> Option<CustomMetricsSummary> {
+        let new = self.evaluate(|kind| {
+            metrics_summary
+                .diff_by_kind(&kind)
+                .and_then(|diff| diff.metrics.as_ref().left().copied())
+        });
+        if new.is_empty() {
+            return None;
+        }
+
+        let old = self.evaluate(|kind| {
+            metrics_summary
+                .diff_by_kind(&kind)
+                .and_then(|diff| diff.metrics.as_ref().right().copied())
+        });
+        let metrics = if old.is_empty() {
+            EitherOrBoth::Left(new)
+        } else {
+            EitherOrBoth::Both(new, old)
+        };
+
+        Some(CustomMetricsSummary {
+            regressions: vec![],
+            summary: MetricsSummary::new(metrics),
+        })
+    }
+
+    /// Check the custom metrics for regressions, store and print them if present
+    pub fn check_and_print(&self, summary: &mut CustomMetricsSummary) {
+        if let Some(regression_config) = &self.regression_config {
+            summary.regressions = regression_config.check(&summary.summary);
+            print_custom_metrics_regressions(&summary.regressions);
+        }
+    }
+
+    fn evaluate<F>(&self, metric: F) -> Metrics<String>
+    where
+        F: Fn(EventKind) -> Option<Metric> + Copy,
+    {
+        Metrics(
+            self.metrics
+                .iter()
+                .filter_map(|(name, expression)| {
+                    expression
+                        .evaluate(metric)
+                        .map(|value| (name.clone(), value))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<api::CustomMetricsConfig> for Config {
+    type Error = String;
+
+    fn try_from(value: api::CustomMetricsConfig) -> Result<Self, Self::Error> {
+        let api::CustomMetricsConfig {
+            hard_limits,
+            metrics,
+            soft_limits,
+        } = value;
+
+        // A custom metric declared multiple times keeps its first position but the last
+        // expression wins
+        let metrics = metrics
+            .iter()
+            .map(|(name, expression)| parse(name, expression))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect::<IndexMap<_, _>>();
+
+        let check_name = |name: &String| {
+            if metrics.contains_key(name) {
+                Ok(name.clone())
+            } else {
+                Err(format!("Limit for unknown custom metric '{name}'"))
+            }
+        };
+
+        let regression_config = if soft_limits.is_empty() && hard_limits.is_empty() {
+            None
+        } else {
+            let hard_limits = hard_limits
+                .iter()
+                .map(|(name, limit)| check_name(name).map(|name| (name, Metric::from(*limit))))
+                .collect::<Result<IndexMap<_, _>, _>>()?;
+            let soft_limits = soft_limits
+                .iter()
+                .map(|(name, limit)| check_name(name).map(|name| (name, *limit)))
+                .collect::<Result<IndexMap<_, _>, _>>()?;
+
+            Some(CustomRegressionConfig {
+                hard_limits: hard_limits.into_iter().collect(),
+                soft_limits: soft_limits.into_iter().collect(),
+            })
+        };
+
+        Ok(Self {
+            metrics: metrics.into_iter().collect(),
+            regression_config,
+        })
+    }
+}
+
+impl RegressionConfig<String> for CustomRegressionConfig {
+    fn check(&self, metrics_summary: &MetricsSummary<String>) -> Vec<ToolRegression> {
+        self.check_regressions(metrics_summary)
+            .into_iter()
+            .map(|regressions| ToolRegression::with(MetricKind::Custom, regressions))
+            .collect()
+    }
+
+    fn get_hard_limits(&self) -> &[(String, Metric)] {
+        &self.hard_limits
+    }
+
+    fn get_improvements(&self) -> &[(String, f64)] {
+        &[]
+    }
+
+    fn get_soft_limits(&self) -> &[(String, f64)] {
+        &self.soft_limits
+    }
+}
+
+impl Expression {
+    /// Evaluate this expression with the values of the [`EventKind`]s returned by `metric`
+    ///
+    /// Returns `None` if `metric` returns `None` for one of the [`EventKind`]s.
+    pub fn evaluate<F>(&self, metric: F) -> Option<Metric>
+    where
+        F: Fn(EventKind) -> Option<Metric> + Copy,
+    {
+        match self {
+            Self::Binary(lhs, operator, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(metric)?, rhs.evaluate(metric)?);
+                let value = match operator {
+                    Operator::Add => lhs + rhs,
+                    Operator::Div => lhs.div0(rhs),
+                    Operator::Mul => lhs * rhs,
+                    Operator::Sub => lhs - rhs,
+                };
+                Some(value)
+            }
+            Self::Constant(value) => Some(*value),
+            Self::Metric(kind) => metric(*kind),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(string)?.into_iter().peekable(),
+        };
+
+        let expression = parser.expression()?;
+        match parser.tokens.next() {
+            None => Ok(expression),
+            Some(Token::Close) => Err("Unbalanced closing parenthesis".to_owned()),
+            Some(_) => Err("Expected an operator".to_owned()),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => f.write_str("+"),
+            Self::Div => f.write_str("/"),
+            Self::Mul => f.write_str("*"),
+            Self::Sub => f.write_str("-"),
+        }
+    }
+}
+
+impl Parser {
+    /// expression ::= term (("+" | "-") term)*
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut lhs = self.term()?;
+        while let Some(operator) = self.next_operator(&[Operator::Add, Operator::Sub]) {
+            lhs = Expression::Binary(Box::new(lhs), operator, Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    /// factor ::= number | event kind | "(" expression ")"
+    fn factor(&mut self) -> Result<Expression, String> {
+        match self.tokens.next() {
+            Some(Token::Number(value)) => Ok(Expression::Constant(value)),
+            Some(Token::Metric(kind)) => Ok(Expression::Metric(kind)),
+            Some(Token::Open) => {
+                let expression = self.expression()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(expression),
+                    _ => Err("Missing closing parenthesis".to_owned()),
+                }
+            }
+            Some(Token::Operator(operator)) => Err(format!(
+                "Expected a metric, a number or '(' but found '{operator}'"
+            )),
+            Some(Token::Close) => {
+                Err("Expected a metric, a number or '(' but found ')'".to_owned())
+            }
+            None => Err("Unexpected end of expression".to_owned()),
+        }
+    }
+
+    fn next_operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+        self.tokens
+            .next_if(|token| matches!(token, Token::Operator(o) if operators.contains(o)))
+            .map(|token| match token {
+                Token::Operator(operator) => operator,
+                _ => unreachable!("Only operators are consumed"),
+            })
+    }
+
+    /// term ::= factor (("*" | "/") factor)*
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut lhs = self.factor()?;
+        while let Some(operator) = self.next_operator(&[Operator::Mul, Operator::Div]) {
+            lhs = Expression::Binary(Box::new(lhs), operator, Box::new(self.factor()?));
+        }
+        Ok(lhs)
+    }
+}
+
+impl Summarize for String {}
+
+/// Parse and verify the `name` and `expression` of a custom metric
+///
+/// The name may consist of ascii alphanumeric characters and `_`.
+pub fn parse(name: &str, expression: &str) -> Result<(String, Expression), String> {
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!(
+            "Invalid name of custom metric '{name}': Only ascii alphanumeric characters and '_' \
+             are allowed"
+        ));
+    }
+
+    expression
+        .parse::<Expression>()
+        .map(|expression| (name.to_owned(), expression))
+        .map_err(|error| format!("Invalid expression of custom metric '{name}': {error}"))
+}
+
+fn tokenize(string: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = string.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        let token = match char {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Operator(Operator::Sub),
+            '*' => Token::Operator(Operator::Mul),
+            '/' => Token::Operator(Operator::Div),
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                {
+                    end = index + c.len_utf8();
+                }
+                let number = &string[start..end];
+                Token::Number(
+                    number
+                        .parse::<Metric>()
+                        .map_err(|_| format!("Invalid number '{number}'"))?,
+                )
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = index + c.len_utf8();
+                }
+                let name = &string[start..end];
+                Token::Metric(
+                    name.parse::<EventKind>()
+                        .map_err(|_| format!("Unknown metric '{name}'"))?,
+                )
+            }
+            c => return Err(format!("Invalid character '{c}' at position {start}")),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::Limit;
+    use crate::runner::callgrind::model::Metrics as CallgrindMetrics;
+
+    fn metrics_summary(
+        new: &[(EventKind, u64)],
+        old: Option<&[(EventKind, u64)]>,
+    ) -> MetricsSummary {
+        let metrics =
+            |m: &[(EventKind, u64)]| CallgrindMetrics::with_metric_kinds(m.iter().copied());
+        MetricsSummary::new(match old {
+            Some(old) => EitherOrBoth::Both(metrics(new), metrics(old)),
+            None => EitherOrBoth::Left(metrics(new)),
+        })
+    }
+
+    fn config(metrics: &[(&str, &str)]) -> api::CustomMetricsConfig {
+        api::CustomMetricsConfig {
+            metrics: metrics
+                .iter()
+                .map(|(n, e)| ((*n).to_owned(), (*e).to_owned()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case::metric("Ir", Metric::Int(10))]
+    #[case::case_insensitive("estimatedcycles", Metric::Int(20))]
+    #[case::constant("42", Metric::Int(42))]
+    #[case::float_constant("2.5", Metric::Float(2.5))]
+    #[case::division("Ir / EstimatedCycles", Metric::Float(0.5))]
+    #[case::division_by_zero("Ir / Bcm", Metric::Float(0.0))]
+    #[case::precedence("Ir + Bc * 2", Metric::Int(18))]
+    #[case::parentheses("(Ir + Bc) * 2", Metric::Int(28))]
+    #[case::left_associative("Ir - Bc - 1", Metric::Int(5))]
+    #[case::saturating("Bc - Ir", Metric::Int(0))]
+    #[case::rate("(Bcm + Bc) / (Bc + Ir + 2) * 100", Metric::Float(25.0))]
+    #[case::no_whitespace("(Ir+Bc)/Bc", Metric::Float(3.5))]
+    fn test_expression_evaluate(#[case] expression: &str, #[case] expected: Metric) {
+        let metrics = CallgrindMetrics::with_metric_kinds([
+            (EventKind::Ir, 10),
+            (EventKind::EstimatedCycles, 20),
+            (EventKind::Bc, 4),
+            (EventKind::Bcm, 0),
+        ]);
+
+        let expression = expression.parse::<Expression>().unwrap();
+        assert_eq!(
+            expression.evaluate(|kind| metrics.metric_by_kind(&kind)),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_expression_evaluate_when_metric_missing() {
+        let expression = "Ir / Dr".parse::<Expression>().unwrap();
+        assert_eq!(
+            expression.evaluate(|kind| (kind == EventKind::Ir).then_some(Metric::Int(1))),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case::empty("", "Unexpected end of expression")]
+    #[case::unknown_metric("Ir / Unknown", "Unknown metric 'Unknown'")]
+    #[case::invalid_number("1.2.3", "Invalid number '1.2.3'")]
+    #[case::invalid_character("Ir % 2", "Invalid character '%' at position 3")]
+    #[case::missing_operand("Ir +", "Unexpected end of expression")]
+    #[case::two_operators("Ir + * Dr", "Expected a metric, a number or '(' but found '*'")]
+    #[case::missing_parenthesis("(Ir + Dr", "Missing closing parenthesis")]
+    #[case::unbalanced_parenthesis("Ir + Dr)", "Unbalanced closing parenthesis")]
+    #[case::missing_operator("Ir Dr", "Expected an operator")]
+    fn test_expression_from_str_when_invalid(#[case] expression: &str, #[case] expected: &str) {
+        assert_eq!(expression.parse::<Expression>(), Err(expected.to_owned()));
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::whitespace("ipc proxy")]
+    #[case::invalid_character("ipc-proxy")]
+    fn test_parse_when_invalid_name(#[case] name: &str) {
+        assert!(parse(name, "Ir").unwrap_err().starts_with("Invalid name"));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let config = Config::try_from(config(&[
+            ("ipc_proxy", "Ir / EstimatedCycles"),
+            ("missing", "Dr + 1"),
+        ]))
+        .unwrap();
+
+        let summary = config
+            .summarize(&metrics_summary(
+                &[(EventKind::Ir, 10), (EventKind::EstimatedCycles, 20)],
+                Some(&[(EventKind::Ir, 10), (EventKind::EstimatedCycles, 40)]),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            summary
+                .summary
+                .diff_by_kind(&"ipc_proxy".to_owned())
+                .unwrap()
+                .metrics,
+            EitherOrBoth::Both(Metric::Float(0.5), Metric::Float(0.25))
+        );
+        assert_eq!(summary.summary.diff_by_kind(&"missing".to_owned()), None);
+    }
+
+    #[test]
+    fn test_summarize_when_no_metric_present() {
+        let config = Config::try_from(config(&[("missing", "Dr + 1")])).unwrap();
+        assert_eq!(
+            config.summarize(&metrics_summary(&[(EventKind::Ir, 10)], None)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_try_from_when_duplicate_name_then_last_expression_wins() {
+        let config = Config::try_from(config(&[("a", "Ir"), ("b", "Dr"), ("a", "Dw")])).unwrap();
+        assert_eq!(
+            config.metrics,
+            vec![
+                ("a".to_owned(), Expression::Metric(EventKind::Dw)),
+                ("b".to_owned(), Expression::Metric(EventKind::Dr)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_from_when_limit_of_unknown_metric_then_error() {
+        let mut api_config = config(&[("a", "Ir")]);
+        api_config.soft_limits.push(("b".to_owned(), 5.0));
+        assert_eq!(
+            Config::try_from(api_config),
+            Err("Limit for unknown custom metric 'b'".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_check_and_print() {
+        let mut api_config = config(&[("ratio", "Ir / Dr")]);
+        api_config.soft_limits.push(("ratio".to_owned(), 10.0));
+        api_config
+            .hard_limits
+            .push(("ratio".to_owned(), Limit::Float(4.0)));
+        let config = Config::try_from(api_config).unwrap();
+
+        let mut summary = config
+            .summarize(&metrics_summary(
+                &[(EventKind::Ir, 10), (EventKind::Dr, 2)],
+                Some(&[(EventKind::Ir, 8), (EventKind::Dr, 2)]),
+            ))
+            .unwrap();
+        config.check_and_print(&mut summary);
+
+        assert_eq!(summary.regressions.len(), 2);
+        assert!(summary
+            .regressions
+            .iter()
+            .all(|r| matches!(r, ToolRegression::Soft { metric, .. } | ToolRegression::Hard { metric, .. } if *metric == MetricKind::Custom("ratio".to_owned()))));
+    }
+}
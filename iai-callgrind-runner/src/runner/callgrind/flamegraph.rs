@@ -9,8 +9,9 @@ use anyhow::{Context, Result};
 use inferno::flamegraph::{Direction, Options};
 
 use super::flamegraph_parser::{FlamegraphMap, FlamegraphParser};
+use super::folding::FoldingRules;
 use super::parser::{CallgrindParser, CallgrindProperties, Sentinel};
-use crate::api::{self, EventKind, FlamegraphKind};
+use crate::api::{self, CyclesModel, EventKind, FlamegraphKind};
 use crate::runner::summary::{BaselineKind, BaselineName, FlamegraphSummaries, FlamegraphSummary};
 use crate::runner::tool::path::{ToolOutputPath, ToolOutputPathKind};
 
@@ -37,10 +38,14 @@ pub struct BaselineFlamegraphGenerator {
 #[derive(Debug, Clone, PartialEq)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Config {
+    /// The [`CyclesModel`] to calculate the [`EventKind::CustomCycles`] if present
+    pub cycles_model: Option<CyclesModel>,
     /// The direction of the flamegraph. Top to bottom or vice versa
     pub direction: Direction,
     /// The event kinds for which a flamegraph should be generated
     pub event_kinds: Vec<EventKind>,
+    /// The [`FoldingRules`] applied to the function names
+    pub folding: FoldingRules,
     /// The [`FlamegraphKind`]
     pub kind: FlamegraphKind,
     /// The minimum width which should be displayed
@@ -132,7 +137,7 @@ impl FlamegraphGenerator for BaselineFlamegraphGenerator {
             let mut flamegraph_summary = FlamegraphSummary::new(*event_kind);
             output_path.set_event_kind(*event_kind);
 
-            let stacks_lines = total.to_stack_format(event_kind)?;
+            let stacks_lines = total.to_stack_format(event_kind, &flamegraph.config.folding)?;
             if flamegraph.is_regular() {
                 Flamegraph::write(
                     &output_path,
@@ -153,6 +158,7 @@ impl FlamegraphGenerator for BaselineFlamegraphGenerator {
                     // flamegraph kind is differential
                     flamegraph.differential_options().unwrap(),
                     *event_kind,
+                    &flamegraph.config.folding,
                     &stacks_lines,
                 )?;
 
@@ -170,10 +176,17 @@ impl FlamegraphGenerator for BaselineFlamegraphGenerator {
 impl From<api::FlamegraphConfig> for Config {
     fn from(value: api::FlamegraphConfig) -> Self {
         Self {
+            cycles_model: None,
             kind: value.kind.unwrap_or(FlamegraphKind::All),
             negate_differential: value.negate_differential.unwrap_or_default(),
             normalize_differential: value.normalize_differential.unwrap_or(false),
             event_kinds: value.event_kinds.unwrap_or_else(|| vec![EventKind::Ir]),
+            folding: FoldingRules::new(
+                value.strip_hashes.unwrap_or(true),
+                value.collapse_generics.unwrap_or(false),
+                value.fold_std.unwrap_or(false),
+                value.fold_crates,
+            ),
             direction: value
                 .direction
                 .map_or_else(|| Direction::Inverted, std::convert::Into::into),
@@ -273,11 +286,11 @@ impl Flamegraph {
 
         if self.config.event_kinds.iter().any(EventKind::is_derived) {
             for map in &mut maps {
-                map.2.make_summary()?;
+                map.2.make_summary(self.config.cycles_model.as_ref())?;
             }
             if let Some(maps) = base_maps.as_mut() {
                 for map in maps {
-                    map.2.make_summary()?;
+                    map.2.make_summary(self.config.cycles_model.as_ref())?;
                 }
             }
         }
@@ -291,9 +304,10 @@ impl Flamegraph {
         base_map: &FlamegraphMap,
         differential_options: inferno::differential::Options,
         event_kind: EventKind,
+        folding: &FoldingRules,
         stacks_lines: &[String],
     ) -> Result<()> {
-        let base_stacks_lines = base_map.to_stack_format(&event_kind)?;
+        let base_stacks_lines = base_map.to_stack_format(&event_kind, folding)?;
 
         let cursor = Cursor::new(stacks_lines.join("\n"));
         let base_cursor = Cursor::new(base_stacks_lines.join("\n"));
@@ -369,7 +383,8 @@ impl FlamegraphGenerator for LoadBaselineFlamegraphGenerator {
                         // This unwrap is safe since we always produce a differential flamegraph
                         flamegraph.differential_options().unwrap(),
                         *event_kind,
-                        &total.to_stack_format(event_kind)?,
+                        &flamegraph.config.folding,
+                        &total.to_stack_format(event_kind, &flamegraph.config.folding)?,
                     )?;
 
                     flamegraph_summary.regular_path = Some(output_path.to_path());
@@ -649,7 +664,7 @@ impl FlamegraphGenerator for SaveBaselineFlamegraphGenerator {
                 &output_path,
                 &mut flamegraph.options(*event_kind, output_path.file_name()),
                 total_map
-                    .to_stack_format(event_kind)?
+                    .to_stack_format(event_kind, &flamegraph.config.folding)?
                     .iter()
                     .map(String::as_str),
             )?;
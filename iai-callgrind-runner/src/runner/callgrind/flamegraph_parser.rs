@@ -6,9 +6,10 @@ use std::path::{Path, PathBuf};
 use anyhow::{anyhow, Result};
 use log::debug;
 
+use super::folding::{Folded, FoldingRules};
 use super::hashmap_parser::{CallgrindMap, HashMapParser, SourcePath};
 use super::parser::{CallgrindParser, CallgrindProperties, Sentinel};
-use crate::api::EventKind;
+use crate::api::{CyclesModel, EventKind};
 use crate::runner::metrics::Metric;
 
 /// The `FlamegraphMap` based on a [`CallgrindMap`]
@@ -35,7 +36,9 @@ impl FlamegraphMap {
     }
 
     /// Calculate the cache summary for each entry in the map in-place
-    pub fn make_summary(&mut self) -> Result<()> {
+    ///
+    /// The [`EventKind::CustomCycles`] are calculated in addition if the `cycles_model` is present.
+    pub fn make_summary(&mut self, cycles_model: Option<&CyclesModel>) -> Result<()> {
         let mut iter = self.0.map.values_mut().peekable();
         if let Some(value) = iter.peek() {
             // If one cost can be summarized then all costs can be summarized.
@@ -45,6 +48,14 @@ impl FlamegraphMap {
                         .metrics
                         .make_summary()
                         .map_err(|error| anyhow!("Failed calculating summary events: {error}"))?;
+                    if let Some(cycles_model) = cycles_model {
+                        value
+                            .metrics
+                            .make_custom_cycles(cycles_model)
+                            .map_err(|error| {
+                                anyhow!("Failed calculating the custom cycles: {error}")
+                            })?;
+                    }
                 }
             }
         }
@@ -67,10 +78,18 @@ impl FlamegraphMap {
 
     /// Convert to stacks string format for this `EventType`
     ///
+    /// The function names are cleaned up and folded with the [`FoldingRules`]. Frames of a folded
+    /// crate are shown without the file and object, so consecutive frames which end up with the
+    /// same name are merged into a single frame.
+    ///
     /// # Errors
     ///
     /// If the event type was not present in the stacks
-    pub fn to_stack_format(&self, event_kind: &EventKind) -> Result<Vec<String>> {
+    pub fn to_stack_format(
+        &self,
+        event_kind: &EventKind,
+        folding: &FoldingRules,
+    ) -> Result<Vec<String>> {
         if self.0.map.is_empty() {
             return Ok(vec![]);
         }
@@ -106,18 +125,29 @@ impl FlamegraphMap {
                 }
             }
 
+            let func = match folding.fold(&id.func) {
+                Folded::Crate(krate) => {
+                    heap.push(HeapElem {
+                        cost,
+                        source: format!("{krate}::*"),
+                    });
+                    continue;
+                }
+                Folded::Function(func) => func,
+            };
+
             let mut source = String::new();
             if let Some(file) = &id.file {
                 match file {
-                    SourcePath::Unknown => write!(source, "{}", id.func).unwrap(),
+                    SourcePath::Unknown => write!(source, "{func}").unwrap(),
                     SourcePath::Rust(path)
                     | SourcePath::Relative(path)
                     | SourcePath::Absolute(path) => {
-                        write!(source, "{}:{}", path.display(), id.func).unwrap();
+                        write!(source, "{}:{func}", path.display()).unwrap();
                     }
                 }
             } else {
-                write!(source, "{}", id.func).unwrap();
+                write!(source, "{func}").unwrap();
             }
 
             if let Some(path) = &id.obj {
@@ -134,10 +164,15 @@ impl FlamegraphMap {
             heap.push(HeapElem { cost, source });
         }
 
+        // The elements are sorted by their inclusive costs, so merging two consecutive elements
+        // with the same source only needs to drop the cheaper one
+        let mut elems = heap.into_sorted_vec();
+        elems.dedup_by(|next, prev| next.source == prev.source);
+
         let mut stacks: Vec<String> = vec![];
-        let len = heap.len();
+        let len = elems.len();
         if len > 1 {
-            for window in heap.into_sorted_vec().windows(2) {
+            for window in elems.windows(2) {
                 // There is only the slice size of 2 possible due to the window size of 2
                 if let [h1, h2] = window {
                     let stack = if let Some(last) = stacks.last() {
@@ -163,9 +198,11 @@ impl FlamegraphMap {
                 }
             }
         } else {
-            // This unwrap is safe since heap.len() == 1 here
-            let elem = heap.pop().unwrap();
-            stacks.push(format!("{} {}", elem.source, elem.cost));
+            stacks.extend(
+                elems
+                    .first()
+                    .map(|elem| format!("{} {}", elem.source, elem.cost)),
+            );
         }
         Ok(stacks)
     }
@@ -0,0 +1,271 @@
+//! Module containing the folding rules for the function names shown in reports
+//!
+//! Callgrind demangles the rust symbols, but the demangled names of heavily generic code are still
+//! hard to read. The [`FoldingRules`] clean up the function names by stripping the hashes of legacy
+//! symbols and collapsing the generic arguments, and fold all frames of a crate into a single node
+//! named after the crate.
+use std::borrow::Cow;
+
+/// The crates of the standard library which are folded with `fold_std`
+pub const STD_CRATES: [&str; 3] = ["alloc", "core", "std"];
+
+/// The folding rules applied to the function names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldingRules {
+    /// If true, collapse the generic arguments of the function names to `<..>`
+    pub collapse_generics: bool,
+    /// The names of the crates which are folded into a single node
+    pub crates: Vec<String>,
+    /// If true, strip the hashes of legacy symbols (for example `::h0123456789abcdef`)
+    pub strip_hashes: bool,
+}
+
+/// A function name after applying the [`FoldingRules`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Folded<'a> {
+    /// The name of a folded crate
+    Crate(&'a str),
+    /// The cleaned up function name
+    Function(Cow<'a, str>),
+}
+
+impl FoldingRules {
+    /// Create new `FoldingRules`
+    ///
+    /// The crates of the standard library ([`STD_CRATES`]) are folded if `fold_std` is true. Since
+    /// crate names appear with underscores in the symbols, dashes in the crate names are replaced
+    /// with underscores.
+    pub fn new<I, T>(strip_hashes: bool, collapse_generics: bool, fold_std: bool, crates: T) -> Self
+    where
+        I: AsRef<str>,
+        T: IntoIterator<Item = I>,
+    {
+        let mut crates = crates
+            .into_iter()
+            .map(|c| c.as_ref().trim().replace('-', "_"))
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        if fold_std {
+            crates.extend(STD_CRATES.iter().map(|c| (*c).to_owned()));
+        }
+        crates.sort();
+        crates.dedup();
+
+        Self {
+            collapse_generics,
+            crates,
+            strip_hashes,
+        }
+    }
+
+    /// Apply the folding rules to the function name `func`
+    pub fn fold<'a>(&'a self, func: &'a str) -> Folded<'a> {
+        if let Some(krate) = root_crate(func) {
+            if let Some(krate) = self.crates.iter().find(|c| *c == krate) {
+                return Folded::Crate(krate);
+            }
+        }
+
+        let mut func = Cow::Borrowed(func);
+        if self.strip_hashes {
+            if let Cow::Owned(stripped) = strip_hashes(&func) {
+                func = Cow::Owned(stripped);
+            }
+        }
+        if self.collapse_generics {
+            if let Cow::Owned(collapsed) = collapse_generics(&func) {
+                func = Cow::Owned(collapsed);
+            }
+        }
+
+        Folded::Function(func)
+    }
+}
+
+impl Default for FoldingRules {
+    fn default() -> Self {
+        Self {
+            collapse_generics: false,
+            crates: Vec::default(),
+            strip_hashes: true,
+        }
+    }
+}
+
+/// Return the name of the crate of the function `func` if it is a rust path
+///
+/// The crate of a qualified path like `<alloc::vec::Vec<T> as core::ops::Drop>::drop` is the
+/// crate of the self type, here `alloc`.
+pub fn root_crate(func: &str) -> Option<&str> {
+    let path = func
+        .trim_start_matches(|c: char| c == '<' || c == '&' || c.is_whitespace())
+        .trim_start_matches("mut ")
+        .trim_start_matches("dyn ");
+    let end = path.find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+
+    (end > 0 && path[end..].starts_with("::")).then(|| &path[..end])
+}
+
+/// Strip the hashes of legacy symbols like `::h0123456789abcdef` from the function name
+pub fn strip_hashes(func: &str) -> Cow<'_, str> {
+    const HASH_LEN: usize = 16;
+
+    let mut result = String::new();
+    let mut rest = func;
+    while let Some(index) = rest.find("::h") {
+        let hash_start = index + 3;
+        let is_hash = rest.len() >= hash_start + HASH_LEN
+            && rest.as_bytes()[hash_start..hash_start + HASH_LEN]
+                .iter()
+                .all(u8::is_ascii_hexdigit)
+            && !rest[hash_start + HASH_LEN..]
+                .starts_with(|c: char| c.is_alphanumeric() || c == '_');
+
+        if is_hash {
+            result.push_str(&rest[..index]);
+            rest = &rest[hash_start + HASH_LEN..];
+        } else {
+            result.push_str(&rest[..hash_start]);
+            rest = &rest[hash_start..];
+        }
+    }
+
+    if result.is_empty() {
+        Cow::Borrowed(func)
+    } else {
+        result.push_str(rest);
+        Cow::Owned(result)
+    }
+}
+
+/// Collapse the generic arguments in the function name to `<..>`
+///
+/// Only angle brackets following a path segment are generic arguments. The leading angle brackets
+/// of qualified paths like `<T as Trait>::func` are preserved but the generic arguments within
+/// them are collapsed. If the angle brackets are unbalanced, the function name is returned
+/// unchanged.
+pub fn collapse_generics(func: &str) -> Cow<'_, str> {
+    if !func.contains('<') {
+        return Cow::Borrowed(func);
+    }
+
+    let mut result = String::with_capacity(func.len());
+    let mut depth = 0usize;
+    let mut prev = None;
+    for c in func.chars() {
+        match c {
+            '<' if depth > 0 => depth += 1,
+            '<' if prev.is_some_and(|p: char| p.is_alphanumeric() || p == '_' || p == ':') => {
+                depth = 1;
+                result.push_str("<..>");
+            }
+            // The `>` of an arrow like in `fn() -> T` doesn't close a generic argument
+            '>' if depth > 0 && prev != Some('-') => depth -= 1,
+            _ if depth > 0 => {}
+            _ => result.push(c),
+        }
+        prev = Some(c);
+    }
+
+    if depth > 0 {
+        Cow::Borrowed(func)
+    } else {
+        Cow::Owned(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::plain("main", None)]
+    #[case::path("std::rt::lang_start", Some("std"))]
+    #[case::generic("core::ptr::drop_in_place<alloc::vec::Vec<u8>>", Some("core"))]
+    #[case::qualified("<alloc::vec::Vec<T> as core::ops::Drop>::drop", Some("alloc"))]
+    #[case::reference("<&mut my_lib::Foo as core::fmt::Debug>::fmt", Some("my_lib"))]
+    #[case::c_function("0x0000000000001234", None)]
+    #[case::impl_block("<impl core::fmt::Debug for my_lib::Foo>::fmt", None)]
+    fn test_root_crate(#[case] func: &str, #[case] expected: Option<&str>) {
+        assert_eq!(root_crate(func), expected);
+    }
+
+    #[rstest]
+    #[case::no_hash("my_lib::bubble_sort", "my_lib::bubble_sort")]
+    #[case::hash("my_lib::bubble_sort::h0123456789abcdef", "my_lib::bubble_sort")]
+    #[case::hash_and_depth("my_lib::fibonacci::h0123456789abcdef'2", "my_lib::fibonacci'2")]
+    #[case::short_hash("my_lib::h0123", "my_lib::h0123")]
+    #[case::hash_like_ident("my_lib::h0123456789abcdefg", "my_lib::h0123456789abcdefg")]
+    #[case::multiple(
+        "<my_lib::Foo as core::fmt::Debug>::fmt::h0123456789abcdef::{{closure}}::hfedcba9876543210",
+        "<my_lib::Foo as core::fmt::Debug>::fmt::{{closure}}"
+    )]
+    fn test_strip_hashes(#[case] func: &str, #[case] expected: &str) {
+        assert_eq!(strip_hashes(func), expected);
+    }
+
+    #[rstest]
+    #[case::no_generics("my_lib::bubble_sort", "my_lib::bubble_sort")]
+    #[case::generic(
+        "core::ptr::drop_in_place<alloc::vec::Vec<u8>>",
+        "core::ptr::drop_in_place<..>"
+    )]
+    #[case::turbofish(
+        "core::iter::Iterator::collect::<Vec<u8>>",
+        "core::iter::Iterator::collect::<..>"
+    )]
+    #[case::qualified(
+        "<alloc::vec::Vec<T, A> as core::ops::Drop>::drop",
+        "<alloc::vec::Vec<..> as core::ops::Drop>::drop"
+    )]
+    #[case::arrow(
+        "my_lib::call<fn() -> u8>::{{closure}}",
+        "my_lib::call<..>::{{closure}}"
+    )]
+    #[case::unbalanced("operator<", "operator<")]
+    fn test_collapse_generics(#[case] func: &str, #[case] expected: &str) {
+        assert_eq!(collapse_generics(func), expected);
+    }
+
+    #[rstest]
+    #[case::default(
+        FoldingRules::default(),
+        "std::rt::lang_start<()>::h0123456789abcdef",
+        Folded::Function(Cow::Borrowed("std::rt::lang_start<()>"))
+    )]
+    #[case::nothing(
+        FoldingRules::new(false, false, false, Vec::<String>::new()),
+        "std::rt::lang_start<()>::h0123456789abcdef",
+        Folded::Function(Cow::Borrowed("std::rt::lang_start<()>::h0123456789abcdef"))
+    )]
+    #[case::collapse_generics(
+        FoldingRules::new(true, true, false, Vec::<String>::new()),
+        "std::rt::lang_start<()>::h0123456789abcdef",
+        Folded::Function(Cow::Borrowed("std::rt::lang_start<..>"))
+    )]
+    #[case::fold_std(
+        FoldingRules::new(true, true, true, Vec::<String>::new()),
+        "std::rt::lang_start<()>::h0123456789abcdef",
+        Folded::Crate("std")
+    )]
+    #[case::fold_crates(
+        FoldingRules::new(true, false, false, ["my-lib"]),
+        "<my_lib::Foo as core::fmt::Debug>::fmt",
+        Folded::Crate("my_lib")
+    )]
+    #[case::not_folded(
+        FoldingRules::new(true, false, true, ["my-lib"]),
+        "bench::main",
+        Folded::Function(Cow::Borrowed("bench::main"))
+    )]
+    fn test_folding_rules_fold(
+        #[case] rules: FoldingRules,
+        #[case] func: &str,
+        #[case] expected: Folded,
+    ) {
+        assert_eq!(rules.fold(func), expected);
+    }
+}
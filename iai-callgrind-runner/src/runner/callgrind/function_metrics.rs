@@ -0,0 +1,318 @@
+//! Module containing the inclusive metrics of functions matched by glob patterns
+use std::path::Path;
+
+use anyhow::Result;
+use either_or_both::EitherOrBoth;
+
+use super::hashmap_parser::{CallgrindMap, HashMapParser, Id};
+use super::model::Metrics;
+use super::parser::CallgrindParser;
+use super::regression::CallgrindRegressionConfig;
+use crate::api;
+use crate::runner::format::print_function_regressions;
+use crate::runner::metrics::{Metric, MetricsSummary};
+use crate::runner::summary::{FunctionSummary, ToolMetricSummary};
+use crate::runner::tool::path::ToolOutputPath;
+use crate::runner::tool::regression::RegressionConfig;
+use crate::util::Glob;
+
+/// The configuration of the function metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The [`Glob`] patterns matching the functions
+    pub functions: Vec<Glob>,
+    /// The regression check configuration applied to the metrics of each [`Glob`] pattern
+    pub regression_config: Option<CallgrindRegressionConfig>,
+}
+
+impl Config {
+    /// Create the [`FunctionSummary`] of each configured [`Glob`] pattern
+    ///
+    /// Patterns which don't match any function in the `new` and `old` map are summarized with
+    /// zero costs, so a function which disappears (or appears) is still shown and compared.
+    pub fn summarize(
+        &self,
+        new: &CallgrindMap,
+        old: Option<&CallgrindMap>,
+    ) -> Vec<FunctionSummary> {
+        self.functions
+            .iter()
+            .filter_map(|glob| {
+                let new_metrics = inclusive_metrics(new, glob);
+                let old_metrics = old.and_then(|old| inclusive_metrics(old, glob));
+                let metrics = match (new_metrics, old_metrics) {
+                    (Some(new), Some(old)) => EitherOrBoth::Both(new, old),
+                    (Some(new), None) => match old.and_then(zero_metrics) {
+                        Some(old) => EitherOrBoth::Both(new, old),
+                        None => EitherOrBoth::Left(new),
+                    },
+                    (None, Some(old)) => EitherOrBoth::Both(zero_metrics(new)?, old),
+                    (None, None) => EitherOrBoth::Left(zero_metrics(new)?),
+                };
+
+                Some(FunctionSummary {
+                    function: glob.as_str().to_owned(),
+                    regressions: vec![],
+                    summary: ToolMetricSummary::Callgrind(MetricsSummary::new(metrics)),
+                })
+            })
+            .collect()
+    }
+
+    /// Check the `functions` for regressions, store and print them if present
+    pub fn check_and_print(&self, functions: &mut [FunctionSummary]) {
+        if let Some(regression_config) = &self.regression_config {
+            for function in functions {
+                if let ToolMetricSummary::Callgrind(metrics_summary) = &function.summary {
+                    function.regressions = regression_config.check(metrics_summary);
+                    print_function_regressions(&function.function, &function.regressions);
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<api::FunctionMetricsConfig> for Config {
+    type Error = String;
+
+    fn try_from(value: api::FunctionMetricsConfig) -> Result<Self, Self::Error> {
+        let api::FunctionMetricsConfig {
+            functions,
+            hard_limits,
+            soft_limits,
+        } = value;
+
+        let regression_config =
+            CallgrindRegressionConfig::try_from_limits(soft_limits, hard_limits)?;
+
+        Ok(Self {
+            functions: functions.iter().map(Into::into).collect(),
+            regression_config,
+        })
+    }
+}
+
+/// Return the inclusive metrics of all functions matching the `glob`
+///
+/// The inclusive costs of direct calls from one matching function to another matching function
+/// are already included in the inclusive costs of the caller and are subtracted, so directly
+/// recursive or nested functions aren't counted twice. Indirect calls through a function which
+/// doesn't match, like `a` calling `helper` calling `a` again, are still counted twice. The call
+/// graph of callgrind doesn't record the call stacks, so it's unknown which calls of the `helper`
+/// originate from a matching function. Returns `None` if no function matches.
+pub fn inclusive_metrics(map: &CallgrindMap, glob: &Glob) -> Option<Metrics> {
+    let is_match = |id: &Id| glob.is_match(&id.func);
+
+    let mut total: Option<Metrics> = None;
+    for (_, value) in map.iter().filter(|(id, _)| is_match(id)) {
+        match total.as_mut() {
+            Some(total) => total.add(&value.metrics),
+            None => total = Some(value.metrics.clone()),
+        }
+    }
+
+    if let Some(total) = total.as_mut() {
+        for call in map
+            .calls
+            .iter()
+            .filter(|((caller, callee), _)| is_match(caller) && is_match(callee))
+            .map(|(_, call)| call)
+        {
+            for (this, other) in total.0.values_mut().zip(call.metrics.0.values()) {
+                *this = *this - *other;
+            }
+        }
+    }
+
+    total
+}
+
+/// Parse all callgrind output files of the `tool_output_path` into a single [`CallgrindMap`]
+///
+/// Returns `None` if there weren't any output files present.
+pub fn parse_total(
+    tool_output_path: &ToolOutputPath,
+    project_root: &Path,
+) -> Result<Option<CallgrindMap>> {
+    if !tool_output_path.exists() {
+        return Ok(None);
+    }
+
+    let parser = HashMapParser {
+        project_root: project_root.to_owned(),
+        sentinel: None,
+    };
+
+    Ok(parser
+        .parse(tool_output_path)?
+        .into_iter()
+        .map(|(_, _, map)| map)
+        .reduce(|mut total, map| {
+            total.add_mut(&map);
+            total
+        }))
+}
+
+/// Return the metrics of the `map` with all costs set to zero
+fn zero_metrics(map: &CallgrindMap) -> Option<Metrics> {
+    map.iter().next().map(|(_, value)| {
+        let mut metrics = value.metrics.clone();
+        for metric in metrics.0.values_mut() {
+            *metric = match metric {
+                Metric::Int(_) => Metric::Int(0),
+                Metric::Float(_) => Metric::Float(0.0),
+            };
+        }
+        metrics
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::callgrind::hashmap_parser::{Call, Value};
+
+    fn id(func: &str) -> Id {
+        Id {
+            file: None,
+            func: func.to_owned(),
+            obj: None,
+        }
+    }
+
+    fn ir(value: u64) -> Metrics {
+        Metrics::with_metric_kinds([(EventKind::Ir, value)])
+    }
+
+    /// `main` calls `decoder::decode` which calls itself recursively and `decoder::read` which
+    /// calls `std::alloc`
+    fn fixture() -> CallgrindMap {
+        let call = |amount, value| Call {
+            amount,
+            metrics: ir(value),
+        };
+        CallgrindMap {
+            calls: HashMap::from([
+                ((id("main"), id("decoder::decode")), call(1, 100)),
+                ((id("decoder::decode"), id("decoder::decode")), call(1, 40)),
+                ((id("decoder::decode"), id("decoder::read")), call(2, 30)),
+                ((id("decoder::read"), id("std::alloc")), call(2, 10)),
+            ]),
+            map: HashMap::from([
+                (id("main"), Value { metrics: ir(120) }),
+                (id("decoder::decode"), Value { metrics: ir(140) }),
+                (id("decoder::read"), Value { metrics: ir(30) }),
+                (id("std::alloc"), Value { metrics: ir(10) }),
+            ]),
+            sentinel: None,
+            sentinel_key: None,
+        }
+    }
+
+    fn config(functions: &[&str]) -> Config {
+        Config {
+            functions: functions.iter().map(Into::into).collect(),
+            regression_config: None,
+        }
+    }
+
+    #[test]
+    fn test_inclusive_metrics_when_single_function() {
+        assert_eq!(
+            inclusive_metrics(&fixture(), &Glob::new("decoder::read")),
+            Some(ir(30))
+        );
+    }
+
+    #[test]
+    fn test_inclusive_metrics_when_nested_and_recursive() {
+        assert_eq!(
+            inclusive_metrics(&fixture(), &Glob::new("decoder::*")),
+            Some(ir(100))
+        );
+    }
+
+    #[test]
+    fn test_inclusive_metrics_when_indirect_recursion_then_counted_twice() {
+        let mut map = fixture();
+        map.calls.insert(
+            (id("decoder::read"), id("helper")),
+            Call {
+                amount: 1,
+                metrics: ir(5),
+            },
+        );
+        map.calls.insert(
+            (id("helper"), id("decoder::read")),
+            Call {
+                amount: 1,
+                metrics: ir(4),
+            },
+        );
+        map.map.insert(id("helper"), Value { metrics: ir(5) });
+        map.map
+            .insert(id("decoder::read"), Value { metrics: ir(34) });
+
+        // The inner `decoder::read` (4) called by the `helper` is already part of the inclusive
+        // costs of the outer `decoder::read` (30) but is added once more
+        assert_eq!(
+            inclusive_metrics(&map, &Glob::new("decoder::read")),
+            Some(ir(34))
+        );
+    }
+
+    #[test]
+    fn test_inclusive_metrics_when_no_match() {
+        assert_eq!(
+            inclusive_metrics(&fixture(), &Glob::new("encoder::*")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summarize_with_old() {
+        let new = fixture();
+        let mut old = fixture();
+        old.map.remove(&id("decoder::read"));
+        old.calls
+            .remove(&(id("decoder::decode"), id("decoder::read")));
+
+        let actual = config(&["decoder::read"]).summarize(&new, Some(&old));
+
+        assert_eq!(
+            actual,
+            vec![FunctionSummary {
+                function: "decoder::read".to_owned(),
+                regressions: vec![],
+                summary: ToolMetricSummary::Callgrind(MetricsSummary::new(EitherOrBoth::Both(
+                    ir(30),
+                    Metrics::with_metric_kinds([(EventKind::Ir, Metric::Int(0))])
+                ))),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_with_limits() {
+        let new = fixture();
+        let mut old = fixture();
+        old.map
+            .insert(id("decoder::read"), Value { metrics: ir(20) });
+
+        let config = Config::try_from(api::FunctionMetricsConfig {
+            functions: vec!["decoder::read".to_owned()],
+            hard_limits: vec![],
+            soft_limits: vec![(EventKind::Ir.into(), 10f64)],
+        })
+        .unwrap();
+        let mut functions = config.summarize(&new, Some(&old));
+        config.check_and_print(&mut functions);
+
+        assert_eq!(functions[0].regressions.len(), 1);
+    }
+}
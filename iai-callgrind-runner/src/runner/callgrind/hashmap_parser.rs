@@ -29,9 +29,20 @@ pub enum SourcePath {
     Absolute(PathBuf),
 }
 
+/// The inclusive costs and the call count of all calls from one function to another
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Call {
+    /// The amount of calls
+    pub amount: u64,
+    /// The inclusive callgrind `Metrics` of all these calls
+    pub metrics: Metrics,
+}
+
 /// The `CallgrindMap`
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CallgrindMap {
+    /// The calls between the functions as mapping from the (caller, callee) to the [`Call`]
+    pub calls: HashMap<(Id, Id), Call>,
     /// The actual data containing the mapping between the [`Id`] and the [`Value`]
     pub map: HashMap<Id, Value>,
     /// The optional [`Sentinel`]
@@ -109,6 +120,14 @@ impl CallgrindMap {
                 self.map.insert(other_key.clone(), other_value.clone());
             }
         }
+        for (other_key, other_call) in &other.calls {
+            if let Some(call) = self.calls.get_mut(other_key) {
+                call.amount += other_call.amount;
+                call.metrics.add(&other_call.metrics);
+            } else {
+                self.calls.insert(other_key.clone(), other_call.clone());
+            }
+        }
     }
 }
 
@@ -146,6 +165,7 @@ impl CallgrindParser for HashMapParser {
         let mut current_id = CurrentId::default();
         let mut cfn_record = None;
 
+        let mut calls = HashMap::<(Id, Id), Call>::new();
         let mut cfn_totals = HashMap::<Id, Value>::new();
         let mut fn_totals = HashMap::<Id, Value>::new();
 
@@ -228,16 +248,28 @@ impl CallgrindParser for HashMapParser {
                             .skip(config.positions_prototype.len()),
                     )?;
 
+                    let id: Id = current_id.try_into().expect("A valid id");
+
                     if let Some(cfn_record) = cfn_record.take() {
+                        let cfn_id = cfn_record.id.expect("cfn record id must be present");
+                        calls
+                            .entry((id.clone(), cfn_id.clone()))
+                            .and_modify(|call| {
+                                call.amount += cfn_record.calls;
+                                call.metrics.add(&metrics);
+                            })
+                            .or_insert(Call {
+                                amount: cfn_record.calls,
+                                metrics: metrics.clone(),
+                            });
                         cfn_totals
-                            .entry(cfn_record.id.expect("cfn record id must be present"))
+                            .entry(cfn_id)
                             .and_modify(|value| value.metrics.add(&metrics))
                             .or_insert(Value {
                                 metrics: metrics.clone(),
                             });
                     }
 
-                    let id = current_id.try_into().expect("A valid id");
                     match fn_totals.get_mut(&id) {
                         Some(value) => value.metrics.add(&metrics),
                         None => {
@@ -264,6 +296,7 @@ impl CallgrindParser for HashMapParser {
         Ok((
             config,
             CallgrindMap {
+                calls,
                 map: fn_totals,
                 sentinel: self.sentinel.clone(),
                 sentinel_key,
@@ -306,7 +339,10 @@ impl PartialOrd for SourcePath {
     }
 }
 
-fn make_path(root: &Path, source: &str) -> SourcePath {
+/// Create a [`SourcePath`] from the `source` path string of a callgrind output file
+///
+/// Paths within the `root` directory are made relative to the `root`.
+pub fn make_path(root: &Path, source: &str) -> SourcePath {
     if source == "???" {
         SourcePath::Unknown
     } else {
@@ -0,0 +1,723 @@
+//! Module containing the parser and model for the hottest instructions of a benchmark
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::{debug, warn};
+
+use super::model::{Metrics, PositionType};
+use super::parser::{parse_header, CallgrindParser, CallgrindProperties};
+use crate::api::EventKind;
+use crate::error::Error;
+use crate::runner::tool::path::ToolOutputPath;
+use crate::util::resolve_binary_path;
+
+/// The `e_type` of position-independent executables in the ELF header
+const ELF_TYPE_DYN: u16 = 3;
+
+/// Hot instructions which are less than this amount of bytes apart are shown in the same block
+const MAX_BLOCK_GAP: u64 = 128;
+
+/// The load address of an executable is aligned to the page size
+const PAGE_SIZE: u64 = 0x1000;
+
+/// A contiguous range of instructions of a single function containing one or more hot
+/// instructions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotBlock {
+    /// The demangled name of the function
+    pub func: String,
+    /// The instructions of this block sorted by their address
+    pub instructions: Vec<HotInstruction>,
+}
+
+/// A single instruction of a [`HotBlock`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotInstruction {
+    /// The runtime address of the instruction as recorded by callgrind
+    pub addr: u64,
+    /// The disassembled instruction if `objdump` could be run successfully
+    pub disassembly: Option<String>,
+    /// True if this instruction is one of the hottest instructions
+    pub is_hot: bool,
+    /// The exclusive metrics of this instruction if it was executed
+    pub metrics: Option<Metrics>,
+}
+
+/// The hottest instructions of a benchmark grouped into [`HotBlock`]s
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HotInstructions {
+    /// The blocks in the order of their hottest instruction
+    pub blocks: Vec<HotBlock>,
+}
+
+/// The parser for the exclusive costs of instructions
+#[derive(Debug)]
+pub struct HotInstructionsParser;
+
+/// The location of an instruction
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstructionAddr {
+    /// The address of the instruction
+    pub addr: u64,
+    /// The name of the function
+    pub func: String,
+    /// The path to the object file (the executable or a shared library)
+    pub obj: PathBuf,
+}
+
+/// The exclusive costs of the instructions of one or more callgrind output files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstructionsMap(pub HashMap<InstructionAddr, Metrics>);
+
+impl HotInstructions {
+    /// Create new `HotInstructions` with the `amount` of the hottest instructions
+    ///
+    /// Only the instructions of the `executable` are considered. The instructions are sorted by
+    /// their [`EventKind::Ir`] metric and instructions of the same function close to each other
+    /// are grouped into a [`HotBlock`]. If `objdump` can be found, each block is disassembled
+    /// including the instructions which weren't executed.
+    ///
+    /// Position-independent executables are loaded by valgrind at a load address which is not
+    /// known to `objdump`, so the runtime addresses recorded by callgrind are translated with the
+    /// load bias (see [`load_bias`]) before disassembling them.
+    pub fn new(instructions: &InstructionsMap, amount: usize, executable: &Path) -> Self {
+        let executable = executable
+            .canonicalize()
+            .unwrap_or_else(|_| executable.to_owned());
+
+        let mut canonical = HashMap::<&Path, bool>::new();
+        let executed = instructions
+            .0
+            .iter()
+            .filter(|(instr, _)| {
+                *canonical.entry(instr.obj.as_path()).or_insert_with(|| {
+                    instr.obj == executable
+                        || instr
+                            .obj
+                            .canonicalize()
+                            .is_ok_and(|path| path == executable)
+                })
+            })
+            .map(|(instr, metrics)| ((instr.func.as_str(), instr.addr), metrics))
+            .collect::<HashMap<_, _>>();
+
+        let mut sorted = executed.iter().collect::<Vec<_>>();
+        sorted.sort_by(|(a_key, a_metrics), (b_key, b_metrics)| {
+            b_metrics
+                .metric_by_kind(&EventKind::Ir)
+                .cmp(&a_metrics.metric_by_kind(&EventKind::Ir))
+                .then_with(|| a_key.cmp(b_key))
+        });
+
+        // Group the hot addresses by function preserving the order of the hottest instruction
+        let mut functions = IndexMap::<&str, Vec<u64>>::new();
+        for ((func, addr), _) in sorted.into_iter().take(amount) {
+            functions.entry(func).or_default().push(*addr);
+        }
+
+        if functions.is_empty() {
+            return Self::default();
+        }
+
+        let objdump = match resolve_binary_path("objdump") {
+            Ok(path) => Some(path),
+            Err(error) => {
+                warn!("Unable to disassemble the hot instructions: {error}");
+                None
+            }
+        };
+        let objdump =
+            objdump.and_then(
+                |objdump| match load_bias(&objdump, &executable, &functions) {
+                    Ok(bias) => Some((objdump, bias)),
+                    Err(error) => {
+                        warn!("Unable to disassemble the hot instructions: {error}");
+                        None
+                    }
+                },
+            );
+
+        let mut blocks = vec![];
+        for (func, mut addrs) in functions {
+            addrs.sort_unstable();
+
+            let mut ranges: Vec<(u64, u64)> = vec![];
+            for addr in addrs.iter().copied() {
+                match ranges.last_mut() {
+                    Some((_, end)) if addr - *end <= MAX_BLOCK_GAP => *end = addr,
+                    Some(_) | None => ranges.push((addr, addr)),
+                }
+            }
+
+            for (start, end) in ranges {
+                let disassembly = objdump.as_ref().and_then(|(objdump, bias)| {
+                    disassemble_block(objdump, &executable, func, (start, end), *bias)
+                });
+
+                let lookup = |addr: u64| executed.get(&(func, addr)).map(|m| (*m).clone());
+
+                let instructions = if let Some(disassembly) = disassembly {
+                    disassembly
+                        .into_iter()
+                        .map(|(addr, asm)| HotInstruction {
+                            addr,
+                            disassembly: Some(asm),
+                            is_hot: addrs.contains(&addr),
+                            metrics: lookup(addr),
+                        })
+                        .collect()
+                } else {
+                    let mut range = executed
+                        .keys()
+                        .filter(|(f, addr)| *f == func && (start..=end).contains(addr))
+                        .map(|(_, addr)| *addr)
+                        .collect::<Vec<_>>();
+                    range.sort_unstable();
+
+                    range
+                        .into_iter()
+                        .map(|addr| HotInstruction {
+                            addr,
+                            disassembly: None,
+                            is_hot: addrs.contains(&addr),
+                            metrics: lookup(addr),
+                        })
+                        .collect()
+                };
+
+                blocks.push(HotBlock {
+                    func: func.to_owned(),
+                    instructions,
+                });
+            }
+        }
+
+        Self { blocks }
+    }
+
+    /// Return true if there are no hot instructions
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+impl HotInstructionsParser {
+    /// Parse all callgrind output files of this [`ToolOutputPath`] into a single
+    /// [`InstructionsMap`]
+    ///
+    /// Returns `None` if there weren't any output files.
+    pub fn parse_total(&self, output_path: &ToolOutputPath) -> Result<Option<InstructionsMap>> {
+        if !output_path.exists() {
+            return Ok(None);
+        }
+
+        let mut total: Option<InstructionsMap> = None;
+        for (_, _, map) in self.parse(output_path)? {
+            if let Some(total) = total.as_mut() {
+                total.add(&map);
+            } else {
+                total = Some(map);
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+impl CallgrindParser for HotInstructionsParser {
+    type Output = InstructionsMap;
+
+    fn parse_single(&self, path: &Path) -> Result<(CallgrindProperties, Self::Output)> {
+        debug!("Parsing instructions from file '{}'", path.display());
+
+        let mut iter = BufReader::new(File::open(path)?)
+            .lines()
+            .map(Result::unwrap);
+        let properties = parse_header(&mut iter)
+            .map_err(|error| Error::ParseError(path.to_owned(), error.to_string()))?;
+
+        let mut instructions_map = InstructionsMap::default();
+
+        // Without instruction addresses (`--dump-instr=no`) there is nothing we can attribute the
+        // costs to
+        let Some(instr_index) = properties
+            .positions_prototype
+            .0
+            .get_index_of(&PositionType::Instr)
+        else {
+            return Ok((properties, instructions_map));
+        };
+
+        let num_positions = properties.positions_prototype.len();
+
+        let mut current_obj = PathBuf::new();
+        let mut current_fn = String::new();
+        // The cost line following a `calls=` line contains the inclusive costs of the call and is
+        // not attributed to the instruction itself
+        let mut is_call_cost = false;
+
+        for line in iter {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once('=') {
+                Some(("ob", obj)) => current_obj = PathBuf::from(obj),
+                Some(("fn", func)) => func.clone_into(&mut current_fn),
+                Some(("calls", _)) => is_call_cost = true,
+                None if line.starts_with(|c: char| c.is_ascii_digit()) => {
+                    if is_call_cost {
+                        is_call_cost = false;
+                        continue;
+                    }
+
+                    let mut split = line.split_ascii_whitespace();
+                    let mut positions = properties.positions_prototype.clone();
+                    positions.set_iter_str(split.by_ref().take(num_positions));
+
+                    let mut metrics = properties.metrics_prototype.clone();
+                    metrics.add_iter_str(split)?;
+
+                    // This unwrap is safe since the index was taken from these positions
+                    let (_, addr) = positions.0.get_index(instr_index).unwrap();
+                    if *addr != 0 {
+                        instructions_map.insert(
+                            InstructionAddr {
+                                addr: *addr,
+                                func: current_fn.clone(),
+                                obj: current_obj.clone(),
+                            },
+                            &metrics,
+                        );
+                    }
+                }
+                // The `totals:` and `summary:` lines, the lines of the called functions and
+                // everything else we're not interested in
+                Some(_) | None => {}
+            }
+        }
+
+        Ok((properties, instructions_map))
+    }
+}
+
+impl InstructionsMap {
+    /// Sum this map with another map
+    pub fn add(&mut self, other: &Self) {
+        for (instr, metrics) in &other.0 {
+            self.insert(instr.clone(), metrics);
+        }
+    }
+
+    /// Add the `metrics` to the [`InstructionAddr`]
+    pub fn insert(&mut self, instr: InstructionAddr, metrics: &Metrics) {
+        if let Some(value) = self.0.get_mut(&instr) {
+            value.add(metrics);
+        } else {
+            self.0.insert(instr, metrics.clone());
+        }
+    }
+
+    /// Return true if this map is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Disassemble the `executable` from address `start` up to and including `end` with `objdump`
+///
+/// The `start` and `end` are runtime addresses which are translated with the load `bias` to the
+/// addresses of the `executable`. The addresses of the returned instructions are runtime addresses
+/// again.
+fn disassemble(
+    objdump: &Path,
+    executable: &Path,
+    start: u64,
+    end: u64,
+    bias: u64,
+) -> Result<Vec<(u64, String)>> {
+    let (Some(start), Some(end)) = (start.checked_sub(bias), end.checked_sub(bias)) else {
+        return Err(anyhow!(
+            "The address {start:#x} is below the load address {bias:#x} of '{}'",
+            executable.display()
+        ));
+    };
+
+    let output = Command::new(objdump)
+        .arg("--disassemble")
+        .arg("--demangle")
+        .arg("--no-show-raw-insn")
+        .arg(format!("--start-address={start:#x}"))
+        .arg(format!("--stop-address={:#x}", end + 1))
+        .arg(executable)
+        .output()
+        .map_err(|error| Error::LaunchError(objdump.to_owned(), error.to_string()))?;
+
+    if output.status.success() {
+        Ok(parse_objdump(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .map(|(addr, asm)| (addr + bias, asm))
+            .collect())
+    } else {
+        Err(anyhow!(
+            "objdump exited with '{}': {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Disassemble a [`HotBlock`] and warn if it can't be disassembled
+fn disassemble_block(
+    objdump: &Path,
+    executable: &Path,
+    func: &str,
+    (start, end): (u64, u64),
+    bias: u64,
+) -> Option<Vec<(u64, String)>> {
+    match disassemble(objdump, executable, start, end, bias) {
+        Ok(lines) if lines.is_empty() => {
+            warn!(
+                "Disassembling the hot instructions of '{func}' at {start:#x}-{end:#x} returned no \
+                 instructions"
+            );
+            None
+        }
+        Ok(lines) => Some(lines),
+        Err(error) => {
+            warn!("Unable to disassemble the hot instructions: {error}");
+            None
+        }
+    }
+}
+
+/// Return the most common page-aligned bias of the `functions` in the `symbols`
+fn find_load_bias(
+    symbols: &HashMap<String, Vec<(u64, u64)>>,
+    functions: &IndexMap<&str, Vec<u64>>,
+) -> Option<u64> {
+    let mut candidates = HashMap::<u64, usize>::new();
+    for (func, addrs) in functions {
+        let (Some(min), Some(max)) = (addrs.iter().min(), addrs.iter().max()) else {
+            continue;
+        };
+
+        // Callgrind marks recursive calls of functions with a suffix like `'2`
+        let name = func
+            .rsplit_once('\'')
+            .filter(|(_, depth)| depth.chars().all(|c| c.is_ascii_digit()))
+            .map_or(*func, |(name, _)| name);
+
+        for (start, size) in symbols.get(name).into_iter().flatten() {
+            let Some(diff) = min.checked_sub(*start) else {
+                continue;
+            };
+            let bias = diff & !(PAGE_SIZE - 1);
+            if max - bias < start + (*size).max(1) {
+                *candidates.entry(bias).or_default() += 1;
+            }
+        }
+    }
+
+    candidates
+        .into_iter()
+        .max_by(|(a_bias, a_count), (b_bias, b_count)| {
+            a_count.cmp(b_count).then_with(|| b_bias.cmp(a_bias))
+        })
+        .map(|(bias, _)| bias)
+}
+
+/// Return true if the `executable` is a position-independent ELF executable
+///
+/// Other files than ELF files are treated as position-dependent.
+fn is_position_independent(executable: &Path) -> Result<bool> {
+    // The e_ident (16 bytes) followed by the e_type (2 bytes)
+    let mut header = [0u8; 18];
+    File::open(executable)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map_err(|error| anyhow!("Failed reading '{}': {error}", executable.display()))?;
+
+    if !header.starts_with(b"\x7fELF") {
+        return Ok(false);
+    }
+
+    let elf_type = match header[5] {
+        1 => u16::from_le_bytes([header[16], header[17]]),
+        2 => u16::from_be_bytes([header[16], header[17]]),
+        _ => return Ok(false),
+    };
+
+    Ok(elf_type == ELF_TYPE_DYN)
+}
+
+/// Return the difference between the runtime addresses and the addresses of the `executable`
+///
+/// The bias of position-dependent executables is zero. Position-independent executables are
+/// loaded by valgrind at a page-aligned load address (usually `0x108000` on amd64) which isn't
+/// recorded in the callgrind output files. The bias is derived from the hot `functions` and
+/// their runtime addresses instead: For each function found in the symbol table of the
+/// `executable`, the page-aligned bias which places all its runtime addresses within the
+/// function is a candidate. The candidate of the most functions is the bias.
+fn load_bias(
+    objdump: &Path,
+    executable: &Path,
+    functions: &IndexMap<&str, Vec<u64>>,
+) -> Result<u64> {
+    if !is_position_independent(executable)? {
+        return Ok(0);
+    }
+
+    let output = Command::new(objdump)
+        .arg("--syms")
+        .arg("--demangle")
+        .arg(executable)
+        .output()
+        .map_err(|error| Error::LaunchError(objdump.to_owned(), error.to_string()))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "objdump exited with '{}': {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    find_load_bias(
+        &parse_symbols(&String::from_utf8_lossy(&output.stdout)),
+        functions,
+    )
+    .ok_or_else(|| {
+        anyhow!(
+            "Unable to determine the load address of the position-independent executable '{}'",
+            executable.display()
+        )
+    })
+}
+
+/// Parse the instruction lines of the output of `objdump --disassemble --no-show-raw-insn`
+///
+/// Instruction lines have the form `  401136:\tpush   %rbp`. All other lines like the file
+/// header or the function labels are ignored.
+fn parse_objdump(output: &str) -> Vec<(u64, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (addr, asm) = line.trim_start().split_once(':')?;
+            let addr = u64::from_str_radix(addr, 16).ok()?;
+            let asm = asm.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+            (!asm.is_empty()).then_some((addr, asm))
+        })
+        .collect()
+}
+
+/// Parse the function symbols of the output of `objdump --syms` into their addresses and sizes
+///
+/// Symbol lines have the form `0000000000001130 g     F .text\t0000000000000025 main`. The
+/// name may be preceded by the symbol visibility like `.hidden`.
+fn parse_symbols(output: &str) -> HashMap<String, Vec<(u64, u64)>> {
+    let mut symbols = HashMap::<String, Vec<(u64, u64)>>::new();
+    for line in output.lines() {
+        let Some((left, right)) = line.split_once('\t') else {
+            continue;
+        };
+        let mut fields = left.split_ascii_whitespace();
+        let Some(addr) = fields.next().and_then(|a| u64::from_str_radix(a, 16).ok()) else {
+            continue;
+        };
+        if !fields.any(|flag| flag == "F") {
+            continue;
+        }
+        let Some((size, name)) = right.trim_start().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let Ok(size) = u64::from_str_radix(size, 16) else {
+            continue;
+        };
+        let name = name.trim();
+        let name = name.strip_prefix(".hidden ").unwrap_or(name).trim();
+        if addr != 0 && !name.is_empty() {
+            symbols
+                .entry(name.to_owned())
+                .or_default()
+                .push((addr, size));
+        }
+    }
+
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn metrics(ir: u64, dr: u64) -> Metrics {
+        Metrics::with_metric_kinds([(EventKind::Ir, ir), (EventKind::Dr, dr)])
+    }
+
+    fn instr(obj: &str, func: &str, addr: u64) -> InstructionAddr {
+        InstructionAddr {
+            addr,
+            func: func.to_owned(),
+            obj: PathBuf::from(obj),
+        }
+    }
+
+    #[test]
+    fn test_parse_objdump() {
+        let output = "
+/project/target/release/bench:     file format elf64-x86-64
+
+
+Disassembly of section .text:
+
+0000000000001130 <lib::some>:
+    1130:\tpush   %rbp
+    1131:\tmov    %rsp,%rbp
+    1134:\tadd    $0x1,%rax
+";
+        assert_eq!(
+            parse_objdump(output),
+            vec![
+                (0x1130, "push %rbp".to_owned()),
+                (0x1131, "mov %rsp,%rbp".to_owned()),
+                (0x1134, "add $0x1,%rax".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hot_instructions_new_only_executable_and_grouped() {
+        let exe = "/does/not/exist/bench";
+        let map = InstructionsMap(HashMap::from([
+            (instr(exe, "lib::some", 0x1130), metrics(10, 0)),
+            (instr(exe, "lib::some", 0x1134), metrics(30, 0)),
+            (instr(exe, "lib::some", 0x2000), metrics(20, 0)),
+            (instr(exe, "lib::other", 0x3000), metrics(5, 0)),
+            (instr("/usr/lib/libc.so.6", "memcpy", 0x10), metrics(100, 0)),
+        ]));
+
+        let hot = HotInstructions::new(&map, 3, Path::new(exe));
+        assert_eq!(
+            hot.blocks
+                .iter()
+                .map(|b| (
+                    b.func.as_str(),
+                    b.instructions
+                        .iter()
+                        .map(|i| (i.addr, i.is_hot))
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("lib::some", vec![(0x1130, true), (0x1134, true)]),
+                ("lib::some", vec![(0x2000, true)])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        let output = "
+/project/target/release/bench:     file format elf64-x86-64
+
+SYMBOL TABLE:
+0000000000000000 l    df *ABS*\t0000000000000000              bench.c
+0000000000001130 g     F .text\t0000000000000025              main
+0000000000001160 l     F .text\t0000000000000010              .hidden <lib::Foo as core::fmt::Debug>::fmt
+0000000000004010 g     O .data\t0000000000000008              COUNTER
+0000000000000000       F *UND*\t0000000000000000              memcpy@GLIBC_2.14
+";
+        assert_eq!(
+            parse_symbols(output),
+            HashMap::from([
+                ("main".to_owned(), vec![(0x1130, 0x25)]),
+                (
+                    "<lib::Foo as core::fmt::Debug>::fmt".to_owned(),
+                    vec![(0x1160, 0x10)]
+                ),
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case::entry(vec![("main", vec![0x10_9130])], Some(0x10_8000))]
+    #[case::recursion(vec![("main'2", vec![0x10_9140, 0x10_9150])], Some(0x10_8000))]
+    #[case::outside_of_function(vec![("main", vec![0x10_9130, 0x10_a130])], None)]
+    #[case::unknown_function(vec![("other", vec![0x10_9130])], None)]
+    #[case::majority(
+        vec![("main", vec![0x10_9130]), ("lib::some", vec![0x10_9200]), ("lib::other", vec![0x20_2200])],
+        Some(0x10_8000)
+    )]
+    fn test_find_load_bias(
+        #[case] functions: Vec<(&str, Vec<u64>)>,
+        #[case] expected: Option<u64>,
+    ) {
+        let symbols = HashMap::from([
+            ("main".to_owned(), vec![(0x1130, 0x25)]),
+            ("lib::some".to_owned(), vec![(0x1200, 0x40)]),
+            ("lib::other".to_owned(), vec![(0x1200, 0x40)]),
+        ]);
+        let functions = functions.into_iter().collect::<IndexMap<_, _>>();
+
+        assert_eq!(find_load_bias(&symbols, &functions), expected);
+    }
+
+    #[inline(never)]
+    #[no_mangle]
+    extern "C" fn iai_callgrind_test_hot_instructions_function(value: u64) -> u64 {
+        value.wrapping_mul(31).rotate_left(7) ^ 0xff
+    }
+
+    /// The executable of these tests is a real position-independent executable
+    #[test]
+    fn test_hot_instructions_new_when_position_independent_executable() {
+        const FUNC: &str = "iai_callgrind_test_hot_instructions_function";
+        const VALGRIND_LOAD_ADDRESS: u64 = 0x10_8000;
+
+        std::hint::black_box(iai_callgrind_test_hot_instructions_function(1));
+
+        let executable = std::env::current_exe().unwrap();
+        let Ok(objdump) = resolve_binary_path("objdump") else {
+            return;
+        };
+        if !is_position_independent(&executable).unwrap() {
+            return;
+        }
+
+        let output = Command::new(objdump)
+            .arg("--syms")
+            .arg(&executable)
+            .output()
+            .unwrap();
+        let symbols = parse_symbols(&String::from_utf8_lossy(&output.stdout));
+        let (start, _) = symbols[FUNC][0];
+        let addr = start + VALGRIND_LOAD_ADDRESS;
+
+        let map = InstructionsMap(HashMap::from([(
+            InstructionAddr {
+                addr,
+                func: FUNC.to_owned(),
+                obj: executable.clone(),
+            },
+            metrics(10, 0),
+        )]));
+
+        let hot = HotInstructions::new(&map, 1, &executable);
+
+        assert_eq!(hot.blocks.len(), 1);
+        let first = &hot.blocks[0].instructions[0];
+        assert_eq!(first.addr, addr);
+        assert!(first.is_hot);
+        assert!(first.disassembly.is_some());
+        assert_eq!(first.metrics, Some(metrics(10, 0)));
+    }
+}
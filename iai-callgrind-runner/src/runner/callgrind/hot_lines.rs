@@ -0,0 +1,390 @@
+//! Module containing the parser and model for the hottest source lines of a benchmark
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use either_or_both::EitherOrBoth;
+use log::debug;
+
+use super::hashmap_parser::{make_path, SourcePath};
+use super::model::{Metrics, PositionType};
+use super::parser::{parse_header, CallgrindParser, CallgrindProperties};
+use crate::api::EventKind;
+use crate::error::Error;
+use crate::runner::metrics::MetricsSummary;
+use crate::runner::tool::path::ToolOutputPath;
+
+/// The [`EventKind`]s shown for each hot line if they were collected
+pub const HOT_LINES_EVENT_KINDS: [EventKind; 7] = [
+    EventKind::Ir,
+    EventKind::I1mr,
+    EventKind::D1mr,
+    EventKind::D1mw,
+    EventKind::ILmr,
+    EventKind::DLmr,
+    EventKind::DLmw,
+];
+
+/// A single hot source line with the new and/or old metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotLine {
+    /// The metrics of this source line
+    pub metrics_summary: MetricsSummary,
+    /// The trimmed content of the source line if the source file could be read
+    pub snippet: Option<String>,
+    /// The location of the source line
+    pub source_line: SourceLine,
+}
+
+/// The hottest source lines of a benchmark sorted by their [`EventKind::Ir`] in descending order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HotLines {
+    /// The hottest source lines within the project root
+    pub lines: Vec<HotLine>,
+    /// The folded metrics of all source lines outside the project root
+    pub other: Option<MetricsSummary>,
+}
+
+/// The parser for the exclusive costs of source lines
+#[derive(Debug)]
+pub struct HotLinesParser {
+    /// The project root directory required to make paths relative
+    pub project_root: PathBuf,
+}
+
+/// The exclusive costs of the source lines of one or more callgrind output files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinesMap {
+    /// The mapping between the source lines within the project root and their metrics
+    pub map: HashMap<SourceLine, Metrics>,
+    /// The summed up metrics of all source lines outside the project root
+    pub other: Option<Metrics>,
+}
+
+/// The location of a source line within the project root
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceLine {
+    /// The line number
+    pub line: u64,
+    /// The path to the source file relative to the project root
+    pub path: PathBuf,
+}
+
+impl HotLines {
+    /// Create new `HotLines` with the `amount` of the hottest source lines
+    ///
+    /// Source lines are sorted by their new [`EventKind::Ir`] metric. Lines without new metrics
+    /// (the line was only present in the `old` map) are not considered. The snippets are read
+    /// from the source files located in the `project_root`.
+    pub fn new(lines: EitherOrBoth<&LinesMap>, amount: usize, project_root: &Path) -> Self {
+        let (new, old) = match lines {
+            EitherOrBoth::Left(new) => (new, None),
+            EitherOrBoth::Both(new, old) => (new, Some(old)),
+            EitherOrBoth::Right(_) => return Self::default(),
+        };
+
+        let mut sorted = new.map.iter().collect::<Vec<_>>();
+        sorted.sort_by(|(a_line, a_metrics), (b_line, b_metrics)| {
+            b_metrics
+                .metric_by_kind(&EventKind::Ir)
+                .cmp(&a_metrics.metric_by_kind(&EventKind::Ir))
+                .then_with(|| a_line.cmp(b_line))
+        });
+
+        let mut sources = HashMap::<&Path, Option<Vec<String>>>::new();
+        let lines = sorted
+            .into_iter()
+            .take(amount)
+            .map(|(source_line, metrics)| {
+                let snippet = sources
+                    .entry(source_line.path.as_path())
+                    .or_insert_with(|| read_source(&project_root.join(&source_line.path)))
+                    .as_ref()
+                    .and_then(|content| {
+                        usize::try_from(source_line.line)
+                            .ok()
+                            .and_then(|line| line.checked_sub(1))
+                            .and_then(|index| content.get(index))
+                    })
+                    .map(|line| line.trim().to_owned());
+
+                let metrics = match old.and_then(|old| old.map.get(source_line)) {
+                    Some(old_metrics) => EitherOrBoth::Both(metrics.clone(), old_metrics.clone()),
+                    None => EitherOrBoth::Left(metrics.clone()),
+                };
+
+                HotLine {
+                    metrics_summary: MetricsSummary::new(metrics),
+                    snippet,
+                    source_line: source_line.clone(),
+                }
+            })
+            .collect();
+
+        let other = match (new.other.as_ref(), old.and_then(|old| old.other.as_ref())) {
+            (Some(new), Some(old)) => Some(EitherOrBoth::Both(new.clone(), old.clone())),
+            (Some(new), None) => Some(EitherOrBoth::Left(new.clone())),
+            (None, Some(_) | None) => None,
+        };
+
+        Self {
+            lines,
+            other: other.map(MetricsSummary::new),
+        }
+    }
+
+    /// Return true if there are no hot lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.other.is_none()
+    }
+}
+
+impl HotLinesParser {
+    /// Parse all callgrind output files of this [`ToolOutputPath`] into a single [`LinesMap`]
+    ///
+    /// Returns `None` if there weren't any output files.
+    pub fn parse_total(&self, output_path: &ToolOutputPath) -> Result<Option<LinesMap>> {
+        if !output_path.exists() {
+            return Ok(None);
+        }
+
+        let mut total: Option<LinesMap> = None;
+        for (_, _, map) in self.parse(output_path)? {
+            if let Some(total) = total.as_mut() {
+                total.add(&map);
+            } else {
+                total = Some(map);
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+impl CallgrindParser for HotLinesParser {
+    type Output = LinesMap;
+
+    fn parse_single(&self, path: &Path) -> Result<(CallgrindProperties, Self::Output)> {
+        debug!("Parsing source lines from file '{}'", path.display());
+
+        let mut iter = BufReader::new(File::open(path)?)
+            .lines()
+            .map(Result::unwrap);
+        let properties = parse_header(&mut iter)
+            .map_err(|error| Error::ParseError(path.to_owned(), error.to_string()))?;
+
+        let mut lines_map = LinesMap::default();
+
+        // Without line information (`--dump-line=no`) there is nothing we can attribute the costs
+        // to
+        let Some(line_index) = properties
+            .positions_prototype
+            .0
+            .get_index_of(&PositionType::Line)
+        else {
+            return Ok((properties, lines_map));
+        };
+
+        let num_positions = properties.positions_prototype.len();
+
+        // The file of the current function as set with `fl=`
+        let mut fn_file: Option<SourcePath> = None;
+        // The file of the current cost lines which may be changed with `fi=` or `fe=`
+        let mut current_file: Option<SourcePath> = None;
+        // The cost line following a `calls=` line contains the inclusive costs of the call and is
+        // not attributed to the source line itself
+        let mut is_call_cost = false;
+
+        for line in iter {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once('=') {
+                Some(("fl", file)) => {
+                    let file = make_path(&self.project_root, file);
+                    fn_file = Some(file.clone());
+                    current_file = Some(file);
+                }
+                Some(("fi" | "fe", file)) => {
+                    current_file = Some(make_path(&self.project_root, file));
+                }
+                Some(("fn", _)) => {
+                    current_file.clone_from(&fn_file);
+                }
+                Some(("calls", _)) => is_call_cost = true,
+                None if line.starts_with(|c: char| c.is_ascii_digit()) => {
+                    if is_call_cost {
+                        is_call_cost = false;
+                        continue;
+                    }
+
+                    let mut split = line.split_ascii_whitespace();
+                    let mut positions = properties.positions_prototype.clone();
+                    positions.set_iter_str(split.by_ref().take(num_positions));
+
+                    let mut metrics = properties.metrics_prototype.clone();
+                    metrics.add_iter_str(split)?;
+
+                    // This unwrap is safe since the index was taken from these positions
+                    let (_, line_number) = positions.0.get_index(line_index).unwrap();
+                    match &current_file {
+                        Some(SourcePath::Relative(path)) if *line_number != 0 => {
+                            lines_map.insert(
+                                SourceLine {
+                                    line: *line_number,
+                                    path: path.clone(),
+                                },
+                                &metrics,
+                            );
+                        }
+                        _ => lines_map.insert_other(&metrics),
+                    }
+                }
+                // The `totals:` and `summary:` lines, the lines of the called functions and
+                // everything else we're not interested in
+                Some(_) | None => {}
+            }
+        }
+
+        Ok((properties, lines_map))
+    }
+}
+
+impl LinesMap {
+    /// Sum this map with another map
+    pub fn add(&mut self, other: &Self) {
+        for (source_line, metrics) in &other.map {
+            self.insert(source_line.clone(), metrics);
+        }
+        if let Some(metrics) = &other.other {
+            self.insert_other(metrics);
+        }
+    }
+
+    /// Add the `metrics` to the [`SourceLine`]
+    pub fn insert(&mut self, source_line: SourceLine, metrics: &Metrics) {
+        if let Some(value) = self.map.get_mut(&source_line) {
+            value.add(metrics);
+        } else {
+            self.map.insert(source_line, metrics.clone());
+        }
+    }
+
+    /// Add the `metrics` to the folded metrics of the source lines outside the project root
+    pub fn insert_other(&mut self, metrics: &Metrics) {
+        if let Some(other) = self.other.as_mut() {
+            other.add(metrics);
+        } else {
+            self.other = Some(metrics.clone());
+        }
+    }
+
+    /// Return true if this map is empty
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty() && self.other.is_none()
+    }
+}
+
+fn read_source(path: &Path) -> Option<Vec<String>> {
+    File::open(path).ok().and_then(|file| {
+        BufReader::new(file)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn metrics(ir: u64, dr: u64) -> Metrics {
+        Metrics::with_metric_kinds([(EventKind::Ir, ir), (EventKind::Dr, dr)])
+    }
+
+    fn source_line(path: &str, line: u64) -> SourceLine {
+        SourceLine {
+            line,
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[rstest]
+    #[case::less(1, vec![("src/lib.rs", 2)])]
+    #[case::equal(2, vec![("src/lib.rs", 2), ("src/lib.rs", 1)])]
+    #[case::more(5, vec![("src/lib.rs", 2), ("src/lib.rs", 1)])]
+    fn test_hot_lines_new_sorted_and_taken(
+        #[case] amount: usize,
+        #[case] expected: Vec<(&str, u64)>,
+    ) {
+        let new = LinesMap {
+            map: HashMap::from([
+                (source_line("src/lib.rs", 1), metrics(10, 0)),
+                (source_line("src/lib.rs", 2), metrics(20, 0)),
+            ]),
+            other: None,
+        };
+
+        let hot_lines = HotLines::new(EitherOrBoth::Left(&new), amount, Path::new("/not/exist"));
+        assert_eq!(
+            hot_lines
+                .lines
+                .iter()
+                .map(|h| (h.source_line.path.to_str().unwrap(), h.source_line.line))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(hot_lines.lines.iter().all(|h| h.snippet.is_none()));
+        assert_eq!(hot_lines.other, None);
+    }
+
+    #[test]
+    fn test_hot_lines_new_with_old() {
+        let new = LinesMap {
+            map: HashMap::from([
+                (source_line("src/lib.rs", 1), metrics(10, 0)),
+                (source_line("src/lib.rs", 2), metrics(20, 0)),
+            ]),
+            other: Some(metrics(5, 5)),
+        };
+        let old = LinesMap {
+            map: HashMap::from([(source_line("src/lib.rs", 2), metrics(15, 0))]),
+            other: Some(metrics(4, 4)),
+        };
+
+        let expected = HotLines {
+            lines: vec![
+                HotLine {
+                    metrics_summary: MetricsSummary::new(EitherOrBoth::Both(
+                        metrics(20, 0),
+                        metrics(15, 0),
+                    )),
+                    snippet: None,
+                    source_line: source_line("src/lib.rs", 2),
+                },
+                HotLine {
+                    metrics_summary: MetricsSummary::new(EitherOrBoth::Left(metrics(10, 0))),
+                    snippet: None,
+                    source_line: source_line("src/lib.rs", 1),
+                },
+            ],
+            other: Some(MetricsSummary::new(EitherOrBoth::Both(
+                metrics(5, 5),
+                metrics(4, 4),
+            ))),
+        };
+
+        assert_eq!(
+            HotLines::new(EitherOrBoth::Both(&new, &old), 10, Path::new("/not/exist")),
+            expected
+        );
+    }
+}
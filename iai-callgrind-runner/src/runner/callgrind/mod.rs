@@ -1,13 +1,24 @@
 //! The main module for callgrind related elements
 
 pub mod args;
+pub mod call_graph;
+pub mod custom_metrics;
 pub mod flamegraph;
 pub mod flamegraph_parser;
+pub mod folding;
+pub mod function_metrics;
 pub mod hashmap_parser;
+pub mod hot_instructions;
+pub mod hot_lines;
 pub mod model;
+pub mod noise;
 pub mod parser;
+pub mod regions;
 pub mod regression;
 pub mod summary_parser;
+pub mod threads;
+pub mod throughput;
+pub mod warm_cache;
 
 use self::model::Metrics;
 use super::metrics::Metric;
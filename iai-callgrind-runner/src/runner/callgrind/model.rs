@@ -9,7 +9,7 @@ use indexmap::{indexmap, IndexMap};
 use serde::{Deserialize, Serialize};
 
 use super::CacheSummary;
-use crate::api::EventKind;
+use crate::api::{CyclesModel, EventKind};
 use crate::runner::metrics::{Metric, Summarize};
 
 /// The callgrind specific `Metrics`
@@ -101,6 +101,37 @@ impl Metrics {
         Ok(())
     }
 
+    /// Calculate and add the [`EventKind::CustomCycles`] of the [`CyclesModel`] in-place
+    ///
+    /// Additional calls to this function will overwrite the metric of the custom cycles.
+    ///
+    /// # Errors
+    ///
+    /// If the necessary cache simulation events (when running callgrind with --cache-sim) or
+    /// branch simulation events (when running callgrind with --branch-sim if the `model` has
+    /// penalties for branch mispredictions) were not present.
+    pub fn make_custom_cycles(&mut self, model: &CyclesModel) -> Result<()> {
+        let CacheSummary {
+            l1_hits,
+            l3_hits,
+            ram_hits,
+            ..
+        } = (&*self).try_into()?;
+
+        let mut cycles =
+            (l1_hits * model.l1_hits) + (l3_hits * model.ll_hits) + (ram_hits * model.ram_hits);
+        if model.bcm > 0 {
+            cycles += self.try_metric_by_kind(&EventKind::Bcm)? * model.bcm;
+        }
+        if model.bim > 0 {
+            cycles += self.try_metric_by_kind(&EventKind::Bim)? * model.bim;
+        }
+
+        self.insert(EventKind::CustomCycles, cycles);
+
+        Ok(())
+    }
+
     /// Return true if costs are already summarized
     ///
     /// This method just probes for [`EventKind::EstimatedCycles`] to detect the summarized state.
@@ -182,6 +213,8 @@ impl Default for Positions {
 
 #[cfg(test)]
 mod tests {
+    use rstest::rstest;
+
     use super::*;
 
     // Not testing here if the numbers make sense. Just if all metrics are present in the correct
@@ -234,4 +267,62 @@ mod tests {
 
         assert_eq!(metrics, expected);
     }
+
+    #[rstest]
+    #[case::default(CyclesModel::default(), 840)]
+    #[case::weights(
+        CyclesModel { l1_hits: 2, ll_hits: 10, ram_hits: 100, ..Default::default() },
+        2400
+    )]
+    #[case::branch_penalties(CyclesModel { bcm: 10, bim: 20, ..Default::default() }, 1070)]
+    fn test_metrics_make_custom_cycles(#[case] model: CyclesModel, #[case] expected: u64) {
+        use EventKind::*;
+
+        let mut metrics = Metrics::with_metric_kinds([
+            (Ir, 1),
+            (Dr, 2),
+            (Dw, 3),
+            (I1mr, 4),
+            (D1mr, 5),
+            (D1mw, 6),
+            (ILmr, 7),
+            (DLmr, 8),
+            (DLmw, 9),
+            (Bc, 20),
+            (Bcm, 15),
+            (Bi, 5),
+            (Bim, 4),
+        ]);
+
+        metrics.make_custom_cycles(&model).unwrap();
+
+        assert_eq!(
+            metrics.metric_by_kind(&CustomCycles),
+            Some(Metric::Int(expected))
+        );
+    }
+
+    #[test]
+    fn test_metrics_make_custom_cycles_when_no_branch_sim_then_error() {
+        use EventKind::*;
+
+        let mut metrics = Metrics::with_metric_kinds([
+            (Ir, 1),
+            (Dr, 2),
+            (Dw, 3),
+            (I1mr, 4),
+            (D1mr, 5),
+            (D1mw, 6),
+            (ILmr, 7),
+            (DLmr, 8),
+            (DLmw, 9),
+        ]);
+
+        metrics
+            .make_custom_cycles(&CyclesModel {
+                bcm: 10,
+                ..Default::default()
+            })
+            .unwrap_err();
+    }
 }
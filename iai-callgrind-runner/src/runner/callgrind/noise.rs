@@ -0,0 +1,292 @@
+//! Module containing the [`Noise`] of the callgrind metrics measured over repeated runs
+//!
+//! Cache simulation and multi-threaded benchmarks are not always perfectly deterministic. If a
+//! benchmark is run multiple times (`--repeat`), the spread of each [`EventKind`] is stored next to
+//! the output files (`callgrind.<name>.noise`, `*.noise.old`, `*.noise.base@<name>`) like the
+//! [`Fingerprint`](crate::runner::tool::fingerprint::Fingerprint). The soft limits of the
+//! regression checks are widened to the larger spread of the new run and the baseline, so only
+//! changes exceeding the observed noise are reported.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use either_or_both::EitherOrBoth;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::api::EventKind;
+use crate::runner::metrics::{Metric, Metrics, MetricsSummary};
+use crate::runner::summary::{BaselineKind, Diffs, ToolMetrics};
+use crate::runner::tool::parser::ParserOutput;
+use crate::runner::tool::path::ToolOutputPath;
+
+/// The spread of the metrics of repeated runs of the same benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Noise {
+    /// The amount of runs
+    pub runs: usize,
+    /// The [`Spread`] of each [`EventKind`]
+    pub spreads: IndexMap<EventKind, Spread>,
+}
+
+/// The spread of a single metric over all runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spread {
+    /// The highest value of all runs
+    pub max: Metric,
+    /// The lowest value of all runs
+    pub min: Metric,
+    /// The difference between `max` and `min` in percent of `min`. If `min` is zero, the
+    /// difference is in percent of `max` instead, so the spread is `100%` and never infinite.
+    /// Serialized as string like the other float values.
+    #[serde(with = "crate::serde::float_64")]
+    pub pct: f64,
+}
+
+impl Noise {
+    /// Create the `Noise` from the total metrics of each run
+    ///
+    /// The derived metrics like [`EventKind::EstimatedCycles`] are calculated for each run before
+    /// the spread is determined. Returns `None` if there are less than two runs.
+    pub fn from_runs(runs: &[Metrics<EventKind>]) -> Option<Self> {
+        if runs.len() < 2 {
+            return None;
+        }
+
+        let mut extremes: IndexMap<EventKind, (Metric, Metric)> = IndexMap::new();
+        for metrics in runs.iter().filter(|metrics| !metrics.is_empty()) {
+            let summarized = MetricsSummary::new(EitherOrBoth::Left(metrics.clone()))
+                .extract_costs()
+                .left()
+                .expect("The new metrics should be present");
+            for (kind, metric) in &summarized {
+                extremes
+                    .entry(*kind)
+                    .and_modify(|(min, max)| {
+                        if *metric < *min {
+                            *min = *metric;
+                        }
+                        if *metric > *max {
+                            *max = *metric;
+                        }
+                    })
+                    .or_insert((*metric, *metric));
+            }
+        }
+
+        Some(Self {
+            runs: runs.len(),
+            spreads: extremes
+                .into_iter()
+                .map(|(kind, (min, max))| {
+                    (
+                        kind,
+                        Spread {
+                            max,
+                            min,
+                            pct: spread_pct(min, max),
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Return the tolerance in percent of the metric `kind` if present
+    pub fn tolerance(&self, kind: &EventKind) -> Option<f64> {
+        self.spreads.get(kind).map(|spread| spread.pct)
+    }
+
+    /// Load the `Noise` of the `output_path` if present
+    pub fn load(output_path: &ToolOutputPath) -> Result<Option<Self>> {
+        let path = output_path.to_noise_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read noise file '{}'", path.display()))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .with_context(|| format!("Failed to parse noise file '{}'", path.display()))
+    }
+
+    /// Save this `Noise` as the noise of the `output_path`
+    pub fn save(&self, output_path: &ToolOutputPath) -> Result<()> {
+        let path = output_path.to_noise_path();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write noise file '{}'", path.display()))
+    }
+}
+
+/// Return the tolerances of the metrics as the larger [`Noise`] of the `new` run and the `base`
+///
+/// The returned tolerances are in percent and empty if there's no noise at all.
+pub fn tolerances(new: Option<&Noise>, base: Option<&Noise>) -> Vec<(EventKind, f64)> {
+    let mut tolerances: IndexMap<EventKind, f64> = IndexMap::new();
+    for noise in [new, base].into_iter().flatten() {
+        for kind in noise.spreads.keys() {
+            // The unwrap is safe because we're iterating over the keys of the spreads
+            let tolerance = noise.tolerance(kind).unwrap();
+            tolerances
+                .entry(*kind)
+                .and_modify(|value| *value = value.max(tolerance))
+                .or_insert(tolerance);
+        }
+    }
+
+    tolerances.into_iter().collect()
+}
+
+/// Return the difference between `max` and `min` in percent
+///
+/// A percentage of zero is infinite and would disable the soft limits of the regression checks
+/// entirely, so if `min` is zero the difference is taken in percent of `max`.
+fn spread_pct(min: Metric, max: Metric) -> f64 {
+    if min == Metric::Int(0) {
+        Diffs::new(max, min).diff_pct.min(100f64)
+    } else {
+        Diffs::new(max, min).diff_pct
+    }
+}
+
+/// Return the sum of the callgrind metrics of all `parsed` output files of a single run
+///
+/// Returns `None` if there are no callgrind metrics.
+pub fn total(parsed: Vec<ParserOutput>) -> Option<Metrics<EventKind>> {
+    let mut total: Option<Metrics<EventKind>> = None;
+    for ParserOutput { metrics, .. } in parsed {
+        if let ToolMetrics::Callgrind(metrics) = metrics {
+            match total.as_mut() {
+                Some(total) => total.add(&metrics),
+                None => total = Some(metrics),
+            }
+        }
+    }
+
+    total
+}
+
+/// Remove the `old` noise and rename the present noise to the `old` noise
+///
+/// This is the counterpart of [`ToolOutputPath::shift`]. If the [`BaselineKind`] is a named
+/// baseline, the present noise is just removed.
+pub fn shift(output_path: &ToolOutputPath) -> Result<()> {
+    let path = output_path.to_noise_path();
+    if output_path.baseline_kind == BaselineKind::Old {
+        let old_path = output_path.to_base_path().to_noise_path();
+        if old_path.exists() {
+            remove(&old_path)?;
+        }
+        if path.exists() {
+            std::fs::rename(&path, &old_path).with_context(|| {
+                format!(
+                    "Failed to move noise file from '{}' to '{}'",
+                    path.display(),
+                    old_path.display()
+                )
+            })?;
+        }
+    } else if path.exists() {
+        remove(&path)?;
+    } else {
+        // do nothing
+    }
+
+    Ok(())
+}
+
+fn remove(path: &Path) -> Result<()> {
+    std::fs::remove_file(path)
+        .with_context(|| format!("Failed to remove noise file '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use EventKind::*;
+
+    use super::*;
+
+    fn metrics(ir: u64, dr: u64) -> Metrics<EventKind> {
+        Metrics::with_metric_kinds([(Ir, ir), (Dr, dr)])
+    }
+
+    fn noise(spreads: &[(EventKind, f64)]) -> Noise {
+        Noise {
+            runs: 3,
+            spreads: spreads
+                .iter()
+                .map(|(kind, pct)| {
+                    (
+                        *kind,
+                        Spread {
+                            max: Metric::Int(0),
+                            min: Metric::Int(0),
+                            pct: *pct,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_noise_from_runs_when_single_run_then_none() {
+        assert_eq!(Noise::from_runs(&[metrics(100, 10)]), None);
+    }
+
+    #[test]
+    fn test_noise_from_runs() {
+        let noise =
+            Noise::from_runs(&[metrics(100, 10), metrics(150, 10), metrics(125, 10)]).unwrap();
+
+        assert_eq!(noise.runs, 3);
+        assert_eq!(
+            noise.spreads.get(&Ir),
+            Some(&Spread {
+                max: Metric::Int(150),
+                min: Metric::Int(100),
+                pct: 50f64
+            })
+        );
+        assert_eq!(noise.tolerance(&Dr), Some(0f64));
+        assert_eq!(noise.tolerance(&EstimatedCycles), None);
+    }
+
+    #[test]
+    fn test_noise_from_runs_when_min_is_zero_then_finite() {
+        let noise =
+            Noise::from_runs(&[metrics(100, 0), metrics(100, 10), metrics(100, 0)]).unwrap();
+
+        assert_eq!(
+            noise.spreads.get(&Dr),
+            Some(&Spread {
+                max: Metric::Int(10),
+                min: Metric::Int(0),
+                pct: 100f64
+            })
+        );
+        assert_eq!(
+            tolerances(Some(&noise), None),
+            vec![(Ir, 0f64), (Dr, 100f64)]
+        );
+    }
+
+    #[test]
+    fn test_tolerances_when_new_and_base() {
+        let new = noise(&[(Ir, 2f64), (Dr, 5f64)]);
+        let base = noise(&[(Ir, 3f64), (Dw, 1f64)]);
+
+        assert_eq!(
+            tolerances(Some(&new), Some(&base)),
+            vec![(Ir, 3f64), (Dr, 5f64), (Dw, 1f64)]
+        );
+    }
+
+    #[test]
+    fn test_tolerances_when_none() {
+        assert_eq!(tolerances(None, None), vec![]);
+    }
+}
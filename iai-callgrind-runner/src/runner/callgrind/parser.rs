@@ -94,12 +94,14 @@ impl CallgrindProperties {
     /// Convert into ``ProfileInfo``
     pub fn into_info(self, path: &Path) -> ProfileInfo {
         ProfileInfo {
+            cache_state: None,
             command: self.cmd.expect("A command should be present"),
             pid: self.pid.expect("A pid should be present"),
             parent_pid: None,
             details: None,
             path: path.to_owned(),
             part: self.part,
+            region: None,
             thread: self.thread,
         }
     }
@@ -0,0 +1,274 @@
+//! Module containing the named regions of a callgrind benchmark
+//!
+//! A region is defined by a function glob pattern. Callgrind is instructed with `--dump-before`
+//! and `--dump-after` to dump the collected costs when entering and leaving a matching function.
+//! The dumps triggered by `--dump-after` contain the costs of the region and are summed up per
+//! region. All other dumps contain the costs outside of any region.
+use super::regression::CallgrindRegressionConfig;
+use crate::api;
+use crate::runner::format::print_region_regressions;
+use crate::runner::summary::{ProfileData, ProfilePart, ToolMetricSummary, ToolMetrics};
+use crate::runner::tool::parser::ParserOutput;
+use crate::runner::tool::regression::RegressionConfig;
+use crate::util::Glob;
+
+/// The prefix of the description of a dump triggered by `--dump-after`
+pub const DUMP_AFTER_TRIGGER: &str = "Trigger: --dump-after=";
+
+/// A named region of a callgrind benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// The [`Glob`] pattern matching the function which defines the region
+    pub function: Glob,
+    /// The name of the region
+    pub name: String,
+    /// The regression check configuration of this region
+    pub regression_config: Option<CallgrindRegressionConfig>,
+}
+
+impl Region {
+    /// Return the callgrind command-line arguments to dump the costs of this region separately
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            format!("--dump-before={}", self.function.as_str()),
+            format!("--dump-after={}", self.function.as_str()),
+        ]
+    }
+}
+
+impl TryFrom<api::Region> for Region {
+    type Error = String;
+
+    fn try_from(value: api::Region) -> Result<Self, Self::Error> {
+        let api::Region {
+            function,
+            hard_limits,
+            name,
+            soft_limits,
+        } = value;
+
+        let regression_config =
+            CallgrindRegressionConfig::try_from_limits(soft_limits, hard_limits)
+                .map_err(|error| format!("Region '{name}': {error}"))?;
+
+        Ok(Self {
+            function: function.into(),
+            name,
+            regression_config,
+        })
+    }
+}
+
+/// Check the parts of the `data` which are regions for regressions, store and print them
+pub fn check_and_print(regions: &[Region], data: &mut ProfileData) {
+    for part in &mut data.parts {
+        let Some(region) = part
+            .details
+            .iter()
+            .find_map(|i| i.region.as_ref())
+            .and_then(|name| regions.iter().find(|r| r.name == *name))
+        else {
+            continue;
+        };
+
+        if let (Some(regression_config), ToolMetricSummary::Callgrind(metrics_summary)) =
+            (&region.regression_config, &part.metrics_summary)
+        {
+            part.regressions = regression_config.check(metrics_summary);
+            print_region_regressions(&region.name, &part.regressions);
+        }
+    }
+}
+
+/// Create the [`ProfileData`] with a [`ProfilePart`] per region
+///
+/// The `new` and `old` parts of the same region are compared with each other. The costs outside
+/// of any region are summarized in a last part without a region name.
+pub fn profile_data(
+    regions: &[Region],
+    parsed_new: Vec<ParserOutput>,
+    parsed_old: Vec<ParserOutput>,
+) -> ProfileData {
+    let (new_regions, new_rest) = merge(regions, parsed_new);
+    let (old_regions, old_rest) = merge(regions, parsed_old);
+
+    let mut parts = vec![];
+    for ((region, new), old) in regions.iter().zip(new_regions).zip(old_regions) {
+        if let Some(mut part) = to_part(new, old) {
+            for info in &mut part.details {
+                info.region = Some(region.name.clone());
+            }
+            parts.push(part);
+        }
+    }
+    if let Some(part) = to_part(new_rest, old_rest) {
+        parts.push(part);
+    }
+
+    ProfileData::from_parts(parts)
+}
+
+/// Add the metrics of the `other` [`ParserOutput`] to the `output`
+fn add(output: &mut ParserOutput, other: &ParserOutput) {
+    if let (ToolMetrics::Callgrind(metrics), ToolMetrics::Callgrind(other_metrics)) =
+        (&mut output.metrics, &other.metrics)
+    {
+        metrics.add(other_metrics);
+    }
+}
+
+/// Sum up the `parsed` outputs per region and the outputs outside of any region
+fn merge(
+    regions: &[Region],
+    parsed: Vec<ParserOutput>,
+) -> (Vec<Option<ParserOutput>>, Option<ParserOutput>) {
+    let mut merged: Vec<Option<ParserOutput>> = vec![None; regions.len()];
+    let mut rest: Option<ParserOutput> = None;
+
+    for output in parsed {
+        let entry = match region_index(regions, &output) {
+            Some(index) => &mut merged[index],
+            None => &mut rest,
+        };
+        match entry {
+            Some(entry) => add(entry, &output),
+            None => *entry = Some(output),
+        }
+    }
+
+    (merged, rest)
+}
+
+/// Return the index of the region if the `output` was dumped when leaving a region
+fn region_index(regions: &[Region], output: &ParserOutput) -> Option<usize> {
+    output
+        .header
+        .desc
+        .iter()
+        .find_map(|desc| desc.strip_prefix(DUMP_AFTER_TRIGGER))
+        .and_then(|func| regions.iter().position(|r| r.function.is_match(func)))
+}
+
+fn to_part(new: Option<ParserOutput>, old: Option<ParserOutput>) -> Option<ProfilePart> {
+    match (new, old) {
+        (Some(new), Some(old)) => Some(ProfilePart::from_new_and_old(new, old)),
+        (Some(new), None) => Some(ProfilePart::from_new(new)),
+        (None, Some(old)) => Some(ProfilePart::from_old(old)),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use either_or_both::EitherOrBoth;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::callgrind::model::Metrics;
+    use crate::runner::metrics::MetricsSummary;
+    use crate::runner::tool::parser::Header;
+
+    fn output(part: u64, trigger: &str, ir: u64) -> ParserOutput {
+        ParserOutput {
+            details: vec![],
+            header: Header {
+                command: "bench".to_owned(),
+                desc: vec![format!("Trigger: {trigger}")],
+                parent_pid: None,
+                part: Some(part),
+                pid: 1,
+                thread: None,
+            },
+            metrics: ToolMetrics::Callgrind(Metrics::with_metric_kinds([(EventKind::Ir, ir)])),
+            path: PathBuf::from(format!("callgrind.bench.out.{part}")),
+        }
+    }
+
+    fn regions() -> Vec<Region> {
+        vec![
+            Region {
+                function: Glob::new("my_crate::parse*"),
+                name: "parse".to_owned(),
+                regression_config: None,
+            },
+            Region {
+                function: Glob::new("my_crate::serialize"),
+                name: "serialize".to_owned(),
+                regression_config: Some(CallgrindRegressionConfig {
+                    fail_fast: false,
+                    hard_limits: vec![],
+                    improvements: vec![],
+                    soft_limits: vec![(EventKind::Ir, 5f64)],
+                }),
+            },
+        ]
+    }
+
+    fn parsed(parse: u64, serialize: u64) -> Vec<ParserOutput> {
+        vec![
+            output(1, "--dump-before=my_crate::parse_header", 5),
+            output(2, "--dump-after=my_crate::parse_header", parse),
+            output(3, "--dump-before=my_crate::parse_body", 1),
+            output(4, "--dump-after=my_crate::parse_body", parse),
+            output(5, "--dump-before=my_crate::serialize", 2),
+            output(6, "--dump-after=my_crate::serialize", serialize),
+            output(7, "Program termination", 3),
+        ]
+    }
+
+    fn ir(value: u64) -> Metrics {
+        Metrics::with_metric_kinds([(EventKind::Ir, value)])
+    }
+
+    #[test]
+    fn test_region_args() {
+        assert_eq!(
+            regions()[0].to_args(),
+            vec![
+                "--dump-before=my_crate::parse*".to_owned(),
+                "--dump-after=my_crate::parse*".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_data_without_old() {
+        let data = profile_data(&regions(), parsed(10, 20), vec![]);
+
+        assert_eq!(data.parts.len(), 3);
+        assert_eq!(
+            data.parts
+                .iter()
+                .map(|p| p.details.clone().left().unwrap().region)
+                .collect::<Vec<_>>(),
+            vec![Some("parse".to_owned()), Some("serialize".to_owned()), None]
+        );
+        assert_eq!(
+            data.parts[0].metrics_summary,
+            ToolMetricSummary::Callgrind(MetricsSummary::new(EitherOrBoth::Left(ir(20))))
+        );
+        assert_eq!(
+            data.parts[2].metrics_summary,
+            ToolMetricSummary::Callgrind(MetricsSummary::new(EitherOrBoth::Left(ir(11))))
+        );
+        assert_eq!(
+            data.total.summary,
+            ToolMetricSummary::Callgrind(MetricsSummary::new(EitherOrBoth::Left(ir(51))))
+        );
+    }
+
+    #[test]
+    fn test_check_and_print_with_old() {
+        let regions = regions();
+        let mut data = profile_data(&regions, parsed(10, 30), parsed(10, 20));
+
+        check_and_print(&regions, &mut data);
+
+        assert!(!data.parts[0].is_regressed());
+        assert!(data.parts[1].is_regressed());
+        assert!(!data.parts[2].is_regressed());
+    }
+}
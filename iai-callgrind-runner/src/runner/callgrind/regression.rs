@@ -1,10 +1,10 @@
 //! Module containing the callgrind specific regression check configuration
 use indexmap::{IndexMap, IndexSet};
 
-use crate::api::{self, EventKind};
+use crate::api::{self, CallgrindMetrics, EventKind, Limit};
 use crate::runner::metrics::{Metric, MetricKind, MetricsSummary};
 use crate::runner::summary::ToolRegression;
-use crate::runner::tool::regression::RegressionConfig;
+use crate::runner::tool::regression::{check_improvements, check_soft_limits, RegressionConfig};
 
 /// The callgrind regression check configuration
 #[derive(Debug, Clone, PartialEq)]
@@ -13,6 +13,8 @@ pub struct CallgrindRegressionConfig {
     pub fail_fast: bool,
     /// The hard limits
     pub hard_limits: Vec<(EventKind, Metric)>,
+    /// The expected improvements in percent
+    pub improvements: Vec<(EventKind, f64)>,
     /// The soft limits
     pub soft_limits: Vec<(EventKind, f64)>,
 }
@@ -22,11 +24,69 @@ impl Default for CallgrindRegressionConfig {
         Self {
             soft_limits: vec![(EventKind::Ir, 10f64)],
             hard_limits: Vec::default(),
+            improvements: Vec::default(),
             fail_fast: Default::default(),
         }
     }
 }
 
+impl CallgrindRegressionConfig {
+    /// Create a new `CallgrindRegressionConfig` from the `soft_limits` and `hard_limits` without
+    /// default limits
+    ///
+    /// This is the regression check configuration of the metrics shown in addition to the total
+    /// metrics of a benchmark like the metrics of functions, regions and threads. In contrast to
+    /// the regression check of the total metrics, these metrics are not checked with the default
+    /// limits, so `None` is returned if there are neither soft nor hard limits.
+    ///
+    /// # Errors
+    ///
+    /// If the limits are invalid
+    pub fn try_from_limits(
+        soft_limits: Vec<(CallgrindMetrics, f64)>,
+        hard_limits: Vec<(CallgrindMetrics, Limit)>,
+    ) -> Result<Option<Self>, String> {
+        if soft_limits.is_empty() && hard_limits.is_empty() {
+            return Ok(None);
+        }
+
+        Self::try_from(api::CallgrindRegressionConfig {
+            fail_fast: None,
+            hard_limits,
+            improvements: Vec::default(),
+            soft_limits,
+        })
+        .map(Some)
+    }
+
+    /// Return a copy of this configuration with the soft limits widened to the `tolerances`
+    ///
+    /// The `tolerances` are the noise in percent per [`EventKind`] (see
+    /// [`super::noise::tolerances`]). A positive soft limit is raised to the tolerance and a
+    /// negative soft limit is lowered to the negative tolerance if the tolerance exceeds the
+    /// limit. Soft limits without tolerance are kept as they are.
+    #[must_use]
+    pub fn with_tolerances(&self, tolerances: &[(EventKind, f64)]) -> Self {
+        let soft_limits = self
+            .soft_limits
+            .iter()
+            .map(|(kind, limit)| {
+                let limit = match tolerances.iter().find(|(k, _)| k == kind) {
+                    Some((_, tolerance)) if limit.is_sign_positive() => limit.max(*tolerance),
+                    Some((_, tolerance)) => limit.min(-*tolerance),
+                    None => *limit,
+                };
+                (*kind, limit)
+            })
+            .collect();
+
+        Self {
+            soft_limits,
+            ..self.clone()
+        }
+    }
+}
+
 impl RegressionConfig<EventKind> for CallgrindRegressionConfig {
     /// Check the `MetricsSummary` for regressions.
     ///
@@ -45,6 +105,10 @@ impl RegressionConfig<EventKind> for CallgrindRegressionConfig {
     fn get_hard_limits(&self) -> &[(EventKind, Metric)] {
         &self.hard_limits
     }
+
+    fn get_improvements(&self) -> &[(EventKind, f64)] {
+        &self.improvements
+    }
 }
 
 impl TryFrom<api::CallgrindRegressionConfig> for CallgrindRegressionConfig {
@@ -54,42 +118,52 @@ impl TryFrom<api::CallgrindRegressionConfig> for CallgrindRegressionConfig {
         let api::CallgrindRegressionConfig {
             soft_limits,
             hard_limits,
+            improvements,
             fail_fast,
         } = value;
 
-        let (soft_limits, hard_limits) = if soft_limits.is_empty() && hard_limits.is_empty() {
-            (IndexMap::from([(EventKind::Ir, 10f64)]), IndexMap::new())
-        } else {
-            let hard_limits = hard_limits
-                .into_iter()
-                .flat_map(|(callgrind_metrics, metric)| {
-                    IndexSet::from(callgrind_metrics)
-                        .into_iter()
-                        .map(move |metric_kind| {
-                            Metric::from(metric)
-                                .try_convert(metric_kind)
-                                .ok_or_else(|| {
-                                    format!(
-                                        "Invalid hard limit for \
+        check_improvements(&improvements)?;
+        check_soft_limits(&soft_limits)?;
+        let improvements = improvements
+            .into_iter()
+            .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
+            .collect::<IndexMap<_, _>>();
+
+        let (soft_limits, hard_limits) =
+            if soft_limits.is_empty() && hard_limits.is_empty() && improvements.is_empty() {
+                (IndexMap::from([(EventKind::Ir, 10f64)]), IndexMap::new())
+            } else {
+                let hard_limits = hard_limits
+                    .into_iter()
+                    .flat_map(|(callgrind_metrics, metric)| {
+                        IndexSet::from(callgrind_metrics)
+                            .into_iter()
+                            .map(move |metric_kind| {
+                                Metric::from(metric)
+                                    .try_convert(metric_kind)
+                                    .ok_or_else(|| {
+                                        format!(
+                                            "Invalid hard limit for \
                                          '{metric_kind:?}/{callgrind_metrics:?}': Expected a \
                                          'Int' but found '{metric:?}'"
-                                    )
-                                })
-                        })
-                })
-                .collect::<Result<IndexMap<EventKind, Metric>, String>>()?;
+                                        )
+                                    })
+                            })
+                    })
+                    .collect::<Result<IndexMap<EventKind, Metric>, String>>()?;
 
-            let soft_limits = soft_limits
-                .into_iter()
-                .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
-                .collect::<IndexMap<_, _>>();
+                let soft_limits = soft_limits
+                    .into_iter()
+                    .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
+                    .collect::<IndexMap<_, _>>();
 
-            (soft_limits, hard_limits)
-        };
+                (soft_limits, hard_limits)
+            };
 
         Ok(Self {
             soft_limits: soft_limits.into_iter().collect(),
             hard_limits: hard_limits.into_iter().collect(),
+            improvements: improvements.into_iter().collect(),
             fail_fast: fail_fast.unwrap_or(false),
         })
     }
@@ -212,6 +286,28 @@ mod tests {
         assert_eq!(regression.check(&summary), expected);
     }
 
+    #[rstest]
+    #[case::no_tolerances(vec![(Ir, 5f64)], vec![], vec![(Ir, 5f64)])]
+    #[case::tolerance_below_limit(vec![(Ir, 5f64)], vec![(Ir, 2f64)], vec![(Ir, 5f64)])]
+    #[case::tolerance_above_limit(vec![(Ir, 5f64)], vec![(Ir, 8f64)], vec![(Ir, 8f64)])]
+    #[case::negative_limit(vec![(Ir, -5f64)], vec![(Ir, 8f64)], vec![(Ir, -8f64)])]
+    #[case::other_kind(vec![(Ir, 5f64)], vec![(Dr, 8f64)], vec![(Ir, 5f64)])]
+    fn test_with_tolerances(
+        #[case] soft_limits: Vec<(EventKind, f64)>,
+        #[case] tolerances: Vec<(EventKind, f64)>,
+        #[case] expected: Vec<(EventKind, f64)>,
+    ) {
+        let regression = CallgrindRegressionConfig {
+            soft_limits,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            regression.with_tolerances(&tolerances).soft_limits,
+            expected
+        );
+    }
+
     #[rstest]
     #[case::empty_then_default(Vec::<(EventKind, f64)>::new(), vec![(EventKind::Ir, 10f64)])]
     #[case::single(vec![(Ir, 0f64)], vec![(Ir, 0f64)])]
@@ -234,6 +330,7 @@ mod tests {
         let expected = CallgrindRegressionConfig {
             soft_limits: expected_soft_limits,
             hard_limits: Vec::default(),
+            improvements: Vec::default(),
             fail_fast: false,
         };
         let api_regression_config = api::CallgrindRegressionConfig {
@@ -242,6 +339,7 @@ mod tests {
                 .map(|(m, l)| (m.into(), l))
                 .collect(),
             hard_limits: Vec::default(),
+            improvements: Vec::default(),
             fail_fast: Option::default(),
         };
 
@@ -303,6 +401,7 @@ mod tests {
                 .into_iter()
                 .map(|(m, l)| (m, l.into()))
                 .collect::<Vec<(EventKind, Metric)>>(),
+            improvements: Vec::default(),
             fail_fast: false,
         };
         let api_regression_config = api::CallgrindRegressionConfig {
@@ -311,6 +410,7 @@ mod tests {
                 .into_iter()
                 .map(|(m, l)| (m.into(), l.into()))
                 .collect(),
+            improvements: Vec::default(),
             fail_fast: Option::default(),
         };
 
@@ -325,6 +425,7 @@ mod tests {
         let api_regression_config = api::CallgrindRegressionConfig {
             soft_limits: Vec::default(),
             hard_limits: vec![(EventKind::Ir.into(), Limit::Float(10f64))],
+            improvements: Vec::default(),
             fail_fast: Option::default(),
         };
 
@@ -333,9 +434,99 @@ mod tests {
         let api_regression_config = api::CallgrindRegressionConfig {
             soft_limits: Vec::default(),
             hard_limits: vec![(CallgrindMetrics::All, Limit::Float(10f64))],
+            improvements: Vec::default(),
+            fail_fast: Option::default(),
+        };
+
+        CallgrindRegressionConfig::try_from(api_regression_config).unwrap_err();
+    }
+
+    #[rstest]
+    #[case::when_improved_enough(5f64, 90, 100, vec![])]
+    #[case::when_improved_exactly(10f64, 90, 100, vec![])]
+    #[case::when_improved_too_little(20f64, 90, 100, vec![(90, 100, -10f64, 20f64)])]
+    #[case::when_unchanged(5f64, 100, 100, vec![(100, 100, 0f64, 5f64)])]
+    #[case::when_regressed(5f64, 110, 100, vec![(110, 100, 10f64, 5f64)])]
+    fn test_regression_check_when_improvements(
+        #[case] improvement: f64,
+        #[case] new: u64,
+        #[case] old: u64,
+        #[case] expected: Vec<(u64, u64, f64, f64)>,
+    ) {
+        let regression = CallgrindRegressionConfig {
+            soft_limits: Vec::default(),
+            improvements: vec![(Ir, improvement)],
+            ..Default::default()
+        };
+
+        let new = cachesim_costs([new, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let old = cachesim_costs([old, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let summary = MetricsSummary::new(EitherOrBoth::Both(new, old));
+        let expected = expected
+            .iter()
+            .map(|(n, o, d, e)| ToolRegression::Improvement {
+                metric: MetricKind::Callgrind(Ir),
+                new: (*n).into(),
+                old: (*o).into(),
+                diff_pct: *d,
+                expected: *e,
+            })
+            .collect::<Vec<ToolRegression>>();
+
+        assert_eq!(regression.check(&summary), expected);
+    }
+
+    #[test]
+    fn test_try_from_regression_config_for_improvements() {
+        let expected = CallgrindRegressionConfig {
+            soft_limits: Vec::default(),
+            hard_limits: Vec::default(),
+            improvements: vec![(ILdmr, 5f64), (DLdmr, 5f64), (DLdmw, 5f64)],
+            fail_fast: false,
+        };
+        let api_regression_config = api::CallgrindRegressionConfig {
+            soft_limits: Vec::default(),
+            hard_limits: Vec::default(),
+            improvements: vec![(CallgrindMetrics::WriteBackBehaviour, 5f64)],
+            fail_fast: Option::default(),
+        };
+
+        assert_eq!(
+            CallgrindRegressionConfig::try_from(api_regression_config).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::zero(0f64)]
+    #[case::negative(-5f64)]
+    #[case::infinite(f64::INFINITY)]
+    #[case::nan(f64::NAN)]
+    fn test_try_from_regression_config_for_improvements_then_error(#[case] improvement: f64) {
+        let api_regression_config = api::CallgrindRegressionConfig {
+            soft_limits: Vec::default(),
+            hard_limits: Vec::default(),
+            improvements: vec![(EventKind::Ir.into(), improvement)],
             fail_fast: Option::default(),
         };
 
         CallgrindRegressionConfig::try_from(api_regression_config).unwrap_err();
     }
+
+    #[rstest]
+    #[case::infinite(f64::INFINITY)]
+    #[case::negative_infinite(f64::NEG_INFINITY)]
+    #[case::nan(f64::NAN)]
+    fn test_try_from_limits_when_invalid_soft_limit_then_error(#[case] limit: f64) {
+        CallgrindRegressionConfig::try_from_limits(vec![(EventKind::Ir.into(), limit)], vec![])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_try_from_limits_when_no_limits_then_none() {
+        assert_eq!(
+            CallgrindRegressionConfig::try_from_limits(vec![], vec![]),
+            Ok(None)
+        );
+    }
 }
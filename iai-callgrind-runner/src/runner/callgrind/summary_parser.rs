@@ -8,6 +8,7 @@ use log::{debug, trace};
 
 use super::model::Metrics;
 use super::parser::{parse_header, CallgrindParser, CallgrindProperties};
+use crate::api::CyclesModel;
 use crate::error::Error;
 use crate::runner::summary::ToolMetrics::Callgrind;
 use crate::runner::tool::parser::{Header, Parser, ParserOutput};
@@ -32,6 +33,8 @@ use crate::runner::tool::path::ToolOutputPath;
 /// self costs. It may be larger as the cost lines may not represent all cost of the program run.
 #[derive(Debug)]
 pub struct SummaryParser {
+    /// The [`CyclesModel`] to calculate the [`crate::api::EventKind::CustomCycles`] if present
+    pub cycles_model: Option<CyclesModel>,
     /// The [`ToolOutputPath`]
     pub output_path: ToolOutputPath,
 }
@@ -40,6 +43,7 @@ impl SummaryParser {
     /// Create a new `SummaryParser`
     pub fn new(output_path: &ToolOutputPath) -> Self {
         Self {
+            cycles_model: None,
             output_path: output_path.clone(),
         }
     }
@@ -85,7 +89,15 @@ impl CallgrindParser for SummaryParser {
             }
         }
 
-        if let Some(metrics) = metrics {
+        if let Some(mut metrics) = metrics {
+            if let Some(cycles_model) = &self.cycles_model {
+                metrics.make_custom_cycles(cycles_model).map_err(|error| {
+                    Error::ParseError(
+                        path.to_owned(),
+                        format!("Failed calculating the custom cycles: {error}"),
+                    )
+                })?;
+            }
             Ok((properties, metrics))
         } else {
             Err(Error::ParseError(
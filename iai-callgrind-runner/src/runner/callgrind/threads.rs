@@ -0,0 +1,356 @@
+//! Module containing the selection and breakdown of the threads and subprocesses of callgrind
+//!
+//! The processes and threads which don't match the selection are removed from the parsed output
+//! before anything else is calculated, so they don't count towards the total of the benchmark.
+//! The remaining outputs are summed up per process and thread, sorted by their costs and can be
+//! checked for regressions with their own limits.
+use std::collections::VecDeque;
+
+use either_or_both::EitherOrBoth;
+use indexmap::IndexMap;
+
+use super::model::Metrics;
+use super::regression::CallgrindRegressionConfig;
+use crate::api::{self, EventKind};
+use crate::runner::format::print_thread_regressions;
+use crate::runner::metrics::{Metric, MetricsSummary};
+use crate::runner::summary::{ThreadSummary, ToolMetricSummary, ToolMetrics};
+use crate::runner::tool::parser::{Header, ParserOutput};
+use crate::runner::tool::regression::RegressionConfig;
+use crate::util::Glob;
+
+/// The metric by which the threads are sorted
+pub const SORT_METRIC: EventKind = EventKind::Ir;
+
+/// The configuration of the selection and breakdown of the threads and subprocesses
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The [`Glob`] patterns matching the commands of the included processes
+    pub commands: Vec<Glob>,
+    /// The regression check configuration applied to each thread
+    pub regression_config: Option<CallgrindRegressionConfig>,
+    /// The amount of the most expensive threads to show
+    pub show: Option<usize>,
+    /// The included thread numbers
+    pub threads: Vec<usize>,
+}
+
+impl Config {
+    /// Return the callgrind command-line arguments to dump the costs of each thread separately
+    pub fn to_args(&self) -> Vec<String> {
+        vec!["--separate-threads=yes".to_owned()]
+    }
+
+    /// Return true if the process and thread of the `header` match the selection
+    ///
+    /// Without `--separate-threads=yes`, the costs of all threads are attributed to the main
+    /// thread `1`.
+    pub fn is_included(&self, header: &Header) -> bool {
+        (self.commands.is_empty() || self.commands.iter().any(|c| c.is_match(&header.command)))
+            && (self.threads.is_empty() || self.threads.contains(&header.thread.unwrap_or(1)))
+    }
+
+    /// Remove the outputs of all processes and threads which don't match the selection
+    pub fn filter(&self, parsed: Vec<ParserOutput>) -> Vec<ParserOutput> {
+        parsed
+            .into_iter()
+            .filter(|output| self.is_included(&output.header))
+            .collect()
+    }
+
+    /// Sum up the `parsed_new` and `parsed_old` outputs per process and thread
+    ///
+    /// Since the pids usually differ between two runs, the new and old threads are paired by their
+    /// command and thread number. Processes with the same command are paired in the order of their
+    /// pids. Threads without a counterpart in the other run stay unpaired. The [`ThreadSummary`]s
+    /// are sorted by the new [`SORT_METRIC`] in descending order.
+    pub fn summarize(
+        &self,
+        parsed_new: &[ParserOutput],
+        parsed_old: &[ParserOutput],
+    ) -> Vec<ThreadSummary> {
+        let mut old_threads: IndexMap<(String, usize), VecDeque<(i32, Metrics)>> = IndexMap::new();
+        for ((pid, thread), (command, old)) in merge(parsed_old) {
+            old_threads
+                .entry((command, thread))
+                .or_default()
+                .push_back((pid, old));
+        }
+
+        let mut threads = merge(parsed_new)
+            .into_iter()
+            .map(|((pid, thread), (command, new))| {
+                let metrics = match old_threads
+                    .get_mut(&(command.clone(), thread))
+                    .and_then(VecDeque::pop_front)
+                {
+                    Some((_, old)) => EitherOrBoth::Both(new, old),
+                    None => EitherOrBoth::Left(new),
+                };
+                thread_summary(command, pid, thread, metrics)
+            })
+            .collect::<Vec<_>>();
+        threads.extend(
+            old_threads
+                .into_iter()
+                .flat_map(|((command, thread), old_threads)| {
+                    old_threads.into_iter().map(move |(pid, old)| {
+                        thread_summary(command.clone(), pid, thread, EitherOrBoth::Right(old))
+                    })
+                }),
+        );
+
+        threads.sort_by_key(|t| std::cmp::Reverse(sort_key(t)));
+        threads
+    }
+
+    /// Check the threads for regressions, store and print them if present
+    pub fn check_and_print(&self, threads: &mut [ThreadSummary]) {
+        let Some(regression_config) = &self.regression_config else {
+            return;
+        };
+
+        for thread in threads {
+            if let ToolMetricSummary::Callgrind(metrics_summary) = &thread.summary {
+                thread.regressions = regression_config.check(metrics_summary);
+                print_thread_regressions(thread, &thread.regressions);
+            }
+        }
+    }
+}
+
+impl TryFrom<api::ThreadsConfig> for Config {
+    type Error = String;
+
+    fn try_from(value: api::ThreadsConfig) -> Result<Self, Self::Error> {
+        let api::ThreadsConfig {
+            commands,
+            hard_limits,
+            show,
+            soft_limits,
+            threads,
+        } = value;
+
+        let regression_config =
+            CallgrindRegressionConfig::try_from_limits(soft_limits, hard_limits)?;
+
+        Ok(Self {
+            commands: commands.into_iter().map(Into::into).collect(),
+            regression_config,
+            show,
+            threads,
+        })
+    }
+}
+
+/// Sum up the metrics of the `parsed` outputs per pid and thread
+fn merge(parsed: &[ParserOutput]) -> IndexMap<(i32, usize), (String, Metrics)> {
+    let mut merged: IndexMap<(i32, usize), (String, Metrics)> = IndexMap::new();
+    for output in parsed {
+        let ToolMetrics::Callgrind(metrics) = &output.metrics else {
+            continue;
+        };
+
+        let key = (output.header.pid, output.header.thread.unwrap_or(1));
+        match merged.get_mut(&key) {
+            Some((_, merged)) => merged.add(metrics),
+            None => {
+                merged.insert(key, (output.header.command.clone(), metrics.clone()));
+            }
+        }
+    }
+
+    merged.sort_keys();
+    merged
+}
+
+/// Create the [`ThreadSummary`] of a single thread without regressions
+fn thread_summary(
+    command: String,
+    pid: i32,
+    thread: usize,
+    metrics: EitherOrBoth<Metrics>,
+) -> ThreadSummary {
+    ThreadSummary {
+        command,
+        pid,
+        regressions: vec![],
+        summary: ToolMetricSummary::Callgrind(MetricsSummary::new(metrics)),
+        thread,
+    }
+}
+
+/// Return the new [`SORT_METRIC`] of the `thread`. Threads without new metrics come last.
+fn sort_key(thread: &ThreadSummary) -> Option<Metric> {
+    let ToolMetricSummary::Callgrind(summary) = &thread.summary else {
+        return None;
+    };
+    summary
+        .diff_by_kind(&SORT_METRIC)
+        .and_then(|diff| diff.metrics.as_ref().left().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn output(pid: i32, thread: usize, command: &str, ir: u64) -> ParserOutput {
+        ParserOutput {
+            details: vec![],
+            header: Header {
+                command: command.to_owned(),
+                desc: vec![],
+                parent_pid: None,
+                part: None,
+                pid,
+                thread: Some(thread),
+            },
+            metrics: ToolMetrics::Callgrind(Metrics::with_metric_kinds([(EventKind::Ir, ir)])),
+            path: PathBuf::from(format!("callgrind.bench.{pid}.out-{thread:02}")),
+        }
+    }
+
+    fn config(commands: &[&str], threads: &[usize]) -> Config {
+        Config {
+            commands: commands.iter().copied().map(Glob::new).collect(),
+            regression_config: None,
+            show: None,
+            threads: threads.to_vec(),
+        }
+    }
+
+    fn ir(thread: &ThreadSummary) -> EitherOrBoth<Metric> {
+        let ToolMetricSummary::Callgrind(summary) = &thread.summary else {
+            panic!("Expected a callgrind summary");
+        };
+        summary.diff_by_kind(&EventKind::Ir).unwrap().metrics
+    }
+
+    #[rstest]
+    #[case::all(&[], &[], &[(1, 1), (1, 2), (2, 1)])]
+    #[case::main_thread(&[], &[1], &[(1, 1), (2, 1)])]
+    #[case::commands(&["*child*"], &[], &[(2, 1)])]
+    #[case::commands_and_threads(&["*bench*"], &[2], &[(1, 2)])]
+    fn test_threads_filter(
+        #[case] commands: &[&str],
+        #[case] threads: &[usize],
+        #[case] expected: &[(i32, usize)],
+    ) {
+        let parsed = vec![
+            output(1, 1, "target/release/bench --iai-run", 10),
+            output(1, 2, "target/release/bench --iai-run", 20),
+            output(2, 1, "target/release/child", 30),
+        ];
+
+        let actual = config(commands, threads)
+            .filter(parsed)
+            .iter()
+            .map(|o| (o.header.pid, o.header.thread.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_threads_summarize_sorts_by_cost() {
+        let parsed_new = vec![
+            output(10, 1, "bench", 10),
+            output(10, 1, "bench", 5),
+            output(10, 2, "bench", 100),
+            output(11, 1, "child", 50),
+        ];
+        let parsed_old = vec![output(20, 1, "bench", 12), output(20, 2, "bench", 80)];
+
+        let threads = config(&[], &[]).summarize(&parsed_new, &parsed_old);
+
+        assert_eq!(
+            threads
+                .iter()
+                .map(|t| (t.pid, t.thread, ir(t)))
+                .collect::<Vec<_>>(),
+            vec![
+                (10, 2, EitherOrBoth::Both(Metric::Int(100), Metric::Int(80))),
+                (11, 1, EitherOrBoth::Left(Metric::Int(50))),
+                (10, 1, EitherOrBoth::Both(Metric::Int(15), Metric::Int(12))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threads_summarize_pairs_by_command_and_thread() {
+        let parsed_new = vec![output(10, 1, "bench", 100), output(11, 1, "child", 50)];
+        let parsed_old = vec![
+            output(5, 1, "child", 40),
+            output(20, 1, "bench", 90),
+            output(20, 2, "bench", 7),
+        ];
+
+        let threads = config(&[], &[]).summarize(&parsed_new, &parsed_old);
+
+        assert_eq!(
+            threads
+                .iter()
+                .map(|t| (t.command.as_str(), t.pid, t.thread, ir(t)))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "bench",
+                    10,
+                    1,
+                    EitherOrBoth::Both(Metric::Int(100), Metric::Int(90))
+                ),
+                (
+                    "child",
+                    11,
+                    1,
+                    EitherOrBoth::Both(Metric::Int(50), Metric::Int(40))
+                ),
+                ("bench", 20, 2, EitherOrBoth::Right(Metric::Int(7))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threads_summarize_pairs_same_commands_by_pid() {
+        let parsed_new = vec![output(30, 1, "child", 20), output(31, 1, "child", 30)];
+        let parsed_old = vec![output(40, 1, "child", 25)];
+
+        let threads = config(&[], &[]).summarize(&parsed_new, &parsed_old);
+
+        assert_eq!(
+            threads.iter().map(|t| (t.pid, ir(t))).collect::<Vec<_>>(),
+            vec![
+                (31, EitherOrBoth::Left(Metric::Int(30))),
+                (30, EitherOrBoth::Both(Metric::Int(20), Metric::Int(25))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threads_check_limits() {
+        let config = Config {
+            regression_config: Some(CallgrindRegressionConfig {
+                fail_fast: false,
+                hard_limits: vec![],
+                improvements: vec![],
+                soft_limits: vec![(EventKind::Ir, 10f64)],
+            }),
+            ..config(&[], &[])
+        };
+        let mut threads = config.summarize(
+            &[output(10, 1, "bench", 100), output(10, 2, "bench", 200)],
+            &[output(20, 1, "bench", 100), output(20, 2, "bench", 100)],
+        );
+
+        config.check_and_print(&mut threads);
+
+        assert_eq!(threads[0].thread, 2);
+        assert_eq!(threads[0].regressions.len(), 1);
+        assert!(threads[1].regressions.is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! Module containing the callgrind metrics normalized by the [`Throughput`] of a benchmark
+//!
+//! The metrics of the total are divided by the amount of bytes or elements processed by a single
+//! run of the benchmark, for example to get the instructions per byte. The `old` metrics are
+//! divided by the current amount, so the normalized metrics of a benchmark stay comparable as long
+//! as its amount doesn't change.
+use indexmap::{IndexMap, IndexSet};
+
+use super::regression::CallgrindRegressionConfig;
+use crate::api::{self, EventKind, Throughput};
+use crate::runner::format::print_throughput_regressions;
+use crate::runner::metrics::{Metric, MetricsDiff, MetricsSummary};
+use crate::runner::summary::{ThroughputSummary, ToolMetricSummary};
+use crate::runner::tool::regression::RegressionConfig;
+
+/// The metrics which are normalized if no metrics are configured
+const DEFAULT_METRICS: [EventKind; 2] = [EventKind::Ir, EventKind::EstimatedCycles];
+
+/// The configuration of the metrics normalized by the [`Throughput`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The metrics to normalize
+    pub metrics: Vec<EventKind>,
+    /// The regression check configuration applied to the normalized metrics
+    pub regression_config: Option<CallgrindRegressionConfig>,
+}
+
+impl Config {
+    /// Divide the metrics of the `metrics_summary` by the amount of the `throughput`
+    ///
+    /// Metrics which are not present in the `metrics_summary` are ignored. Returns `None` if none
+    /// of the metrics is present.
+    pub fn summarize(
+        &self,
+        throughput: Throughput,
+        metrics_summary: &MetricsSummary,
+    ) -> Option<ThroughputSummary> {
+        let amount = Metric::Int(amount(throughput));
+        let summary = self
+            .metrics
+            .iter()
+            .filter_map(|kind| {
+                metrics_summary.diff_by_kind(kind).map(|diff| {
+                    (
+                        *kind,
+                        MetricsDiff::new(diff.metrics.map(|metric| metric.div0(amount))),
+                    )
+                })
+            })
+            .collect::<MetricsSummary>();
+
+        summary.all_diffs().next()?;
+        Some(ThroughputSummary {
+            regressions: vec![],
+            summary: ToolMetricSummary::Callgrind(summary),
+        })
+    }
+
+    /// Check the normalized metrics for regressions, store and print them if present
+    pub fn check_and_print(&self, throughput: Throughput, summary: &mut ThroughputSummary) {
+        if let (Some(regression_config), ToolMetricSummary::Callgrind(metrics_summary)) =
+            (&self.regression_config, &summary.summary)
+        {
+            summary.regressions = regression_config.check(metrics_summary);
+            print_throughput_regressions(throughput, &summary.regressions);
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            metrics: DEFAULT_METRICS.to_vec(),
+            regression_config: None,
+        }
+    }
+}
+
+impl TryFrom<api::ThroughputConfig> for Config {
+    type Error = String;
+
+    fn try_from(value: api::ThroughputConfig) -> Result<Self, Self::Error> {
+        let api::ThroughputConfig {
+            hard_limits,
+            metrics,
+            soft_limits,
+        } = value;
+
+        let metrics = if metrics.is_empty() {
+            DEFAULT_METRICS.to_vec()
+        } else {
+            metrics
+                .into_iter()
+                .flat_map(IndexSet::from)
+                .collect::<IndexSet<_>>()
+                .into_iter()
+                .collect()
+        };
+
+        // The normalized metrics are floats, so the hard limits are not type checked like the
+        // hard limits of the total
+        let hard_limits = hard_limits
+            .into_iter()
+            .flat_map(|(m, l)| {
+                IndexSet::from(m)
+                    .into_iter()
+                    .map(move |e| (e, Metric::from(l)))
+            })
+            .collect::<IndexMap<_, _>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let regression_config =
+            match CallgrindRegressionConfig::try_from_limits(soft_limits, Vec::default())? {
+                Some(config) => Some(CallgrindRegressionConfig {
+                    hard_limits,
+                    ..config
+                }),
+                None if hard_limits.is_empty() => None,
+                None => Some(CallgrindRegressionConfig {
+                    fail_fast: false,
+                    hard_limits,
+                    improvements: Vec::default(),
+                    soft_limits: Vec::default(),
+                }),
+            };
+
+        Ok(Self {
+            metrics,
+            regression_config,
+        })
+    }
+}
+
+/// Return the amount of bytes or elements of the [`Throughput`]
+pub fn amount(throughput: Throughput) -> u64 {
+    match throughput {
+        Throughput::Bytes(amount) | Throughput::Elements(amount) => amount,
+    }
+}
+
+/// Return the unit of the [`Throughput`] in singular
+pub fn unit(throughput: Throughput) -> &'static str {
+    match throughput {
+        Throughput::Bytes(_) => "byte",
+        Throughput::Elements(_) => "element",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use either_or_both::EitherOrBoth;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::{CallgrindMetrics, Limit};
+    use crate::runner::callgrind::model::Metrics;
+
+    fn metrics_summary(new: u64, old: Option<u64>) -> MetricsSummary {
+        let metrics = |ir| Metrics::with_metric_kinds([(EventKind::Ir, Metric::Int(ir))]);
+        MetricsSummary::new(match old {
+            Some(old) => EitherOrBoth::Both(metrics(new), metrics(old)),
+            None => EitherOrBoth::Left(metrics(new)),
+        })
+    }
+
+    #[test]
+    fn test_summarize() {
+        let summary = Config::default()
+            .summarize(Throughput::Bytes(4), &metrics_summary(100, Some(80)))
+            .unwrap();
+
+        let ToolMetricSummary::Callgrind(metrics_summary) = summary.summary else {
+            panic!("Expected a callgrind summary");
+        };
+        assert_eq!(
+            metrics_summary
+                .diff_by_kind(&EventKind::Ir)
+                .unwrap()
+                .metrics,
+            EitherOrBoth::Both(Metric::Float(25f64), Metric::Float(20f64))
+        );
+        assert_eq!(
+            metrics_summary.diff_by_kind(&EventKind::EstimatedCycles),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summarize_when_amount_is_zero() {
+        let summary = Config::default()
+            .summarize(Throughput::Elements(0), &metrics_summary(100, None))
+            .unwrap();
+
+        let ToolMetricSummary::Callgrind(metrics_summary) = summary.summary else {
+            panic!("Expected a callgrind summary");
+        };
+        assert_eq!(
+            metrics_summary
+                .diff_by_kind(&EventKind::Ir)
+                .unwrap()
+                .metrics,
+            EitherOrBoth::Left(Metric::Float(0f64))
+        );
+    }
+
+    #[test]
+    fn test_summarize_when_no_metric_present() {
+        let config = Config {
+            metrics: vec![EventKind::Dr],
+            regression_config: None,
+        };
+        assert_eq!(
+            config.summarize(Throughput::Bytes(4), &metrics_summary(100, None)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_api_config_when_default() {
+        assert_eq!(
+            Config::try_from(api::ThroughputConfig::default()),
+            Ok(Config::default())
+        );
+    }
+
+    #[test]
+    fn test_check_with_float_hard_limit() {
+        let config = Config::try_from(api::ThroughputConfig {
+            hard_limits: vec![(CallgrindMetrics::from(EventKind::Ir), Limit::Float(20.5))],
+            metrics: vec![CallgrindMetrics::from(EventKind::Ir)],
+            soft_limits: vec![],
+        })
+        .unwrap();
+        let mut summary = config
+            .summarize(Throughput::Bytes(4), &metrics_summary(100, None))
+            .unwrap();
+
+        config.check_and_print(Throughput::Bytes(4), &mut summary);
+        assert_eq!(summary.regressions.len(), 1);
+    }
+
+    #[rstest]
+    #[case::infinite(f64::INFINITY)]
+    #[case::nan(f64::NAN)]
+    fn test_try_from_api_config_when_invalid_soft_limit_then_error(#[case] limit: f64) {
+        Config::try_from(api::ThroughputConfig {
+            hard_limits: vec![],
+            metrics: vec![],
+            soft_limits: vec![(CallgrindMetrics::from(EventKind::Ir), limit)],
+        })
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,359 @@
+//! Module containing the warm-cache mode of callgrind library benchmarks
+//!
+//! The benchmark harness runs the benchmark function `warmup + iterations` times in the same
+//! process, so the state of the cache simulation is preserved between the iterations. Callgrind
+//! is instructed with `--dump-after` to dump the collected costs when leaving the function which
+//! runs a single iteration. The dumps of the warmup iterations are not measured, but the first
+//! warmup iteration is reported as cold iteration. The dumps of the measured iterations are
+//! averaged and build the total of the benchmark.
+use std::ffi::OsString;
+
+use super::regions::DUMP_AFTER_TRIGGER;
+use crate::runner::metrics::Metric;
+use crate::runner::summary::{
+    CacheState, ProfileData, ProfilePart, ProfileTotal, ToolMetricSummary, ToolMetrics,
+};
+use crate::runner::tool::parser::ParserOutput;
+use crate::util::Glob;
+
+/// The glob pattern matching the exported function which runs a single iteration of a benchmark
+///
+/// The export name of this function is set by the `#[library_benchmark]` macro.
+pub const ITERATION_FUNCTION: &str = "__iai_callgrind::*::__run*";
+
+/// The configuration of the warm-cache mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmCache {
+    /// The amount of measured iterations
+    pub iterations: usize,
+    /// The amount of unmeasured iterations before the measured iterations
+    pub warmup: usize,
+}
+
+impl WarmCache {
+    /// Create a new `WarmCache` if the benchmark should run in the warm-cache mode
+    ///
+    /// Returns `None` if the benchmark function runs only once which is the default.
+    ///
+    /// # Errors
+    ///
+    /// If the amount of measured `iterations` is zero
+    pub fn new(warmup: Option<usize>, iterations: Option<usize>) -> Result<Option<Self>, String> {
+        let warmup = warmup.unwrap_or(0);
+        let iterations = iterations.unwrap_or(1);
+
+        if iterations == 0 {
+            return Err("The amount of measured iterations must be greater than 0".to_owned());
+        }
+
+        Ok((warmup > 0 || iterations > 1).then_some(Self { iterations, warmup }))
+    }
+
+    /// Return the callgrind command-line arguments to dump the costs of each iteration
+    pub fn to_args(&self) -> Vec<String> {
+        vec![format!("--dump-after={ITERATION_FUNCTION}")]
+    }
+
+    /// Return the argument for the benchmark harness to run all iterations
+    pub fn to_executable_arg(&self) -> OsString {
+        OsString::from(format!(
+            "--iai-iterations={}",
+            self.warmup + self.iterations
+        ))
+    }
+
+    /// Create the [`ProfileData`] of the warm-cache mode
+    ///
+    /// The total is the average over the measured iterations. If there are warmup iterations, the
+    /// first of them is reported in an additional [`ProfilePart`] with the [`CacheState::Cold`].
+    /// If no iterations could be found in the new output, for example because the benchmark ran
+    /// without the `--dump-after` argument, the usual [`ProfileData`] is returned.
+    pub fn profile_data(
+        &self,
+        parsed_new: Vec<ParserOutput>,
+        parsed_old: Vec<ParserOutput>,
+    ) -> ProfileData {
+        let (new_cold, new_warm) = self.merge(&parsed_new);
+        if new_warm.is_none() {
+            return ProfileData::new(parsed_new, (!parsed_old.is_empty()).then_some(parsed_old));
+        }
+        let (old_cold, old_warm) = self.merge(&parsed_old);
+
+        let mut parts = vec![];
+        for (cache_state, new, old) in [
+            (CacheState::Cold, new_cold, old_cold),
+            (CacheState::Warm, new_warm, old_warm),
+        ] {
+            if let Some(mut part) = to_part(new, old) {
+                for info in &mut part.details {
+                    info.cache_state = Some(cache_state);
+                }
+                parts.push(part);
+            }
+        }
+
+        let summary = parts
+            .last()
+            .map_or_else(ToolMetricSummary::default, |part| {
+                part.metrics_summary.clone()
+            });
+
+        ProfileData {
+            parts,
+            total: ProfileTotal {
+                regressions: vec![],
+                summary,
+            },
+        }
+    }
+
+    /// Sum up the `parsed` outputs per iteration and return the cold and averaged warm iterations
+    ///
+    /// The outputs are sorted by pid, thread and part. Each thread has its own sequence of dumps,
+    /// and an iteration ends with the dump triggered by leaving the [`ITERATION_FUNCTION`]. The
+    /// costs after the last iteration, for example the dump at program termination, are not part
+    /// of any iteration.
+    fn merge(&self, parsed: &[ParserOutput]) -> (Option<ParserOutput>, Option<ParserOutput>) {
+        let function = Glob::new(ITERATION_FUNCTION);
+        let mut iterations: Vec<Option<ParserOutput>> = vec![None; self.warmup + self.iterations];
+
+        let mut current = None;
+        let mut index = 0;
+        let mut pending: Option<ParserOutput> = None;
+        for output in parsed {
+            let stream = (output.header.pid, output.header.thread);
+            if current != Some(stream) {
+                current = Some(stream);
+                index = 0;
+                pending = None;
+            }
+
+            match pending.as_mut() {
+                Some(pending) => add(pending, output),
+                None => pending = Some(output.clone()),
+            }
+
+            let is_iteration_end = output
+                .header
+                .desc
+                .iter()
+                .filter_map(|desc| desc.strip_prefix(DUMP_AFTER_TRIGGER))
+                .any(|func| function.is_match(func));
+            if is_iteration_end {
+                if let (Some(iteration), Some(pending)) =
+                    (iterations.get_mut(index), pending.take())
+                {
+                    match iteration {
+                        Some(iteration) => add(iteration, &pending),
+                        None => *iteration = Some(pending),
+                    }
+                }
+                index += 1;
+            }
+        }
+
+        let cold = if self.warmup > 0 {
+            iterations[0].take()
+        } else {
+            None
+        };
+
+        let mut warm: Option<ParserOutput> = None;
+        for iteration in iterations.into_iter().skip(self.warmup).flatten() {
+            match warm.as_mut() {
+                Some(warm) => add(warm, &iteration),
+                None => warm = Some(iteration),
+            }
+        }
+        if let Some(warm) = warm.as_mut() {
+            average(warm, self.iterations as u64);
+        }
+
+        (cold, warm)
+    }
+}
+
+/// Add the metrics of the `other` [`ParserOutput`] to the `output`
+fn add(output: &mut ParserOutput, other: &ParserOutput) {
+    if let (ToolMetrics::Callgrind(metrics), ToolMetrics::Callgrind(other_metrics)) =
+        (&mut output.metrics, &other.metrics)
+    {
+        metrics.add(other_metrics);
+    }
+}
+
+/// Divide the metrics of the `output` by the amount of iterations
+///
+/// Integer metrics are rounded to the nearest integer to preserve their type.
+#[allow(clippy::cast_precision_loss)]
+fn average(output: &mut ParserOutput, iterations: u64) {
+    if let ToolMetrics::Callgrind(metrics) = &mut output.metrics {
+        for metric in metrics.0.values_mut() {
+            *metric = match *metric {
+                Metric::Int(a) => Metric::Int(a.saturating_add(iterations / 2) / iterations),
+                Metric::Float(a) => Metric::Float(a / iterations as f64),
+            };
+        }
+    }
+}
+
+fn to_part(new: Option<ParserOutput>, old: Option<ParserOutput>) -> Option<ProfilePart> {
+    match (new, old) {
+        (Some(new), Some(old)) => Some(ProfilePart::from_new_and_old(new, old)),
+        (Some(new), None) => Some(ProfilePart::from_new(new)),
+        (None, Some(old)) => Some(ProfilePart::from_old(old)),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::callgrind::model::Metrics;
+    use crate::runner::tool::parser::Header;
+
+    fn output(part: u64, thread: usize, trigger: &str, ir: u64) -> ParserOutput {
+        ParserOutput {
+            details: vec![],
+            header: Header {
+                command: "bench".to_owned(),
+                desc: vec![format!("Trigger: {trigger}")],
+                parent_pid: None,
+                part: Some(part),
+                pid: 1,
+                thread: Some(thread),
+            },
+            metrics: ToolMetrics::Callgrind(Metrics::with_metric_kinds([(EventKind::Ir, ir)])),
+            path: PathBuf::from(format!("callgrind.bench.out.{part}-{thread:02}")),
+        }
+    }
+
+    fn iteration(part: u64, thread: usize, ir: u64) -> ParserOutput {
+        output(
+            part,
+            thread,
+            "--dump-after=__iai_callgrind::bench_vec::__run_with_size",
+            ir,
+        )
+    }
+
+    fn ir(data: &ProfileData) -> Vec<(Option<CacheState>, Metric)> {
+        data.parts
+            .iter()
+            .map(|p| {
+                let ToolMetricSummary::Callgrind(summary) = &p.metrics_summary else {
+                    panic!("Expected a callgrind summary");
+                };
+                (
+                    p.details.clone().left().unwrap().cache_state,
+                    *summary
+                        .diff_by_kind(&EventKind::Ir)
+                        .unwrap()
+                        .metrics
+                        .as_ref()
+                        .left()
+                        .unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case::default(None, None, None)]
+    #[case::one_iteration(Some(0), Some(1), None)]
+    #[case::warmup(Some(1), None, Some(WarmCache { iterations: 1, warmup: 1 }))]
+    #[case::iterations(None, Some(3), Some(WarmCache { iterations: 3, warmup: 0 }))]
+    fn test_warm_cache_new(
+        #[case] warmup: Option<usize>,
+        #[case] iterations: Option<usize>,
+        #[case] expected: Option<WarmCache>,
+    ) {
+        assert_eq!(WarmCache::new(warmup, iterations).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_warm_cache_new_when_zero_iterations() {
+        WarmCache::new(Some(1), Some(0)).unwrap_err();
+    }
+
+    #[test]
+    fn test_warm_cache_args() {
+        let warm_cache = WarmCache {
+            iterations: 3,
+            warmup: 2,
+        };
+        assert_eq!(
+            warm_cache.to_args(),
+            vec!["--dump-after=__iai_callgrind::*::__run*".to_owned()]
+        );
+        assert_eq!(
+            warm_cache.to_executable_arg(),
+            OsString::from("--iai-iterations=5")
+        );
+    }
+
+    #[test]
+    fn test_profile_data_with_warmup() {
+        let warm_cache = WarmCache {
+            iterations: 2,
+            warmup: 2,
+        };
+        let parsed = vec![
+            iteration(1, 1, 100),
+            iteration(2, 1, 20),
+            iteration(3, 1, 11),
+            iteration(4, 1, 12),
+            output(5, 1, "Program termination", 5),
+        ];
+
+        let data = warm_cache.profile_data(parsed, vec![]);
+
+        assert_eq!(
+            ir(&data),
+            vec![
+                (Some(CacheState::Cold), Metric::Int(100)),
+                (Some(CacheState::Warm), Metric::Int(12))
+            ]
+        );
+        assert_eq!(data.total.summary, data.parts[1].metrics_summary);
+    }
+
+    #[test]
+    fn test_profile_data_without_warmup_sums_threads() {
+        let warm_cache = WarmCache {
+            iterations: 3,
+            warmup: 0,
+        };
+        let parsed = vec![
+            iteration(1, 1, 10),
+            iteration(2, 1, 20),
+            iteration(3, 1, 30),
+            iteration(1, 2, 1),
+            iteration(2, 2, 2),
+            iteration(3, 2, 3),
+        ];
+
+        let data = warm_cache.profile_data(parsed, vec![]);
+
+        assert_eq!(ir(&data), vec![(Some(CacheState::Warm), Metric::Int(22))]);
+    }
+
+    #[test]
+    fn test_profile_data_without_iterations() {
+        let warm_cache = WarmCache {
+            iterations: 2,
+            warmup: 1,
+        };
+        let parsed = vec![output(1, 1, "Program termination", 5)];
+
+        let data = warm_cache.profile_data(parsed.clone(), vec![]);
+
+        assert_eq!(data, ProfileData::new(parsed, None));
+    }
+}
@@ -275,6 +275,13 @@ impl BenchmarkSummaries {
         self.summaries.iter().any(BenchmarkSummary::is_regressed)
     }
 
+    /// Return true if any regressions were encountered which fail the benchmark run
+    ///
+    /// See [`BenchmarkSummary::is_failed`]
+    pub fn is_failed(&self) -> bool {
+        self.summaries.iter().any(BenchmarkSummary::is_failed)
+    }
+
     /// Set the total execution from `start` to `now`
     pub fn elapsed(&mut self, start: Instant) {
         self.total_time = Some(start.elapsed());
@@ -0,0 +1,258 @@
+//! Module containing the limits of benchmarks relative to other benchmarks with the same id
+//!
+//! In contrast to the soft and hard limits, which compare a benchmark with its own baseline, these
+//! limits compare the metrics of two different benchmarks of the same benchmark group within the
+//! same benchmark run.
+use indexmap::IndexSet;
+
+use super::format::print_comparison_regressions;
+use super::metrics::MetricKind;
+use super::summary::{BenchmarkSummary, ComparisonSummary, ToolMetricSummary, ToolRegression};
+use crate::api::{self, EventKind, ValgrindTool};
+use crate::util::Glob;
+
+/// A limit of the metrics of benchmarks relative to the metrics of other benchmarks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareLimit {
+    /// The [`Glob`] pattern matching the `function::id` of the benchmarks to compare against
+    pub baseline: Glob,
+    /// The [`Glob`] pattern matching the `function::id` of the limited benchmarks
+    pub benchmark: Glob,
+    /// The maximum percentage of the metrics of the `baseline` per [`EventKind`]
+    pub limits: Vec<(EventKind, f64)>,
+}
+
+impl CompareLimit {
+    /// Check the callgrind metrics of the `benchmark` against the `baseline` for regressions
+    ///
+    /// Returns `None` if one of the benchmarks doesn't have callgrind metrics.
+    pub fn check(
+        &self,
+        benchmark: &BenchmarkSummary,
+        baseline: &BenchmarkSummary,
+    ) -> Option<Vec<ToolRegression>> {
+        let total = |summary: &BenchmarkSummary| {
+            summary
+                .profiles
+                .iter()
+                .find(|p| p.tool == ValgrindTool::Callgrind)
+                .map(|p| p.summaries.total.summary.clone())
+        };
+
+        let Some(ToolMetricSummary::Callgrind(metrics_summary)) =
+            ToolMetricSummary::from_self_and_other(&total(benchmark)?, &total(baseline)?)
+        else {
+            return None;
+        };
+
+        let mut regressions = vec![];
+        for (event_kind, percentage) in &self.limits {
+            if let Some((new, old, diffs)) = metrics_summary
+                .diff_by_kind(event_kind)
+                .and_then(|d| d.metrics.as_ref().both().zip(d.diffs))
+                .map(|((new, old), diffs)| (*new, *old, diffs))
+            {
+                // The limit is the maximum percentage of the baseline, so a limit of `80%` is
+                // exceeded if the difference to the baseline is above `-20%`
+                let limit = percentage - 100f64;
+                if diffs.diff_pct > limit {
+                    regressions.push(ToolRegression::Soft {
+                        metric: MetricKind::Callgrind(*event_kind),
+                        new,
+                        old,
+                        diff_pct: diffs.diff_pct,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        Some(regressions)
+    }
+}
+
+impl From<api::CompareLimit> for CompareLimit {
+    fn from(value: api::CompareLimit) -> Self {
+        Self {
+            baseline: value.baseline.into(),
+            benchmark: value.benchmark.into(),
+            limits: value
+                .limits
+                .into_iter()
+                .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
+                .collect(),
+        }
+    }
+}
+
+/// Check the `summary` against all `others` with the same id, store and print the regressions
+///
+/// The `summary` can be the limited benchmark or the baseline of a [`CompareLimit`]. In both cases,
+/// the result is stored in the `summary` because the `others` have already been reported.
+pub fn check_and_print(
+    limits: &[CompareLimit],
+    summary: &mut BenchmarkSummary,
+    others: &[BenchmarkSummary],
+) {
+    let Some(name) = benchmark_name(summary) else {
+        return;
+    };
+
+    for other in others {
+        let Some(other_name) = benchmark_name(other) else {
+            continue;
+        };
+
+        for limit in limits {
+            let (regressions, benchmark, baseline) =
+                if limit.benchmark.is_match(&name) && limit.baseline.is_match(&other_name) {
+                    (limit.check(summary, other), &name, &other_name)
+                } else if limit.benchmark.is_match(&other_name) && limit.baseline.is_match(&name) {
+                    (limit.check(other, summary), &other_name, &name)
+                } else {
+                    continue;
+                };
+
+            if let Some(regressions) = regressions {
+                print_comparison_regressions(benchmark, baseline, &regressions);
+                summary.comparisons.push(ComparisonSummary {
+                    baseline: baseline.clone(),
+                    benchmark: benchmark.clone(),
+                    regressions,
+                });
+            }
+        }
+    }
+}
+
+/// Return the `function::id` of the benchmark if it has an id
+fn benchmark_name(summary: &BenchmarkSummary) -> Option<String> {
+    summary
+        .id
+        .as_ref()
+        .map(|id| format!("{}::{id}", summary.function_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use either_or_both::EitherOrBoth;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::api::CallgrindMetrics;
+    use crate::runner::callgrind::model::Metrics;
+    use crate::runner::common::ModulePath;
+    use crate::runner::metrics::{Metric, MetricsSummary};
+    use crate::runner::summary::{BenchmarkKind, Profile, ProfileData, ProfileTotal};
+
+    fn summary(function_name: &str, id: &str, ir: u64) -> BenchmarkSummary {
+        let mut summary = BenchmarkSummary::new(
+            BenchmarkKind::LibraryBenchmark,
+            PathBuf::from("/root"),
+            PathBuf::from("/root"),
+            PathBuf::from("benches/bench.rs"),
+            PathBuf::from("target/bench"),
+            &ModulePath::new("bench::group").join(function_name),
+            function_name,
+            Some(id.to_owned()),
+            None,
+            None,
+            (None, None),
+        );
+        summary.profiles.push(Profile {
+            cache_model: None,
+            custom_metrics: None,
+            cycles_model: None,
+            tool: ValgrindTool::Callgrind,
+            log_paths: vec![],
+            out_paths: vec![],
+            summaries: ProfileData {
+                parts: vec![],
+                total: ProfileTotal {
+                    regressions: vec![],
+                    summary: ToolMetricSummary::Callgrind(MetricsSummary::new(EitherOrBoth::Left(
+                        Metrics::with_metric_kinds([(EventKind::Ir, ir)]),
+                    ))),
+                },
+            },
+            flamegraphs: vec![],
+            functions: vec![],
+            threads: vec![],
+            throughput: None,
+        });
+        summary
+    }
+
+    fn limits() -> Vec<CompareLimit> {
+        vec![CompareLimit::from(api::CompareLimit {
+            baseline: "baseline_impl::*".to_owned(),
+            benchmark: "optimized::*".to_owned(),
+            limits: vec![(CallgrindMetrics::from(EventKind::Ir), 80f64)],
+        })]
+    }
+
+    #[test]
+    fn test_check_and_print_when_below_limit() {
+        let mut optimized = summary("optimized", "small", 70);
+        check_and_print(
+            &limits(),
+            &mut optimized,
+            &[summary("baseline_impl", "small", 100)],
+        );
+
+        assert_eq!(optimized.comparisons.len(), 1);
+        assert!(!optimized.is_regressed());
+    }
+
+    #[test]
+    fn test_check_and_print_when_exceeds_limit() {
+        let mut optimized = summary("optimized", "small", 90);
+        check_and_print(
+            &limits(),
+            &mut optimized,
+            &[summary("baseline_impl", "small", 100)],
+        );
+
+        assert_eq!(
+            optimized.comparisons,
+            vec![ComparisonSummary {
+                baseline: "baseline_impl::small".to_owned(),
+                benchmark: "optimized::small".to_owned(),
+                regressions: vec![ToolRegression::Soft {
+                    metric: MetricKind::Callgrind(EventKind::Ir),
+                    new: Metric::Int(90),
+                    old: Metric::Int(100),
+                    diff_pct: -10f64,
+                    limit: -20f64,
+                }]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_and_print_when_baseline_runs_last() {
+        let mut baseline = summary("baseline_impl", "small", 100);
+        check_and_print(
+            &limits(),
+            &mut baseline,
+            &[summary("optimized", "small", 90)],
+        );
+
+        assert!(baseline.is_regressed());
+        assert_eq!(baseline.comparisons[0].benchmark, "optimized::small");
+    }
+
+    #[test]
+    fn test_check_and_print_when_not_matching() {
+        let mut other = summary("other", "small", 100);
+        check_and_print(
+            &limits(),
+            &mut other,
+            &[summary("baseline_impl", "small", 10)],
+        );
+
+        assert!(other.comparisons.is_empty());
+    }
+}
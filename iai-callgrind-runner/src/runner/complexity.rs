@@ -0,0 +1,243 @@
+//! Module containing the complexity fit of parameterized library benchmarks
+//!
+//! The benchmarks of the same benchmark function which are annotated with an input size form a
+//! series. The metrics of this series are fitted with the method of least squares to the models
+//! `c * f(n)` with `f(n)` one of `1`, `log n`, `n`, `n log n` and `n^2`. The model with the
+//! smallest root mean square error (normalized by the mean of the metrics) is the best fit.
+use either_or_both::EitherOrBoth;
+
+use super::format::print_complexity;
+use super::metrics::Metric;
+use super::summary::{
+    BenchmarkSummary, Complexity, ComplexityFit, ComplexitySummary, ToolMetricSummary,
+};
+use crate::api::{EventKind, RegressionPolicy, ValgrindTool};
+
+/// The models in the order of their precedence if two models fit equally well
+const MODELS: [Complexity; 5] = [
+    Complexity::Constant,
+    Complexity::Logarithmic,
+    Complexity::Linear,
+    Complexity::Linearithmic,
+    Complexity::Quadratic,
+];
+
+/// The input sizes and metrics of the benchmarks of a single benchmark function
+#[derive(Debug, Clone, Default)]
+pub struct Series(Vec<(u64, EitherOrBoth<Metric>)>);
+
+impl Series {
+    /// Add the metric of the callgrind total of the `summary` with the input `size`
+    ///
+    /// Benchmarks without callgrind metrics or without the `metric` are ignored.
+    pub fn add(&mut self, size: u64, metric: EventKind, summary: &BenchmarkSummary) {
+        if let Some(metrics) = summary
+            .profiles
+            .iter()
+            .find(|p| p.tool == ValgrindTool::Callgrind)
+            .and_then(|p| match &p.summaries.total.summary {
+                ToolMetricSummary::Callgrind(metrics_summary) => {
+                    metrics_summary.diff_by_kind(&metric)
+                }
+                _ => None,
+            })
+            .map(|diff| diff.metrics)
+        {
+            self.0.push((size, metrics));
+        }
+    }
+
+    /// Fit the `new` and, if all benchmarks have one, the `old` metrics
+    ///
+    /// Returns `None` if there are less than two different input sizes.
+    pub fn fit(&self, metric: EventKind) -> Option<ComplexitySummary> {
+        let new = self
+            .0
+            .iter()
+            .filter_map(|(size, metrics)| metrics.left().map(|m| (*size, m)))
+            .collect::<Vec<_>>();
+        let old = self
+            .0
+            .iter()
+            .map(|(size, metrics)| metrics.right().map(|m| (*size, m)))
+            .collect::<Option<Vec<_>>>();
+
+        let fits = match (fit(&new), old.as_deref().and_then(fit)) {
+            (Some(new), Some(old)) => EitherOrBoth::Both(new, old),
+            (Some(new), None) => EitherOrBoth::Left(new),
+            (None, Some(old)) => EitherOrBoth::Right(old),
+            (None, None) => return None,
+        };
+        let regressed = fits
+            .as_ref()
+            .both()
+            .is_some_and(|(new, old)| new.class != old.class);
+
+        Some(ComplexitySummary {
+            fits,
+            metric,
+            regressed,
+        })
+    }
+}
+
+/// Fit the complexity of the `series`, store it in the `summary` and print it
+///
+/// A changed complexity class is not considered a regression if the [`RegressionPolicy`] is
+/// `Ignore`.
+pub fn fit_and_print(
+    series: &Series,
+    metric: EventKind,
+    regression_policy: RegressionPolicy,
+    summary: &mut BenchmarkSummary,
+) {
+    if let Some(mut complexity) = series.fit(metric) {
+        if regression_policy == RegressionPolicy::Ignore {
+            complexity.regressed = false;
+        }
+        print_complexity(&summary.function_name, &complexity);
+        summary.complexity = Some(complexity);
+    }
+}
+
+/// Return the best fitting [`Complexity`] model of the `points` if there are at least two sizes
+fn fit(points: &[(u64, Metric)]) -> Option<ComplexityFit> {
+    let first = points.first()?;
+    if points.iter().all(|(size, _)| *size == first.0) {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let points = points
+        .iter()
+        .map(|(size, metric)| (*size as f64, f64::from(*metric)))
+        .collect::<Vec<_>>();
+    #[allow(clippy::cast_precision_loss)]
+    let len = points.len() as f64;
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / len;
+
+    let mut best: Option<ComplexityFit> = None;
+    for class in MODELS {
+        let numerator = points
+            .iter()
+            .map(|(n, y)| model(class, *n) * y)
+            .sum::<f64>();
+        let denominator = points
+            .iter()
+            .map(|(n, _)| model(class, *n).powi(2))
+            .sum::<f64>();
+        if denominator == 0f64 {
+            continue;
+        }
+
+        let coefficient = numerator / denominator;
+        let squares = points
+            .iter()
+            .map(|(n, y)| (y - coefficient * model(class, *n)).powi(2))
+            .sum::<f64>();
+        let rms = if mean == 0f64 {
+            0f64
+        } else {
+            (squares / len).sqrt() / mean
+        };
+
+        if best.as_ref().map_or(true, |b| rms < b.rms) {
+            best = Some(ComplexityFit {
+                class,
+                coefficient,
+                rms,
+            });
+        }
+    }
+
+    best
+}
+
+/// Return the value of the model function `f(n)` of the [`Complexity`] class
+fn model(class: Complexity, n: f64) -> f64 {
+    match class {
+        Complexity::Constant => 1f64,
+        Complexity::Logarithmic => n.max(1f64).log2(),
+        Complexity::Linear => n,
+        Complexity::Linearithmic => n * n.max(1f64).log2(),
+        Complexity::Quadratic => n * n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn points<F>(func: F) -> Vec<(u64, Metric)>
+    where
+        F: Fn(f64) -> f64,
+    {
+        [8, 64, 512, 4096]
+            .into_iter()
+            .map(|n| {
+                #[allow(clippy::cast_precision_loss)]
+                #[allow(clippy::cast_possible_truncation)]
+                #[allow(clippy::cast_sign_loss)]
+                let y = func(n as f64).round() as u64;
+                (n, Metric::Int(y))
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case::constant(|_| 1000f64, Complexity::Constant)]
+    #[case::logarithmic(|n: f64| 100f64.mul_add(n.log2(), 10f64), Complexity::Logarithmic)]
+    #[case::linear(|n: f64| 20f64.mul_add(n, 500f64), Complexity::Linear)]
+    #[case::linearithmic(|n: f64| 3f64 * n * n.log2(), Complexity::Linearithmic)]
+    #[case::quadratic(|n: f64| n.mul_add(n, 100f64), Complexity::Quadratic)]
+    fn test_fit(#[case] func: fn(f64) -> f64, #[case] expected: Complexity) {
+        assert_eq!(fit(&points(func)).unwrap().class, expected);
+    }
+
+    #[test]
+    fn test_fit_when_single_size() {
+        assert_eq!(fit(&[(10, Metric::Int(100)), (10, Metric::Int(110))]), None);
+    }
+
+    #[test]
+    fn test_series_fit_when_class_changed() {
+        let new = points(|n| n * n);
+        let old = points(|n| 10f64 * n);
+        let series = Series(
+            new.into_iter()
+                .zip(old)
+                .map(|((size, new), (_, old))| (size, EitherOrBoth::Both(new, old)))
+                .collect(),
+        );
+
+        let summary = series.fit(EventKind::Ir).unwrap();
+        assert!(summary.regressed);
+        assert_eq!(
+            summary.fits.as_ref().map(|f| f.class),
+            EitherOrBoth::Both(Complexity::Quadratic, Complexity::Linear)
+        );
+    }
+
+    #[test]
+    fn test_series_fit_when_old_incomplete() {
+        let mut series = Series(
+            points(|n| 10f64 * n)
+                .into_iter()
+                .map(|(size, new)| (size, EitherOrBoth::Both(new, new)))
+                .collect(),
+        );
+        series
+            .0
+            .push((8192, EitherOrBoth::Left(Metric::Int(81920))));
+
+        let summary = series.fit(EventKind::Ir).unwrap();
+        assert!(!summary.regressed);
+        assert_eq!(
+            summary.fits.as_ref().map(|f| f.class),
+            EitherOrBoth::Left(Complexity::Linear)
+        );
+    }
+}
@@ -0,0 +1,711 @@
+//! The module containing the project-wide configuration file `iai-callgrind.toml`
+//!
+//! The configuration file is searched in the workspace root and in the package directory of the
+//! benchmark. Both files are optional. If both are present, the settings of the package directory
+//! take precedence over the settings of the workspace root. A configuration file consists of the
+//! following tables which can also be repeated in a `[profile.<NAME>]` table:
+//!
+//! * `[args]`: The defaults of the command-line arguments (`--callgrind-limits`, ...). These act
+//!   like command-line arguments which are overridden by the arguments on the command-line and
+//!   the `IAI_CALLGRIND_*` environment variables.
+//! * `[config]`: The configuration of the benchmarks as if specified in code (`default-tool`,
+//!   `tools`, `valgrind-args`, ...) plus the tool specific tables `[config.callgrind]`, ...
+//!   These are the lowest level of the configuration and overridden by any configuration in the
+//!   benchmark files, beginning with the configuration of the `main!` macro.
+//! * `[override."<GLOB>"]`: Like `[config]` but only applied to benchmarks which module path
+//!   matches the glob pattern.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory};
+use glob::Pattern;
+use indexmap::IndexMap;
+use log::debug;
+use serde::Deserialize;
+
+use super::args::{self, CommandLineArgs};
+use super::common::ModulePath;
+use super::tool::regression::ToolRegressionConfig;
+use crate::api::{
+    self, update_option, BinaryBenchmarkConfig, LibraryBenchmarkConfig, RawArgs, RegressionPolicy,
+    Tool, ToolOutputFormat, Tools, ValgrindTool,
+};
+
+/// The name of the configuration file
+pub const FILE_NAME: &str = "iai-callgrind.toml";
+
+/// The configuration files of the workspace and the package merged with the selected profile
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    /// The default values of the command-line arguments of all `[args]` tables
+    args: IndexMap<String, String>,
+    /// The `[config]` and `[override]` tables in the order they are applied
+    layers: Vec<Layer>,
+}
+
+/// The benchmark configuration from the configuration files
+///
+/// The values of the `[config]` tables are resolved into a single `Settings` per benchmark (see
+/// [`ConfigFile::resolve`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    /// The valgrind tool to run instead of the default callgrind
+    pub default_tool: Option<ValgrindTool>,
+    /// The configuration of the output format
+    pub output_format: api::OutputFormat,
+    /// The policy how failed regression checks are treated
+    pub regression_policy: Option<RegressionPolicy>,
+    /// The defaults of the tools from the tool specific tables like `[config.callgrind]`
+    pub tool_defaults: IndexMap<ValgrindTool, ToolDefaults>,
+    /// The valgrind tools to run in addition to the default tool
+    pub tools: Option<Vec<ValgrindTool>>,
+    /// The arguments to pass to all tools
+    pub valgrind_args: RawArgs,
+}
+
+/// The defaults of a single valgrind tool from a tool specific table like `[config.callgrind]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolDefaults {
+    /// The command-line arguments of the tool
+    pub args: RawArgs,
+    /// The soft and hard limits of the tool
+    pub limits: Option<ToolRegressionConfig>,
+    /// The metrics shown in the terminal output
+    pub metrics: Option<ToolOutputFormat>,
+}
+
+/// A `[config]` or `[override."<GLOB>"]` table
+#[derive(Debug, Clone)]
+struct Layer {
+    pattern: Option<Pattern>,
+    settings: Settings,
+}
+
+/// The deserialized content of a configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawFile {
+    args: IndexMap<String, RawValue>,
+    config: RawSettings,
+    #[serde(rename = "override")]
+    overrides: IndexMap<String, RawSettings>,
+    profile: IndexMap<String, RawSection>,
+}
+
+/// The tables which can be present at the top-level and in a profile
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawSection {
+    args: IndexMap<String, RawValue>,
+    config: RawSettings,
+    #[serde(rename = "override")]
+    overrides: IndexMap<String, RawSettings>,
+}
+
+/// The deserialized `[config]` or `[override."<GLOB>"]` table
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct RawSettings {
+    bbv: Option<RawToolSettings>,
+    cachegrind: Option<RawToolSettings>,
+    callgrind: Option<RawToolSettings>,
+    default_tool: Option<String>,
+    dhat: Option<RawToolSettings>,
+    drd: Option<RawToolSettings>,
+    helgrind: Option<RawToolSettings>,
+    massif: Option<RawToolSettings>,
+    memcheck: Option<RawToolSettings>,
+    regression_policy: Option<String>,
+    show_grid: Option<bool>,
+    show_intermediate: Option<bool>,
+    tolerance: Option<f64>,
+    tools: Option<String>,
+    truncate_description: Option<RawValue>,
+    valgrind_args: Option<String>,
+}
+
+/// The deserialized tool specific table like `[config.callgrind]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawToolSettings {
+    args: Option<String>,
+    limits: Option<String>,
+    metrics: Option<String>,
+}
+
+/// A scalar value which is converted to the command-line syntax
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl ConfigFile {
+    /// Load the configuration files from the `workspace_root` and the `package_dir`
+    ///
+    /// If present, the tables of the `profile` are applied on top of the top-level tables.
+    ///
+    /// # Errors
+    ///
+    /// If a configuration file could not be read or is invalid or if the `profile` does not exist
+    /// in any of the configuration files
+    pub fn load(workspace_root: &Path, package_dir: &Path, profile: Option<&str>) -> Result<Self> {
+        let mut paths = vec![workspace_root.join(FILE_NAME)];
+        if package_dir != workspace_root {
+            paths.push(package_dir.join(FILE_NAME));
+        }
+
+        let mut files = vec![];
+        for path in paths.into_iter().filter(|p| p.is_file()) {
+            debug!("Loading configuration file: '{}'", path.display());
+
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read '{}'", path.display()))?;
+            let file = toml::from_str::<RawFile>(&content)
+                .with_context(|| format!("Failed to parse '{}'", path.display()))?;
+            files.push((path, file));
+        }
+
+        Self::from_files(files, profile)
+    }
+
+    fn from_files(files: Vec<(PathBuf, RawFile)>, profile: Option<&str>) -> Result<Self> {
+        let mut sections = vec![];
+        let mut profiles = vec![];
+        for (path, file) in files {
+            sections.push((
+                path.clone(),
+                RawSection {
+                    args: file.args,
+                    config: file.config,
+                    overrides: file.overrides,
+                },
+            ));
+            profiles.push((path, file.profile));
+        }
+
+        if let Some(name) = profile {
+            let num_sections = sections.len();
+            for (path, mut profiles) in profiles {
+                if let Some(section) = profiles.shift_remove(name) {
+                    sections.push((path, section));
+                }
+            }
+            if sections.len() == num_sections {
+                bail!("The profile '{name}' was not found in any {FILE_NAME}");
+            }
+        }
+
+        let mut this = Self::default();
+        let mut overrides = vec![];
+        for (path, section) in sections {
+            let context = || format!("Invalid configuration in '{}'", path.display());
+
+            for (key, value) in section.args {
+                this.args.insert(key, value.to_string());
+            }
+
+            this.layers.push(Layer {
+                pattern: None,
+                settings: section.config.try_into().with_context(context)?,
+            });
+
+            for (glob, config) in section.overrides {
+                let pattern = Pattern::new(&glob)
+                    .map_err(|error| anyhow!("Invalid override pattern '{glob}': {error}"))
+                    .with_context(context)?;
+                overrides.push(Layer {
+                    pattern: Some(pattern),
+                    settings: config
+                        .try_into()
+                        .with_context(|| format!("Invalid override '{glob}'"))
+                        .with_context(context)?,
+                });
+            }
+        }
+        this.layers.extend(overrides);
+
+        Ok(this)
+    }
+
+    /// Return the defaults of the `[args]` tables as command-line arguments
+    ///
+    /// Arguments which were already given on the command-line or with an environment variable
+    /// according to the `matches` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// If a key of the `[args]` tables is not a (long) command-line argument
+    pub fn command_line_args(&self, matches: &ArgMatches) -> Result<Vec<String>> {
+        let command = CommandLineArgs::command();
+
+        let mut command_line_args = vec![];
+        for (key, value) in &self.args {
+            let arg = command
+                .get_arguments()
+                .find(|arg| !arg.is_hide_set() && arg.get_long() == Some(key.as_str()))
+                .filter(|arg| arg.get_id() != "config_profile")
+                .ok_or_else(|| {
+                    anyhow!("Invalid key '{key}' in [args] of {FILE_NAME}: Unknown argument")
+                })?;
+
+            if let Some(ValueSource::CommandLine | ValueSource::EnvVariable) =
+                matches.value_source(arg.get_id().as_str())
+            {
+                continue;
+            }
+
+            command_line_args.push(format!("--{key}={value}"));
+        }
+
+        Ok(command_line_args)
+    }
+
+    /// Resolve the [`Settings`] of the benchmark with the `module_path` and the optional `id`
+    ///
+    /// The `[config]` tables are always applied. The `[override."<GLOB>"]` tables only if the
+    /// pattern matches the `module_path` or the `module_path` joined with the `id`.
+    pub fn resolve(&self, module_path: &ModulePath, id: Option<&str>) -> Settings {
+        let module_path_with_id = id.map(|id| module_path.join(id));
+
+        self.layers
+            .iter()
+            .filter(|layer| {
+                layer.pattern.as_ref().map_or(true, |pattern| {
+                    pattern.matches(module_path.as_str())
+                        || module_path_with_id
+                            .as_ref()
+                            .is_some_and(|path| pattern.matches(path.as_str()))
+                })
+            })
+            .fold(Settings::default(), |mut settings, layer| {
+                settings.update(&layer.settings);
+                settings
+            })
+    }
+}
+
+impl Settings {
+    /// Return these settings as the lowest level [`BinaryBenchmarkConfig`]
+    pub fn to_binary_benchmark_config(&self) -> BinaryBenchmarkConfig {
+        BinaryBenchmarkConfig {
+            default_tool: self.default_tool,
+            output_format: self.output_format(),
+            regression_policy: self.regression_policy,
+            tools: self.tools(),
+            valgrind_args: self.valgrind_args.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Return these settings as the lowest level [`LibraryBenchmarkConfig`]
+    pub fn to_library_benchmark_config(&self) -> LibraryBenchmarkConfig {
+        LibraryBenchmarkConfig {
+            default_tool: self.default_tool,
+            output_format: self.output_format(),
+            regression_policy: self.regression_policy,
+            tools: self.tools(),
+            valgrind_args: self.valgrind_args.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Return the [`ToolDefaults`] of the tool with this `kind` if present
+    pub fn tool(&self, kind: ValgrindTool) -> Option<&ToolDefaults> {
+        self.tool_defaults.get(&kind)
+    }
+
+    /// Update these settings with the `other` settings
+    ///
+    /// Like in `update_from_all` of the `LibraryBenchmarkConfig`, values of `other` override the
+    /// values of these settings and arguments are appended.
+    pub fn update(&mut self, other: &Self) {
+        self.default_tool = update_option(&self.default_tool, &other.default_tool);
+        self.output_format = api::OutputFormat {
+            show_grid: update_option(
+                &self.output_format.show_grid,
+                &other.output_format.show_grid,
+            ),
+            show_intermediate: update_option(
+                &self.output_format.show_intermediate,
+                &other.output_format.show_intermediate,
+            ),
+            tolerance: update_option(
+                &self.output_format.tolerance,
+                &other.output_format.tolerance,
+            ),
+            truncate_description: update_option(
+                &self.output_format.truncate_description,
+                &other.output_format.truncate_description,
+            ),
+        };
+        self.regression_policy = update_option(&self.regression_policy, &other.regression_policy);
+        self.tools = update_option(&self.tools, &other.tools);
+        self.valgrind_args.update(&other.valgrind_args);
+
+        for (kind, other) in &other.tool_defaults {
+            let defaults = self.tool_defaults.entry(*kind).or_default();
+            defaults.args.update(&other.args);
+            defaults.limits = update_option(&defaults.limits, &other.limits);
+            defaults.metrics = update_option(&defaults.metrics, &other.metrics);
+        }
+    }
+
+    fn output_format(&self) -> Option<api::OutputFormat> {
+        (self.output_format != api::OutputFormat::default()).then(|| self.output_format.clone())
+    }
+
+    fn tools(&self) -> Tools {
+        Tools(
+            self.tools
+                .iter()
+                .flatten()
+                .map(|kind| Tool::new(*kind))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<RawSettings> for Settings {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RawSettings) -> Result<Self> {
+        let default_tool = value
+            .default_tool
+            .map(|tool| tool.parse::<ValgrindTool>())
+            .transpose()
+            .context("Invalid 'default-tool'")?;
+        let regression_policy = value
+            .regression_policy
+            .map(|policy| policy.parse::<RegressionPolicy>())
+            .transpose()
+            .context("Invalid 'regression-policy'")?;
+        let tools = value
+            .tools
+            .map(|tools| {
+                tools
+                    .split(',')
+                    .map(|tool| tool.trim().parse::<ValgrindTool>())
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()
+            .context("Invalid 'tools'")?;
+        let truncate_description = value
+            .truncate_description
+            .map(|value| args::parse_truncate_description(&value.to_string()).map(Into::into))
+            .transpose()
+            .map_err(|error| anyhow!("Invalid 'truncate-description': {error}"))?;
+        let valgrind_args = value
+            .valgrind_args
+            .map(|raw| args::parse_args(&raw))
+            .transpose()
+            .map_err(|error| anyhow!("Invalid 'valgrind-args': {error}"))?
+            .unwrap_or_default();
+
+        let mut tool_defaults = IndexMap::new();
+        for (kind, raw) in [
+            (ValgrindTool::Callgrind, value.callgrind),
+            (ValgrindTool::Cachegrind, value.cachegrind),
+            (ValgrindTool::DHAT, value.dhat),
+            (ValgrindTool::Memcheck, value.memcheck),
+            (ValgrindTool::Helgrind, value.helgrind),
+            (ValgrindTool::DRD, value.drd),
+            (ValgrindTool::Massif, value.massif),
+            (ValgrindTool::BBV, value.bbv),
+        ] {
+            if let Some(raw) = raw {
+                tool_defaults.insert(kind, ToolDefaults::new(kind, raw)?);
+            }
+        }
+
+        Ok(Self {
+            default_tool,
+            output_format: api::OutputFormat {
+                show_grid: value.show_grid,
+                show_intermediate: value.show_intermediate,
+                tolerance: value.tolerance,
+                truncate_description,
+            },
+            regression_policy,
+            tool_defaults,
+            tools,
+            valgrind_args,
+        })
+    }
+}
+
+impl ToolDefaults {
+    fn new(kind: ValgrindTool, raw: RawToolSettings) -> Result<Self> {
+        let args = raw
+            .args
+            .map(|raw| args::parse_args(&raw))
+            .transpose()
+            .map_err(|error| anyhow!("Invalid 'args' for {kind}: {error}"))?
+            .unwrap_or_default();
+
+        let limits = raw
+            .limits
+            .map(|limits| match kind {
+                ValgrindTool::Callgrind => args::parse_callgrind_limits(&limits),
+                ValgrindTool::Cachegrind => args::parse_cachegrind_limits(&limits),
+                ValgrindTool::DHAT => args::parse_dhat_limits(&limits),
+                _ => Err("Limits are not supported for this tool".to_owned()),
+            })
+            .transpose()
+            .map_err(|error| anyhow!("Invalid 'limits' for {kind}: {error}"))?;
+
+        let metrics = raw
+            .metrics
+            .map(|metrics| match kind {
+                ValgrindTool::Callgrind => args::parse_callgrind_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::Callgrind(m.into_iter().map(Into::into).collect())),
+                ValgrindTool::Cachegrind => args::parse_cachegrind_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::Cachegrind(m.into_iter().map(Into::into).collect())),
+                ValgrindTool::DHAT => args::parse_dhat_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::DHAT(m.into_iter().collect())),
+                ValgrindTool::Memcheck => args::parse_memcheck_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::Memcheck(m.into_iter().collect())),
+                ValgrindTool::Helgrind => args::parse_helgrind_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::Helgrind(m.into_iter().collect())),
+                ValgrindTool::DRD => args::parse_drd_metrics(&metrics)
+                    .map(|m| ToolOutputFormat::DRD(m.into_iter().collect())),
+                ValgrindTool::Massif | ValgrindTool::BBV => {
+                    Err("Metrics are not supported for this tool".to_owned())
+                }
+            })
+            .transpose()
+            .map_err(|error| anyhow!("Invalid 'metrics' for {kind}: {error}"))?;
+
+        Ok(Self {
+            args,
+            limits,
+            metrics,
+        })
+    }
+}
+
+impl Display for RawValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::String(value) => f.write_str(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use indexmap::indexset;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::{CallgrindMetrics, EventKind};
+    use crate::runner::callgrind::regression::CallgrindRegressionConfig;
+
+    fn config_file(files: &[&str], profile: Option<&str>) -> Result<ConfigFile> {
+        ConfigFile::from_files(
+            files
+                .iter()
+                .enumerate()
+                .map(|(index, content)| {
+                    (
+                        PathBuf::from(format!("{index}/{FILE_NAME}")),
+                        toml::from_str(content).unwrap(),
+                    )
+                })
+                .collect(),
+            profile,
+        )
+    }
+
+    #[test]
+    fn test_config_file_resolve_tool_defaults() {
+        let config_file = config_file(
+            &[r#"
+            [config]
+            valgrind-args = "--trace-children=no"
+
+            [config.callgrind]
+            args = "--dump-instr=yes"
+            limits = "ir=5%"
+            metrics = "ir,totalrw"
+            "#],
+            None,
+        )
+        .unwrap();
+
+        let settings = config_file.resolve(&ModulePath::new("bench::group::func"), None);
+        assert_eq!(
+            settings.valgrind_args,
+            RawArgs::new(["--trace-children=no"])
+        );
+        assert_eq!(
+            settings.tool(ValgrindTool::Callgrind),
+            Some(&ToolDefaults {
+                args: RawArgs::new(["--dump-instr=yes"]),
+                limits: Some(ToolRegressionConfig::Callgrind(CallgrindRegressionConfig {
+                    soft_limits: vec![(EventKind::Ir, 5.0)],
+                    ..Default::default()
+                })),
+                metrics: Some(ToolOutputFormat::Callgrind(vec![
+                    CallgrindMetrics::SingleEvent(EventKind::Ir),
+                    CallgrindMetrics::SingleEvent(EventKind::TotalRW)
+                ])),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case::no_profile(None, Some(ValgrindTool::DHAT), Some(RegressionPolicy::WarnOnly))]
+    #[case::profile_in_workspace(
+        Some("ci"),
+        Some(ValgrindTool::Cachegrind),
+        Some(RegressionPolicy::WarnOnly)
+    )]
+    #[case::profile_in_package(
+        Some("strict"),
+        Some(ValgrindTool::DHAT),
+        Some(RegressionPolicy::Enforce)
+    )]
+    fn test_config_file_resolve_profiles(
+        #[case] profile: Option<&str>,
+        #[case] expected_default_tool: Option<ValgrindTool>,
+        #[case] expected_policy: Option<RegressionPolicy>,
+    ) {
+        let workspace = r#"
+            [config]
+            default-tool = "callgrind"
+            regression-policy = "warn-only"
+
+            [profile.ci.config]
+            default-tool = "cachegrind"
+            "#;
+        let package = r#"
+            [config]
+            default-tool = "dhat"
+
+            [profile.strict.config]
+            regression-policy = "enforce"
+            "#;
+
+        let settings = config_file(&[workspace, package], profile)
+            .unwrap()
+            .resolve(&ModulePath::new("bench::group::func"), None);
+
+        assert_eq!(settings.default_tool, expected_default_tool);
+        assert_eq!(settings.regression_policy, expected_policy);
+    }
+
+    #[test]
+    fn test_config_file_when_profile_not_found_then_error() {
+        let error = config_file(&["[profile.ci.config]"], Some("other")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("The profile 'other' was not found in any {FILE_NAME}")
+        );
+    }
+
+    #[rstest]
+    #[case::no_match("bench::group::func", None, None)]
+    #[case::module_path("bench::slow::func", None, Some(1.0))]
+    #[case::module_path_with_id("bench::group::func", Some("large"), Some(2.0))]
+    #[case::both("bench::slow::func", Some("large"), Some(2.0))]
+    fn test_config_file_resolve_overrides(
+        #[case] module_path: &str,
+        #[case] id: Option<&str>,
+        #[case] expected: Option<f64>,
+    ) {
+        let config_file = config_file(
+            &[r#"
+            [override."*::slow::*"]
+            tolerance = 1.0
+
+            [override."*::large"]
+            tolerance = 2.0
+            "#],
+            None,
+        )
+        .unwrap();
+
+        let settings = config_file.resolve(&ModulePath::new(module_path), id);
+        assert_eq!(settings.output_format.tolerance, expected);
+    }
+
+    #[rstest]
+    #[case::unknown_key("[config]\nunknown = 1")]
+    #[case::invalid_tool("[config]\ndefault-tool = \"unknown\"")]
+    #[case::limits_not_supported("[config.memcheck]\nlimits = \"errors=0\"")]
+    #[case::metrics_not_supported("[config.massif]\nmetrics = \"all\"")]
+    #[case::invalid_pattern("[override.\"[\"]\ntolerance = 1.0")]
+    fn test_config_file_when_invalid_then_error(#[case] content: &str) {
+        let result = toml::from_str::<RawFile>(content)
+            .map_err(Into::into)
+            .and_then(|file| ConfigFile::from_files(vec![(PathBuf::from(FILE_NAME), file)], None));
+        result.unwrap_err();
+    }
+
+    #[rstest]
+    #[case::default(
+        &[],
+        &["--callgrind-limits=ir=5%", "--callgrind-metrics=ir", "--nosummary=true"]
+    )]
+    #[case::overridden_on_command_line(
+        &["--nosummary=false"],
+        &["--callgrind-limits=ir=5%", "--callgrind-metrics=ir"]
+    )]
+    #[case::with_filter(
+        &["some_filter"],
+        &["--callgrind-limits=ir=5%", "--callgrind-metrics=ir", "--nosummary=true"]
+    )]
+    #[serial_test::serial]
+    fn test_config_file_command_line_args(#[case] raw_args: &[&str], #[case] expected: &[&str]) {
+        std::env::remove_var("IAI_CALLGRIND_CALLGRIND_METRICS");
+
+        let config_file = config_file(
+            &[r#"
+            [args]
+            callgrind-limits = "ir=5%"
+            callgrind-metrics = "ir"
+            nosummary = true
+            "#],
+            None,
+        )
+        .unwrap();
+
+        let matches = CommandLineArgs::command().get_matches_from(raw_args);
+        let command_line_args = config_file.command_line_args(&matches).unwrap();
+        assert_eq!(command_line_args, expected);
+
+        let args = CommandLineArgs::try_parse_from(
+            command_line_args
+                .iter()
+                .map(String::as_str)
+                .chain(raw_args.iter().copied()),
+        )
+        .unwrap();
+        assert!(args.callgrind_limits.is_some());
+        assert_eq!(args.callgrind_metrics, Some(indexset! {EventKind::Ir}));
+        assert_eq!(args.nosummary, !raw_args.contains(&"--nosummary=false"));
+        assert_eq!(args.filter.is_some(), raw_args.contains(&"some_filter"));
+    }
+
+    #[rstest]
+    #[case::unknown("unknown")]
+    #[case::hidden("bench")]
+    #[case::profile("config-profile")]
+    fn test_config_file_command_line_args_when_invalid_key(#[case] key: &str) {
+        let config_file = config_file(&[&format!("[args]\n{key} = true")], None).unwrap();
+        let matches = CommandLineArgs::command().get_matches_from(Vec::<String>::new());
+        config_file.command_line_args(&matches).unwrap_err();
+    }
+}
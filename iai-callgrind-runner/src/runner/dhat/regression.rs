@@ -4,7 +4,7 @@ use indexmap::{IndexMap, IndexSet};
 use crate::api::{self, DhatMetric};
 use crate::runner::metrics::{Metric, MetricKind, MetricsSummary};
 use crate::runner::summary::ToolRegression;
-use crate::runner::tool::regression::RegressionConfig;
+use crate::runner::tool::regression::{check_improvements, check_soft_limits, RegressionConfig};
 
 /// The dhat regression check configuration
 #[derive(Debug, Clone, PartialEq)]
@@ -13,6 +13,8 @@ pub struct DhatRegressionConfig {
     pub fail_fast: bool,
     /// The hard limits
     pub hard_limits: Vec<(DhatMetric, Metric)>,
+    /// The expected improvements in percent
+    pub improvements: Vec<(DhatMetric, f64)>,
     /// The soft limits
     pub soft_limits: Vec<(DhatMetric, f64)>,
 }
@@ -22,6 +24,7 @@ impl Default for DhatRegressionConfig {
         Self {
             soft_limits: vec![(DhatMetric::TotalBytes, 10f64)],
             hard_limits: Vec::default(),
+            improvements: Vec::default(),
             fail_fast: Default::default(),
         }
     }
@@ -42,6 +45,10 @@ impl RegressionConfig<DhatMetric> for DhatRegressionConfig {
     fn get_hard_limits(&self) -> &[(DhatMetric, Metric)] {
         &self.hard_limits
     }
+
+    fn get_improvements(&self) -> &[(DhatMetric, f64)] {
+        &self.improvements
+    }
 }
 
 impl TryFrom<api::DhatRegressionConfig> for DhatRegressionConfig {
@@ -51,44 +58,54 @@ impl TryFrom<api::DhatRegressionConfig> for DhatRegressionConfig {
         let api::DhatRegressionConfig {
             soft_limits,
             hard_limits,
+            improvements,
             fail_fast,
         } = value;
 
-        let (soft_limits, hard_limits) = if soft_limits.is_empty() && hard_limits.is_empty() {
-            (
-                IndexMap::from([(DhatMetric::TotalBytes, 10f64)]),
-                IndexMap::new(),
-            )
-        } else {
-            let hard_limits = hard_limits
-                .into_iter()
-                .flat_map(|(dhat_metrics, metric)| {
-                    IndexSet::from(dhat_metrics)
-                        .into_iter()
-                        .map(move |metric_kind| {
-                            Metric::from(metric)
-                                .try_convert(metric_kind)
-                                .ok_or_else(|| {
-                                    format!(
-                                        "Invalid hard limit for \
+        check_improvements(&improvements)?;
+        check_soft_limits(&soft_limits)?;
+        let improvements = improvements
+            .into_iter()
+            .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
+            .collect::<IndexMap<_, _>>();
+
+        let (soft_limits, hard_limits) =
+            if soft_limits.is_empty() && hard_limits.is_empty() && improvements.is_empty() {
+                (
+                    IndexMap::from([(DhatMetric::TotalBytes, 10f64)]),
+                    IndexMap::new(),
+                )
+            } else {
+                let hard_limits = hard_limits
+                    .into_iter()
+                    .flat_map(|(dhat_metrics, metric)| {
+                        IndexSet::from(dhat_metrics)
+                            .into_iter()
+                            .map(move |metric_kind| {
+                                Metric::from(metric)
+                                    .try_convert(metric_kind)
+                                    .ok_or_else(|| {
+                                        format!(
+                                            "Invalid hard limit for \
                                          '{metric_kind:?}/{dhat_metrics:?}': Expected a 'Int' but \
                                          found '{metric:?}'"
-                                    )
-                                })
-                        })
-                })
-                .collect::<Result<IndexMap<DhatMetric, Metric>, String>>()?;
-
-            let soft_limits = soft_limits
-                .into_iter()
-                .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
-                .collect::<IndexMap<_, _>>();
-
-            (soft_limits, hard_limits)
-        };
+                                        )
+                                    })
+                            })
+                    })
+                    .collect::<Result<IndexMap<DhatMetric, Metric>, String>>()?;
+
+                let soft_limits = soft_limits
+                    .into_iter()
+                    .flat_map(|(m, l)| IndexSet::from(m).into_iter().map(move |e| (e, l)))
+                    .collect::<IndexMap<_, _>>();
+
+                (soft_limits, hard_limits)
+            };
         Ok(Self {
             soft_limits: soft_limits.into_iter().collect(),
             hard_limits: hard_limits.into_iter().collect(),
+            improvements: improvements.into_iter().collect(),
             fail_fast: fail_fast.unwrap_or(false),
         })
     }
@@ -13,19 +13,29 @@ use indexmap::{indexset, IndexSet};
 
 use super::args::NoCapture;
 use super::bin_bench::BinBench;
+use super::callgrind::hot_instructions::HotInstructions;
+use super::callgrind::hot_lines::{HotLines, HOT_LINES_EVENT_KINDS};
+use super::callgrind::{threads, throughput};
 use super::common::{Baselines, BenchmarkSummaries, Config, ModulePath};
 use super::lib_bench::LibBench;
 use super::meta::Metadata;
 use super::metrics::{Metric, MetricKind, MetricsDiff};
-use super::summary::{Diffs, ProfileData, ProfileInfo, ToolMetricSummary, ToolRegression};
+use super::summary::{
+    Complexity, ComplexityFit, ComplexitySummary, CustomMetricsSummary, Diffs, FunctionSummary,
+    ProfileData, ProfileInfo, SnapshotState, SnapshotSummary, ThreadSummary, ThroughputSummary,
+    ToolMetricSummary, ToolRegression,
+};
 use crate::api::{
-    self, CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DhatMetric, DhatMetrics,
-    ErrorMetric, EventKind, Tool, ToolOutputFormat, ValgrindTool,
+    self, CacheModel, CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DhatMetric,
+    DhatMetrics, ErrorMetric, EventKind, RegressionPolicy, Throughput, Tool, ToolOutputFormat,
+    ValgrindTool,
 };
 use crate::util::{
     make_relative, to_string_signed_short, to_string_unsigned_short, truncate_str_utf8,
 };
 
+/// The width in bytes of a column of the metrics of the [`CacheModel`]s shown side by side
+pub const CACHE_MODEL_WIDTH: usize = 15;
 /// The width in bytes of the difference (and factor)
 pub const DIFF_WIDTH: usize = 9;
 /// The width in bytes of the FIELD as in `  FIELD: METRIC | METRIC (DIFF_PCT) [FACTOR]`
@@ -205,7 +215,7 @@ pub trait Formatter {
         function_name: &str,
         id: &str,
         details: Option<&str>,
-        summaries: Vec<(ValgrindTool, ToolMetricSummary)>,
+        summaries: Vec<(ValgrindTool, Option<CacheModel>, ToolMetricSummary)>,
     ) -> Result<()>;
 }
 
@@ -450,36 +460,8 @@ impl OutputFormat {
 
     /// Update the output format from the [`Tool`] if present
     pub fn update(&mut self, tool: Option<&Tool>) {
-        if let Some(tool) = tool {
-            if let Some(format) = &tool.output_format {
-                match format {
-                    ToolOutputFormat::Callgrind(metrics) => {
-                        self.callgrind = metrics.iter().fold(IndexSet::new(), |mut acc, m| {
-                            acc.extend(IndexSet::from(*m));
-                            acc
-                        });
-                    }
-                    ToolOutputFormat::Cachegrind(metrics) => {
-                        self.cachegrind = metrics.iter().fold(IndexSet::new(), |mut acc, m| {
-                            acc.extend(IndexSet::from(*m));
-                            acc
-                        });
-                    }
-                    ToolOutputFormat::DHAT(metrics) => {
-                        self.dhat = metrics.iter().copied().collect();
-                    }
-                    ToolOutputFormat::Memcheck(metrics) => {
-                        self.memcheck = metrics.iter().copied().collect();
-                    }
-                    ToolOutputFormat::Helgrind(metrics) => {
-                        self.helgrind = metrics.iter().copied().collect();
-                    }
-                    ToolOutputFormat::DRD(metrics) => {
-                        self.drd = metrics.iter().copied().collect();
-                    }
-                    ToolOutputFormat::None => {}
-                }
-            }
+        if let Some(format) = tool.and_then(|t| t.output_format.as_ref()) {
+            self.update_metrics(format);
         }
     }
 
@@ -524,6 +506,37 @@ impl OutputFormat {
             self.show_intermediate = show_intermediate;
         }
     }
+
+    /// Update the metrics shown in the terminal output with the metrics of the `format`
+    pub fn update_metrics(&mut self, format: &ToolOutputFormat) {
+        match format {
+            ToolOutputFormat::Callgrind(metrics) => {
+                self.callgrind = metrics.iter().fold(IndexSet::new(), |mut acc, m| {
+                    acc.extend(IndexSet::from(*m));
+                    acc
+                });
+            }
+            ToolOutputFormat::Cachegrind(metrics) => {
+                self.cachegrind = metrics.iter().fold(IndexSet::new(), |mut acc, m| {
+                    acc.extend(IndexSet::from(*m));
+                    acc
+                });
+            }
+            ToolOutputFormat::DHAT(metrics) => {
+                self.dhat = metrics.iter().copied().collect();
+            }
+            ToolOutputFormat::Memcheck(metrics) => {
+                self.memcheck = metrics.iter().copied().collect();
+            }
+            ToolOutputFormat::Helgrind(metrics) => {
+                self.helgrind = metrics.iter().copied().collect();
+            }
+            ToolOutputFormat::DRD(metrics) => {
+                self.drd = metrics.iter().copied().collect();
+            }
+            ToolOutputFormat::None => {}
+        }
+    }
 }
 
 impl Default for OutputFormat {
@@ -580,6 +593,7 @@ impl SummaryFormatter {
     }
 
     /// Print the summary
+    #[allow(clippy::too_many_lines)]
     pub fn print(&self, summaries: &BenchmarkSummaries) {
         if self.output_format_kind == OutputFormatKind::Default {
             let total_benchmarks = summaries.num_benchmarks();
@@ -594,16 +608,54 @@ impl SummaryFormatter {
                 println!("\nRegressions:\n");
                 let mut num_regressed = 0;
                 for summary in summaries.summaries.iter().filter(|p| p.is_regressed()) {
+                    let policy = if summary.regression_policy == RegressionPolicy::WarnOnly {
+                        format!(" {}", "(warn only)".yellow())
+                    } else {
+                        String::new()
+                    };
                     if let Some(id) = &summary.id {
-                        println!("  {} {}:", summary.module_path.green(), id.cyan());
+                        println!("  {} {}{policy}:", summary.module_path.green(), id.cyan());
                     } else {
-                        println!("  {}:", summary.module_path.green());
+                        println!("  {}{policy}:", summary.module_path.green());
                     }
-                    for regression in summary
+                    let comparisons = summary.comparisons.iter().flat_map(|c| {
+                        c.regressions
+                            .iter()
+                            .map(|r| (Some(format!("{} vs {}", c.benchmark, c.baseline)), r))
+                    });
+                    let snapshot_regressions = summary.snapshot.iter().flat_map(|s| {
+                        s.regressions
+                            .iter()
+                            .map(|r| (Some("snapshot".to_owned()), r))
+                    });
+                    for (prefix, regression) in summary
                         .profiles
                         .iter()
-                        .flat_map(|t| &t.summaries.total.regressions)
+                        .flat_map(|t| {
+                            t.summaries
+                                .total
+                                .regressions
+                                .iter()
+                                .map(|r| (None, r))
+                                .chain(t.functions.iter().flat_map(|f| {
+                                    f.regressions.iter().map(|r| (Some(&f.function), r))
+                                }))
+                                .chain(t.summaries.parts.iter().flat_map(|p| {
+                                    let region = p.details.iter().find_map(|i| i.region.as_ref());
+                                    p.regressions.iter().map(move |r| (region, r))
+                                }))
+                                .map(|(prefix, r)| (prefix.cloned(), r))
+                                .chain(t.throughput.iter().flat_map(|s| {
+                                    let prefix = summary
+                                        .throughput
+                                        .map(|t| format!("per {}", throughput::unit(t)));
+                                    s.regressions.iter().map(move |r| (prefix.clone(), r))
+                                }))
+                        })
+                        .chain(comparisons)
+                        .chain(snapshot_regressions)
                     {
+                        let prefix = prefix.map(|p| format!("{p}: ")).unwrap_or_default();
                         match regression {
                             ToolRegression::Soft {
                                 metric,
@@ -613,7 +665,7 @@ impl SummaryFormatter {
                                 limit,
                             } => {
                                 println!(
-                                    "    {metric} ({} -> {}): {:>6}{} exceeds limit of {:>6}{}",
+                                    "    {prefix}{metric} ({} -> {}): {:>6}{} exceeds limit of {:>6}{}",
                                     old,
                                     new.to_string().bold(),
                                     to_string_signed_short(*diff_pct).bright_red().bold(),
@@ -629,12 +681,52 @@ impl SummaryFormatter {
                                 limit,
                             } => {
                                 println!(
-                                    "    {metric} ({0}): {0} exceeds limit of {1} by {2}",
+                                    "    {prefix}{metric} ({0}): {0} exceeds limit of {1} by {2}",
                                     new.to_string().bold(),
                                     limit.to_string().bright_black(),
                                     diff.to_string().bright_red().bold()
                                 );
                             }
+                            ToolRegression::Improvement {
+                                metric,
+                                new,
+                                old,
+                                diff_pct,
+                                expected,
+                            } => {
+                                println!(
+                                    "    {prefix}{metric} ({} -> {}): {:>6}{} misses expected \
+                                     improvement of {:>6}{}",
+                                    old,
+                                    new.to_string().bold(),
+                                    to_string_signed_short(*diff_pct).bright_red().bold(),
+                                    "%".bright_red().bold(),
+                                    to_string_signed_short(-*expected).bright_black(),
+                                    "%".bright_black()
+                                );
+                            }
+                        }
+                    }
+
+                    if let Some((new, old)) = summary
+                        .complexity
+                        .as_ref()
+                        .filter(|c| c.regressed)
+                        .and_then(|c| c.fits.as_ref().both())
+                    {
+                        println!(
+                            "    Complexity changed from {} to {}",
+                            old.class,
+                            new.class.to_string().bright_red().bold()
+                        );
+                    }
+
+                    if let Some(snapshot) = &summary.snapshot {
+                        if snapshot.state == SnapshotState::Missing {
+                            println!("    Snapshot missing: {}", snapshot.path.display());
+                        }
+                        for metric in &snapshot.unmatched_metrics {
+                            println!("    snapshot: {metric} is missing");
                         }
                     }
 
@@ -642,11 +734,15 @@ impl SummaryFormatter {
                 }
 
                 let num_not_regressed = total_benchmarks - num_regressed;
+                let result = if summaries.is_failed() {
+                    "Regressed".bright_red().bold()
+                } else {
+                    "Ok".green().bold()
+                };
                 println!(
-                    "\nIai-Callgrind result: {}. {num_not_regressed} without regressions; \
+                    "\nIai-Callgrind result: {result}. {num_not_regressed} without regressions; \
                      {num_regressed} regressed; {total_benchmarks} benchmarks finished in \
                      {total_time:>6}s",
-                    "Regressed".bright_red().bold(),
                 );
             } else {
                 println!(
@@ -920,6 +1016,12 @@ impl VerticalFormatter {
     fn format_multiple_segment_header(&mut self, details: &EitherOrBoth<ProfileInfo>) {
         fn fields(detail: &ProfileInfo) -> String {
             let mut result = String::new();
+            if let Some(region) = &detail.region {
+                write!(result, "region: {region} ").unwrap();
+            }
+            if let Some(cache_state) = &detail.cache_state {
+                write!(result, "cache: {cache_state} ").unwrap();
+            }
             write!(result, "pid: {}", detail.pid).unwrap();
 
             if let Some(ppid) = detail.parent_pid {
@@ -999,20 +1101,398 @@ impl VerticalFormatter {
     }
 
     /// Format the tool headline shown for all tools
-    pub fn format_tool_headline(&mut self, tool: ValgrindTool) {
+    ///
+    /// The [`CacheModel`] is shown next to the tool if present.
+    pub fn format_tool_headline(&mut self, tool: ValgrindTool, cache_model: Option<CacheModel>) {
         self.write_indent(&IndentKind::ToolHeadline);
 
-        let id = tool.id();
+        let title = tool_title(tool, cache_model);
         writeln!(
             self,
             "{} {} {}",
             "=======".bright_black(),
-            id.to_ascii_uppercase(),
-            "=".repeat(MAX_WIDTH.saturating_sub(id.len() + 9))
+            title,
+            "=".repeat(MAX_WIDTH.saturating_sub(title.len() + 9))
                 .bright_black(),
         )
         .unwrap();
     }
+
+    /// Format the new metrics of a `tool` which was run with multiple [`CacheModel`]s side by side
+    ///
+    /// Only the metrics selected in the [`OutputFormat`] are shown.
+    pub fn format_cache_models(
+        &mut self,
+        tool: ValgrindTool,
+        summaries: &[(CacheModel, &ToolMetricSummary)],
+    ) {
+        fn rows<K: Display>(
+            kinds: &IndexSet<K>,
+            metrics: impl Fn(&K) -> Vec<Option<Metric>>,
+        ) -> Vec<(String, Vec<Option<Metric>>)> {
+            kinds
+                .iter()
+                .map(|kind| (format!("{kind}:"), metrics(kind)))
+                .filter(|(_, metrics)| metrics.iter().any(Option::is_some))
+                .collect()
+        }
+
+        let rows = match tool {
+            ValgrindTool::Callgrind => rows(&self.output_format.callgrind, |kind| {
+                summaries
+                    .iter()
+                    .map(|(_, summary)| match summary {
+                        ToolMetricSummary::Callgrind(metrics) => metrics
+                            .diff_by_kind(kind)
+                            .and_then(|diff| diff.metrics.left()),
+                        _ => None,
+                    })
+                    .collect()
+            }),
+            ValgrindTool::Cachegrind => rows(&self.output_format.cachegrind, |kind| {
+                summaries
+                    .iter()
+                    .map(|(_, summary)| match summary {
+                        ToolMetricSummary::Cachegrind(metrics) => metrics
+                            .diff_by_kind(kind)
+                            .and_then(|diff| diff.metrics.left()),
+                        _ => None,
+                    })
+                    .collect()
+            }),
+            _ => return,
+        };
+
+        if rows.is_empty() {
+            return;
+        }
+
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(
+            self,
+            "{} {} {}",
+            "##".yellow(),
+            "Cache models".bold(),
+            format!("({})", tool.id()).bright_black()
+        )
+        .unwrap();
+
+        self.write_indent(&IndentKind::Normal);
+        write!(self, "{}", " ".repeat(FIELD_WIDTH)).unwrap();
+        for (cache_model, _) in summaries {
+            write!(
+                self,
+                "{}",
+                format!("{:>CACHE_MODEL_WIDTH$}", cache_model.id()).bold()
+            )
+            .unwrap();
+        }
+        writeln!(self).unwrap();
+
+        for (field, metrics) in rows {
+            self.write_indent(&IndentKind::Normal);
+            write!(self, "{field:<FIELD_WIDTH$}").unwrap();
+            for metric in metrics {
+                match metric {
+                    Some(metric) => write!(self, "{:>CACHE_MODEL_WIDTH$}", metric.to_string()),
+                    None => write!(self, "{NOT_AVAILABLE:>CACHE_MODEL_WIDTH$}"),
+                }
+                .unwrap();
+            }
+            writeln!(self).unwrap();
+        }
+    }
+
+    /// Format the user-defined metrics of callgrind
+    pub fn format_custom_metrics(&mut self, summary: &CustomMetricsSummary) {
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(self, "{} {}", "##".yellow(), "Custom metrics".bold()).unwrap();
+
+        self.format_metrics(summary.summary.all_diffs());
+    }
+
+    /// Format the hottest instructions
+    ///
+    /// Each block is shown as a table with the address, the metrics and the disassembly of each
+    /// instruction. The hottest instructions are marked with a `>`. Only the columns of the
+    /// [`EventKind`]s which were collected are shown.
+    pub fn format_hot_instructions(&mut self, hot_instructions: &HotInstructions) {
+        if hot_instructions.is_empty() {
+            return;
+        }
+
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(self, "{} {}", "##".yellow(), "Hot instructions".bold()).unwrap();
+
+        for block in &hot_instructions.blocks {
+            self.write_indent(&IndentKind::Normal);
+            writeln!(self, "{}", block.func.blue().bold()).unwrap();
+
+            let event_kinds = HOT_LINES_EVENT_KINDS
+                .iter()
+                .filter(|e| {
+                    block.instructions.iter().any(|i| {
+                        i.metrics
+                            .as_ref()
+                            .is_some_and(|m| m.metric_by_kind(e).is_some())
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            self.write_indent(&IndentKind::Normal);
+            write!(self, "  {:>10}", "Address").unwrap();
+            for event_kind in &event_kinds {
+                write!(self, " {:>10}", event_kind.to_string()).unwrap();
+            }
+            writeln!(self).unwrap();
+
+            for instruction in &block.instructions {
+                self.write_indent(&IndentKind::Normal);
+                let marker = if instruction.is_hot { ">" } else { " " };
+                write!(
+                    self,
+                    "{} {:>10}",
+                    marker.yellow().bold(),
+                    format!("{:#x}", instruction.addr)
+                )
+                .unwrap();
+
+                for event_kind in &event_kinds {
+                    let value = instruction
+                        .metrics
+                        .as_ref()
+                        .and_then(|m| m.metric_by_kind(event_kind))
+                        .map_or_else(|| "-".to_owned(), |m| m.to_string());
+                    if instruction.is_hot {
+                        write!(self, " {:>10}", value.bold()).unwrap();
+                    } else {
+                        write!(self, " {value:>10}").unwrap();
+                    }
+                }
+
+                if let Some(disassembly) = &instruction.disassembly {
+                    write!(self, "  {disassembly}").unwrap();
+                }
+                writeln!(self).unwrap();
+            }
+        }
+    }
+
+    /// Format the inclusive metrics of the functions matching the configured glob patterns
+    pub fn format_functions(&mut self, functions: &[FunctionSummary]) {
+        for function in functions {
+            self.write_indent(&IndentKind::ToolSubHeadline);
+            writeln!(
+                self,
+                "{} {} {}",
+                "##".yellow(),
+                "Function".bold(),
+                function.function.blue().bold()
+            )
+            .unwrap();
+
+            if let ToolMetricSummary::Callgrind(summary) = &function.summary {
+                self.format_metrics(
+                    self.output_format
+                        .callgrind
+                        .clone()
+                        .iter()
+                        .filter_map(|e| summary.diff_by_kind(e).map(|d| (e, d))),
+                );
+            }
+        }
+    }
+
+    /// Format the hottest source lines
+    ///
+    /// Cache misses are only shown if they are non-zero in the new or old metrics.
+    pub fn format_hot_lines(&mut self, hot_lines: &HotLines) {
+        fn is_shown(event_kind: EventKind, diff: &MetricsDiff) -> bool {
+            event_kind == EventKind::Ir
+                || !matches!(
+                    diff.metrics,
+                    EitherOrBoth::Left(Metric::Int(0))
+                        | EitherOrBoth::Right(Metric::Int(0))
+                        | EitherOrBoth::Both(Metric::Int(0), Metric::Int(0))
+                )
+        }
+
+        if hot_lines.is_empty() {
+            return;
+        }
+
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(self, "{} {}", "##".yellow(), "Hot lines".bold()).unwrap();
+
+        for hot_line in &hot_lines.lines {
+            self.write_indent(&IndentKind::Normal);
+            writeln!(
+                self,
+                "{}",
+                format!(
+                    "{}:{}",
+                    hot_line.source_line.path.display(),
+                    hot_line.source_line.line
+                )
+                .blue()
+                .bold()
+            )
+            .unwrap();
+
+            if let Some(snippet) = &hot_line.snippet {
+                let max = MAX_WIDTH - FIELD_WIDTH;
+                let truncated = truncate_str_utf8(snippet, max);
+                if truncated.len() < snippet.len() {
+                    self.write_left_indented(&format!("{truncated}..."));
+                } else {
+                    self.write_left_indented(snippet);
+                }
+            }
+
+            self.format_metrics(HOT_LINES_EVENT_KINDS.iter().filter_map(|e| {
+                hot_line
+                    .metrics_summary
+                    .diff_by_kind(e)
+                    .filter(|d| is_shown(*e, d))
+                    .map(|d| (e, d))
+            }));
+        }
+
+        if let Some(other) = &hot_lines.other {
+            self.write_indent(&IndentKind::Normal);
+            writeln!(self, "{}", "Outside of the project root".blue().bold()).unwrap();
+            self.format_metrics(HOT_LINES_EVENT_KINDS.iter().filter_map(|e| {
+                other
+                    .diff_by_kind(e)
+                    .filter(|d| is_shown(*e, d))
+                    .map(|d| (e, d))
+            }));
+        }
+    }
+
+    /// Format the `amount` most expensive threads
+    ///
+    /// The `threads` are expected to be sorted by their costs in descending order.
+    pub fn format_threads(&mut self, threads: &[ThreadSummary], amount: usize) {
+        if threads.is_empty() {
+            return;
+        }
+
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(
+            self,
+            "{} {} {}",
+            "##".yellow(),
+            "Threads".bold(),
+            format!("(sorted by {})", threads::SORT_METRIC).bright_black()
+        )
+        .unwrap();
+
+        for thread in threads.iter().take(amount) {
+            let headline = format!(
+                "pid: {} thread: {} {}",
+                thread.pid, thread.thread, thread.command
+            );
+            let truncated = truncate_str_utf8(&headline, MAX_WIDTH - FIELD_WIDTH);
+            self.write_indent(&IndentKind::Normal);
+            if truncated.len() < headline.len() {
+                writeln!(self, "{}", format!("{truncated}...").blue().bold()).unwrap();
+            } else {
+                writeln!(self, "{}", headline.blue().bold()).unwrap();
+            }
+
+            if let ToolMetricSummary::Callgrind(summary) = &thread.summary {
+                self.format_metrics(
+                    self.output_format
+                        .callgrind
+                        .clone()
+                        .iter()
+                        .filter_map(|e| summary.diff_by_kind(e).map(|d| (e, d))),
+                );
+            }
+        }
+    }
+
+    /// Format the metrics normalized by the amount of bytes or elements of the [`Throughput`]
+    pub fn format_throughput(&mut self, throughput: Throughput, summary: &ThroughputSummary) {
+        let amount = throughput::amount(throughput);
+        let unit = throughput::unit(throughput);
+
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(
+            self,
+            "{} {} {}",
+            "##".yellow(),
+            format!("Per {unit}").bold(),
+            format!("({amount} {unit}{})", if amount == 1 { "" } else { "s" }).bright_black()
+        )
+        .unwrap();
+
+        if let ToolMetricSummary::Callgrind(summary) = &summary.summary {
+            self.format_metrics(summary.all_diffs());
+        }
+    }
+
+    /// Print the metrics of a tool run with multiple [`CacheModel`]s side by side if the output
+    /// format is not json
+    pub fn print_cache_models(
+        &mut self,
+        tool: ValgrindTool,
+        summaries: &[(CacheModel, &ToolMetricSummary)],
+    ) {
+        if self.output_format.is_default() && !self.output_format.show_only_comparison {
+            self.format_cache_models(tool, summaries);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the user-defined metrics of callgrind if the output format is not json
+    pub fn print_custom_metrics(&mut self, summary: &CustomMetricsSummary) {
+        if self.output_format.is_default() {
+            self.format_custom_metrics(summary);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the inclusive metrics of the functions if the output format is not json
+    pub fn print_functions(&mut self, functions: &[FunctionSummary]) {
+        if self.output_format.is_default() {
+            self.format_functions(functions);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the hottest instructions if the output format is not json
+    pub fn print_hot_instructions(&mut self, hot_instructions: &HotInstructions) {
+        if self.output_format.is_default() && !self.output_format.show_only_comparison {
+            self.format_hot_instructions(hot_instructions);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the hottest source lines if the output format is not json
+    pub fn print_hot_lines(&mut self, hot_lines: &HotLines) {
+        if self.output_format.is_default() && !self.output_format.show_only_comparison {
+            self.format_hot_lines(hot_lines);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the `amount` most expensive threads if the output format is not json
+    pub fn print_threads(&mut self, threads: &[ThreadSummary], amount: usize) {
+        if self.output_format.is_default() {
+            self.format_threads(threads, amount);
+            self.print_buffer();
+        }
+    }
+
+    /// Print the metrics normalized by the [`Throughput`] if the output format is not json
+    pub fn print_throughput(&mut self, throughput: Throughput, summary: &ThroughputSummary) {
+        if self.output_format.is_default() {
+            self.format_throughput(throughput, summary);
+            self.print_buffer();
+        }
+    }
 }
 
 impl Display for VerticalFormatter {
@@ -1116,7 +1596,11 @@ impl Formatter for VerticalFormatter {
     ) -> Result<()> {
         if self.output_format.show_only_comparison {
             // no usual data to show
-        } else if data.has_multiple() && self.output_format.show_intermediate {
+        } else if data.has_multiple()
+            && (self.output_format.show_intermediate
+                || data.has_regions()
+                || data.has_cache_states())
+        {
             let mut first = true;
             for part in &data.parts {
                 self.format_multiple_segment_header(&part.details);
@@ -1179,22 +1663,22 @@ impl Formatter for VerticalFormatter {
         function_name: &str,
         id: &str,
         details: Option<&str>,
-        summaries: Vec<(ValgrindTool, ToolMetricSummary)>,
+        summaries: Vec<(ValgrindTool, Option<CacheModel>, ToolMetricSummary)>,
     ) -> Result<()> {
         if self.output_format.is_default() {
             ComparisonHeader::new(function_name, id, details, &self.output_format).print();
 
             let is_multiple = summaries.len() > 1;
-            for (tool, summary) in summaries
+            for (tool, cache_model, summary) in summaries
                 .iter()
-                .filter(|(_, s)| *s != ToolMetricSummary::None)
+                .filter(|(_, _, s)| *s != ToolMetricSummary::None)
             {
                 if is_multiple || *tool != ValgrindTool::Callgrind {
                     self.format_line(&format!(
                         "{}{} {}\n",
                         self.indent_sub_header,
                         "-------".bright_black(),
-                        tool.to_string().to_uppercase()
+                        tool_title(*tool, *cache_model)
                     ))?;
                 }
                 self.format_single(*tool, &(None, None), None, summary, false)?;
@@ -1262,6 +1746,35 @@ pub fn no_capture_footer(nocapture: NoCapture) -> Option<String> {
     }
 }
 
+/// Print the result of checking or updating the snapshot of the `benchmark`
+pub fn print_snapshot(benchmark: &str, snapshot: &SnapshotSummary) {
+    let path = snapshot.path.display();
+    match snapshot.state {
+        SnapshotState::Created => println!("Created snapshot {path}"),
+        SnapshotState::Updated => println!("Updated snapshot {path}"),
+        SnapshotState::Matched | SnapshotState::Unchanged => {}
+        SnapshotState::Missing => eprintln!(
+            "{}: The snapshot {path} of {} does not exist. Create it with --update-snapshots",
+            "Snapshot missing".bold().bright_red(),
+            benchmark.blue().bold()
+        ),
+        SnapshotState::Mismatched => {
+            eprintln!(
+                "{}: The metrics of {} differ from the snapshot {path}",
+                "Snapshot mismatch".bold().bright_red(),
+                benchmark.blue().bold()
+            );
+            print_regressions(&snapshot.regressions);
+            for metric in &snapshot.unmatched_metrics {
+                eprintln!(
+                    "Metric {} is missing in either the snapshot or the benchmark",
+                    metric.bold()
+                );
+            }
+        }
+    }
+}
+
 /// Print the summary of the --list argument
 pub fn print_benchmark_list_summary(sum: u64) {
     if sum != 0 {
@@ -1270,6 +1783,80 @@ pub fn print_benchmark_list_summary(sum: u64) {
     println!("0 tests, {sum} benchmarks");
 }
 
+/// Print the regressions of the `benchmark` relative to the `baseline` benchmark if present
+pub fn print_comparison_regressions(
+    benchmark: &str,
+    baseline: &str,
+    regressions: &[ToolRegression],
+) {
+    if !regressions.is_empty() {
+        eprintln!(
+            "Regressions of {} compared to {}:",
+            benchmark.blue().bold(),
+            baseline.blue().bold()
+        );
+        print_regressions(regressions);
+    }
+}
+
+/// Print the fitted complexity of the benchmark `function` and the change of the class if any
+pub fn print_complexity(function: &str, complexity: &ComplexitySummary) {
+    let format_fit = |fit: &ComplexityFit| {
+        let coefficient = to_string_unsigned_short(fit.coefficient);
+        let model = match fit.class {
+            Complexity::Constant => coefficient,
+            Complexity::Logarithmic => format!("{coefficient} * log n"),
+            Complexity::Linear => format!("{coefficient} * n"),
+            Complexity::Linearithmic => format!("{coefficient} * n log n"),
+            Complexity::Quadratic => format!("{coefficient} * n^2"),
+        };
+        format!(
+            "{} ~ {model} (rms: {}%)",
+            fit.class.to_string().bold(),
+            to_string_unsigned_short(fit.rms * 100f64)
+        )
+    };
+
+    let fits = match &complexity.fits {
+        EitherOrBoth::Both(new, old) => format!("{} | old: {}", format_fit(new), format_fit(old)),
+        EitherOrBoth::Left(new) => format_fit(new),
+        EitherOrBoth::Right(old) => format!("old: {}", format_fit(old)),
+    };
+    println!(
+        "Complexity of {} ({}): {fits}",
+        function.blue().bold(),
+        complexity.metric
+    );
+
+    if complexity.regressed {
+        if let Some((new, old)) = complexity.fits.as_ref().both() {
+            eprintln!(
+                "Performance has {0}: Complexity of {1} changed from {2} to {3}",
+                "regressed".bold().bright_red(),
+                function.blue().bold(),
+                old.class,
+                new.class.to_string().bold().bright_red()
+            );
+        }
+    }
+}
+
+/// Print the regressions of the user-defined metrics of callgrind if present
+pub fn print_custom_metrics_regressions(regressions: &[ToolRegression]) {
+    if !regressions.is_empty() {
+        eprintln!("Regressions in {}:", "custom metrics".blue().bold());
+        print_regressions(regressions);
+    }
+}
+
+/// Print the regressions of the functions matching the `function` glob pattern if present
+pub fn print_function_regressions(function: &str, regressions: &[ToolRegression]) {
+    if !regressions.is_empty() {
+        eprintln!("Regressions in function {}:", function.blue().bold());
+        print_regressions(regressions);
+    }
+}
+
 /// Print a single benchmark for the --list argument
 pub fn print_list_benchmark(module_path: &ModulePath, id: Option<&String>) {
     match id {
@@ -1314,6 +1901,14 @@ pub fn print_no_capture_footer(
     }
 }
 
+/// Print the regressions of a region if present
+pub fn print_region_regressions(region: &str, regressions: &[ToolRegression]) {
+    if !regressions.is_empty() {
+        eprintln!("Regressions in region {}:", region.blue().bold());
+        print_regressions(regressions);
+    }
+}
+
 /// Print detected regressions to `stderr`
 pub fn print_regressions(regressions: &[ToolRegression]) {
     for regression in regressions {
@@ -1328,6 +1923,7 @@ pub fn print_regressions(regressions: &[ToolRegression]) {
                 let metric_name = match metric {
                     MetricKind::None => continue,
                     MetricKind::Callgrind(event_kind) => event_kind.to_string(),
+                    MetricKind::Custom(name) => name.clone(),
                     MetricKind::Cachegrind(cachegrind_metric) => cachegrind_metric.to_string(),
                     MetricKind::Dhat(dhat_metric) => dhat_metric.to_string(),
                     MetricKind::Memcheck(error_metric)
@@ -1368,6 +1964,7 @@ pub fn print_regressions(regressions: &[ToolRegression]) {
                 let metric_name = match metric {
                     MetricKind::None => continue,
                     MetricKind::Callgrind(event_kind) => event_kind.to_string(),
+                    MetricKind::Custom(name) => name.clone(),
                     MetricKind::Cachegrind(cachegrind_metric) => cachegrind_metric.to_string(),
                     MetricKind::Dhat(dhat_metric) => dhat_metric.to_string(),
                     MetricKind::Memcheck(error_metric)
@@ -1384,10 +1981,68 @@ pub fn print_regressions(regressions: &[ToolRegression]) {
                     limit.to_string().bright_black(),
                 );
             }
+            ToolRegression::Improvement {
+                metric,
+                new,
+                old,
+                diff_pct,
+                expected,
+            } => {
+                let metric_name = match metric {
+                    MetricKind::None => continue,
+                    MetricKind::Callgrind(event_kind) => event_kind.to_string(),
+                    MetricKind::Custom(name) => name.clone(),
+                    MetricKind::Cachegrind(cachegrind_metric) => cachegrind_metric.to_string(),
+                    MetricKind::Dhat(dhat_metric) => dhat_metric.to_string(),
+                    MetricKind::Memcheck(error_metric)
+                    | MetricKind::Helgrind(error_metric)
+                    | MetricKind::DRD(error_metric) => error_metric.to_string(),
+                };
+
+                eprintln!(
+                    "Performance has {0}: {1} ({old} -> {2}) changed by {3:>+6} (<={4:>+6})",
+                    "not improved".bold().bright_red(),
+                    metric_name,
+                    new.to_string().bold(),
+                    format!("{}%", to_string_signed_short(*diff_pct))
+                        .bold()
+                        .bright_red(),
+                    format!("{}%", to_string_signed_short(-*expected)).bright_black()
+                );
+            }
         }
     }
 }
 
+/// Print the regressions of a thread if present
+pub fn print_thread_regressions(thread: &ThreadSummary, regressions: &[ToolRegression]) {
+    if !regressions.is_empty() {
+        eprintln!(
+            "Regressions in thread {} of pid {}:",
+            thread.thread.to_string().blue().bold(),
+            thread.pid.to_string().blue().bold()
+        );
+        print_regressions(regressions);
+    }
+}
+
+/// Print the regressions of the metrics normalized by the [`Throughput`] if present
+pub fn print_throughput_regressions(throughput: Throughput, regressions: &[ToolRegression]) {
+    if !regressions.is_empty() {
+        eprintln!("Regressions per {}:", throughput::unit(throughput).bold());
+        print_regressions(regressions);
+    }
+}
+
+/// Return the uppercase tool id with the [`CacheModel`] in parentheses if present
+fn tool_title(tool: ValgrindTool, cache_model: Option<CacheModel>) -> String {
+    let id = tool.id().to_ascii_uppercase();
+    match cache_model {
+        Some(cache_model) => format!("{id} ({cache_model})"),
+        None => id,
+    }
+}
+
 fn truncate_description(description: &str, truncate_description: Option<usize>) -> Cow<'_, str> {
     if let Some(num) = truncate_description {
         let new_description = truncate_str_utf8(description, num);
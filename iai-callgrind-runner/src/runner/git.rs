@@ -0,0 +1,324 @@
+//! Module containing the baselines of git commits
+//!
+//! A baseline given as `git:<ref>` on the command-line is resolved to a commit with the local `git`
+//! binary and named after the abbreviated commit hash (`git_<commit>`), so a baseline stored for
+//! a commit can be found again independently of the branch or tag which pointed to it. If the
+//! baseline is missing, it can be created by running the same benchmark file in a git worktree of
+//! the commit. The worktree and its cargo target directory are kept in the [`WORKTREES_DIR`] of the
+//! target directory, so they are created and built only once per commit.
+
+use std::ffi::OsStr;
+use std::io::stderr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+
+use super::baseline;
+use super::common::Config;
+use super::summary::BaselineName;
+
+/// The prefix of a baseline given as git reference on the command-line
+pub const REF_PREFIX: &str = "git:";
+
+/// The prefix of the name of a baseline of a git commit
+pub const NAME_PREFIX: &str = "git_";
+
+/// The special reference for the merge-base of `HEAD` and the main branch
+pub const MERGE_BASE: &str = "merge-base";
+
+/// The name of the directory below the target directory with the worktrees of the commits
+///
+/// The name starts with a `.`, so it can't collide with the directory of a benchmark.
+pub const WORKTREES_DIR: &str = ".git-worktrees";
+
+/// The length of the abbreviated commit hash in the name of the baseline
+const COMMIT_LENGTH: usize = 12;
+
+/// The references of the main branch tried in this order to resolve the merge-base
+const MAIN_REFS: [&str; 5] = [
+    "origin/HEAD",
+    "origin/main",
+    "main",
+    "origin/master",
+    "master",
+];
+
+/// The environment variables of the baseline arguments which must not leak into the benchmark run
+/// creating the baseline
+const BASELINE_ENVS: [&str; 4] = [
+    "IAI_CALLGRIND_BASELINE",
+    "IAI_CALLGRIND_GIT_BASELINE_CREATE",
+    "IAI_CALLGRIND_LOAD_BASELINE",
+    "IAI_CALLGRIND_SAVE_BASELINE",
+];
+
+/// Return the name of the baseline of the `commit`
+pub fn baseline_name(commit: &str) -> BaselineName {
+    let commit = &commit[..commit.len().min(COMMIT_LENGTH)];
+    format!("{NAME_PREFIX}{commit}")
+        .parse()
+        .expect("A git commit hash should be a valid baseline name")
+}
+
+/// Return the abbreviated commit hash if the baseline `name` is the baseline of a git commit
+pub fn commit_of(name: &BaselineName) -> Option<String> {
+    name.to_string()
+        .strip_prefix(NAME_PREFIX)
+        .filter(|commit| !commit.is_empty() && commit.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(ToOwned::to_owned)
+}
+
+/// Create the baseline of the git commit given with `--baseline` if it is missing and
+/// `--git-baseline-create` is true
+///
+/// The baseline is missing if there are no files of this baseline in the directory of the
+/// benchmark file. The commit is checked out in a git worktree and the benchmark file is run there
+/// with `cargo bench -- --save-baseline=git_<commit>` and the same command-line arguments. The
+/// worktree and the cargo target directory of the commit are stored in the [`WORKTREES_DIR`] of
+/// the target directory and reused by all benchmark files, so the commit is checked out and built
+/// only once. The home directory is shared, so the created baseline ends up in the same place as
+/// the output of the current benchmark run.
+pub fn create_missing_baseline(config: &Config) -> Result<()> {
+    let meta = &config.meta;
+    let Some((name, commit)) = meta
+        .args
+        .baseline
+        .as_ref()
+        .and_then(|name| commit_of(name).map(|commit| (name, commit)))
+    else {
+        return Ok(());
+    };
+
+    let bench_dir = meta
+        .target_dir
+        .join(config.module_path.components().iter().collect::<PathBuf>());
+    if !baseline::baseline_files(&bench_dir, name)?.is_empty() {
+        return Ok(());
+    }
+    if !meta.args.git_baseline_create {
+        debug!("Baseline '{name}' of commit '{commit}' not found");
+        return Ok(());
+    }
+
+    let package_dir = config
+        .package_dir
+        .strip_prefix(&meta.project_root)
+        .with_context(|| "The package directory should be below the project root")?;
+    let commit_dir = meta.target_dir.join(WORKTREES_DIR).join(&commit);
+    let worktree = commit_dir.join("worktree");
+
+    if worktree.join(".git").exists() {
+        eprintln!(
+            "Creating baseline '{name}' of commit '{commit}' in the git worktree '{}'",
+            worktree.display()
+        );
+    } else {
+        eprintln!(
+            "Creating baseline '{name}' of commit '{commit}' in the new git worktree '{}'",
+            worktree.display()
+        );
+        add_worktree(&worktree, &commit, &meta.project_root)?;
+    }
+
+    run_cargo_bench(
+        config,
+        name,
+        &worktree.join(package_dir),
+        &commit_dir.join("target"),
+    )?;
+
+    if baseline::baseline_files(&bench_dir, name)?.is_empty() {
+        return Err(anyhow!(
+            "Creating the baseline '{name}' of commit '{commit}' did not produce any output files"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Resolve the git `reference` to the full hash of a commit
+///
+/// The special reference `merge-base` is resolved to the merge-base of `HEAD` and the first of
+/// `origin/HEAD`, `origin/main`, `main`, `origin/master` and `master` which exists.
+pub fn resolve(reference: &str) -> Result<String> {
+    let cwd = std::env::current_dir()?;
+    if reference == MERGE_BASE {
+        let main = MAIN_REFS
+            .iter()
+            .find(|main| rev_parse(main, &cwd).is_ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unable to resolve the merge-base: None of {} exists",
+                    MAIN_REFS.join(", ")
+                )
+            })?;
+        git(
+            &[
+                OsStr::new("merge-base"),
+                OsStr::new("HEAD"),
+                OsStr::new(main),
+            ],
+            &cwd,
+        )
+    } else {
+        rev_parse(reference, &cwd)
+    }
+}
+
+/// Check out the `commit` in the new git `worktree` of the repository in `cwd`
+///
+/// Leftovers of a previous attempt are removed first. `git worktree prune` forgets about worktrees
+/// whose directory was deleted for example by `cargo clean`, so the `worktree` can be added again.
+fn add_worktree(worktree: &Path, commit: &str, cwd: &Path) -> Result<()> {
+    if worktree.exists() {
+        std::fs::remove_dir_all(worktree)
+            .with_context(|| format!("Failed to remove '{}'", worktree.display()))?;
+    }
+    git(&[OsStr::new("worktree"), OsStr::new("prune")], cwd)?;
+    git(
+        &[
+            OsStr::new("worktree"),
+            OsStr::new("add"),
+            OsStr::new("--detach"),
+            worktree.as_os_str(),
+            OsStr::new(commit),
+        ],
+        cwd,
+    )
+    .map(|_| ())
+}
+
+/// Run a `git` command with the `args` in the directory `cwd` and return the trimmed stdout
+fn git(args: &[&OsStr], cwd: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .with_context(|| "Failed to run 'git'")?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    } else {
+        Err(anyhow!(
+            "Failed to run 'git {}': {}",
+            args.iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Resolve the `reference` to the full hash of a commit with `git rev-parse`
+fn rev_parse(reference: &str, cwd: &Path) -> Result<String> {
+    git(
+        &[
+            OsStr::new("rev-parse"),
+            OsStr::new("--verify"),
+            OsStr::new("--quiet"),
+            OsStr::new(&format!("{reference}^{{commit}}")),
+        ],
+        cwd,
+    )
+    .with_context(|| format!("Unable to resolve the git reference '{reference}'"))
+}
+
+/// Run the benchmark file with `cargo bench` in the `package_dir` of the worktree to save the
+/// baseline `name`
+///
+/// The `target_dir` is the cargo target directory of the worktree and shared by all benchmark
+/// files, so only the first run builds the commit.
+/// The stdout of cargo is redirected to stderr to keep the (possibly json) output of the current
+/// benchmark run clean.
+fn run_cargo_bench(
+    config: &Config,
+    name: &BaselineName,
+    package_dir: &Path,
+    target_dir: &Path,
+) -> Result<()> {
+    let meta = &config.meta;
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let mut command = Command::new(&cargo);
+    command
+        .args(["bench", "--bench", &meta.bench_name, "--"])
+        .args(meta.raw_args.iter().filter(|arg| is_passed_through(arg)))
+        .arg(format!("--save-baseline={name}"))
+        .current_dir(package_dir)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .env(
+            "IAI_CALLGRIND_HOME",
+            std::env::current_dir()?.join(&meta.home),
+        )
+        .stdout(Stdio::from(stderr()));
+    for env in BASELINE_ENVS {
+        command.env_remove(env);
+    }
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run '{}'", cargo.to_string_lossy()))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to create the baseline '{name}': 'cargo bench' exited with {status}"
+        ))
+    }
+}
+
+/// Return true if the command-line `arg` of the current benchmark run is passed through to the
+/// benchmark run creating the baseline
+fn is_passed_through(arg: &str) -> bool {
+    let name = arg.split_once('=').map_or(arg, |(name, _)| name);
+    !matches!(
+        name,
+        "--bench"
+            | "--baseline"
+            | "--git-baseline-create"
+            | "--home"
+            | "--load-baseline"
+            | "--save-baseline"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_baseline_name() {
+        assert_eq!(
+            baseline_name("0123456789abcdef0123456789abcdef01234567").to_string(),
+            "git_0123456789ab"
+        );
+    }
+
+    #[rstest]
+    #[case::commit("git_0123456789ab", Some("0123456789ab"))]
+    #[case::not_hex("git_main", None)]
+    #[case::empty("git_", None)]
+    #[case::other("main", None)]
+    fn test_commit_of(#[case] name: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            commit_of(&name.parse().unwrap()),
+            expected.map(ToOwned::to_owned)
+        );
+    }
+
+    #[rstest]
+    #[case::bench("--bench", false)]
+    #[case::baseline("--baseline=git:main", false)]
+    #[case::save_baseline("--save-baseline", false)]
+    #[case::home("--home=/tmp", false)]
+    #[case::callgrind_args("--callgrind-args=--cache-sim=yes", true)]
+    #[case::filter("my_bench", true)]
+    fn test_is_passed_through(#[case] arg: &str, #[case] expected: bool) {
+        assert_eq!(is_passed_through(arg), expected);
+    }
+}
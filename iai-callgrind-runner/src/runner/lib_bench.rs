@@ -13,15 +13,24 @@ use std::time::Instant;
 use anyhow::Result;
 use log::warn;
 
+use super::baseline;
+use super::callgrind::noise;
+use super::callgrind::warm_cache::WarmCache;
 use super::common::{Assistant, AssistantKind, Baselines, BenchmarkSummaries, Config, ModulePath};
+use super::compare::{self, CompareLimit};
+use super::complexity::{self, Series};
+use super::config_file::Settings;
 use super::format::{LibraryBenchmarkHeader, OutputFormat};
 use super::meta::Metadata;
+use super::snapshot::{self, SnapshotMode};
 use super::summary::{BaselineKind, BaselineName, BenchmarkKind, BenchmarkSummary, SummaryOutput};
 use super::tool::config::ToolConfigs;
+use super::tool::fingerprint;
 use super::tool::path::{ToolOutputPath, ToolOutputPathKind};
 use super::tool::run::RunOptions;
 use crate::api::{
-    EntryPoint, LibraryBenchmarkConfig, LibraryBenchmarkGroups, RawArgs, ValgrindTool,
+    EntryPoint, EventKind, InstrumentationMode, LibraryBenchmarkConfig, LibraryBenchmarkGroups,
+    Parameter, RawArgs, RegressionPolicy, Throughput, ValgrindTool,
 };
 use crate::error::Error;
 use crate::runner::format;
@@ -37,6 +46,7 @@ struct BaselineBenchmark {
 struct Group {
     benches: Vec<LibBench>,
     compare_by_id: bool,
+    compare_limits: Vec<CompareLimit>,
     module_path: ModulePath,
     name: String,
     setup: Option<Assistant>,
@@ -64,6 +74,8 @@ pub struct LibBench {
     pub group_index: usize,
     /// The id of the benchmark as in `#[bench::id]`
     pub id: Option<String>,
+    /// The [`InstrumentationMode`] of this benchmark
+    pub instrumentation_mode: InstrumentationMode,
     /// The index of the element in the iterator of `#[benches::id(iter = ITERATOR)]` if present
     pub iter_index: Option<usize>,
     /// The [`ModulePath`].
@@ -73,8 +85,16 @@ pub struct LibBench {
     pub module_path: ModulePath,
     /// The [`OutputFormat`]
     pub output_format: OutputFormat,
+    /// The structured [`Parameter`]s of the benchmark function
+    pub parameters: Vec<Parameter>,
+    /// The [`RegressionPolicy`] of this benchmark
+    pub regression_policy: RegressionPolicy,
     /// The [`RunOptions`]
     pub run_options: RunOptions,
+    /// The input size of `#[benches::id(size = ...)]` if present
+    pub size: Option<u64>,
+    /// The amount of bytes or elements processed by the benchmark if declared
+    pub throughput: Option<Throughput>,
     /// The tool configurations for this benchmark run
     pub tools: ToolConfigs,
 }
@@ -154,7 +174,10 @@ impl Benchmark for BaselineBenchmark {
         out_path.init()?;
 
         for path in lib_bench.tools.output_paths(&out_path) {
+            path.init()?;
             path.shift()?;
+            fingerprint::shift(&path)?;
+            noise::shift(&path)?;
             if path.kind == ToolOutputPathKind::Out {
                 path.to_log_output().shift()?;
             }
@@ -194,6 +217,7 @@ impl Benchmark for BaselineBenchmark {
 impl Groups {
     /// Create this `Groups` from a [`crate::api::LibraryBenchmark`] submitted by the benchmarking
     /// harness
+    #[allow(clippy::too_many_lines)]
     fn from_library_benchmark(
         module_path: &ModulePath,
         benchmark_groups: LibraryBenchmarkGroups,
@@ -237,6 +261,11 @@ impl Groups {
                 compare_by_id: library_benchmark_group
                     .compare_by_id
                     .unwrap_or(defaults::COMPARE_BY_ID),
+                compare_limits: library_benchmark_group
+                    .compare_limits
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
             };
 
             for (group_index, library_benchmark_benches) in library_benchmark_group
@@ -247,15 +276,27 @@ impl Groups {
                 for (bench_index, library_benchmark_bench) in
                     library_benchmark_benches.benches.into_iter().enumerate()
                 {
-                    let config = group_config.clone().update_from_all([
-                        library_benchmark_benches.config.as_ref(),
-                        library_benchmark_bench.config.as_ref(),
-                    ]);
-
                     let module_path = group
                         .module_path
                         .join(&library_benchmark_bench.function_name);
 
+                    if meta.args.filter.as_ref().is_some_and(|filter| {
+                        !filter.apply_parameters(&library_benchmark_bench.parameters)
+                    }) {
+                        continue;
+                    }
+
+                    // The configuration files are the lowest level of the configuration
+                    let settings = meta
+                        .config_file
+                        .resolve(&module_path, library_benchmark_bench.id.as_deref());
+                    let config = settings.to_library_benchmark_config().update_from_all([
+                        Some(&global_config),
+                        library_benchmark_group.config.as_ref(),
+                        library_benchmark_benches.config.as_ref(),
+                        library_benchmark_bench.config.as_ref(),
+                    ]);
+
                     if let Some(iter_count) = library_benchmark_bench.iter_count {
                         match (iter_count, &library_benchmark_bench.id) {
                             (0, Some(id)) => {
@@ -269,13 +310,16 @@ impl Groups {
                                     let lib_bench = LibBench::new(
                                         library_benchmark_bench.id.clone(),
                                         library_benchmark_bench.args.clone(),
+                                        library_benchmark_bench.parameters.clone(),
                                         module_path.clone(),
                                         library_benchmark_bench.function_name.clone(),
                                         meta,
                                         config.clone(),
+                                        &settings,
                                         group_index,
                                         bench_index,
                                         Some(iter_index),
+                                        library_benchmark_bench.sizes.get(iter_index).copied(),
                                         default_tool,
                                     )?;
                                     group.benches.push(lib_bench);
@@ -286,13 +330,16 @@ impl Groups {
                         let lib_bench = LibBench::new(
                             library_benchmark_bench.id,
                             library_benchmark_bench.args,
+                            library_benchmark_bench.parameters,
                             module_path,
                             library_benchmark_bench.function_name,
                             meta,
                             config,
+                            &settings,
                             group_index,
                             bench_index,
                             None,
+                            library_benchmark_bench.sizes.first().copied(),
                             default_tool,
                         )?;
                         group.benches.push(lib_bench);
@@ -309,6 +356,7 @@ impl Groups {
     /// Run all [`LibBench`] benchmarks
     fn run(&self, benchmark: &dyn Benchmark, config: &Config) -> Result<BenchmarkSummaries> {
         let mut benchmark_summaries = BenchmarkSummaries::default();
+        let snapshot_mode = SnapshotMode::from_args(&config.meta.args);
         for group in &self.0 {
             if let Some(setup) = &group.setup {
                 setup.run(config, &group.module_path)?;
@@ -316,32 +364,74 @@ impl Groups {
 
             let mut lib_bench_summaries: HashMap<String, Vec<BenchmarkSummary>> =
                 HashMap::with_capacity(group.benches.len());
-            for bench in &group.benches {
+            let mut series: HashMap<&str, Series> = HashMap::new();
+            for (index, bench) in group.benches.iter().enumerate() {
                 let fail_fast = bench
                     .tools
                     .0
                     .iter()
                     .any(|c| c.regression_config.is_fail_fast());
 
-                let lib_bench_summary = benchmark.run(bench, config, group)?;
-                lib_bench_summary.print_and_save(&config.meta.args.output_format)?;
-                lib_bench_summary.check_regression(fail_fast)?;
+                let is_compared = group.compare_by_id && bench.output_format.is_default();
 
-                benchmark_summaries.add_summary(lib_bench_summary.clone());
-                if group.compare_by_id && bench.output_format.is_default() {
-                    if let Some(id) = &lib_bench_summary.id {
-                        if let Some(sums) = lib_bench_summaries.get_mut(id) {
-                            for sum in sums.iter() {
+                let mut lib_bench_summary = benchmark.run(bench, config, group)?;
+                if let Some(id) = lib_bench_summary.id.clone() {
+                    if let Some(sums) = lib_bench_summaries.get(&id) {
+                        if is_compared {
+                            for sum in sums {
                                 sum.compare_and_print(
-                                    id,
+                                    &id,
                                     &lib_bench_summary,
                                     &bench.output_format,
                                 )?;
                             }
-                            sums.push(lib_bench_summary);
-                        } else {
-                            lib_bench_summaries.insert(id.clone(), vec![lib_bench_summary]);
                         }
+                        if bench.regression_policy != RegressionPolicy::Ignore {
+                            compare::check_and_print(
+                                &group.compare_limits,
+                                &mut lib_bench_summary,
+                                sums,
+                            );
+                        }
+                    }
+                }
+                if let (Some(size), Some(metric)) = (bench.size, bench.complexity_metric()) {
+                    let function_series = series.entry(&bench.function_name).or_default();
+                    function_series.add(size, metric, &lib_bench_summary);
+
+                    // The complexity is fitted when the last benchmark of the series has finished
+                    if !group.benches[index + 1..]
+                        .iter()
+                        .any(|b| b.function_name == bench.function_name && b.size.is_some())
+                    {
+                        complexity::fit_and_print(
+                            function_series,
+                            metric,
+                            bench.regression_policy,
+                            &mut lib_bench_summary,
+                        );
+                    }
+                }
+                if let Some(mode) = snapshot_mode {
+                    snapshot::check_or_update(
+                        mode,
+                        &config.package_dir,
+                        &group.module_path,
+                        &bench.name(),
+                        &bench.output_format,
+                        &mut lib_bench_summary,
+                    )?;
+                }
+                lib_bench_summary.print_and_save(&config.meta.args.output_format)?;
+                lib_bench_summary.check_regression(fail_fast)?;
+
+                benchmark_summaries.add_summary(lib_bench_summary.clone());
+                if is_compared || !group.compare_limits.is_empty() {
+                    if let Some(id) = lib_bench_summary.id.clone() {
+                        lib_bench_summaries
+                            .entry(id)
+                            .or_default()
+                            .push(lib_bench_summary);
                     }
                 }
             }
@@ -359,13 +449,16 @@ impl LibBench {
     fn new(
         id: Option<String>,
         display: Option<String>,
+        parameters: Vec<Parameter>,
         module_path: ModulePath,
         function_name: String,
         meta: &Metadata,
         config: LibraryBenchmarkConfig,
+        settings: &Settings,
         group_index: usize,
         bench_index: usize,
         iter_index: Option<usize>,
+        size: Option<u64>,
         default_tool: ValgrindTool,
     ) -> Result<Self> {
         let id = if let Some(iter_index) = iter_index {
@@ -394,12 +487,28 @@ impl LibBench {
             config.default_tool.unwrap_or(default_tool)
         };
 
+        // In the `ClientRequests` mode, the benchmark harness switches on the instrumentation of
+        // callgrind and cachegrind only around the call of the benchmark function. The
+        // instrumentation has to be switched off at the start for both tools regardless of the
+        // default tool.
+        let instrumentation_mode = config.instrumentation_mode.unwrap_or_default();
+        if instrumentation_mode == InstrumentationMode::ClientRequests {
+            default_args.insert(
+                ValgrindTool::Callgrind,
+                RawArgs::new(["--instr-atstart=no"]),
+            );
+            default_args.insert(
+                ValgrindTool::Cachegrind,
+                RawArgs::new(["--instr-at-start=no"]),
+            );
+        }
+
         let mut output_format = config
             .output_format
             .map_or_else(OutputFormat::default, Into::into);
         output_format.kind = meta.args.output_format;
 
-        let tool_configs = ToolConfigs::new(
+        let mut tool_configs = ToolConfigs::new(
             &mut output_format,
             config.tools,
             &module_path,
@@ -409,23 +518,62 @@ impl LibBench {
             &EntryPoint::Default,
             &config.valgrind_args,
             &default_args,
+            settings,
         )
         .map_err(|error| {
             Error::ConfigurationError(module_path.clone(), id.clone(), error.to_string())
         })?;
 
+        let regression_policy = meta
+            .args
+            .regression_policy
+            .or(config.regression_policy)
+            .unwrap_or_default();
+        if regression_policy == RegressionPolicy::Ignore {
+            tool_configs.clear_regression_configs();
+        }
+
+        // Callgrind can't detect the end of an iteration with the `ClientRequests` instrumentation
+        // mode since the function running the iteration is left with the instrumentation
+        // switched off.
+        if let Some(warm_cache) = WarmCache::new(config.warmup, config.iterations)
+            .and_then(|warm_cache| {
+                if warm_cache.is_some()
+                    && instrumentation_mode == InstrumentationMode::ClientRequests
+                {
+                    Err(
+                        "The instrumentation mode `ClientRequests` can't be combined with warmup \
+                         or iterations"
+                            .to_owned(),
+                    )
+                } else {
+                    Ok(warm_cache)
+                }
+            })
+            .map_err(|error| Error::ConfigurationError(module_path.clone(), id.clone(), error))?
+        {
+            tool_configs.warm_cache(warm_cache).map_err(|error| {
+                Error::ConfigurationError(module_path.clone(), id.clone(), error.to_string())
+            })?;
+        }
+
         Ok(Self {
             group_index,
             bench_index,
             iter_index,
             id,
+            instrumentation_mode,
             function_name,
             display,
+            parameters,
             run_options: RunOptions {
                 env_clear: config.env_clear.unwrap_or(true),
                 envs,
                 ..Default::default()
             },
+            regression_policy,
+            size,
+            throughput: config.throughput,
             tools: tool_configs,
             module_path,
             output_format,
@@ -433,6 +581,15 @@ impl LibBench {
         })
     }
 
+    /// The metric to fit the complexity with if callgrind is enabled for this benchmark
+    fn complexity_metric(&self) -> Option<EventKind> {
+        self.tools
+            .0
+            .iter()
+            .find(|t| t.tool == ValgrindTool::Callgrind && t.is_enabled)
+            .and_then(|t| t.complexity_metric)
+    }
+
     /// The name of this `LibBench` consisting of the name of the benchmark function and if present,
     /// the id of the bench attribute (`#[bench::ID(...)]`)
     ///
@@ -460,6 +617,11 @@ impl LibBench {
         }
 
         args.push(OsString::from(self.module_path.to_string()));
+
+        if self.instrumentation_mode == InstrumentationMode::ClientRequests {
+            args.push(OsString::from("--iai-client-requests"));
+        }
+
         args
     }
 
@@ -480,7 +642,7 @@ impl LibBench {
             None
         };
 
-        Ok(BenchmarkSummary::new(
+        let mut benchmark_summary = BenchmarkSummary::new(
             BenchmarkKind::LibraryBenchmark,
             config.meta.project_root.clone(),
             config.package_dir.clone(),
@@ -492,7 +654,12 @@ impl LibBench {
             description,
             summary_output,
             baselines,
-        ))
+        );
+        benchmark_summary.throughput = self.throughput;
+        benchmark_summary.regression_policy = self.regression_policy;
+        benchmark_summary.parameters.clone_from(&self.parameters);
+
+        Ok(benchmark_summary)
     }
 }
 
@@ -611,6 +778,24 @@ impl Runner {
 
     /// Run all benchmarks in all groups
     fn run(&self) -> Result<BenchmarkSummaries> {
+        let benchmark_dirs = self
+            .groups
+            .0
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(move |lib_bench| {
+                    self.benchmark
+                        .output_path(lib_bench, &self.config, group)
+                        .dir
+                })
+            })
+            .collect::<Vec<_>>();
+        baseline::write_index(
+            &self.config.meta.target_dir,
+            &self.config.module_path,
+            &benchmark_dirs,
+        )?;
+
         if let Some(setup) = &self.setup {
             setup.run(&self.config, &self.config.module_path)?;
         }
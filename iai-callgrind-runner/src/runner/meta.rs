@@ -4,14 +4,15 @@ use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use cargo_metadata::TargetKind;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use log::debug;
 
 use super::args::CommandLineArgs;
+use super::config_file::{self, ConfigFile};
 use super::envs;
-use crate::util::resolve_binary_path;
+use crate::util::{command_output, resolve_binary_path};
 
 /// The basic commands (like valgrind) to be executed with default arguments
 #[derive(Debug, Clone)]
@@ -34,8 +35,17 @@ pub struct Metadata {
     pub args: CommandLineArgs,
     /// The name of the benchmark to run (might be different to the name of the file)
     pub bench_name: String,
+    /// The project-wide configuration from the `iai-callgrind.toml` files
+    pub config_file: ConfigFile,
+    /// The absolute path of the home directory of all benchmark output files (per default
+    /// `$WORKSPACE_ROOT/target/iai`)
+    pub home: PathBuf,
     /// The path to the project top-level directory
     pub project_root: PathBuf,
+    /// The raw command-line arguments as in ARGS of `cargo bench -- ARGS`
+    pub raw_args: Vec<String>,
+    /// The output of `rustc --version` if available
+    pub rustc_version: Option<String>,
     /// The absolute path of the `HOME` (per default `$WORKSPACE_ROOT/target/iai`). Plus, if
     /// configured, the target of the host like `x86_64-linux-unknown-gnu`. The final component is
     /// the `CARGO_PKG_NAME`.
@@ -46,6 +56,8 @@ pub struct Metadata {
     pub target_dir: PathBuf,
     /// The valgrind [`Cmd`]
     pub valgrind: Cmd,
+    /// The output of `valgrind --version` if available
+    pub valgrind_version: Option<String>,
     /// The valgrind wrapper [`Cmd`]
     pub valgrind_wrapper: Option<Cmd>,
 }
@@ -70,12 +82,15 @@ impl From<&Metadata> for Command {
 
 impl Metadata {
     /// Create a `new` Metadata
+    #[allow(clippy::too_many_lines)]
     pub fn new(
         raw_command_line_args: &[String],
         package_name: &str,
         bench_file: &Path,
     ) -> Result<Self> {
-        let args = CommandLineArgs::parse_from(raw_command_line_args);
+        let matches = CommandLineArgs::command().get_matches_from(raw_command_line_args);
+        let mut args =
+            CommandLineArgs::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
 
         let arch = std::env::consts::ARCH.to_owned();
         debug!("Detected architecture: {arch}");
@@ -101,28 +116,56 @@ impl Metadata {
         let project_root = meta.workspace_root.into_std_path_buf();
         debug!("Detected project root: '{}'", project_root.display());
 
-        let target_dir = {
-            let mut home = args.home.as_ref().map_or_else(
-                || {
-                    std::env::var_os(envs::CARGO_TARGET_DIR)
-                        .map_or_else(|| meta.target_directory.into_std_path_buf(), PathBuf::from)
-                        .join("iai")
-                },
-                Clone::clone,
+        let package_dir = package.manifest_path.parent().map_or_else(
+            || project_root.clone(),
+            |dir| dir.as_std_path().to_path_buf(),
+        );
+        let config_file =
+            ConfigFile::load(&project_root, &package_dir, args.config_profile.as_deref())?;
+
+        // The `[args]` of the configuration files are the defaults of the command-line arguments
+        // and are overridden by the real command-line arguments and environment variables
+        let default_args = config_file.command_line_args(&matches)?;
+        if !default_args.is_empty() {
+            debug!(
+                "Default arguments from {}: {default_args:?}",
+                config_file::FILE_NAME
             );
-
-            if args.separate_targets {
-                home = home.join(env!("IC_BUILD_TRIPLE").to_ascii_lowercase());
-            }
-            home.join(
-                std::env::var_os(envs::CARGO_PKG_NAME).map_or_else(PathBuf::new, PathBuf::from),
-            )
-        };
+            args =
+                CommandLineArgs::try_parse_from(default_args.iter().chain(raw_command_line_args))
+                    .map_err(|error| {
+                    anyhow!(
+                        "Invalid [args] in {}: {}",
+                        config_file::FILE_NAME,
+                        error.to_string().trim_end()
+                    )
+                })?;
+        }
+
+        let home = args.home.as_ref().map_or_else(
+            || {
+                std::env::var_os(envs::CARGO_TARGET_DIR)
+                    .map_or_else(|| meta.target_directory.into_std_path_buf(), PathBuf::from)
+                    .join("iai")
+            },
+            Clone::clone,
+        );
+        let mut target_dir = home.clone();
+        if args.separate_targets {
+            target_dir.push(env!("IC_BUILD_TRIPLE").to_ascii_lowercase());
+        }
+        target_dir
+            .push(std::env::var_os(envs::CARGO_PKG_NAME).map_or_else(PathBuf::new, PathBuf::from));
 
         debug!("Detected target directory: '{}'", target_dir.display());
 
+        let rustc = std::env::var_os(envs::RUSTC).unwrap_or_else(|| OsString::from("rustc"));
+        let rustc_version = command_output(&rustc, &["--version"]);
         // Invoke Valgrind, disabling ASLR if possible because ASLR could noise up the results a bit
         let valgrind_path = resolve_binary_path("valgrind")?;
+        let valgrind_version = command_output(valgrind_path.as_os_str(), &["--version"]);
+        debug!("Detected versions: {rustc_version:?}, {valgrind_version:?}");
+
         let valgrind_wrapper = if args.allow_aslr.unwrap_or_default() {
             debug!("Running with ASLR enabled");
             None
@@ -175,10 +218,15 @@ impl Metadata {
                 bin: valgrind_path,
                 args: vec![],
             },
+            valgrind_version,
             valgrind_wrapper,
             project_root,
+            rustc_version,
+            home,
+            raw_args: raw_command_line_args.to_vec(),
             args,
             bench_name,
+            config_file,
         })
     }
 }
@@ -50,6 +50,8 @@ pub enum MetricKind {
     None,
     /// The Callgrind metric kind
     Callgrind(EventKind),
+    /// The user-defined metric of Callgrind with its name
+    Custom(String),
     /// The Cachegrind metric kind
     Cachegrind(CachegrindMetric),
     /// The DHAT metric kind
@@ -234,6 +236,19 @@ impl FromStr for Metric {
     }
 }
 
+impl Mul for Metric {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Self::Int(a.saturating_mul(b)),
+            (Self::Int(a), Self::Float(b)) => Self::Float((a as f64) * b),
+            (Self::Float(a), Self::Int(b)) => Self::Float(a * (b as f64)),
+            (Self::Float(a), Self::Float(b)) => Self::Float(a * b),
+        }
+    }
+}
+
 impl Mul<u64> for Metric {
     type Output = Self;
 
@@ -291,6 +306,7 @@ impl Display for MetricKind {
         match self {
             Self::None => Ok(()),
             Self::Callgrind(metric) => f.write_fmt(format_args!("Callgrind: {metric}")),
+            Self::Custom(name) => f.write_fmt(format_args!("Callgrind: {name}")),
             Self::Cachegrind(metric) => f.write_fmt(format_args!("Cachegrind: {metric}")),
             Self::Dhat(metric) => f.write_fmt(format_args!("DHAT: {metric}")),
             Self::Memcheck(metric) => f.write_fmt(format_args!("Memcheck: {metric}")),
@@ -597,6 +613,19 @@ where
     }
 }
 
+impl<K> FromIterator<(K, MetricsDiff)> for MetricsSummary<K>
+where
+    K: Hash + Eq,
+{
+    /// Collect already calculated [`MetricsDiff`]s without summarizing them again
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, MetricsDiff)>,
+    {
+        Self(iter.into_iter().collect())
+    }
+}
+
 impl From<Metric> for f64 {
     fn from(value: Metric) -> Self {
         match value {
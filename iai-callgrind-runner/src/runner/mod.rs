@@ -1,10 +1,14 @@
 //! The main runner module
 
 pub mod args;
+pub mod baseline;
 pub mod bin_bench;
 pub mod cachegrind;
 pub mod callgrind;
 pub mod common;
+pub mod compare;
+pub mod complexity;
+pub mod config_file;
 pub mod dhat;
 
 /// Names of environment variables which are used in different places
@@ -23,12 +27,17 @@ pub mod envs {
     pub const IAI_CALLGRIND_COLOR: &str = "IAI_CALLGRIND_COLOR";
     /// Set the logging output of Iai-Callgrind
     pub const IAI_CALLGRIND_LOG: &str = "IAI_CALLGRIND_LOG";
+
+    /// The path to the rust compiler
+    pub const RUSTC: &str = "RUSTC";
 }
 
 pub mod format;
+pub mod git;
 pub mod lib_bench;
 pub mod meta;
 pub mod metrics;
+pub mod snapshot;
 pub mod summary;
 pub mod tool;
 
@@ -89,7 +98,8 @@ impl PostRun {
         }
     }
 
-    /// Print the summary returning [`Error::RegressionError`] if regressions were present
+    /// Print the summary returning [`Error::RegressionError`] if regressions were present which
+    /// fail the benchmark run
     ///
     /// The summary is not printed if `nosummary` is true or the [`OutputFormatKind`] is not the
     /// default format (i.e. JSON).
@@ -97,7 +107,7 @@ impl PostRun {
         self.benchmark_summaries
             .print(self.nosummary, self.output_format_kind);
 
-        if self.benchmark_summaries.is_regressed() {
+        if self.benchmark_summaries.is_failed() {
             Err(Error::RegressionError(false).into())
         } else {
             Ok(())
@@ -228,8 +238,15 @@ where
     bincode::deserialize(&encoded).with_context(|| "Failed to decode configuration")
 }
 
-/// Run this benchmark
+/// Run this benchmark or the `baseline` subcommand if it is the first argument
 pub fn run() -> Result<()> {
+    if std::env::args_os()
+        .nth(1)
+        .is_some_and(|arg| arg == "baseline")
+    {
+        return baseline::run(std::env::args_os().skip(1));
+    }
+
     let RunnerArgs {
         bench_kind,
         package_dir,
@@ -277,6 +294,8 @@ pub fn run() -> Result<()> {
                 return lib_bench::list(benchmark_groups, &config);
             }
 
+            git::create_missing_baseline(&config)?;
+
             lib_bench::run(benchmark_groups, config)
                 .map(|summaries| PostRun::new(nosummary, output_format, summaries))?
         }
@@ -316,6 +335,8 @@ pub fn run() -> Result<()> {
                 return bin_bench::list(benchmark_groups, &config);
             }
 
+            git::create_missing_baseline(&config)?;
+
             bin_bench::run(benchmark_groups, config)
                 .map(|summaries| PostRun::new(nosummary, output_format, summaries))?
         }
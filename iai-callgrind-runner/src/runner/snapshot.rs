@@ -0,0 +1,527 @@
+//! Module containing the snapshot files of the benchmark metrics
+//!
+//! A snapshot file records the metrics of a single benchmark in a human-readable toml file which
+//! is meant to be committed to the repository. With `--update-snapshots` the snapshot files are
+//! created or overwritten and with `--check-snapshots` the metrics of the benchmark run have to
+//! match the snapshot within the `--snapshot-tolerance` or the benchmark fails.
+use std::fmt::Display;
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use indexmap::{IndexMap, IndexSet};
+
+use super::args::CommandLineArgs;
+use super::common::ModulePath;
+use super::format::{print_snapshot, OutputFormat};
+use super::metrics::{Metric, MetricKind, MetricsSummary, Summarize};
+use super::summary::{
+    BenchmarkSummary, SnapshotState, SnapshotSummary, ToolMetricSummary, ToolRegression,
+};
+use crate::api::{RegressionPolicy, ValgrindTool};
+use crate::util::{percentage_diff, truncate_str_utf8};
+
+/// The directory of the snapshot files relative to the package directory
+pub const DIR: &str = "benches/snapshots";
+
+/// The recorded metrics by table name and metric name
+///
+/// The table name is the tool id or `<tool id>-<cache model>` if the tool was run with a
+/// [`crate::api::CacheModel`].
+type Metrics = IndexMap<String, IndexMap<String, Metric>>;
+
+/// Whether the snapshots are checked or updated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotMode {
+    /// Check the metrics against the snapshots with the tolerance in percent
+    Check(f64),
+    /// Create or overwrite the snapshots with the new metrics
+    Update,
+}
+
+/// The snapshot of the metrics of a single benchmark
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    /// The [`MetricKind`] of each metric in `metrics`
+    kinds: IndexMap<(String, String), MetricKind>,
+    /// The recorded metrics
+    metrics: Metrics,
+}
+
+impl SnapshotMode {
+    /// Return the `SnapshotMode` from the command-line arguments if snapshots are enabled
+    pub fn from_args(args: &CommandLineArgs) -> Option<Self> {
+        if args.update_snapshots {
+            Some(Self::Update)
+        } else if args.check_snapshots {
+            Some(Self::Check(
+                args.snapshot_tolerance.unwrap_or_default().abs(),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl Snapshot {
+    /// Collect the metrics of the totals of the `summary` which are selected in the
+    /// `output_format`
+    fn from_summary(summary: &BenchmarkSummary, output_format: &OutputFormat) -> Self {
+        let mut snapshot = Self {
+            kinds: IndexMap::new(),
+            metrics: IndexMap::new(),
+        };
+        for profile in summary.profiles.iter() {
+            let tool = profile.tool;
+            let table = profile
+                .cache_model
+                .map_or_else(|| tool.id(), |model| format!("{}-{model}", tool.id()));
+            let table = table.as_str();
+            match &profile.summaries.total.summary {
+                ToolMetricSummary::None => {}
+                ToolMetricSummary::Callgrind(metrics) => {
+                    snapshot.add(table, metrics, &output_format.callgrind, |k| {
+                        MetricKind::Callgrind(*k)
+                    });
+                }
+                ToolMetricSummary::Cachegrind(metrics) => {
+                    snapshot.add(table, metrics, &output_format.cachegrind, |k| {
+                        MetricKind::Cachegrind(*k)
+                    });
+                }
+                ToolMetricSummary::Dhat(metrics) => {
+                    snapshot.add(table, metrics, &output_format.dhat, |k| {
+                        MetricKind::Dhat(*k)
+                    });
+                }
+                ToolMetricSummary::ErrorTool(metrics) => match tool {
+                    ValgrindTool::Memcheck => {
+                        snapshot.add(table, metrics, &output_format.memcheck, |k| {
+                            MetricKind::Memcheck(*k)
+                        });
+                    }
+                    ValgrindTool::Helgrind => {
+                        snapshot.add(table, metrics, &output_format.helgrind, |k| {
+                            MetricKind::Helgrind(*k)
+                        });
+                    }
+                    ValgrindTool::DRD => {
+                        snapshot.add(table, metrics, &output_format.drd, |k| MetricKind::DRD(*k));
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        snapshot
+    }
+
+    /// Add the new metrics of the `summary` which are in the `selected` metrics
+    fn add<K, F>(
+        &mut self,
+        table: &str,
+        summary: &MetricsSummary<K>,
+        selected: &IndexSet<K>,
+        to_metric_kind: F,
+    ) where
+        K: Hash + Eq + Summarize + Display + Clone,
+        F: Fn(&K) -> MetricKind,
+    {
+        for kind in selected {
+            if let Some(metric) = summary
+                .diff_by_kind(kind)
+                .and_then(|diff| diff.metrics.left())
+            {
+                let name = kind.to_string();
+                self.kinds
+                    .insert((table.to_owned(), name.clone()), to_metric_kind(kind));
+                self.metrics
+                    .entry(table.to_owned())
+                    .or_default()
+                    .insert(name, metric);
+            }
+        }
+    }
+
+    /// Compare the metrics of this snapshot with the `old` recorded metrics
+    ///
+    /// Return the metrics which differ by more than the `tolerance` in percent and the names of
+    /// the metrics which are only present in either this snapshot or the `old` metrics.
+    fn compare(&self, old: &Metrics, tolerance: f64) -> (Vec<ToolRegression>, Vec<String>) {
+        let mut regressions = vec![];
+        let mut unmatched = vec![];
+        for (tool, metrics) in &self.metrics {
+            for (name, new) in metrics {
+                match old.get(tool).and_then(|m| m.get(name)) {
+                    Some(old) => {
+                        let diff_pct = percentage_diff(*new, *old);
+                        if diff_pct.abs() > tolerance {
+                            regressions.push(ToolRegression::Soft {
+                                metric: self.kinds[&(tool.clone(), name.clone())].clone(),
+                                new: *new,
+                                old: *old,
+                                diff_pct,
+                                limit: if diff_pct.is_sign_negative() {
+                                    -tolerance
+                                } else {
+                                    tolerance
+                                },
+                            });
+                        }
+                    }
+                    None => unmatched.push(format!("{tool}: {name}")),
+                }
+            }
+        }
+        for (tool, metrics) in old {
+            for name in metrics.keys() {
+                if !self.metrics.get(tool).is_some_and(|m| m.contains_key(name)) {
+                    unmatched.push(format!("{tool}: {name}"));
+                }
+            }
+        }
+
+        (regressions, unmatched)
+    }
+
+    /// Return the content of the snapshot file for the benchmark with the `module_path`
+    fn to_toml(&self, module_path: &str) -> Result<String> {
+        let mut table = toml::Table::new();
+        for (tool, metrics) in &self.metrics {
+            let mut tool_table = toml::Table::new();
+            for (name, metric) in metrics {
+                let value = match metric {
+                    Metric::Int(int) => i64::try_from(*int).map_or_else(
+                        |_| toml::Value::String(int.to_string()),
+                        toml::Value::Integer,
+                    ),
+                    Metric::Float(float) => toml::Value::Float(*float),
+                };
+                tool_table.insert(name.clone(), value);
+            }
+            table.insert(tool.clone(), toml::Value::Table(tool_table));
+        }
+
+        Ok(format!(
+            "# The metrics of the benchmark `{module_path}`\n#\n# Created with `cargo bench -- \
+             --update-snapshots` and checked with `cargo bench -- --check-snapshots`\n\n{}",
+            toml::to_string(&table)?
+        ))
+    }
+}
+
+/// Parse the metrics of the snapshot file `content`
+fn parse(content: &str) -> Result<Metrics> {
+    let table: toml::Table = toml::from_str(content)?;
+    let mut metrics = Metrics::new();
+    for (tool, value) in table {
+        let toml::Value::Table(tool_table) = value else {
+            return Err(anyhow!("Expected a table of metrics for '{tool}'"));
+        };
+        let tool_metrics = metrics.entry(tool.clone()).or_default();
+        for (name, value) in tool_table {
+            let metric = match value {
+                toml::Value::Integer(int) => u64::try_from(int)
+                    .map(Metric::Int)
+                    .map_err(|_| anyhow!("Invalid metric '{tool}.{name}': {int} is negative"))?,
+                toml::Value::Float(float) => Metric::Float(float),
+                toml::Value::String(string) => string
+                    .parse::<Metric>()
+                    .map_err(|error| anyhow!("Invalid metric '{tool}.{name}': {error}"))?,
+                _ => return Err(anyhow!("Invalid metric '{tool}.{name}': Expected a number")),
+            };
+            tool_metrics.insert(name, metric);
+        }
+    }
+
+    Ok(metrics)
+}
+
+/// Return the path to the snapshot file of the benchmark `name` in the group `module_path`
+///
+/// The path is `<package_dir>/benches/snapshots/<bench file>/<group>/<name>.toml`, where the name
+/// is sanitized in the same way as the directory names of the benchmark output files.
+pub fn path(package_dir: &Path, module_path: &ModulePath, name: &str) -> PathBuf {
+    let module_path: PathBuf = module_path.to_string().split("::").collect();
+    let sanitized_name = sanitize_filename::sanitize_with_options(
+        name,
+        sanitize_filename::Options {
+            windows: false,
+            truncate: false,
+            replacement: "_",
+        },
+    );
+    let sanitized_name = truncate_str_utf8(&sanitized_name, 200);
+    package_dir
+        .join(DIR)
+        .join(module_path)
+        .join(format!("{sanitized_name}.toml"))
+}
+
+/// Check the metrics of the benchmark `summary` against or update its snapshot file
+///
+/// The result is printed and stored in the `summary`. Snapshots are not checked if the
+/// [`RegressionPolicy`] is `Ignore`.
+///
+/// # Errors
+///
+/// If the snapshot file could not be read, parsed or written
+pub fn check_or_update(
+    mode: SnapshotMode,
+    package_dir: &Path,
+    module_path: &ModulePath,
+    name: &str,
+    output_format: &OutputFormat,
+    summary: &mut BenchmarkSummary,
+) -> Result<()> {
+    if matches!(mode, SnapshotMode::Check(_))
+        && summary.regression_policy == RegressionPolicy::Ignore
+    {
+        return Ok(());
+    }
+
+    let path = path(package_dir, module_path, name);
+    let snapshot = Snapshot::from_summary(summary, output_format);
+    let content = if path.exists() {
+        Some(
+            fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read snapshot file '{}'", path.display()))?,
+        )
+    } else {
+        None
+    };
+
+    let mut snapshot_summary = SnapshotSummary {
+        path,
+        regressions: vec![],
+        state: SnapshotState::Missing,
+        unmatched_metrics: vec![],
+    };
+    match mode {
+        SnapshotMode::Check(tolerance) => {
+            if let Some(content) = content {
+                let old = parse(&content).with_context(|| {
+                    format!(
+                        "Failed to parse snapshot file '{}'",
+                        snapshot_summary.path.display()
+                    )
+                })?;
+                let (regressions, unmatched) = snapshot.compare(&old, tolerance);
+                snapshot_summary.state = if regressions.is_empty() && unmatched.is_empty() {
+                    SnapshotState::Matched
+                } else {
+                    SnapshotState::Mismatched
+                };
+                snapshot_summary.regressions = regressions;
+                snapshot_summary.unmatched_metrics = unmatched;
+            }
+        }
+        SnapshotMode::Update => {
+            let new_content = snapshot.to_toml(&module_path.join(name).to_string())?;
+            snapshot_summary.state = match content {
+                Some(content) if content == new_content => SnapshotState::Unchanged,
+                Some(_) => SnapshotState::Updated,
+                None => SnapshotState::Created,
+            };
+            if snapshot_summary.state != SnapshotState::Unchanged {
+                let path = &snapshot_summary.path;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("Failed to create snapshot directory '{}'", parent.display())
+                    })?;
+                }
+                fs::write(path, new_content).with_context(|| {
+                    format!("Failed to write snapshot file '{}'", path.display())
+                })?;
+            }
+        }
+    }
+
+    print_snapshot(&module_path.join(name).to_string(), &snapshot_summary);
+    summary.snapshot = Some(snapshot_summary);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::{CacheModel, EventKind};
+    use crate::runner::callgrind::model::Metrics as CallgrindMetrics;
+    use crate::runner::summary::{BenchmarkKind, Profile, ProfileData, ProfileTotal};
+
+    fn snapshot(metrics: &[(&str, Metric)]) -> Snapshot {
+        let mut snapshot = Snapshot {
+            kinds: IndexMap::new(),
+            metrics: IndexMap::new(),
+        };
+        for (name, metric) in metrics {
+            let kind = match *name {
+                "Instructions" => EventKind::Ir,
+                "Estimated Cycles" => EventKind::EstimatedCycles,
+                _ => EventKind::Dr,
+            };
+            snapshot.kinds.insert(
+                ("callgrind".to_owned(), (*name).to_owned()),
+                MetricKind::Callgrind(kind),
+            );
+            snapshot
+                .metrics
+                .entry("callgrind".to_owned())
+                .or_default()
+                .insert((*name).to_owned(), *metric);
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_path() {
+        assert_eq!(
+            path(
+                Path::new("/root/package"),
+                &ModulePath::new("bench_file::group"),
+                "function.some/id"
+            ),
+            PathBuf::from("/root/package/benches/snapshots/bench_file/group/function.some_id.toml")
+        );
+    }
+
+    #[test]
+    fn test_to_toml_and_parse_roundtrip() {
+        let snapshot = snapshot(&[
+            ("Instructions", Metric::Int(1234)),
+            ("Estimated Cycles", Metric::Float(12.5)),
+            ("Dr", Metric::Int(u64::MAX)),
+        ]);
+
+        let content = snapshot.to_toml("bench_file::group::function").unwrap();
+        assert!(content.starts_with("# The metrics of the benchmark `bench_file::group::function`"));
+        assert!(
+            content.contains("\n[callgrind]\nInstructions = 1234\n\"Estimated Cycles\" = 12.5\n")
+        );
+        assert_eq!(parse(&content).unwrap(), snapshot.metrics);
+    }
+
+    #[rstest]
+    #[case::equal(1000, 0.0, vec![], vec![])]
+    #[case::within_tolerance(1010, 1.0, vec![], vec![])]
+    #[case::higher(1011, 1.0, vec![(1011, 1.1, 1.0)], vec![])]
+    #[case::lower(989, 1.0, vec![(989, -1.1, -1.0)], vec![])]
+    fn test_compare(
+        #[case] new: u64,
+        #[case] tolerance: f64,
+        #[case] expected_regressions: Vec<(u64, f64, f64)>,
+        #[case] expected_unmatched: Vec<String>,
+    ) {
+        let snapshot = snapshot(&[("Instructions", Metric::Int(new))]);
+        let old = indexmap! {
+            "callgrind".to_owned() => indexmap! {"Instructions".to_owned() => Metric::Int(1000)}
+        };
+
+        let (regressions, unmatched) = snapshot.compare(&old, tolerance);
+        assert_eq!(unmatched, expected_unmatched);
+        assert_eq!(regressions.len(), expected_regressions.len());
+        for (regression, (new, diff_pct, limit)) in regressions.iter().zip(expected_regressions) {
+            let ToolRegression::Soft {
+                metric,
+                new: actual_new,
+                old,
+                diff_pct: actual_diff_pct,
+                limit: actual_limit,
+            } = regression
+            else {
+                panic!("Expected a soft regression");
+            };
+            assert_eq!(*metric, MetricKind::Callgrind(EventKind::Ir));
+            assert_eq!(*actual_new, Metric::Int(new));
+            assert_eq!(*old, Metric::Int(1000));
+            assert!((actual_diff_pct - diff_pct).abs() < 1e-9);
+            assert!((actual_limit - limit).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_compare_unmatched_metrics() {
+        let snapshot = snapshot(&[("Instructions", Metric::Int(1000))]);
+        let old = indexmap! {
+            "callgrind".to_owned() => indexmap! {"Estimated Cycles".to_owned() => Metric::Int(1)},
+            "dhat".to_owned() => indexmap! {"Total bytes".to_owned() => Metric::Int(1)},
+        };
+
+        let (regressions, unmatched) = snapshot.compare(&old, 0.0);
+        assert!(regressions.is_empty());
+        assert_eq!(
+            unmatched,
+            vec![
+                "callgrind: Instructions".to_owned(),
+                "callgrind: Estimated Cycles".to_owned(),
+                "dhat: Total bytes".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_summary_when_cache_models() {
+        let mut summary = BenchmarkSummary::new(
+            BenchmarkKind::LibraryBenchmark,
+            PathBuf::from("/root"),
+            PathBuf::from("/root"),
+            PathBuf::from("benches/bench.rs"),
+            PathBuf::from("target/bench"),
+            &ModulePath::new("bench::group::function"),
+            "function",
+            None,
+            None,
+            None,
+            (None, None),
+        );
+        for (cache_model, ir) in [(None, 1), (Some(CacheModel::Server), 2)] {
+            summary.profiles.push(Profile {
+                cache_model,
+                custom_metrics: None,
+                cycles_model: None,
+                tool: ValgrindTool::Callgrind,
+                log_paths: vec![],
+                out_paths: vec![],
+                summaries: ProfileData {
+                    parts: vec![],
+                    total: ProfileTotal {
+                        regressions: vec![],
+                        summary: ToolMetricSummary::Callgrind(MetricsSummary::new(
+                            either_or_both::EitherOrBoth::Left(
+                                CallgrindMetrics::with_metric_kinds([(EventKind::Ir, ir)]),
+                            ),
+                        )),
+                    },
+                },
+                flamegraphs: vec![],
+                functions: vec![],
+                threads: vec![],
+                throughput: None,
+            });
+        }
+
+        let snapshot = Snapshot::from_summary(&summary, &OutputFormat::default());
+        assert_eq!(
+            snapshot.metrics,
+            indexmap! {
+                "callgrind".to_owned() => indexmap! {"Instructions".to_owned() => Metric::Int(1)},
+                "callgrind-server".to_owned() =>
+                    indexmap! {"Instructions".to_owned() => Metric::Int(2)},
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::negative("[callgrind]\nInstructions = -1\n")]
+    #[case::not_a_number("[callgrind]\nInstructions = true\n")]
+    #[case::not_a_table("Instructions = 1\n")]
+    fn test_parse_when_invalid(#[case] content: &str) {
+        parse(content).unwrap_err();
+    }
+}
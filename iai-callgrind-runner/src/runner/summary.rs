@@ -20,12 +20,15 @@ use super::format::{Formatter, OutputFormat, OutputFormatKind, VerticalFormatter
 use super::metrics::{Metric, MetricKind, Metrics, MetricsSummary};
 use super::tool::parser::ParserOutput;
 use super::tool::regression::RegressionMetrics;
-use crate::api::{CachegrindMetric, DhatMetric, ErrorMetric, EventKind, ValgrindTool};
+use crate::api::{
+    CacheModel, CachegrindMetric, CyclesModel, DhatMetric, ErrorMetric, EventKind, Parameter,
+    RegressionPolicy, Throughput, ValgrindTool,
+};
 use crate::error::Error;
 use crate::util::{factor_diff, make_absolute, percentage_diff};
 
 /// The version of the summary json schema
-pub const SCHEMA_VERSION: &str = "6";
+pub const SCHEMA_VERSION: &str = "7";
 
 /// The `BaselineKind` describing the baseline
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -47,6 +50,52 @@ pub enum BenchmarkKind {
     BinaryBenchmark,
 }
 
+/// The state of the cache of the iterations summarized in a [`ProfilePart`]
+///
+/// Only present if the benchmark ran in the warm-cache mode (`warmup` or `iterations`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum CacheState {
+    /// The first (warmup) iteration which runs with a cold cache
+    Cold,
+    /// The average of the measured iterations which run with a warm cache
+    Warm,
+}
+
+/// The complexity class of a benchmark function fitted over the input sizes of its benchmarks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum Complexity {
+    /// Constant complexity `O(1)`
+    Constant,
+    /// Logarithmic complexity `O(log n)`
+    Logarithmic,
+    /// Linear complexity `O(n)`
+    Linear,
+    /// Linearithmic complexity `O(n log n)`
+    Linearithmic,
+    /// Quadratic complexity `O(n^2)`
+    Quadratic,
+}
+
+/// The state of the snapshot file of a benchmark after checking or updating it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum SnapshotState {
+    /// The snapshot file did not exist and was created
+    Created,
+    /// The metrics match the snapshot within the tolerance
+    Matched,
+    /// The metrics differ from the snapshot
+    Mismatched,
+    /// The snapshot file to check the metrics against does not exist
+    Missing,
+    /// The metrics are the same as in the snapshot file which was left untouched
+    Unchanged,
+    /// The snapshot file was overwritten with the new metrics
+    Updated,
+}
+
 /// The format (json, ...) in which the summary file should be saved or printed
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -127,6 +176,25 @@ pub enum ToolRegression {
         /// The limit
         limit: Metric,
     },
+    /// A missing performance improvement triggered by an expected improvement
+    Improvement {
+        /// The metric kind per tool
+        metric: MetricKind,
+        /// The value of the new benchmark run
+        new: Metric,
+        /// The value of the old benchmark run
+        old: Metric,
+        /// The difference between new and old in percent. Serialized as string to preserve
+        /// infinity values and avoid null in json.
+        #[serde(with = "crate::serde::float_64")]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        diff_pct: f64,
+        /// The expected improvement in percent which was not reached. Serialized as string to
+        /// preserve infinity values and avoid null in json.
+        #[serde(with = "crate::serde::float_64")]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        expected: f64,
+    },
 }
 
 /// A `Baseline` depending on the [`BaselineKind`] which points to the corresponding path
@@ -161,6 +229,12 @@ pub struct BenchmarkSummary {
     pub benchmark_exe: PathBuf,
     /// The path to the benchmark file
     pub benchmark_file: PathBuf,
+    /// The comparisons with other benchmarks of the same group and id which have limits
+    pub comparisons: Vec<ComparisonSummary>,
+    /// The complexity of the benchmark function fitted over the input sizes of its benchmarks.
+    ///
+    /// Only present in the last benchmark of a function with a `size` annotation.
+    pub complexity: Option<ComplexitySummary>,
     /// More details describing this benchmark run
     pub details: Option<String>,
     /// The name of the function under test
@@ -173,17 +247,73 @@ pub struct BenchmarkSummary {
     pub module_path: String,
     /// The directory of the package
     pub package_dir: PathBuf,
+    /// The parameters of the benchmark function with the values of the benchmark arguments.
+    ///
+    /// Empty if the values aren't known at compile time, for example in `iter` mode.
+    pub parameters: Vec<Parameter>,
     /// The summary of other valgrind tool runs
     pub profiles: Profiles,
     /// The project's root directory
     pub project_root: PathBuf,
+    /// The policy how failed regression checks of this benchmark are treated
+    pub regression_policy: RegressionPolicy,
+    /// The result of checking or updating the snapshot file if snapshots are enabled
+    pub snapshot: Option<SnapshotSummary>,
     /// The destination and kind of the summary file
     pub summary_output: Option<SummaryOutput>,
+    /// The amount of bytes or elements processed by the benchmark if declared
+    pub throughput: Option<Throughput>,
     /// The version of this format. Only backwards incompatible changes cause an increase of the
     /// version
     pub version: String,
 }
 
+/// The result of a limit of this benchmark relative to another benchmark with the same id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ComparisonSummary {
+    /// The `function::id` of the benchmark which is compared against
+    pub baseline: String,
+    /// The `function::id` of the benchmark which is limited
+    pub benchmark: String,
+    /// The detected regressions if any (currently only callgrind)
+    pub regressions: Vec<ToolRegression>,
+}
+
+/// The best fit of a [`Complexity`] model to the metrics of a series of benchmarks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ComplexityFit {
+    /// The fitted complexity class
+    pub class: Complexity,
+    /// The coefficient `c` of the model `c * f(n)`
+    pub coefficient: f64,
+    /// The root mean square error of the fit normalized by the mean of the metrics
+    pub rms: f64,
+}
+
+/// The complexity fits of the `new` and `old` metrics of a benchmark function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ComplexitySummary {
+    /// The fits of the `new` (left) and/or `old` (right) metrics
+    pub fits: EitherOrBoth<ComplexityFit>,
+    /// The metric which was fitted
+    pub metric: EventKind,
+    /// True if the complexity class of the `new` fit differs from the `old` fit
+    pub regressed: bool,
+}
+
+/// The summary of the user-defined metrics of callgrind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CustomMetricsSummary {
+    /// The detected regressions if any
+    pub regressions: Vec<ToolRegression>,
+    /// The summary of the custom metrics by name
+    pub summary: MetricsSummary<String>,
+}
+
 /// The differences between two `Metrics` as percentage and factor
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -228,12 +358,35 @@ pub struct FlamegraphSummary {
     pub regular_path: Option<PathBuf>,
 }
 
+/// The inclusive metrics of all functions matching a glob pattern (currently only callgrind)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FunctionSummary {
+    /// The glob pattern matching the functions
+    pub function: String,
+    /// The detected regressions if any
+    pub regressions: Vec<ToolRegression>,
+    /// The summary of the inclusive metrics of all matching functions
+    pub summary: ToolMetricSummary,
+}
+
 /// The `ToolSummary` containing all information about a valgrind tool run
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Profile {
+    /// The [`CacheModel`] of the cache simulation if the tool was run with a cache model
+    /// (currently only callgrind and cachegrind)
+    pub cache_model: Option<CacheModel>,
+    /// The user-defined metrics calculated from the metrics of the total if configured
+    /// (currently only callgrind)
+    pub custom_metrics: Option<CustomMetricsSummary>,
+    /// The [`CyclesModel`] of the [`EventKind::CustomCycles`] if the tool was run with a cycles
+    /// model (currently only callgrind)
+    pub cycles_model: Option<CyclesModel>,
     /// Details and information about the created flamegraphs if any
     pub flamegraphs: Vec<FlamegraphSummary>,
+    /// The inclusive metrics of the functions matching the configured glob patterns if any
+    pub functions: Vec<FunctionSummary>,
     /// The paths to the `*.log` files. All tools produce at least one log file
     pub log_paths: Vec<PathBuf>,
     /// The paths to the `*.out` files. Not all tools produce an output in addition to the log
@@ -241,6 +394,12 @@ pub struct Profile {
     pub out_paths: Vec<PathBuf>,
     /// The metrics and details about the tool run
     pub summaries: ProfileData,
+    /// The metrics of the selected threads and subprocesses sorted by their costs if configured
+    /// (currently only callgrind)
+    pub threads: Vec<ThreadSummary>,
+    /// The metrics normalized by the [`Throughput`] of the benchmark if declared (currently only
+    /// callgrind)
+    pub throughput: Option<ThroughputSummary>,
     /// The Valgrind tool like `DHAT`, `Memcheck` etc.
     pub tool: ValgrindTool,
 }
@@ -262,6 +421,10 @@ pub struct ProfileData {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, AsRef)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct ProfileInfo {
+    /// The [`CacheState`] of the iterations if the benchmark ran in the warm-cache mode (only
+    /// callgrind)
+    #[as_ref(ignore)]
+    pub cache_state: Option<CacheState>,
     /// The executed command extracted from Valgrind output
     pub command: String,
     /// More details for example from the logging output of the tool run
@@ -274,6 +437,9 @@ pub struct ProfileInfo {
     pub path: PathBuf,
     /// The pid of this process
     pub pid: i32,
+    /// The name of the region if this tool run segment is a region (only callgrind)
+    #[as_ref(ignore)]
+    pub region: Option<String>,
     /// The thread of this tool run (only callgrind)
     pub thread: Option<usize>,
 }
@@ -289,6 +455,8 @@ pub struct ProfilePart {
     pub details: EitherOrBoth<ProfileInfo>,
     /// The [`ToolMetricSummary`]
     pub metrics_summary: ToolMetricSummary,
+    /// The detected regressions if any (currently only regions of callgrind)
+    pub regressions: Vec<ToolRegression>,
 }
 
 /// The total metrics over all [`ProfilePart`]s and if detected any [`ToolRegression`]
@@ -307,6 +475,21 @@ pub struct ProfileTotal {
 #[derive(Default)]
 pub struct Profiles(Vec<Profile>);
 
+/// The result of checking or updating the committed snapshot file of a benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SnapshotSummary {
+    /// The path to the snapshot file
+    pub path: PathBuf,
+    /// The metrics which differ from the snapshot by more than the tolerance
+    pub regressions: Vec<ToolRegression>,
+    /// The [`SnapshotState`]
+    pub state: SnapshotState,
+    /// The metrics (as `tool: metric`) which are present either only in the snapshot or only in
+    /// the benchmark run
+    pub unmatched_metrics: Vec<String>,
+}
+
 /// Manage the summary output file with this `SummaryOutput`
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -317,6 +500,36 @@ pub struct SummaryOutput {
     path: PathBuf,
 }
 
+/// The metrics of a single thread of a process summed up over all parts (currently only
+/// callgrind)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ThreadSummary {
+    /// The executed command of the process
+    pub command: String,
+    /// The pid of the process
+    pub pid: i32,
+    /// The detected regressions if any
+    pub regressions: Vec<ToolRegression>,
+    /// The summary of the metrics of this thread
+    pub summary: ToolMetricSummary,
+    /// The thread number
+    pub thread: usize,
+}
+
+/// The metrics of the total divided by the amount of bytes or elements of the [`Throughput`]
+///
+/// The `old` metrics are normalized with the current [`Throughput`], so changing the amount of
+/// bytes or elements of a benchmark changes the normalized `old` metrics, too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ThroughputSummary {
+    /// The detected regressions if any
+    pub regressions: Vec<ToolRegression>,
+    /// The summary of the normalized metrics
+    pub summary: ToolMetricSummary,
+}
+
 impl Display for BaselineName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.0)
@@ -361,13 +574,19 @@ impl BenchmarkSummary {
             kind,
             benchmark_file: make_absolute(&project_root, benchmark_file),
             benchmark_exe: make_absolute(&project_root, benchmark_exe),
+            comparisons: vec![],
+            complexity: None,
             module_path: module_path.to_string(),
             function_name: function_name.to_owned(),
             id,
             details,
+            parameters: vec![],
             profiles: Profiles::default(),
             summary_output: output,
+            throughput: None,
             project_root,
+            regression_policy: RegressionPolicy::default(),
+            snapshot: None,
             package_dir,
             baselines,
         }
@@ -424,16 +643,29 @@ impl BenchmarkSummary {
     ///
     /// If a regressions is present and are configured to be `fail_fast` an error is returned
     pub fn check_regression(&self, fail_fast: bool) -> Result<()> {
-        if self.profiles.is_regressed() && fail_fast {
+        if self.is_failed() && fail_fast {
             return Err(Error::RegressionError(true).into());
         }
 
         Ok(())
     }
 
-    /// Return true if any [`Profile`] has regressed
+    /// Return true if this benchmark has regressed and the [`RegressionPolicy`] is to enforce the
+    /// regression checks
+    pub fn is_failed(&self) -> bool {
+        self.regression_policy == RegressionPolicy::Enforce && self.is_regressed()
+    }
+
+    /// Return true if any [`Profile`], any comparison with another benchmark, the complexity or
+    /// the snapshot check has regressed
     pub fn is_regressed(&self) -> bool {
         self.profiles.is_regressed()
+            || self.comparisons.iter().any(ComparisonSummary::is_regressed)
+            || self.complexity.as_ref().is_some_and(|c| c.regressed)
+            || self
+                .snapshot
+                .as_ref()
+                .is_some_and(SnapshotSummary::is_regressed)
     }
 
     /// Compare this summary with another and print the result of the comparison
@@ -446,12 +678,16 @@ impl BenchmarkSummary {
         let mut summaries = vec![];
 
         for profile in self.profiles.iter() {
-            if let Some(other_profile) = other.profiles.iter().find(|s| s.tool == profile.tool) {
+            if let Some(other_profile) = other
+                .profiles
+                .iter()
+                .find(|s| s.tool == profile.tool && s.cache_model == profile.cache_model)
+            {
                 if let Some(summary) = ToolMetricSummary::from_self_and_other(
                     &profile.summaries.total.summary,
                     &other_profile.summaries.total.summary,
                 ) {
-                    summaries.push((profile.tool, summary));
+                    summaries.push((profile.tool, profile.cache_model, summary));
                 }
             }
         }
@@ -471,6 +707,41 @@ impl BenchmarkSummary {
     }
 }
 
+impl Display for CacheState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Cold => "cold",
+            Self::Warm => "warm",
+        })
+    }
+}
+
+impl ComparisonSummary {
+    /// Return true if the comparison with the baseline benchmark has regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Constant => "O(1)",
+            Self::Logarithmic => "O(log n)",
+            Self::Linear => "O(n)",
+            Self::Linearithmic => "O(n log n)",
+            Self::Quadratic => "O(n^2)",
+        })
+    }
+}
+
+impl CustomMetricsSummary {
+    /// Return true if the custom metrics have regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
 impl Diffs {
     /// Create a new `Diffs` calculating the percentage and factor from the `new` and `old` metrics
     pub fn new(new: Metric, old: Metric) -> Self {
@@ -493,10 +764,29 @@ impl FlamegraphSummary {
     }
 }
 
+impl FunctionSummary {
+    /// Return true if the metrics of the functions have regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
 impl Profile {
-    /// Return true if one of the summaries has regressed
+    /// Return true if one of the summaries, regions, the metrics of one of the functions or
+    /// threads, the normalized metrics or the custom metrics have regressed
     pub fn is_regressed(&self) -> bool {
         self.summaries.is_regressed()
+            || self.summaries.parts.iter().any(ProfilePart::is_regressed)
+            || self.functions.iter().any(FunctionSummary::is_regressed)
+            || self.threads.iter().any(ThreadSummary::is_regressed)
+            || self
+                .throughput
+                .as_ref()
+                .is_some_and(ThroughputSummary::is_regressed)
+            || self
+                .custom_metrics
+                .as_ref()
+                .is_some_and(CustomMetricsSummary::is_regressed)
     }
 }
 
@@ -516,6 +806,41 @@ impl ProfileData {
         self.parts.len() > 1
     }
 
+    /// Return true if one of the parts is a region
+    pub fn has_regions(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| p.details.iter().any(|i| i.region.is_some()))
+    }
+
+    /// Return true if one of the parts has a [`CacheState`]
+    pub fn has_cache_states(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| p.details.iter().any(|i| i.cache_state.is_some()))
+    }
+
+    /// Create a new `ProfileData` from already paired [`ProfilePart`]s
+    ///
+    /// The total is the sum over all `parts`.
+    pub fn from_parts(parts: Vec<ProfilePart>) -> Self {
+        let mut total: Option<ToolMetricSummary> = None;
+        for part in &parts {
+            match total.as_mut() {
+                Some(total) => total.add_mut(&part.metrics_summary),
+                None => total = Some(part.metrics_summary.clone()),
+            }
+        }
+
+        Self {
+            parts,
+            total: ProfileTotal {
+                summary: total.unwrap_or_default(),
+                regressions: vec![],
+            },
+        }
+    }
+
     /// Used internally to group the output by pid, then by parts and then by threads
     ///
     /// The grouping simplifies the zipping of the new and old parser output later.
@@ -674,18 +999,25 @@ impl ProfileData {
 impl From<ParserOutput> for ProfileInfo {
     fn from(value: ParserOutput) -> Self {
         Self {
+            cache_state: None,
             command: value.header.command,
             pid: value.header.pid,
             parent_pid: value.header.parent_pid,
             details: (!value.details.is_empty()).then(|| value.details.join("\n")),
             path: value.path,
             part: value.header.part,
+            region: None,
             thread: value.header.thread,
         }
     }
 }
 
 impl ProfilePart {
+    /// Return true if this part has regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+
     /// Return true if an error checking valgrind tool (like `Memcheck`) has errors detected
     pub fn new_has_errors(&self) -> bool {
         match &self.metrics_summary {
@@ -705,6 +1037,7 @@ impl ProfilePart {
         Self {
             details: EitherOrBoth::Left(new.into()),
             metrics_summary,
+            regressions: vec![],
         }
     }
 
@@ -714,6 +1047,7 @@ impl ProfilePart {
         Self {
             details: EitherOrBoth::Right(old.into()),
             metrics_summary,
+            regressions: vec![],
         }
     }
 
@@ -730,6 +1064,7 @@ impl ProfilePart {
         Self {
             details: EitherOrBoth::Both(new.into(), old.into()),
             metrics_summary,
+            regressions: vec![],
         }
     }
 }
@@ -782,6 +1117,16 @@ impl IntoIterator for Profiles {
     }
 }
 
+impl SnapshotSummary {
+    /// Return true if the metrics don't match the snapshot or the snapshot is missing
+    pub fn is_regressed(&self) -> bool {
+        matches!(
+            self.state,
+            SnapshotState::Mismatched | SnapshotState::Missing
+        )
+    }
+}
+
 impl SummaryOutput {
     /// Create a new `SummaryOutput` with `dir` as base dir and an extension fitting the
     /// [`SummaryFormat`]
@@ -815,6 +1160,20 @@ impl SummaryOutput {
     }
 }
 
+impl ThreadSummary {
+    /// Return true if the metrics of the thread have regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
+impl ThroughputSummary {
+    /// Return true if the normalized metrics have regressed
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
 impl ToolMetricSummary {
     /// Sum up another summary metrics to these metrics
     pub fn add_mut(&mut self, other: &Self) {
@@ -1008,6 +1367,15 @@ impl ToolRegression {
                 diff,
                 limit,
             },
+            RegressionMetrics::Improvement(metric, new, old, diff_pct, expected) => {
+                Self::Improvement {
+                    metric: apply(metric),
+                    new,
+                    old,
+                    diff_pct,
+                    expected,
+                }
+            }
         }
     }
 }
@@ -6,25 +6,43 @@ use std::io::stderr;
 use std::path::Path;
 
 use anyhow::{anyhow, Result};
+use either_or_both::EitherOrBoth;
 
 use super::args::ToolArgs;
+use super::fingerprint::{self, Fingerprint};
 use super::parser::{parser_factory, ParserOutput};
 use super::path::ToolOutputPath;
 use super::regression::{RegressionConfig, ToolRegressionConfig};
 use super::run::{RunOptions, ToolCommand};
-use crate::api::{self, EntryPoint, RawArgs, Tool, Tools, ValgrindTool};
+use crate::api::{
+    self, CacheModel, CyclesModel, EntryPoint, EventKind, RawArgs, Throughput, Tool, Tools,
+    ValgrindTool,
+};
 use crate::runner::args::NoCapture;
+use crate::runner::callgrind::call_graph::{CallGraph, Config as CallGraphConfig};
+use crate::runner::callgrind::custom_metrics::Config as CustomMetricsConfig;
 use crate::runner::callgrind::flamegraph::{
     BaselineFlamegraphGenerator, Config as FlamegraphConfig, Flamegraph, FlamegraphGenerator,
     LoadBaselineFlamegraphGenerator, SaveBaselineFlamegraphGenerator,
 };
+use crate::runner::callgrind::function_metrics::{self, Config as FunctionMetricsConfig};
+use crate::runner::callgrind::hashmap_parser::CallgrindMap;
+use crate::runner::callgrind::hot_instructions::{HotInstructions, HotInstructionsParser};
+use crate::runner::callgrind::hot_lines::{HotLines, HotLinesParser, LinesMap};
+use crate::runner::callgrind::noise::{self, Noise};
 use crate::runner::callgrind::parser::Sentinel;
+use crate::runner::callgrind::regions::{self, Region};
+use crate::runner::callgrind::threads::Config as ThreadsConfig;
+use crate::runner::callgrind::throughput::Config as ThroughputConfig;
+use crate::runner::callgrind::warm_cache::WarmCache;
 use crate::runner::common::{Baselines, Config, ModulePath, Sandbox};
+use crate::runner::config_file::{Settings, ToolDefaults};
 use crate::runner::format::{print_no_capture_footer, Formatter, OutputFormat, VerticalFormatter};
 use crate::runner::meta::Metadata;
 use crate::runner::summary::{
-    BaselineKind, BaselineName, BenchmarkSummary, Profile, ProfileData, ProfileTotal,
-    ToolMetricSummary, ToolRegression,
+    BaselineKind, BaselineName, BenchmarkSummary, CustomMetricsSummary, FunctionSummary, Profile,
+    ProfileData, ProfileTotal, Profiles, ThreadSummary, ThroughputSummary, ToolMetricSummary,
+    ToolRegression,
 };
 use crate::runner::{cachegrind, callgrind, DEFAULT_TOGGLE};
 use crate::util::Glob;
@@ -43,32 +61,72 @@ pub enum ToolFlamegraphConfig {
 pub struct ToolConfig {
     /// The arguments to pass to the valgrind executable
     pub args: ToolArgs,
+    /// The [`CacheModel`] of the cache simulation (currently only callgrind and cachegrind)
+    pub cache_model: Option<CacheModel>,
+    /// The configuration of the call graph export (currently only callgrind)
+    pub call_graph_config: Option<CallGraphConfig>,
+    /// The metric to fit the complexity of benchmarks with input sizes (currently only callgrind)
+    pub complexity_metric: Option<EventKind>,
+    /// The configuration of the user-defined metrics (currently only callgrind)
+    pub custom_metrics: Option<CustomMetricsConfig>,
+    /// The [`CyclesModel`] of the custom cycles estimation (currently only callgrind)
+    pub cycles_model: Option<CyclesModel>,
     /// The [`EntryPoint`] of this tool
     pub entry_point: EntryPoint,
     /// The tool specific flamegraph configuration
     pub flamegraph_config: ToolFlamegraphConfig,
     /// The [`Glob`] patterns used to matched a function in the call stack of a program point
     pub frames: Vec<Glob>,
+    /// The configuration of the inclusive metrics of functions (currently only callgrind)
+    pub function_metrics: Option<FunctionMetricsConfig>,
+    /// The amount of the hottest instructions to show (currently only callgrind)
+    pub hot_instructions: Option<usize>,
+    /// The amount of the hottest source lines to show (currently only callgrind)
+    pub hot_lines: Option<usize>,
     /// If true, this tool is the default tool for the benchmark run
     pub is_default: bool,
     /// If true, this tool is enabled for this benchmark
     pub is_enabled: bool,
+    /// The named regions which are reported separately (currently only callgrind)
+    pub regions: Vec<Region>,
     /// The tool specific regression check configuration
     pub regression_config: ToolRegressionConfig,
+    /// The amount of runs to measure the noise of the metrics if greater than `1` (currently
+    /// only callgrind)
+    pub repeat: Option<usize>,
+    /// The selection and breakdown of the threads and subprocesses (currently only callgrind)
+    pub threads_config: Option<ThreadsConfig>,
+    /// The configuration of the metrics normalized by the [`Throughput`] (currently only
+    /// callgrind)
+    pub throughput_config: Option<ThroughputConfig>,
     /// The [`ValgrindTool`]
     pub tool: ValgrindTool,
+    /// The configuration of the warm-cache mode (currently only callgrind in library benchmarks)
+    pub warm_cache: Option<WarmCache>,
 }
 
 #[derive(Debug)]
 struct ToolConfigBuilder {
+    cache_model: Option<CacheModel>,
+    call_graph_config: Option<CallGraphConfig>,
+    complexity_metric: Option<EventKind>,
+    custom_metrics: Option<CustomMetricsConfig>,
+    cycles_model: Option<CyclesModel>,
     entry_point: Option<EntryPoint>,
     flamegraph_config: ToolFlamegraphConfig,
     frames: Vec<String>,
+    function_metrics: Option<FunctionMetricsConfig>,
+    hot_instructions: Option<usize>,
+    hot_lines: Option<usize>,
     is_default: bool,
     is_enabled: bool,
     kind: ValgrindTool,
     raw_args: RawArgs,
+    regions: Vec<Region>,
     regression_config: ToolRegressionConfig,
+    repeat: Option<usize>,
+    threads_config: Option<ThreadsConfig>,
+    throughput_config: Option<ThroughputConfig>,
     tool: Option<Tool>,
 }
 
@@ -77,26 +135,44 @@ struct ToolConfigBuilder {
 pub struct ToolConfigs(pub Vec<ToolConfig>);
 
 impl ToolConfig {
-    /// Create a new `ToolConfig`
-    pub fn new(
-        tool: ValgrindTool,
-        is_enabled: bool,
-        args: ToolArgs,
-        regression_config: ToolRegressionConfig,
-        flamegraph_config: ToolFlamegraphConfig,
-        entry_point: EntryPoint,
-        is_default: bool,
-        frames: Vec<Glob>,
-    ) -> Self {
-        Self {
-            args,
-            entry_point,
-            flamegraph_config,
-            frames,
-            is_default,
-            is_enabled,
-            regression_config,
-            tool,
+    /// Create the call graph files if configured
+    pub fn create_call_graph(&self, meta: &Metadata, output_path: &ToolOutputPath) -> Result<()> {
+        if let Some(config) = &self.call_graph_config {
+            let sentinel = match &self.entry_point {
+                EntryPoint::None => None,
+                EntryPoint::Default => Some(Sentinel::default()),
+                EntryPoint::Custom(custom) => Some(Sentinel::new(custom.as_str())),
+            };
+            CallGraph::new(config.clone()).create(
+                output_path,
+                sentinel.as_ref(),
+                &meta.project_root,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the arguments for the benchmark executable of this tool run
+    ///
+    /// In the warm-cache mode, the benchmark harness is instructed to run all iterations.
+    pub fn executable_args(&self, executable_args: &[OsString]) -> Vec<OsString> {
+        let mut args = executable_args.to_vec();
+        if let Some(warm_cache) = &self.warm_cache {
+            args.push(warm_cache.to_executable_arg());
+        }
+        args
+    }
+
+    /// Return the [`ToolOutputPath`] of this tool
+    ///
+    /// The output files of a tool run with a [`CacheModel`] are stored in a separate directory per
+    /// cache model.
+    pub fn output_path(&self, output_path: &ToolOutputPath) -> ToolOutputPath {
+        let output_path = output_path.to_tool_output(self.tool);
+        match self.cache_model {
+            Some(cache_model) => output_path.to_cache_model_output(cache_model),
+            None => output_path,
         }
     }
 
@@ -109,28 +185,120 @@ impl ToolConfig {
     ) -> Result<Profile> {
         let parser = parser_factory(self, meta.project_root.clone(), output_path);
 
-        let parsed_new = parser.parse()?;
-        let parsed_old = if let Some(parsed_old) = parsed_old {
+        let parsed_new = self.filter_parsed(parser.parse()?);
+        let parsed_old = self.filter_parsed(if let Some(parsed_old) = parsed_old {
             parsed_old
         } else {
             parser.parse_base()?
-        };
-
-        let data = match (parsed_new.is_empty(), parsed_old.is_empty()) {
-            (true, false | true) => return Err(anyhow!("A new dataset should always be present")),
-            (false, true) => ProfileData::new(parsed_new, None),
-            (false, false) => ProfileData::new(parsed_new, Some(parsed_old)),
+        });
+
+        let threads = self
+            .threads_config
+            .as_ref()
+            .map(|config| config.summarize(&parsed_new, &parsed_old))
+            .unwrap_or_default();
+
+        let data = match (
+            parsed_new.is_empty(),
+            parsed_old.is_empty(),
+            self.warm_cache,
+        ) {
+            (true, _, _) if self.threads_config.is_some() => {
+                return Err(anyhow!(
+                    "None of the processes or threads matches the selected commands and threads"
+                ))
+            }
+            (true, _, _) => return Err(anyhow!("A new dataset should always be present")),
+            (false, _, Some(warm_cache)) => warm_cache.profile_data(parsed_new, parsed_old),
+            (false, _, None) if !self.regions.is_empty() => {
+                regions::profile_data(&self.regions, parsed_new, parsed_old)
+            }
+            (false, true, None) => ProfileData::new(parsed_new, None),
+            (false, false, None) => ProfileData::new(parsed_new, Some(parsed_old)),
         };
 
         Ok(Profile {
+            cache_model: self.cache_model,
+            custom_metrics: None,
+            cycles_model: self.cycles_model,
             tool: self.tool,
             log_paths: output_path.to_log_output().real_paths()?,
             out_paths: output_path.real_paths()?,
             summaries: data,
             flamegraphs: vec![],
+            functions: vec![],
+            threads,
+            throughput: None,
         })
     }
 
+    /// Remove the parsed outputs of the processes and threads which are not selected
+    ///
+    /// Returns the `parsed` outputs unchanged if there's no selection configured.
+    pub fn filter_parsed(&self, parsed: Vec<ParserOutput>) -> Vec<ParserOutput> {
+        match &self.threads_config {
+            Some(config) => config.filter(parsed),
+            None => parsed,
+        }
+    }
+
+    /// Parse all callgrind output files into a single [`CallgrindMap`] if function metrics are
+    /// configured
+    ///
+    /// Returns `None` if function metrics are not configured for this tool or there weren't any
+    /// output files present.
+    fn parse_functions(
+        &self,
+        meta: &Metadata,
+        output_path: &ToolOutputPath,
+    ) -> Result<Option<CallgrindMap>> {
+        if self.function_metrics.is_some() {
+            function_metrics::parse_total(output_path, &meta.project_root)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse the source lines of all callgrind output files if hot lines are configured
+    ///
+    /// Returns `None` if hot lines are not configured for this tool or there weren't any output
+    /// files present.
+    fn parse_lines(
+        &self,
+        meta: &Metadata,
+        output_path: &ToolOutputPath,
+    ) -> Result<Option<LinesMap>> {
+        if self.tool == ValgrindTool::Callgrind && self.hot_lines.is_some() {
+            HotLinesParser {
+                project_root: meta.project_root.clone(),
+            }
+            .parse_total(output_path)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return the regression check configuration with the soft limits widened to the [`Noise`] of
+    /// the new run and the baseline (currently only callgrind)
+    fn regression_config_with_noise(
+        &self,
+        output_path: &ToolOutputPath,
+    ) -> Result<ToolRegressionConfig> {
+        if let ToolRegressionConfig::Callgrind(regression_config) = &self.regression_config {
+            let tolerances = noise::tolerances(
+                Noise::load(output_path)?.as_ref(),
+                Noise::load(&output_path.to_base_path())?.as_ref(),
+            );
+            if !tolerances.is_empty() {
+                return Ok(ToolRegressionConfig::Callgrind(
+                    regression_config.with_tolerances(&tolerances),
+                ));
+            }
+        }
+
+        Ok(self.regression_config.clone())
+    }
+
     fn print(
         &self,
         config: &Config,
@@ -146,6 +314,104 @@ impl ToolConfig {
             self.is_default,
         )
     }
+
+    fn print_functions(
+        &self,
+        meta: &Metadata,
+        output_format: &OutputFormat,
+        output_path: &ToolOutputPath,
+        old_map: Option<&CallgrindMap>,
+    ) -> Result<Vec<FunctionSummary>> {
+        let mut functions = vec![];
+        if let Some(config) = &self.function_metrics {
+            if let Some(new_map) = self.parse_functions(meta, output_path)? {
+                functions = config.summarize(&new_map, old_map);
+                VerticalFormatter::new(output_format.clone()).print_functions(&functions);
+            }
+        }
+
+        Ok(functions)
+    }
+
+    fn print_hot_instructions(
+        &self,
+        output_format: &OutputFormat,
+        output_path: &ToolOutputPath,
+        executable: &Path,
+    ) -> Result<()> {
+        if let Some(amount) = self.hot_instructions {
+            if let Some(instructions) = HotInstructionsParser.parse_total(output_path)? {
+                VerticalFormatter::new(output_format.clone()).print_hot_instructions(
+                    &HotInstructions::new(&instructions, amount, executable),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_hot_lines(
+        &self,
+        meta: &Metadata,
+        output_format: &OutputFormat,
+        output_path: &ToolOutputPath,
+        old_lines: Option<&LinesMap>,
+    ) -> Result<()> {
+        if let Some(amount) = self.hot_lines {
+            if let Some(new_lines) = self.parse_lines(meta, output_path)? {
+                let lines = match old_lines {
+                    Some(old_lines) => EitherOrBoth::Both(&new_lines, old_lines),
+                    None => EitherOrBoth::Left(&new_lines),
+                };
+                VerticalFormatter::new(output_format.clone()).print_hot_lines(&HotLines::new(
+                    lines,
+                    amount,
+                    &meta.project_root,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_custom_metrics(
+        &self,
+        output_format: &OutputFormat,
+        total: &ProfileTotal,
+    ) -> Option<CustomMetricsSummary> {
+        let (Some(config), ToolMetricSummary::Callgrind(metrics_summary)) =
+            (&self.custom_metrics, &total.summary)
+        else {
+            return None;
+        };
+
+        let summary = config.summarize(metrics_summary)?;
+        VerticalFormatter::new(output_format.clone()).print_custom_metrics(&summary);
+        Some(summary)
+    }
+
+    fn print_threads(&self, output_format: &OutputFormat, threads: &[ThreadSummary]) {
+        if let Some(amount) = self.threads_config.as_ref().and_then(|c| c.show) {
+            VerticalFormatter::new(output_format.clone()).print_threads(threads, amount);
+        }
+    }
+
+    fn print_throughput(
+        &self,
+        output_format: &OutputFormat,
+        throughput: Option<Throughput>,
+        total: &ProfileTotal,
+    ) -> Option<ThroughputSummary> {
+        let (Some(config), Some(throughput), ToolMetricSummary::Callgrind(metrics_summary)) =
+            (&self.throughput_config, throughput, &total.summary)
+        else {
+            return None;
+        };
+
+        let summary = config.summarize(throughput, metrics_summary)?;
+        VerticalFormatter::new(output_format.clone()).print_throughput(throughput, &summary);
+        Some(summary)
+    }
 }
 
 impl ToolConfigBuilder {
@@ -160,16 +426,170 @@ impl ToolConfigBuilder {
             _ => ToolArgs::try_from_raw_args(self.kind, &[&self.raw_args])?,
         };
 
-        Ok(ToolConfig::new(
-            self.kind,
-            self.is_enabled,
+        Ok(ToolConfig {
             args,
-            self.regression_config,
-            self.flamegraph_config,
-            self.entry_point.unwrap_or(EntryPoint::None),
-            self.is_default,
-            self.frames.iter().map(Into::into).collect(),
-        ))
+            cache_model: self.cache_model,
+            call_graph_config: self.call_graph_config,
+            complexity_metric: self.complexity_metric,
+            custom_metrics: self.custom_metrics,
+            cycles_model: self.cycles_model,
+            entry_point: self.entry_point.unwrap_or(EntryPoint::None),
+            flamegraph_config: self.flamegraph_config,
+            frames: self.frames.iter().map(Into::into).collect(),
+            function_metrics: self.function_metrics,
+            hot_instructions: self.hot_instructions,
+            hot_lines: self.hot_lines,
+            is_default: self.is_default,
+            is_enabled: self.is_enabled,
+            regions: self.regions,
+            regression_config: self.regression_config,
+            repeat: self.repeat,
+            threads_config: self.threads_config,
+            throughput_config: self.throughput_config,
+            tool: self.kind,
+            warm_cache: None,
+        })
+    }
+
+    /// Apply the command-line arguments of the [`CacheModel`] if present
+    ///
+    /// The cache model takes precedence over any `--I1`, `--D1` and `--LL` arguments. The cache
+    /// simulation is switched on since it is off by default in cachegrind.
+    fn cache_model(&mut self) {
+        if let Some(cache_model) = self.cache_model {
+            self.raw_args.extend_ignore_flag([
+                format!("--I1={}", cache_model.i1()),
+                format!("--D1={}", cache_model.d1()),
+                format!("--LL={}", cache_model.ll()),
+                "--cache-sim=yes".to_owned(),
+            ]);
+        }
+    }
+
+    /// Return the [`CacheModel`]s the tool of `kind` is run with
+    ///
+    /// The cache models of the command-line take precedence over the cache models of the `tool`.
+    /// Duplicate cache models are only run once. If there are no cache models or the tool doesn't
+    /// simulate caches, the tool runs once without a cache model.
+    fn cache_models(
+        kind: ValgrindTool,
+        tool: Option<&Tool>,
+        meta: &Metadata,
+    ) -> Vec<Option<CacheModel>> {
+        let mut cache_models = vec![];
+        if matches!(kind, ValgrindTool::Callgrind | ValgrindTool::Cachegrind) {
+            let models = if meta.args.cache_models.is_empty() {
+                tool.and_then(|t| t.cache_models.as_deref())
+                    .unwrap_or_default()
+            } else {
+                meta.args.cache_models.as_slice()
+            };
+            for model in models {
+                if !cache_models.contains(&Some(*model)) {
+                    cache_models.push(Some(*model));
+                }
+            }
+        }
+
+        if cache_models.is_empty() {
+            cache_models.push(None);
+        }
+        cache_models
+    }
+
+    fn call_graph_config(&mut self) {
+        if self.kind == ValgrindTool::Callgrind {
+            if let Some(config) = self.tool.as_ref().and_then(|t| t.call_graph_config.clone()) {
+                self.call_graph_config = Some(config.into());
+            }
+        }
+    }
+
+    fn function_metrics(&mut self) -> Result<()> {
+        if self.kind == ValgrindTool::Callgrind {
+            if let Some(config) = self
+                .tool
+                .as_ref()
+                .and_then(|t| t.function_metrics.clone())
+                .filter(|c| !c.functions.is_empty())
+            {
+                self.function_metrics = Some(config.try_into().map_err(|error| {
+                    anyhow!("Invalid function metrics limits for {}: {error}", self.kind)
+                })?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the tool arguments of the configuration file
+    ///
+    /// The arguments are applied before the tool arguments of the benchmark, so the tool arguments
+    /// of the benchmark take precedence.
+    fn config_file_args(&mut self, defaults: Option<&ToolDefaults>) {
+        if let Some(defaults) = defaults {
+            self.raw_args.update(&defaults.args);
+        }
+    }
+
+    /// Set the metric to fit the complexity of the benchmark (currently only callgrind)
+    ///
+    /// The metric defaults to [`EventKind::Ir`] if the tool doesn't specify one.
+    fn complexity_metric(&mut self) {
+        if self.kind == ValgrindTool::Callgrind {
+            self.complexity_metric = Some(
+                self.tool
+                    .as_ref()
+                    .and_then(|t| t.complexity_metric)
+                    .unwrap_or(EventKind::Ir),
+            );
+        }
+    }
+
+    /// Merge the custom metrics of the `tool` with the custom metrics of the command-line
+    ///
+    /// A custom metric of the command-line takes precedence over a custom metric of the `tool`
+    /// with the same name (currently only callgrind).
+    fn custom_metrics(&mut self, meta: &Metadata) -> Result<()> {
+        if self.kind == ValgrindTool::Callgrind {
+            let mut config = self
+                .tool
+                .as_ref()
+                .and_then(|t| t.custom_metrics.clone())
+                .unwrap_or_default();
+            if let Some(metrics) = &meta.args.callgrind_custom_metrics {
+                config.metrics.extend(metrics.clone());
+            }
+
+            if !config.metrics.is_empty() {
+                self.custom_metrics = Some(config.try_into().map_err(|error| {
+                    anyhow!("Invalid custom metrics for {}: {error}", self.kind)
+                })?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the [`CyclesModel`] of the command-line or the `tool` (currently only callgrind)
+    ///
+    /// The custom cycles are calculated from the metrics of the cache simulation and the branch
+    /// simulation if the model has penalties for branch mispredictions, so the simulations are
+    /// switched on.
+    fn cycles_model(&mut self, meta: &Metadata) {
+        if self.kind == ValgrindTool::Callgrind {
+            self.cycles_model = meta
+                .args
+                .cycles_model
+                .or_else(|| self.tool.as_ref().and_then(|t| t.cycles_model));
+
+            if let Some(cycles_model) = self.cycles_model {
+                self.raw_args.extend_ignore_flag(["--cache-sim=yes"]);
+                if cycles_model.has_branch_penalties() {
+                    self.raw_args.extend_ignore_flag(["--branch-sim=yes"]);
+                }
+            }
+        }
     }
 
     /// Build the entry point
@@ -260,6 +680,35 @@ impl ToolConfigBuilder {
                 self.flamegraph_config = flamegraph_config.clone().into();
             }
         }
+
+        if let ToolFlamegraphConfig::Callgrind(config) = &mut self.flamegraph_config {
+            config.cycles_model = self.cycles_model;
+        }
+    }
+
+    fn hot_instructions(&mut self, meta: &Metadata) {
+        if self.kind == ValgrindTool::Callgrind {
+            self.hot_instructions = meta
+                .args
+                .callgrind_hot_instructions
+                .or_else(|| self.tool.as_ref().and_then(|t| t.hot_instructions))
+                .filter(|amount| *amount > 0);
+
+            // The costs of the instructions are only available with `--dump-instr=yes`
+            if self.hot_instructions.is_some() {
+                self.raw_args.extend_ignore_flag(["--dump-instr=yes"]);
+            }
+        }
+    }
+
+    fn hot_lines(&mut self, meta: &Metadata) {
+        if self.kind == ValgrindTool::Callgrind {
+            self.hot_lines = meta
+                .args
+                .callgrind_hot_lines
+                .or_else(|| self.tool.as_ref().and_then(|t| t.hot_lines))
+                .filter(|amount| *amount > 0);
+        }
     }
 
     fn meta_args(&mut self, meta: &Metadata) {
@@ -282,6 +731,7 @@ impl ToolConfigBuilder {
     fn new(
         valgrind_tool: ValgrindTool,
         tool: Option<Tool>,
+        defaults: Option<&ToolDefaults>,
         is_default: bool,
         default_args: &HashMap<ValgrindTool, RawArgs>,
         module_path: &ModulePath,
@@ -289,19 +739,32 @@ impl ToolConfigBuilder {
         meta: &Metadata,
         valgrind_args: &RawArgs,
         default_entry_point: &EntryPoint,
+        cache_model: Option<CacheModel>,
     ) -> Result<Self> {
         let mut builder = Self {
             is_enabled: is_default || tool.as_ref().map_or(true, |t| t.enable.unwrap_or(true)),
             tool,
+            cache_model,
+            call_graph_config: None,
+            complexity_metric: None,
+            custom_metrics: None,
+            cycles_model: None,
             entry_point: Option::default(),
             flamegraph_config: ToolFlamegraphConfig::None,
             frames: Vec::default(),
+            function_metrics: None,
+            hot_instructions: None,
+            hot_lines: None,
             is_default,
+            regions: Vec::default(),
             raw_args: default_args
                 .get(&valgrind_tool)
                 .cloned()
                 .unwrap_or_default(),
             regression_config: ToolRegressionConfig::None,
+            repeat: None,
+            threads_config: None,
+            throughput_config: None,
             kind: valgrind_tool,
         };
 
@@ -309,15 +772,48 @@ impl ToolConfigBuilder {
         // `ToolConfig` can happen here in one go instead of having a separate director for it.
         builder.valgrind_args(valgrind_args);
         builder.entry_point(default_entry_point, module_path, id);
+        builder.config_file_args(defaults);
         builder.tool_args();
         builder.meta_args(meta);
+        builder.cache_model();
+        builder.cycles_model(meta);
         builder.flamegraph_config();
-        builder.regression_config(meta)?;
+        builder.call_graph_config();
+        builder.function_metrics()?;
+        builder.hot_instructions(meta);
+        builder.hot_lines(meta);
+        builder.repeat(meta);
+        builder.regions()?;
+        builder.threads_config()?;
+        builder.complexity_metric();
+        builder.throughput_config()?;
+        builder.custom_metrics(meta)?;
+        builder.regression_config(meta, defaults)?;
 
         Ok(builder)
     }
 
-    fn regression_config(&mut self, meta: &Metadata) -> Result<()> {
+    fn regions(&mut self) -> Result<()> {
+        if self.kind == ValgrindTool::Callgrind {
+            if let Some(regions) = self.tool.as_ref().and_then(|t| t.regions.clone()) {
+                for region in regions {
+                    let region = Region::try_from(region).map_err(|error| {
+                        anyhow!("Invalid region limits for {}: {error}", self.kind)
+                    })?;
+                    self.raw_args.extend_ignore_flag(region.to_args());
+                    self.regions.push(region);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn regression_config(
+        &mut self,
+        meta: &Metadata,
+        defaults: Option<&ToolDefaults>,
+    ) -> Result<()> {
         let meta_limits = match self.kind {
             ValgrindTool::Callgrind => meta.args.callgrind_limits.clone(),
             ValgrindTool::Cachegrind => meta.args.cachegrind_limits.clone(),
@@ -325,15 +821,20 @@ impl ToolConfigBuilder {
             _ => None,
         };
 
+        let file_limits = defaults.and_then(|d| d.limits.clone());
+
         let mut regression_config = if let Some(tool) = &self.tool {
             meta_limits
                 .map(Ok)
                 .or_else(|| tool.regression_config.clone().map(TryInto::try_into))
                 .transpose()
                 .map_err(|error| anyhow!("Invalid limits for {}: {error}", self.kind))?
+                .or(file_limits)
                 .unwrap_or(ToolRegressionConfig::None)
         } else {
-            meta_limits.unwrap_or(ToolRegressionConfig::None)
+            meta_limits
+                .or(file_limits)
+                .unwrap_or(ToolRegressionConfig::None)
         };
 
         if let Some(fail_fast) = meta.args.regression_fail_fast {
@@ -356,6 +857,46 @@ impl ToolConfigBuilder {
         Ok(())
     }
 
+    fn repeat(&mut self, meta: &Metadata) {
+        if self.kind == ValgrindTool::Callgrind {
+            self.repeat = meta
+                .args
+                .repeat
+                .or_else(|| self.tool.as_ref().and_then(|t| t.repeat))
+                .filter(|runs| *runs > 1);
+        }
+    }
+
+    fn threads_config(&mut self) -> Result<()> {
+        if self.kind == ValgrindTool::Callgrind {
+            if let Some(threads_config) = self.tool.as_ref().and_then(|t| t.threads_config.clone())
+            {
+                let threads_config = ThreadsConfig::try_from(threads_config)
+                    .map_err(|error| anyhow!("Invalid thread limits for {}: {error}", self.kind))?;
+                self.raw_args.extend_ignore_flag(threads_config.to_args());
+                self.threads_config = Some(threads_config);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn throughput_config(&mut self) -> Result<()> {
+        if self.kind == ValgrindTool::Callgrind {
+            let throughput_config =
+                match self.tool.as_ref().and_then(|t| t.throughput_config.clone()) {
+                    Some(throughput_config) => ThroughputConfig::try_from(throughput_config)
+                        .map_err(|error| {
+                            anyhow!("Invalid throughput limits for {}: {error}", self.kind)
+                        })?,
+                    None => ThroughputConfig::default(),
+                };
+            self.throughput_config = Some(throughput_config);
+        }
+
+        Ok(())
+    }
+
     fn tool_args(&mut self) {
         if let Some(tool) = self.tool.as_ref() {
             self.raw_args.update(&tool.raw_args);
@@ -381,6 +922,9 @@ impl ToolConfigs {
     /// `valgrind_args` are from the in-benchmark configuration: `LibraryBenchmarkConfig` or
     /// `BinaryBenchmarkConfig`
     ///
+    /// The tool specific defaults of the configuration files in `settings` are the lowest level
+    /// and overridden by the configuration of the tools in the benchmark files.
+    ///
     /// # Errors
     ///
     /// This function will return an error if the configs cannot be created
@@ -394,22 +938,46 @@ impl ToolConfigs {
         default_entry_point: &EntryPoint,
         valgrind_args: &RawArgs,
         default_args: &HashMap<ValgrindTool, RawArgs>,
+        settings: &Settings,
     ) -> Result<Self> {
+        for format in settings
+            .tool_defaults
+            .values()
+            .filter_map(|d| d.metrics.as_ref())
+        {
+            output_format.update_metrics(format);
+        }
+
         let extracted_tool = tools.consume(default_tool);
 
         output_format.update(extracted_tool.as_ref());
-        let default_tool_config = ToolConfigBuilder::new(
-            default_tool,
-            extracted_tool,
-            true,
-            default_args,
-            module_path,
-            id,
-            meta,
-            valgrind_args,
-            default_entry_point,
-        )?
-        .build()?;
+
+        // The default tool runs once per cache model. Only the first run is treated as the run of
+        // the default tool (for example to apply the `--nocapture` option) but all runs are
+        // enabled.
+        let mut tool_configs = Self(vec![]);
+        for (index, cache_model) in
+            ToolConfigBuilder::cache_models(default_tool, extracted_tool.as_ref(), meta)
+                .into_iter()
+                .enumerate()
+        {
+            let mut tool_config = ToolConfigBuilder::new(
+                default_tool,
+                extracted_tool.clone(),
+                settings.tool(default_tool),
+                index == 0,
+                default_args,
+                module_path,
+                id,
+                meta,
+                valgrind_args,
+                default_entry_point,
+                cache_model,
+            )?
+            .build()?;
+            tool_config.is_enabled = true;
+            tool_configs.0.push(tool_config);
+        }
 
         // The tool selection from the command line or env args overwrites the tool selection from
         // the benchmark file. However, any tool configurations from the benchmark files are
@@ -428,28 +996,87 @@ impl ToolConfigs {
             meta_tools
         };
 
-        let mut tool_configs = Self(vec![default_tool_config]);
-        tool_configs.extend(meta_tools.into_iter().map(|tool| {
+        tool_configs.extend(meta_tools.into_iter().flat_map(|tool| {
             output_format.update(Some(&tool));
 
-            ToolConfigBuilder::new(
-                tool.kind,
-                Some(tool),
-                false,
-                default_args,
-                module_path,
-                id,
-                meta,
-                valgrind_args,
-                default_entry_point,
-            )?
-            .build()
+            let defaults = settings.tool(tool.kind);
+            ToolConfigBuilder::cache_models(tool.kind, Some(&tool), meta)
+                .into_iter()
+                .map(move |cache_model| {
+                    ToolConfigBuilder::new(
+                        tool.kind,
+                        Some(tool.clone()),
+                        defaults,
+                        false,
+                        default_args,
+                        module_path,
+                        id,
+                        meta,
+                        valgrind_args,
+                        default_entry_point,
+                        cache_model,
+                    )?
+                    .build()
+                })
         }))?;
 
         output_format.update_from_meta(meta);
         Ok(tool_configs)
     }
 
+    /// Remove the regression check configurations of all tools
+    ///
+    /// This includes the limits of the regions, function metrics, throughput and custom metrics,
+    /// so no regression checks are performed at all, for example if the
+    /// [`api::RegressionPolicy`] is `Ignore`.
+    pub fn clear_regression_configs(&mut self) {
+        for tool_config in &mut self.0 {
+            tool_config.regression_config = ToolRegressionConfig::None;
+            if let Some(function_metrics) = &mut tool_config.function_metrics {
+                function_metrics.regression_config = None;
+            }
+            for region in &mut tool_config.regions {
+                region.regression_config = None;
+            }
+            if let Some(threads_config) = &mut tool_config.threads_config {
+                threads_config.regression_config = None;
+            }
+            if let Some(throughput_config) = &mut tool_config.throughput_config {
+                throughput_config.regression_config = None;
+            }
+            if let Some(custom_metrics) = &mut tool_config.custom_metrics {
+                custom_metrics.regression_config = None;
+            }
+        }
+    }
+
+    /// Run the callgrind benchmarks in the warm-cache mode
+    ///
+    /// Callgrind dumps the costs after each iteration of the benchmark function. The other tools
+    /// run the benchmark function only once.
+    ///
+    /// # Errors
+    ///
+    /// If callgrind is configured with regions, since the regions can't be reported separately
+    /// from the iterations
+    pub fn warm_cache(&mut self, warm_cache: WarmCache) -> Result<()> {
+        for tool_config in self
+            .0
+            .iter_mut()
+            .filter(|t| t.tool == ValgrindTool::Callgrind)
+        {
+            if !tool_config.regions.is_empty() {
+                return Err(anyhow!(
+                    "Callgrind regions can't be combined with warmup or iterations"
+                ));
+            }
+            tool_config.args.other.extend(warm_cache.to_args());
+            tool_config.warm_cache = Some(warm_cache);
+        }
+
+        Ok(())
+    }
+
     /// Return true if there are any [`Tool`]s enabled
     pub fn has_tools_enabled(&self) -> bool {
         self.0.iter().any(|t| t.is_enabled)
@@ -465,7 +1092,7 @@ impl ToolConfigs {
         self.0
             .iter()
             .filter(|t| t.is_enabled)
-            .map(|t| output_path.to_tool_output(t.tool))
+            .map(|t| t.output_path(output_path))
             .collect()
     }
 
@@ -484,14 +1111,34 @@ impl ToolConfigs {
     fn print_headline(&self, tool_config: &ToolConfig, output_format: &OutputFormat) {
         if output_format.is_default()
             && !output_format.show_only_comparison
-            && (self.has_multiple() || tool_config.tool != ValgrindTool::Callgrind)
+            && (self.has_multiple()
+                || tool_config.tool != ValgrindTool::Callgrind
+                || tool_config.cache_model.is_some())
         {
             let mut formatter = VerticalFormatter::new(output_format.clone());
-            formatter.format_tool_headline(tool_config.tool);
+            formatter.format_tool_headline(tool_config.tool, tool_config.cache_model);
             formatter.print_buffer();
         }
     }
 
+    /// Print the metrics of the tools which were run with multiple [`CacheModel`]s side by side
+    fn print_cache_models(profiles: &Profiles, output_format: &OutputFormat) {
+        for tool in [ValgrindTool::Callgrind, ValgrindTool::Cachegrind] {
+            let summaries = profiles
+                .iter()
+                .filter(|profile| profile.tool == tool)
+                .filter_map(|profile| {
+                    profile
+                        .cache_model
+                        .map(|cache_model| (cache_model, &profile.summaries.total.summary))
+                })
+                .collect::<Vec<_>>();
+            if summaries.len() > 1 {
+                VerticalFormatter::new(output_format.clone()).print_cache_models(tool, &summaries);
+            }
+        }
+    }
+
     /// Check for regressions as defined in [`RegressionConfig`] and print an error if a regression
     /// occurred
     ///
@@ -539,15 +1186,72 @@ impl ToolConfigs {
             self.print_headline(tool_config, output_format);
 
             let tool = tool_config.tool;
-            let output_path = output_path.to_tool_output(tool);
+            let output_path = tool_config.output_path(output_path);
+
+            if let Some(fingerprint) = Fingerprint::load(&output_path)? {
+                fingerprint::check(
+                    config.meta.args.baseline_mismatch,
+                    &fingerprint,
+                    &output_path.to_base_path(),
+                    &ModulePath::new(&benchmark_summary.module_path),
+                )?;
+            }
 
             let mut profile = tool_config.parse(&config.meta, &output_path, None)?;
 
             tool_config.print(config, output_format, &profile.summaries, baselines)?;
+            profile.throughput = tool_config.print_throughput(
+                output_format,
+                benchmark_summary.throughput,
+                &profile.summaries.total,
+            );
+            profile.custom_metrics =
+                tool_config.print_custom_metrics(output_format, &profile.summaries.total);
+            profile.functions = tool_config.print_functions(
+                &config.meta,
+                output_format,
+                &output_path,
+                tool_config
+                    .parse_functions(&config.meta, &output_path.to_base_path())?
+                    .as_ref(),
+            )?;
+            tool_config.print_threads(output_format, &profile.threads);
+            tool_config.print_hot_lines(
+                &config.meta,
+                output_format,
+                &output_path,
+                tool_config
+                    .parse_lines(&config.meta, &output_path.to_base_path())?
+                    .as_ref(),
+            )?;
+            tool_config.print_hot_instructions(
+                output_format,
+                &output_path,
+                &benchmark_summary.benchmark_exe,
+            )?;
             profile.summaries.total.regressions = Self::check_and_print_regressions(
-                &tool_config.regression_config,
+                &tool_config.regression_config_with_noise(&output_path)?,
                 &profile.summaries.total,
             );
+            regions::check_and_print(&tool_config.regions, &mut profile.summaries);
+            if let Some(function_metrics) = &tool_config.function_metrics {
+                function_metrics.check_and_print(&mut profile.functions);
+            }
+            if let Some(threads_config) = &tool_config.threads_config {
+                threads_config.check_and_print(&mut profile.threads);
+            }
+            if let (Some(config), Some(throughput), Some(summary)) = (
+                &tool_config.throughput_config,
+                benchmark_summary.throughput,
+                profile.throughput.as_mut(),
+            ) {
+                config.check_and_print(throughput, summary);
+            }
+            if let (Some(config), Some(summary)) =
+                (&tool_config.custom_metrics, profile.custom_metrics.as_mut())
+            {
+                config.check_and_print(summary);
+            }
 
             if ValgrindTool::Callgrind == tool {
                 if let ToolFlamegraphConfig::Callgrind(flamegraph_config) =
@@ -567,6 +1271,7 @@ impl ToolConfigs {
                     )?;
                 }
             }
+            tool_config.create_call_graph(&config.meta, &output_path)?;
 
             benchmark_summary.profiles.push(profile);
 
@@ -574,6 +1279,7 @@ impl ToolConfigs {
             log_path.dump_log(log::Level::Info, &mut stderr())?;
         }
 
+        Self::print_cache_models(&benchmark_summary.profiles, output_format);
         Ok(benchmark_summary)
     }
 
@@ -606,13 +1312,22 @@ impl ToolConfigs {
             } else {
                 NoCapture::False
             };
-            let command = ToolCommand::new(tool, &config.meta, nocapture);
 
-            let output_path = output_path.to_tool_output(tool);
+            let output_path = tool_config.output_path(output_path);
+
+            let fingerprint = Fingerprint::new(&config.meta, &tool_config.args);
+            fingerprint::check(
+                config.meta.args.baseline_mismatch,
+                &fingerprint,
+                &output_path.to_base_path(),
+                module_path,
+            )?;
 
             let parser =
                 parser_factory(tool_config, config.meta.project_root.clone(), &output_path);
             let parsed_old = parser.parse_base()?;
+            let old_lines = tool_config.parse_lines(&config.meta, &output_path.to_base_path())?;
+            let old_map = tool_config.parse_functions(&config.meta, &output_path.to_base_path())?;
 
             let log_path = output_path.to_log_output();
 
@@ -627,64 +1342,129 @@ impl ToolConfigs {
                 }
             }
 
-            // We're implicitly applying the default here: In the absence of a user provided sandbox
-            // we don't run the benchmarks in a sandbox. Everything from here on runs
-            // with the current directory set to the sandbox directory until the sandbox
-            // is reset.
-            let sandbox = run_options
-                .sandbox
-                .as_ref()
-                .map(|sandbox| Sandbox::setup(sandbox, &config.meta))
-                .transpose()?;
+            // If the noise is measured, the benchmark is run multiple times. Only the output files
+            // of the last run are kept and used for the comparison with the baseline.
+            let mut output = None;
+            let mut totals = vec![];
+            for run in 0..tool_config.repeat.unwrap_or(1) {
+                if run > 0 {
+                    output_path.clear()?;
+                    log_path.clear()?;
+                }
 
-            let mut child = run_options
-                .setup
-                .as_ref()
-                .map_or(Ok(None), |setup| setup.run(config, module_path))?;
-
-            if let Some(delay) = run_options.delay.as_ref() {
-                if let Err(error) = delay.run() {
-                    if let Some(mut child) = child.take() {
-                        // To avoid zombies
-                        child.kill()?;
-                        return Err(error);
+                // We're implicitly applying the default here: In the absence of a user provided
+                // sandbox we don't run the benchmarks in a sandbox. Everything from here on runs
+                // with the current directory set to the sandbox directory until the sandbox
+                // is reset.
+                let sandbox = run_options
+                    .sandbox
+                    .as_ref()
+                    .map(|sandbox| Sandbox::setup(sandbox, &config.meta))
+                    .transpose()?;
+
+                let mut child = run_options
+                    .setup
+                    .as_ref()
+                    .map_or(Ok(None), |setup| setup.run(config, module_path))?;
+
+                if let Some(delay) = run_options.delay.as_ref() {
+                    if let Err(error) = delay.run() {
+                        if let Some(mut child) = child.take() {
+                            // To avoid zombies
+                            child.kill()?;
+                            return Err(error);
+                        }
                     }
                 }
-            }
 
-            let output = command.run(
-                tool_config.clone(),
-                executable,
-                executable_args,
-                run_options.clone(),
-                &output_path,
-                module_path,
-                child,
-            )?;
+                let command = ToolCommand::new(tool, &config.meta, nocapture);
+                output = Some(command.run(
+                    tool_config.clone(),
+                    executable,
+                    &tool_config.executable_args(executable_args),
+                    run_options.clone(),
+                    &output_path,
+                    module_path,
+                    child,
+                )?);
+
+                if let Some(teardown) = run_options.teardown.as_ref() {
+                    teardown.run(config, module_path)?;
+                }
 
-            if let Some(teardown) = run_options.teardown.as_ref() {
-                teardown.run(config, module_path)?;
-            }
+                // We print the no capture footer after the teardown to keep the output consistent
+                // with library benchmarks.
+                print_no_capture_footer(
+                    nocapture,
+                    run_options.stdout.as_ref(),
+                    run_options.stderr.as_ref(),
+                );
 
-            // We print the no capture footer after the teardown to keep the output consistent with
-            // library benchmarks.
-            print_no_capture_footer(
-                nocapture,
-                run_options.stdout.as_ref(),
-                run_options.stderr.as_ref(),
-            );
+                if let Some(sandbox) = sandbox {
+                    sandbox.reset()?;
+                }
 
-            if let Some(sandbox) = sandbox {
-                sandbox.reset()?;
+                if tool_config.repeat.is_some() {
+                    totals.extend(noise::total(tool_config.filter_parsed(parser.parse()?)));
+                }
+            }
+
+            fingerprint.save(&output_path)?;
+            if let Some(noise) = Noise::from_runs(&totals) {
+                noise.save(&output_path)?;
             }
 
             let mut profile = tool_config.parse(&config.meta, &output_path, Some(parsed_old))?;
 
             tool_config.print(config, output_format, &profile.summaries, baselines)?;
+            profile.throughput = tool_config.print_throughput(
+                output_format,
+                benchmark_summary.throughput,
+                &profile.summaries.total,
+            );
+            profile.custom_metrics =
+                tool_config.print_custom_metrics(output_format, &profile.summaries.total);
+            profile.functions = tool_config.print_functions(
+                &config.meta,
+                output_format,
+                &output_path,
+                old_map.as_ref(),
+            )?;
+            tool_config.print_threads(output_format, &profile.threads);
+            tool_config.print_hot_lines(
+                &config.meta,
+                output_format,
+                &output_path,
+                old_lines.as_ref(),
+            )?;
+            tool_config.print_hot_instructions(
+                output_format,
+                &output_path,
+                &benchmark_summary.benchmark_exe,
+            )?;
             profile.summaries.total.regressions = Self::check_and_print_regressions(
-                &tool_config.regression_config,
+                &tool_config.regression_config_with_noise(&output_path)?,
                 &profile.summaries.total,
             );
+            regions::check_and_print(&tool_config.regions, &mut profile.summaries);
+            if let Some(function_metrics) = &tool_config.function_metrics {
+                function_metrics.check_and_print(&mut profile.functions);
+            }
+            if let Some(threads_config) = &tool_config.threads_config {
+                threads_config.check_and_print(&mut profile.threads);
+            }
+            if let (Some(config), Some(throughput), Some(summary)) = (
+                &tool_config.throughput_config,
+                benchmark_summary.throughput,
+                profile.throughput.as_mut(),
+            ) {
+                config.check_and_print(throughput, summary);
+            }
+            if let (Some(config), Some(summary)) =
+                (&tool_config.custom_metrics, profile.custom_metrics.as_mut())
+            {
+                config.check_and_print(summary);
+            }
 
             if tool_config.tool == ValgrindTool::Callgrind {
                 if save_baseline {
@@ -721,13 +1501,17 @@ impl ToolConfigs {
                     // do nothing
                 }
             }
+            tool_config.create_call_graph(&config.meta, &output_path)?;
 
             benchmark_summary.profiles.push(profile);
 
-            output.dump_log(log::Level::Info);
+            if let Some(output) = output {
+                output.dump_log(log::Level::Info);
+            }
             log_path.dump_log(log::Level::Info, &mut stderr())?;
         }
 
+        Self::print_cache_models(&benchmark_summary.profiles, output_format);
         Ok(benchmark_summary)
     }
 }
@@ -0,0 +1,285 @@
+//! The module containing the [`Fingerprint`] of the environment of a benchmark run
+//!
+//! A fingerprint is stored next to the output files of each tool (`<tool>.<name>.fingerprint`,
+//! `*.fingerprint.old`, `*.fingerprint.base@<name>`). Comparing against a baseline which was created
+//! with different valgrind arguments, another valgrind or rustc version or for another target
+//! usually produces meaningless differences, so the fingerprint of the baseline is compared with
+//! the fingerprint of the new benchmark run before the benchmark is run.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::args::ToolArgs;
+use super::path::{ToolOutputPath, ToolOutputPathKind};
+use crate::error::Error;
+use crate::runner::args::BaselineMismatch;
+use crate::runner::common::ModulePath;
+use crate::runner::meta::Metadata;
+use crate::runner::summary::BaselineKind;
+
+/// The environment in which the output files of a tool were created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// The output of `rustc --version` if available
+    pub rustc_version: Option<String>,
+    /// The target triple of the host
+    pub target: String,
+    /// The effective command-line arguments of valgrind without the output file arguments
+    pub tool_args: Vec<String>,
+    /// The output of `valgrind --version` if available
+    pub valgrind_version: Option<String>,
+}
+
+impl Fingerprint {
+    /// Create a new `Fingerprint` from the [`Metadata`] and the [`ToolArgs`] of a tool
+    ///
+    /// The `tool_args` are expected to be the arguments of the
+    /// [`crate::runner::tool::config::ToolConfig`] which don't contain the output file arguments.
+    pub fn new(meta: &Metadata, tool_args: &ToolArgs) -> Self {
+        Self {
+            rustc_version: meta.rustc_version.clone(),
+            target: env!("IC_BUILD_TRIPLE").to_ascii_lowercase(),
+            tool_args: tool_args
+                .to_vec()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            valgrind_version: meta.valgrind_version.clone(),
+        }
+    }
+
+    /// Load the `Fingerprint` of the `output_path` if present
+    pub fn load(output_path: &ToolOutputPath) -> Result<Option<Self>> {
+        let path = output_path.to_fingerprint_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Self::read(&path).map(Some)
+    }
+
+    /// Read the `Fingerprint` from the file at `path`
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fingerprint file '{}'", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse fingerprint file '{}'", path.display()))
+    }
+
+    /// Save this `Fingerprint` as the fingerprint of the `output_path`
+    pub fn save(&self, output_path: &ToolOutputPath) -> Result<()> {
+        let path = output_path.to_fingerprint_path();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write fingerprint file '{}'", path.display()))
+    }
+
+    /// Return the differences of this (new) fingerprint to the fingerprint of the `base`
+    ///
+    /// Each difference is a human-readable line. The list is empty if there are no differences.
+    pub fn diff(&self, base: &Self) -> Vec<String> {
+        fn display(value: Option<&String>) -> &str {
+            value.map_or("unknown", String::as_str)
+        }
+
+        let mut differences = vec![];
+        if self.valgrind_version != base.valgrind_version {
+            differences.push(format!(
+                "valgrind version: '{}' -> '{}'",
+                display(base.valgrind_version.as_ref()),
+                display(self.valgrind_version.as_ref())
+            ));
+        }
+        if self.rustc_version != base.rustc_version {
+            differences.push(format!(
+                "rustc version: '{}' -> '{}'",
+                display(base.rustc_version.as_ref()),
+                display(self.rustc_version.as_ref())
+            ));
+        }
+        if self.target != base.target {
+            differences.push(format!("target: '{}' -> '{}'", base.target, self.target));
+        }
+        if self.tool_args != base.tool_args {
+            let removed = base
+                .tool_args
+                .iter()
+                .filter(|arg| !self.tool_args.contains(arg))
+                .map(|arg| format!("'{arg}'"))
+                .collect::<Vec<_>>();
+            let added = self
+                .tool_args
+                .iter()
+                .filter(|arg| !base.tool_args.contains(arg))
+                .map(|arg| format!("'{arg}'"))
+                .collect::<Vec<_>>();
+
+            let mut difference = "valgrind arguments:".to_owned();
+            if removed.is_empty() && added.is_empty() {
+                write!(
+                    difference,
+                    " '{}' -> '{}'",
+                    base.tool_args.join(" "),
+                    self.tool_args.join(" ")
+                )
+                .unwrap();
+            } else {
+                if !removed.is_empty() {
+                    write!(difference, " removed {}", removed.join(", ")).unwrap();
+                }
+                if !added.is_empty() {
+                    if !removed.is_empty() {
+                        difference.push(';');
+                    }
+                    write!(difference, " added {}", added.join(", ")).unwrap();
+                }
+            }
+            differences.push(difference);
+        }
+
+        differences
+    }
+}
+
+/// Compare the `new` fingerprint with the fingerprint of the baseline of the `base_path`
+///
+/// Nothing is compared if the baseline has no fingerprint, for example if it was created with an
+/// older version of iai-callgrind. Depending on the [`BaselineMismatch`], differences are
+/// ignored, printed as warning or returned as error.
+pub fn check(
+    baseline_mismatch: BaselineMismatch,
+    new: &Fingerprint,
+    base_path: &ToolOutputPath,
+    module_path: &ModulePath,
+) -> Result<()> {
+    if baseline_mismatch == BaselineMismatch::Ignore {
+        return Ok(());
+    }
+    let Some(base) = Fingerprint::load(base_path)? else {
+        return Ok(());
+    };
+
+    let differences = new.diff(&base);
+    if differences.is_empty() {
+        return Ok(());
+    }
+
+    let baseline = match &base_path.kind {
+        ToolOutputPathKind::BaseOut(name)
+        | ToolOutputPathKind::BaseLog(name)
+        | ToolOutputPathKind::BaseXtree(name)
+        | ToolOutputPathKind::BaseXleak(name) => format!("baseline '{name}'"),
+        _ => "old benchmark run".to_owned(),
+    };
+    let mut message = format!("The {baseline} was created in a different environment:");
+    for difference in differences {
+        write!(message, "\n  {difference}").unwrap();
+    }
+
+    match baseline_mismatch {
+        BaselineMismatch::Error => {
+            Err(Error::BenchmarkError(base_path.tool, module_path.clone(), message).into())
+        }
+        BaselineMismatch::Warn => {
+            warn!("{module_path}: {message}");
+            Ok(())
+        }
+        BaselineMismatch::Ignore => Ok(()),
+    }
+}
+
+/// Remove the `old` fingerprint and rename the present fingerprint to the `old` fingerprint
+///
+/// This is the counterpart of [`ToolOutputPath::shift`]. If the [`BaselineKind`] is a named
+/// baseline, the present fingerprint is just removed.
+pub fn shift(output_path: &ToolOutputPath) -> Result<()> {
+    let path = output_path.to_fingerprint_path();
+    if output_path.baseline_kind == BaselineKind::Old {
+        let old_path = output_path.to_base_path().to_fingerprint_path();
+        if old_path.exists() {
+            remove(&old_path)?;
+        }
+        if path.exists() {
+            std::fs::rename(&path, &old_path).with_context(|| {
+                format!(
+                    "Failed to move fingerprint file from '{}' to '{}'",
+                    path.display(),
+                    old_path.display()
+                )
+            })?;
+        }
+    } else if path.exists() {
+        remove(&path)?;
+    } else {
+        // do nothing
+    }
+
+    Ok(())
+}
+
+fn remove(path: &Path) -> Result<()> {
+    std::fs::remove_file(path)
+        .with_context(|| format!("Failed to remove fingerprint file '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fingerprint(valgrind_version: &str, tool_args: &[&str]) -> Fingerprint {
+        Fingerprint {
+            rustc_version: Some("rustc 1.74.1".to_owned()),
+            target: "x86_64-unknown-linux-gnu".to_owned(),
+            tool_args: tool_args.iter().map(|arg| (*arg).to_owned()).collect(),
+            valgrind_version: Some(valgrind_version.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_diff_when_equal() {
+        let new = fingerprint("valgrind-3.22.0", &["--tool=callgrind"]);
+        assert!(new.diff(&new.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_when_versions_and_args_differ() {
+        let base = fingerprint(
+            "valgrind-3.21.0",
+            &["--tool=callgrind", "--cache-sim=no", "--verbose"],
+        );
+        let new = fingerprint("valgrind-3.22.0", &["--tool=callgrind", "--cache-sim=yes"]);
+
+        assert_eq!(
+            new.diff(&base),
+            vec![
+                "valgrind version: 'valgrind-3.21.0' -> 'valgrind-3.22.0'".to_owned(),
+                "valgrind arguments: removed '--cache-sim=no', '--verbose'; added \
+                 '--cache-sim=yes'"
+                    .to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_when_order_of_args_differs() {
+        let base = fingerprint("valgrind-3.22.0", &["--cache-sim=no", "--cache-sim=yes"]);
+        let mut new = fingerprint("valgrind-3.22.0", &["--cache-sim=yes", "--cache-sim=no"]);
+        new.rustc_version = None;
+
+        assert_eq!(
+            new.diff(&base),
+            vec![
+                "rustc version: 'rustc 1.74.1' -> 'unknown'".to_owned(),
+                "valgrind arguments: '--cache-sim=no --cache-sim=yes' -> '--cache-sim=yes \
+                 --cache-sim=no'"
+                    .to_owned()
+            ]
+        );
+    }
+}
@@ -4,6 +4,7 @@
 pub mod args;
 pub mod config;
 pub mod error_metric_parser;
+pub mod fingerprint;
 pub mod generic_parser;
 pub mod logfile_parser;
 pub mod parser;
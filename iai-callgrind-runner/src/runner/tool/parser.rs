@@ -110,6 +110,7 @@ pub fn parser_factory(
 ) -> Box<dyn Parser> {
     match tool_config.tool {
         ValgrindTool::Callgrind => Box::new(callgrind::summary_parser::SummaryParser {
+            cycles_model: tool_config.cycles_model,
             output_path: output_path.clone(),
         }),
         ValgrindTool::Cachegrind => Box::new(cachegrind::summary_parser::SummaryParser {
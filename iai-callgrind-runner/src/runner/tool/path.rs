@@ -12,7 +12,7 @@ use lazy_static::lazy_static;
 use log::log_enabled;
 use regex::Regex;
 
-use crate::api::ValgrindTool;
+use crate::api::{CacheModel, ValgrindTool};
 use crate::runner::callgrind::parser::parse_header;
 use crate::runner::common::ModulePath;
 use crate::runner::summary::BaselineKind;
@@ -291,6 +291,21 @@ impl ToolOutputPath {
         }
     }
 
+    /// Convert this tool output path to the output path of the given [`CacheModel`]
+    ///
+    /// The output files of a cache model are stored in a subdirectory named after the cache model.
+    #[must_use]
+    pub fn to_cache_model_output(&self, cache_model: CacheModel) -> Self {
+        Self {
+            kind: self.kind.clone(),
+            tool: self.tool,
+            baseline_kind: self.baseline_kind.clone(),
+            name: self.name.clone(),
+            dir: self.dir.join(cache_model.id()),
+            modifiers: self.modifiers.clone(),
+        }
+    }
+
     /// Convert this tool output to the according log output
     ///
     /// All tools have a log output even the ones which are out-file based.
@@ -511,6 +526,43 @@ impl ToolOutputPath {
         ))
     }
 
+    /// Return the path of the file with the
+    /// [`Fingerprint`](crate::runner::tool::fingerprint::Fingerprint) of this output path
+    ///
+    /// There is only a single fingerprint file for all output files of a tool, so the fingerprint
+    /// path is the same for the `out`, `log`, `xtree` and `xleak` output of the same baseline.
+    pub fn to_fingerprint_path(&self) -> PathBuf {
+        self.to_tool_file_path("fingerprint")
+    }
+
+    /// Return the path of the file with the [`Noise`](crate::runner::callgrind::noise::Noise) of
+    /// this output path
+    ///
+    /// Like the fingerprint, there is only a single noise file for all output files of a tool.
+    pub fn to_noise_path(&self) -> PathBuf {
+        self.to_tool_file_path("noise")
+    }
+
+    /// Return the path of a file which exists only once per tool and baseline, like the
+    /// fingerprint, with the given `name` as extension
+    fn to_tool_file_path(&self, name: &str) -> PathBuf {
+        let extension = match &self.kind {
+            ToolOutputPathKind::Out
+            | ToolOutputPathKind::Log
+            | ToolOutputPathKind::Xtree
+            | ToolOutputPathKind::Xleak => name.to_owned(),
+            ToolOutputPathKind::OldOut
+            | ToolOutputPathKind::OldLog
+            | ToolOutputPathKind::OldXtree
+            | ToolOutputPathKind::OldXleak => format!("{name}.old"),
+            ToolOutputPathKind::BaseOut(base)
+            | ToolOutputPathKind::BaseLog(base)
+            | ToolOutputPathKind::BaseXtree(base)
+            | ToolOutputPathKind::BaseXleak(base) => format!("{name}.base@{base}"),
+        };
+        self.dir.join(format!("{}.{extension}", self.prefix()))
+    }
+
     /// Walk the benchmark directory (non-recursive)
     pub fn walk_dir(&self) -> Result<impl Iterator<Item = DirEntry>> {
         std::fs::read_dir(&self.dir)
@@ -1302,4 +1354,25 @@ mod tests {
             ))
             .is_none());
     }
+
+    #[test]
+    fn test_tool_output_path_to_cache_model_output() {
+        let output_path = ToolOutputPath::new(
+            ToolOutputPathKind::Out,
+            ValgrindTool::Callgrind,
+            &BaselineKind::Old,
+            &PathBuf::from("/root"),
+            &ModulePath::new("hello::world"),
+            "bench_thread_in_subprocess.two",
+        );
+
+        let actual = output_path.to_cache_model_output(CacheModel::Server);
+
+        assert_eq!(
+            actual.dir,
+            PathBuf::from("/root/hello/world/bench_thread_in_subprocess.two/server")
+        );
+        assert_eq!(actual.prefix(), output_path.prefix());
+        assert_eq!(actual.kind, output_path.kind);
+    }
 }
@@ -1,5 +1,5 @@
 //! The module containing the basic elements for regression check configurations
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::hash::Hash;
 
 use either_or_both::EitherOrBoth;
@@ -19,6 +19,8 @@ pub enum RegressionMetrics<T> {
     Soft(T, Metric, Metric, f64, f64),
     /// The result of a checked hard limit
     Hard(T, Metric, Metric, Metric),
+    /// The result of a checked expected improvement
+    Improvement(T, Metric, Metric, f64, f64),
 }
 
 /// The tool specific regression check configuration
@@ -106,16 +108,72 @@ pub trait RegressionConfig<T: Hash + Eq + Summarize + Display + Clone> {
                 ));
             }
         }
+
+        for (metric, new_cost, old_cost, pct, improvement) in self
+            .get_improvements()
+            .iter()
+            .filter_map(|(kind, improvement)| {
+                metrics_summary.diff_by_kind(kind).and_then(|d| {
+                    if let EitherOrBoth::Both(new, old) = d.metrics {
+                        // This unwrap is safe since the diffs are calculated if both costs are
+                        // present
+                        Some((kind, new, old, d.diffs.unwrap().diff_pct, improvement))
+                    } else {
+                        None
+                    }
+                })
+            })
+        {
+            if pct > -*improvement {
+                regressions.push(RegressionMetrics::Improvement(
+                    metric.clone(),
+                    new_cost,
+                    old_cost,
+                    pct,
+                    *improvement,
+                ));
+            }
+        }
         regressions
     }
 
     /// Return the hard limits
     fn get_hard_limits(&self) -> &[(T, Metric)];
 
+    /// Return the expected improvements
+    fn get_improvements(&self) -> &[(T, f64)];
+
     /// Return the soft limits
     fn get_soft_limits(&self) -> &[(T, f64)];
 }
 
+/// Verify that the expected `improvements` are positive percentages
+pub fn check_improvements<T: Debug>(improvements: &[(T, f64)]) -> Result<(), String> {
+    if let Some((metrics, improvement)) = improvements
+        .iter()
+        .find(|(_, improvement)| !improvement.is_finite() || *improvement <= 0f64)
+    {
+        Err(format!(
+            "Invalid expected improvement for '{metrics:?}': Expected a positive percentage but \
+             found '{improvement}'"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Verify that the `soft_limits` are finite percentages
+pub fn check_soft_limits<T: Debug>(soft_limits: &[(T, f64)]) -> Result<(), String> {
+    if let Some((metrics, limit)) = soft_limits.iter().find(|(_, limit)| !limit.is_finite()) {
+        Err(format!(
+            "Invalid soft limit for '{metrics:?}': Expected a finite percentage but found \
+             '{limit}'"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 impl ToolRegressionConfig {
     /// Return true if the configuration has fail fast set to true
     pub fn is_fail_fast(&self) -> bool {
@@ -196,6 +196,18 @@ pub fn bool_to_yesno(value: bool) -> String {
     }
 }
 
+/// Return the trimmed stdout of a successfully executed command or `None`
+pub fn command_output(program: &OsStr, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_owned())
+        .filter(|stdout| !stdout.is_empty())
+}
+
 /// Copy a directory recursively from `source` to `dest` preserving mode, ownership and timestamps
 ///
 /// If `follow_symlinks` is true copy the symlinked file or directory instead of the symlink itself
@@ -6,7 +6,8 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
 use anyhow::Result;
-use iai_callgrind_runner::api::ValgrindTool;
+use iai_callgrind_runner::api::{EventKind, ValgrindTool};
+use iai_callgrind_runner::runner::callgrind::model::Metrics;
 use iai_callgrind_runner::runner::summary::BaselineKind;
 use iai_callgrind_runner::runner::tool::path::{ToolOutputPath, ToolOutputPathKind};
 use pretty_assertions::assert_eq;
@@ -230,6 +231,11 @@ where
     );
 }
 
+/// Create the callgrind [`Metrics`] of the `Ir` and `Dr` events used in the test fixtures
+pub fn callgrind_metrics(ir: u64, dr: u64) -> Metrics {
+    Metrics::with_metric_kinds([(EventKind::Ir, ir), (EventKind::Dr, dr)])
+}
+
 pub fn get_project_root() -> PathBuf {
     let meta = cargo_metadata::MetadataCommand::new()
         .no_deps()
@@ -1,3 +1,5 @@
 mod test_flamegraph_parser;
 mod test_hashmap_parser;
+mod test_hot_instructions_parser;
+mod test_hot_lines_parser;
 mod test_summary_parser;
@@ -1,5 +1,6 @@
 use iai_callgrind_runner::api::EventKind;
 use iai_callgrind_runner::runner::callgrind::flamegraph_parser::FlamegraphParser;
+use iai_callgrind_runner::runner::callgrind::folding::FoldingRules;
 use iai_callgrind_runner::runner::callgrind::parser::{CallgrindParser, Sentinel};
 use rstest::rstest;
 
@@ -24,7 +25,10 @@ fn test_flamegraph_parser(#[case] name: &str, #[case] sentinel: Option<Sentinel>
 
     let result = parser.parse(&output).unwrap();
     assert_eq!(result.len(), 1);
-    let stacks = result[0].2.to_stack_format(&EventKind::Ir).unwrap();
+    let stacks = result[0]
+        .2
+        .to_stack_format(&EventKind::Ir, &FoldingRules::default())
+        .unwrap();
 
     assert_eq!(stacks.len(), expected_stacks.len());
     // Assert line by line or else the output on error is unreadable. Also, provide an additional
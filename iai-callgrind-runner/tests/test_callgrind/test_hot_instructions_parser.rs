@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use iai_callgrind_runner::api::ValgrindTool;
+use iai_callgrind_runner::runner::callgrind::hot_instructions::{
+    HotInstructionsParser, InstructionAddr, InstructionsMap,
+};
+use iai_callgrind_runner::runner::callgrind::parser::CallgrindParser;
+use iai_callgrind_runner::runner::tool::path::ToolOutputPathKind;
+use pretty_assertions::assert_eq;
+
+use crate::common::{callgrind_metrics, Fixtures};
+
+fn instr(obj: &str, func: &str, addr: u64) -> InstructionAddr {
+    InstructionAddr {
+        addr,
+        func: func.to_owned(),
+        obj: PathBuf::from(obj),
+    }
+}
+
+#[test]
+fn test_hot_instructions_parser_self_costs() {
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        "hot_instructions.self_costs",
+    );
+    let expected = InstructionsMap(HashMap::from([
+        (
+            instr("/project/target/release/bench", "lib::some", 0x1130),
+            callgrind_metrics(10, 2),
+        ),
+        (
+            instr("/project/target/release/bench", "lib::some", 0x1134),
+            callgrind_metrics(6, 1),
+        ),
+        (
+            instr("/usr/lib/libc.so.6", "memcpy", 0x2000),
+            callgrind_metrics(20, 10),
+        ),
+    ]));
+
+    let actual = HotInstructionsParser.parse(&output).unwrap();
+
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].2, expected);
+}
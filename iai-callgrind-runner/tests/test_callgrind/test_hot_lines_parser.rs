@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use iai_callgrind_runner::api::ValgrindTool;
+use iai_callgrind_runner::runner::callgrind::hot_lines::{HotLinesParser, LinesMap, SourceLine};
+use iai_callgrind_runner::runner::callgrind::parser::CallgrindParser;
+use iai_callgrind_runner::runner::tool::path::ToolOutputPathKind;
+use pretty_assertions::assert_eq;
+
+use crate::common::{callgrind_metrics, Fixtures};
+
+fn parse(name: &str) -> LinesMap {
+    let parser = HotLinesParser {
+        project_root: PathBuf::from("/project"),
+    };
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        name,
+    );
+
+    let mut parsed = parser.parse(&output).unwrap();
+    assert_eq!(parsed.len(), 1);
+    parsed.remove(0).2
+}
+
+fn source_line(path: &str, line: u64) -> SourceLine {
+    SourceLine {
+        line,
+        path: PathBuf::from(path),
+    }
+}
+
+#[test]
+fn test_hot_lines_parser_self_costs() {
+    let expected = LinesMap {
+        map: HashMap::from([
+            (source_line("src/lib.rs", 3), callgrind_metrics(10, 2)),
+            (source_line("src/lib.rs", 4), callgrind_metrics(6, 1)),
+            (source_line("src/inlined.rs", 11), callgrind_metrics(7, 0)),
+        ]),
+        other: Some(callgrind_metrics(23, 10)),
+    };
+
+    assert_eq!(parse("hot_lines.self_costs"), expected);
+}
+
+#[test]
+fn test_hot_lines_parser_when_line_zero_then_other() {
+    let expected = LinesMap {
+        map: HashMap::new(),
+        other: Some(callgrind_metrics(10, 2)),
+    };
+
+    assert_eq!(parse("hot_lines.line_zero"), expected);
+}
@@ -4,7 +4,7 @@ use crate::__internal::error::{Error, Errors};
 use crate::__internal::{
     InternalBinaryBenchmark, InternalBinaryBenchmarkBench, InternalBinaryBenchmarkConfig,
     InternalBinaryBenchmarkGroup, InternalBinaryBenchmarkGroups, InternalCommandKind,
-    InternalMacroBinBench, ModulePath,
+    InternalMacroBinBench, InternalMacroParameterValue, ModulePath,
 };
 use crate::{BenchmarkId, ValgrindTool};
 
@@ -70,6 +70,7 @@ impl GroupsBuilder {
         has_setup: bool,
         has_teardown: bool,
         compare_by_id: Option<bool>,
+        compare_limits: Vec<crate::__internal::InternalCompareLimit>,
         benches: InternalMacroBinBenches,
     ) {
         let mut internal_group = InternalBinaryBenchmarkGroup {
@@ -78,6 +79,7 @@ impl GroupsBuilder {
             has_setup,
             has_teardown,
             compare_by_id,
+            compare_limits,
             ..Default::default()
         };
 
@@ -169,6 +171,9 @@ impl GroupsBuilder {
                     config: macro_bin_bench.config.map(|f| f()),
                     has_setup: macro_bin_bench.setup.is_some(),
                     has_teardown: macro_bin_bench.teardown.is_some(),
+                    parameters: InternalMacroParameterValue::to_parameters(
+                        macro_bin_bench.parameters,
+                    ),
                 };
                 internal_binary_benchmark.benches.push(bench);
             }
@@ -239,6 +244,7 @@ impl GroupsBuilder {
                             has_setup: bench.setup.is_some() || binary_benchmark.setup.is_some(),
                             has_teardown: bench.teardown.is_some()
                                 || binary_benchmark.teardown.is_some(),
+                            parameters: vec![],
                         };
                         internal_binary_benchmark.benches.push(internal_bench);
                     }
@@ -267,6 +273,7 @@ impl GroupsBuilder {
                                     || binary_benchmark.setup.is_some(),
                                 has_teardown: bench.teardown.is_some()
                                     || binary_benchmark.teardown.is_some(),
+                                parameters: vec![],
                             };
                             internal_binary_benchmark.benches.push(internal_bench);
                         }
@@ -1,5 +1,14 @@
+#[cfg(feature = "client_requests")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::ValgrindTool;
 
+/// True if the instrumentation is controlled with client requests
+///
+/// Set by the benchmark harness if the runner requests the `ClientRequests` instrumentation mode
+#[cfg(feature = "client_requests")]
+static CLIENT_REQUESTS: AtomicBool = AtomicBool::new(false);
+
 type MacroLibBenches<'a> = &'a [&'a (
     &'static str,
     fn() -> Option<crate::__internal::InternalLibraryBenchmarkConfig>,
@@ -49,6 +58,7 @@ impl GroupsBuilder {
         id: String,
         config: Option<crate::__internal::InternalLibraryBenchmarkConfig>,
         compare_by_id: Option<bool>,
+        compare_limits: Vec<crate::__internal::InternalCompareLimit>,
         has_setup: bool,
         has_teardown: bool,
         benches: MacroLibBenches,
@@ -59,6 +69,7 @@ impl GroupsBuilder {
             has_setup,
             has_teardown,
             compare_by_id,
+            compare_limits,
             ..Default::default()
         };
 
@@ -68,15 +79,27 @@ impl GroupsBuilder {
                 config: get_config(),
             };
             for macro_lib_bench in *macro_lib_benches {
+                let iter_count = match macro_lib_bench.func {
+                    super::InternalLibFunctionKind::Iter(func) => Some(func(None)),
+                    super::InternalLibFunctionKind::Default(_) => None,
+                };
+                let sizes = match (macro_lib_bench.size, iter_count) {
+                    (Some(size), Some(iter_count)) => {
+                        (0..iter_count).map(|i| size(Some(i))).collect()
+                    }
+                    (Some(size), None) => vec![size(None)],
+                    (None, _) => vec![],
+                };
                 let bench = crate::__internal::InternalLibraryBenchmarkBench {
                     id: macro_lib_bench.id_display.map(ToString::to_string),
                     args: macro_lib_bench.args_display.map(ToString::to_string),
                     function_name: (*function_name).to_owned(),
                     config: macro_lib_bench.config.map(|f| f()),
-                    iter_count: match macro_lib_bench.func {
-                        super::InternalLibFunctionKind::Iter(func) => Some(func(None)),
-                        super::InternalLibFunctionKind::Default(_) => None,
-                    },
+                    iter_count,
+                    parameters: super::InternalMacroParameterValue::to_parameters(
+                        macro_lib_bench.parameters,
+                    ),
+                    sizes,
                 };
                 benches.benches.push(bench);
             }
@@ -90,3 +113,46 @@ impl GroupsBuilder {
         self.0
     }
 }
+
+/// Switch on the `ClientRequests` instrumentation mode for this benchmark run
+#[cfg(feature = "client_requests")]
+pub fn enable_client_requests() {
+    CLIENT_REQUESTS.store(true, Ordering::Relaxed);
+}
+
+/// Without the `client_requests` feature the client requests are no-ops and nothing would be
+/// measured
+///
+/// # Panics
+///
+/// Always panics
+#[cfg(not(feature = "client_requests"))]
+pub fn enable_client_requests() {
+    panic!(
+        "The instrumentation mode `ClientRequests` requires the `client_requests` feature of \
+         iai-callgrind"
+    );
+}
+
+/// Start the instrumentation of callgrind and cachegrind if in `ClientRequests` mode
+///
+/// The client requests of a tool which is not running are ignored by valgrind, so there's no need
+/// to know the actual tool.
+#[inline(always)]
+pub fn start_instrumentation() {
+    #[cfg(feature = "client_requests")]
+    if CLIENT_REQUESTS.load(Ordering::Relaxed) {
+        crate::client_requests::callgrind::start_instrumentation();
+        crate::client_requests::cachegrind::start_instrumentation();
+    }
+}
+
+/// Stop the instrumentation of callgrind and cachegrind if in `ClientRequests` mode
+#[inline(always)]
+pub fn stop_instrumentation() {
+    #[cfg(feature = "client_requests")]
+    if CLIENT_REQUESTS.load(Ordering::Relaxed) {
+        crate::client_requests::cachegrind::stop_instrumentation();
+        crate::client_requests::callgrind::stop_instrumentation();
+    }
+}
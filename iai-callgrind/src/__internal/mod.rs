@@ -6,6 +6,8 @@ pub mod bin_bench;
 pub mod error;
 pub mod lib_bench;
 
+use std::future::Future;
+
 // The runner api is not used directly in order to decouple the user interface and
 // documentation from the internal usage.
 //
@@ -24,17 +26,22 @@ pub use iai_callgrind_runner::api::{
     BinaryBenchmarkGroup as InternalBinaryBenchmarkGroup,
     BinaryBenchmarkGroups as InternalBinaryBenchmarkGroups,
     CachegrindRegressionConfig as InternalCachegrindRegressionConfig,
+    CallGraphConfig as InternalCallGraphConfig,
     CallgrindRegressionConfig as InternalCallgrindRegressionConfig, Command as InternalCommand,
-    CommandKind as InternalCommandKind, Delay as InternalDelay,
-    DhatRegressionConfig as InternalDhatRegressionConfig, EntryPoint as InternalEntryPoint,
-    ExitWith as InternalExitWith, Fixtures as InternalFixtures,
+    CommandKind as InternalCommandKind, CompareLimit as InternalCompareLimit,
+    CustomMetricsConfig as InternalCustomMetricsConfig, CyclesModel as InternalCyclesModel,
+    Delay as InternalDelay, DhatRegressionConfig as InternalDhatRegressionConfig,
+    EntryPoint as InternalEntryPoint, ExitWith as InternalExitWith, Fixtures as InternalFixtures,
     FlamegraphConfig as InternalFlamegraphConfig,
+    FunctionMetricsConfig as InternalFunctionMetricsConfig,
     LibraryBenchmark as InternalLibraryBenchmarkBenches,
     LibraryBenchmarkBench as InternalLibraryBenchmarkBench,
     LibraryBenchmarkConfig as InternalLibraryBenchmarkConfig,
     LibraryBenchmarkGroup as InternalLibraryBenchmarkGroup,
     LibraryBenchmarkGroups as InternalLibraryBenchmarkGroups, OutputFormat as InternalOutputFormat,
-    RawArgs as InternalRawArgs, Sandbox as InternalSandbox, Tool as InternalTool,
+    Parameter as InternalParameter, ParameterValue as InternalParameterValue,
+    RawArgs as InternalRawArgs, Region as InternalRegion, Sandbox as InternalSandbox,
+    ThroughputConfig as InternalThroughputConfig, Tool as InternalTool,
     ToolFlamegraphConfig as InternalToolFlamegraphConfig,
     ToolOutputFormat as InternalToolOutputFormat,
     ToolRegressionConfig as InternalToolRegressionConfig, Tools as InternalTools,
@@ -69,6 +76,33 @@ pub enum InternalBinFunctionKind {
     Default(fn() -> crate::Command),
 }
 
+/// Used in iai-callgrind-macros to store the value of a benchmark parameter known at compile time
+#[derive(Debug, Clone, Copy)]
+pub enum InternalMacroParameterValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(&'static str),
+}
+
+impl InternalMacroParameterValue {
+    /// Convert the `parameters` of the macros into the parameters of the runner api
+    pub fn to_parameters(parameters: &[(&str, Self)]) -> Vec<InternalParameter> {
+        parameters
+            .iter()
+            .map(|(name, value)| InternalParameter {
+                name: (*name).to_owned(),
+                value: match value {
+                    Self::Bool(value) => InternalParameterValue::Bool(*value),
+                    Self::Int(value) => InternalParameterValue::Int(*value),
+                    Self::Float(value) => InternalParameterValue::Float(*value),
+                    Self::String(value) => InternalParameterValue::String((*value).to_owned()),
+                },
+            })
+            .collect()
+    }
+}
+
 /// Used in iai-callgrind-macros to store the essential information about a library benchmark
 #[derive(Debug, Clone)]
 pub struct InternalMacroLibBench {
@@ -76,6 +110,8 @@ pub struct InternalMacroLibBench {
     pub config: Option<fn() -> InternalLibraryBenchmarkConfig>,
     pub func: InternalLibFunctionKind,
     pub id_display: Option<&'static str>,
+    pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
+    pub size: Option<fn(Option<usize>) -> u64>,
 }
 
 /// Used in iai-callgrind-macros to store the essential information about a binary benchmark
@@ -85,10 +121,51 @@ pub struct InternalMacroBinBench {
     pub config: Option<fn() -> InternalBinaryBenchmarkConfig>,
     pub func: InternalBinFunctionKind,
     pub id_display: Option<&'static str>,
+    pub parameters: &'static [(&'static str, InternalMacroParameterValue)],
     pub setup: InternalBinAssistantKind,
     pub teardown: InternalBinAssistantKind,
 }
 
+/// The poll function generated by iai-callgrind-macros for `async` library benchmarks
+///
+/// This function is generated within the `__iai_callgrind_wrapper_mod` of the benchmark, so only
+/// the polls of the future are within the entry point of callgrind.
+pub type InternalPollFn<F> = fn(
+    std::pin::Pin<&mut F>,
+    &mut std::task::Context<'_>,
+) -> std::task::Poll<<F as Future>::Output>;
+
+/// The future passed to the [`crate::Executor`] polling the inner future with the
+/// [`InternalPollFn`]
+struct InternalPolled<F: Future> {
+    future: std::pin::Pin<Box<F>>,
+    poll: InternalPollFn<F>,
+}
+
+impl<F: Future> Future for InternalPolled<F> {
+    type Output = F::Output;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let poll = self.poll;
+        poll(self.future.as_mut(), cx)
+    }
+}
+
+/// Used in iai-callgrind-macros to run the `future` of an `async` library benchmark
+pub fn block_on<E, F>(executor: &E, future: F, poll: InternalPollFn<F>) -> F::Output
+where
+    E: crate::Executor + ?Sized,
+    F: Future,
+{
+    executor.block_on(InternalPolled {
+        future: Box::pin(future),
+        poll,
+    })
+}
+
 /// A small internal helper to easily create module paths like `file::group::benchmark::id`
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ModulePath(String);
@@ -9,7 +9,9 @@ use std::time::Duration;
 use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
 
-use crate::{DelayKind, ExitWith, Stdin, Stdio, ValgrindTool, __internal};
+use crate::{
+    DelayKind, ExitWith, RegressionPolicy, Stdin, Stdio, Throughput, ValgrindTool, __internal,
+};
 
 /// [low level api](`crate::binary_benchmark_group`) only: Create a new benchmark id
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1572,6 +1574,67 @@ impl BinaryBenchmarkConfig {
         self.0.setup_parallel = Some(setup_parallel);
         self
     }
+
+    /// Set the [`RegressionPolicy`] how failed regression checks of the benchmarks are treated
+    ///
+    /// See also [`crate::LibraryBenchmarkConfig::regression_policy`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # macro_rules! env { ($m:tt) => {{ "/some/path" }} }
+    /// use iai_callgrind::{
+    ///     binary_benchmark, binary_benchmark_group, main, BinaryBenchmarkConfig, Command,
+    ///     RegressionPolicy,
+    /// };
+    ///
+    /// #[binary_benchmark(
+    ///     config = BinaryBenchmarkConfig::default().regression_policy(RegressionPolicy::Ignore)
+    /// )]
+    /// fn bench_binary() -> iai_callgrind::Command {
+    ///     Command::new(env!("CARGO_BIN_EXE_my-echo")).arg("100").build()
+    /// }
+    ///
+    /// binary_benchmark_group!(name = some_group; benchmarks = bench_binary);
+    /// # fn main() {
+    /// main!(binary_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn regression_policy(&mut self, regression_policy: RegressionPolicy) -> &mut Self {
+        self.0.regression_policy = Some(regression_policy);
+        self
+    }
+
+    /// Declare the amount of bytes or elements processed by a single run of the [`Command`]
+    ///
+    /// See also [`crate::LibraryBenchmarkConfig::throughput`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # macro_rules! env { ($m:tt) => {{ "/some/path" }} }
+    /// use iai_callgrind::{
+    ///     binary_benchmark, binary_benchmark_group, main, BinaryBenchmarkConfig, Command,
+    ///     Throughput,
+    /// };
+    ///
+    /// #[binary_benchmark]
+    /// #[bench::lines(
+    ///     config = BinaryBenchmarkConfig::default().throughput(Throughput::Elements(100))
+    /// )]
+    /// fn bench_binary() -> iai_callgrind::Command {
+    ///     Command::new(env!("CARGO_BIN_EXE_my-echo")).arg("100").build()
+    /// }
+    ///
+    /// binary_benchmark_group!(name = some_group; benchmarks = bench_binary);
+    /// # fn main() {
+    /// main!(binary_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn throughput(&mut self, throughput: Throughput) -> &mut Self {
+        self.0.throughput = Some(throughput);
+        self
+    }
 }
 
 impl BinaryBenchmarkGroup {
@@ -6,8 +6,8 @@ use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
 
 use super::{
-    CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DhatMetric, DhatMetrics, Direction,
-    ErrorMetric, EventKind, FlamegraphKind, Limit, ValgrindTool, __internal,
+    __internal, CacheModel, CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DhatMetric,
+    DhatMetrics, Direction, ErrorMetric, EventKind, FlamegraphKind, Limit, ValgrindTool,
 };
 use crate::EntryPoint;
 
@@ -61,6 +61,42 @@ pub struct Bbv(__internal::InternalTool);
 #[derive(Debug, Clone, IntoInner, AsRef)]
 pub struct Cachegrind(__internal::InternalTool);
 
+/// The `CallGraphConfig` to export the callgrind call graph
+///
+/// The call graph is exported to a DOT file (located in the `target/iai` directory next to the
+/// callgrind output files) which can be rendered with [graphviz](https://graphviz.org/), for
+/// example with `dot -Tsvg callgrind.some_func.total.Ir.callgraph.dot -o callgraph.svg`. Like
+/// `gprof2dot`, the nodes are the functions with their inclusive costs and the edges are the calls
+/// with their inclusive costs and call counts. If a baseline is present the nodes are colored by
+/// the difference of their inclusive costs to the baseline: regressions in red and improvements in
+/// green. Otherwise, the nodes are colored by their inclusive costs.
+///
+/// Additionally, the callgrind output files are pruned to the subtree of the [`EntryPoint`] and
+/// written next to the original files with a `.pruned` suffix. These files are usually much
+/// smaller than the full profile including the benchmark harness and can be opened with
+/// `KCachegrind` or `callgrind_annotate`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+/// use iai_callgrind::{LibraryBenchmarkConfig, CallGraphConfig, main, Callgrind};
+/// # #[library_benchmark]
+/// # fn some_func() {}
+/// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+/// # fn main() {
+/// main!(
+///     config = LibraryBenchmarkConfig::default()
+///                 .tool(Callgrind::default()
+///                     .call_graph(CallGraphConfig::default().node_threshold(1.0))
+///                 );
+///     library_benchmark_groups = some_group
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, IntoInner, AsRef)]
+pub struct CallGraphConfig(__internal::InternalCallGraphConfig);
+
 /// The configuration for Callgrind
 ///
 /// Can be specified in [`crate::LibraryBenchmarkConfig::tool`] or
@@ -86,6 +122,88 @@ pub struct Cachegrind(__internal::InternalTool);
 #[derive(Debug, Clone, IntoInner, AsRef)]
 pub struct Callgrind(__internal::InternalTool);
 
+/// A limit of the callgrind metrics of benchmarks relative to other benchmarks with the same id
+///
+/// Can be specified in the `compare_limits` argument of the [`crate::library_benchmark_group`] and
+/// [`crate::binary_benchmark_group`] macros. The benchmarks are identified by `function::id`, where
+/// `function` is the name of the benchmark function and `id` is the id of the bench (the part
+/// after the `::` in `#[bench::id(...)]`). Both parts can be matched with glob patterns which
+/// follow the same rules as [`EntryPoint::Custom`].
+///
+/// After a benchmark has run, it is compared with all benchmarks of the same group and id which
+/// have run before. If the limits of a matching `CompareLimit` are exceeded, the regressions are
+/// reported like regressions of the soft and hard limits and the benchmark run fails with exit
+/// code `3`.
+///
+/// # Examples
+///
+/// ```rust
+/// use iai_callgrind::{
+///     library_benchmark, library_benchmark_group, main, CompareLimit, EventKind,
+/// };
+///
+/// #[library_benchmark]
+/// #[bench::small(10)]
+/// fn baseline_impl(value: u64) -> u64 {
+///     std::hint::black_box(value)
+/// }
+///
+/// #[library_benchmark]
+/// #[bench::small(10)]
+/// fn optimized(value: u64) -> u64 {
+///     std::hint::black_box(value)
+/// }
+///
+/// // The `optimized` benchmarks must use at most 80% of the instructions of the `baseline_impl`
+/// // benchmarks with the same id
+/// library_benchmark_group!(
+///     name = my_group;
+///     compare_limits = [
+///         CompareLimit::new("optimized::*", "baseline_impl::*").limits([(EventKind::Ir, 80f64)])
+///     ];
+///     benchmarks = baseline_impl, optimized
+/// );
+///
+/// # fn main() {
+/// main!(library_benchmark_groups = my_group);
+/// # }
+/// ```
+#[derive(Debug, Clone, IntoInner, AsRef)]
+pub struct CompareLimit(__internal::InternalCompareLimit);
+
+/// The `CyclesModel` of the custom cycles estimation ([`EventKind::CustomCycles`])
+///
+/// The [`EventKind::EstimatedCycles`] use fixed weights for the cache hits of each level. With a
+/// `CyclesModel` the weights can be adjusted to the latencies of the target hardware. The custom
+/// cycles are the sum of the [`EventKind::L1hits`], [`EventKind::LLhits`] and
+/// [`EventKind::RamHits`] multiplied with their weight plus the mispredicted conditional branches
+/// ([`EventKind::Bcm`]) and indirect branches ([`EventKind::Bim`]) multiplied with their penalty.
+/// The default weights are the weights of the [`EventKind::EstimatedCycles`] (`1`, `5` and `35`)
+/// without any penalties for branch mispredictions.
+///
+/// Can be specified in [`Callgrind::cycles_model`].
+///
+/// # Example
+///
+/// ```rust
+/// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+/// use iai_callgrind::{main, Callgrind, CyclesModel, LibraryBenchmarkConfig};
+/// # #[library_benchmark]
+/// # fn some_func() {}
+/// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+/// # fn main() {
+/// main!(
+///     config = LibraryBenchmarkConfig::default()
+///         .tool(Callgrind::default()
+///             .cycles_model(CyclesModel::default().ll_hits(12).ram_hits(200))
+///         );
+///     library_benchmark_groups = some_group
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, IntoInner, AsRef)]
+pub struct CyclesModel(__internal::InternalCyclesModel);
+
 /// The configuration for Dhat
 ///
 /// Can be specified in [`crate::LibraryBenchmarkConfig::tool`] or
@@ -280,6 +398,21 @@ pub struct Memcheck(__internal::InternalTool);
 #[derive(Debug, Clone, Default, IntoInner, AsRef)]
 pub struct OutputFormat(__internal::InternalOutputFormat);
 
+/// A named region of a callgrind benchmark which is reported separately
+///
+/// A region is defined by a function glob pattern with the same rules as [`EntryPoint::Custom`].
+/// Use it with [`Callgrind::regions`].
+///
+/// # Examples
+///
+/// ```
+/// use iai_callgrind::{EventKind, Region};
+///
+/// let region = Region::new("parse", "my_crate::parse*").soft_limits([(EventKind::Ir, 5f64)]);
+/// ```
+#[derive(Debug, Clone, IntoInner, AsRef)]
+pub struct Region(__internal::InternalRegion);
+
 impl Bbv {
     /// Create a new `BBV` configuration with initial command-line arguments
     ///
@@ -489,6 +622,7 @@ impl Cachegrind {
                 __internal::InternalCachegrindRegressionConfig {
                     soft_limits: iter.collect(),
                     hard_limits: Vec::default(),
+                    improvements: Vec::default(),
                     fail_fast: None,
                 },
             ));
@@ -535,6 +669,44 @@ impl Cachegrind {
                 __internal::InternalCachegrindRegressionConfig {
                     soft_limits: Vec::default(),
                     hard_limits: iter.collect(),
+                    improvements: Vec::default(),
+                    fail_fast: None,
+                },
+            ));
+        }
+        self
+    }
+
+    /// Set the minimum improvements in percent which are expected from the benchmark
+    ///
+    /// Same as [`Callgrind::expected_improvements`] but for [`CachegrindMetrics`].
+    ///
+    /// # Examples
+    ///
+    /// The instructions are expected to drop by at least 5%:
+    ///
+    /// ```
+    /// use iai_callgrind::{Cachegrind, CachegrindMetric};
+    ///
+    /// let config = Cachegrind::default().expected_improvements([(CachegrindMetric::Ir, 5f64)]);
+    /// ```
+    pub fn expected_improvements<K, T>(&mut self, improvements: T) -> &mut Self
+    where
+        K: Into<CachegrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        let iter = improvements.into_iter().map(|(k, l)| (k.into(), l));
+
+        if let Some(__internal::InternalToolRegressionConfig::Cachegrind(config)) =
+            &mut self.0.regression_config
+        {
+            config.improvements.extend(iter);
+        } else {
+            self.0.regression_config = Some(__internal::InternalToolRegressionConfig::Cachegrind(
+                __internal::InternalCachegrindRegressionConfig {
+                    soft_limits: Vec::default(),
+                    hard_limits: Vec::default(),
+                    improvements: iter.collect(),
                     fail_fast: None,
                 },
             ));
@@ -564,12 +736,35 @@ impl Cachegrind {
                 __internal::InternalCachegrindRegressionConfig {
                     soft_limits: Vec::default(),
                     hard_limits: Vec::default(),
+                    improvements: Vec::default(),
                     fail_fast: Some(value),
                 },
             ));
         }
         self
     }
+
+    /// Run the cache simulation with the cache hierarchy of a [`CacheModel`]
+    ///
+    /// See also [`Callgrind::cache_model`] for more details. Note the cache simulation of
+    /// cachegrind is switched on if a cache model is selected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{CacheModel, Cachegrind};
+    ///
+    /// let config = Cachegrind::default()
+    ///     .cache_model(CacheModel::Desktop)
+    ///     .cache_model(CacheModel::Embedded);
+    /// ```
+    pub fn cache_model(&mut self, cache_model: CacheModel) -> &mut Self {
+        self.0
+            .cache_models
+            .get_or_insert_with(Vec::new)
+            .push(cache_model);
+        self
+    }
 }
 
 impl Default for Cachegrind {
@@ -578,6 +773,59 @@ impl Default for Cachegrind {
     }
 }
 
+impl CallGraphConfig {
+    /// The [`EventKind`] used to calculate the costs of the nodes and edges
+    ///
+    /// The default is [`EventKind::Ir`]. Note it is an error to specify an [`EventKind`] which
+    /// isn't recorded by callgrind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{CallGraphConfig, EventKind};
+    ///
+    /// let config = CallGraphConfig::default().event_kind(EventKind::EstimatedCycles);
+    /// ```
+    pub fn event_kind(&mut self, event_kind: EventKind) -> &mut Self {
+        self.0.event_kind = Some(event_kind);
+        self
+    }
+
+    /// Hide nodes and edges with inclusive costs below this percentage of the total costs
+    ///
+    /// The total costs are the inclusive costs of the [`EntryPoint`] if present. The default is
+    /// `0.5` (percent).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::CallGraphConfig;
+    ///
+    /// let config = CallGraphConfig::default().node_threshold(2.0);
+    /// ```
+    pub fn node_threshold(&mut self, percentage: f64) -> &mut Self {
+        self.0.node_threshold = Some(percentage);
+        self
+    }
+
+    /// If true, write the callgrind output files pruned to the subtree of the [`EntryPoint`]
+    ///
+    /// The default is `true`. If the [`EntryPoint`] is [`EntryPoint::None`], no pruned files are
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::CallGraphConfig;
+    ///
+    /// let config = CallGraphConfig::default().prune(false);
+    /// ```
+    pub fn prune(&mut self, value: bool) -> &mut Self {
+        self.0.prune = Some(value);
+        self
+    }
+}
+
 impl Callgrind {
     /// Create a new `Callgrind` configuration with initial command-line arguments
     ///
@@ -783,6 +1031,7 @@ impl Callgrind {
                 __internal::InternalCallgrindRegressionConfig {
                     soft_limits: iter.collect(),
                     hard_limits: Vec::default(),
+                    improvements: Vec::default(),
                     fail_fast: None,
                 },
             ));
@@ -857,6 +1106,47 @@ impl Callgrind {
                 __internal::InternalCallgrindRegressionConfig {
                     soft_limits: Vec::default(),
                     hard_limits: iter.collect(),
+                    improvements: Vec::default(),
+                    fail_fast: None,
+                },
+            ));
+        }
+        self
+    }
+
+    /// Set the minimum improvements in percent which are expected from the benchmark
+    ///
+    /// Verifies an optimization: A metric which didn't decrease by at least the given percentage
+    /// compared to the old run or baseline fails the benchmark run. Like soft limits, expected
+    /// improvements are only checked if there is an old run or baseline to compare against. The
+    /// percentage has to be positive.
+    ///
+    /// # Examples
+    ///
+    /// The instructions are expected to drop by at least 5%:
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default().expected_improvements([(EventKind::Ir, 5f64)]);
+    /// ```
+    pub fn expected_improvements<K, T>(&mut self, improvements: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        let iter = improvements.into_iter().map(|(k, l)| (k.into(), l));
+
+        if let Some(__internal::InternalToolRegressionConfig::Callgrind(config)) =
+            &mut self.0.regression_config
+        {
+            config.improvements.extend(iter);
+        } else {
+            self.0.regression_config = Some(__internal::InternalToolRegressionConfig::Callgrind(
+                __internal::InternalCallgrindRegressionConfig {
+                    soft_limits: Vec::default(),
+                    hard_limits: Vec::default(),
+                    improvements: iter.collect(),
                     fail_fast: None,
                 },
             ));
@@ -864,161 +1154,1069 @@ impl Callgrind {
         self
     }
 
-    /// If set to true, then the benchmarks fail on the first encountered regression
+    /// If set to true, then the benchmarks fail on the first encountered regression
+    ///
+    /// The default is `false` and the whole benchmark run fails with a regression error after all
+    /// benchmarks have been run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::Callgrind;
+    ///
+    /// let config = Callgrind::default().fail_fast(true);
+    /// ```
+    pub fn fail_fast(&mut self, value: bool) -> &mut Self {
+        if let Some(__internal::InternalToolRegressionConfig::Callgrind(config)) =
+            &mut self.0.regression_config
+        {
+            config.fail_fast = Some(value);
+        } else {
+            self.0.regression_config = Some(__internal::InternalToolRegressionConfig::Callgrind(
+                __internal::InternalCallgrindRegressionConfig {
+                    soft_limits: Vec::default(),
+                    hard_limits: Vec::default(),
+                    improvements: Vec::default(),
+                    fail_fast: Some(value),
+                },
+            ));
+        }
+        self
+    }
+
+    /// Report the inclusive metrics of the functions matching the glob patterns
+    ///
+    /// The glob patterns follow the same rules as [`EntryPoint::Custom`]: A `*` matches any
+    /// sequence of characters and a `?` matches a single character. For each glob pattern, the
+    /// inclusive costs of all matching functions are summed up and shown as an additional group of
+    /// metrics below the callgrind metrics of the benchmark. Direct calls from one matching
+    /// function to another matching function are only counted once, but indirect calls through a
+    /// function which doesn't match are counted twice. Like the metrics of the whole benchmark,
+    /// the metrics of the functions are compared with the baseline and regression checks can be
+    /// applied with [`Callgrind::function_soft_limits`] and [`Callgrind::function_hard_limits`].
+    ///
+    /// Note that the inclusive metrics are only collected within the [`EntryPoint`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Callgrind::default().function_metrics(["my_crate::decoder::*"]));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// The output then looks similar to this:
+    ///
+    /// ```text
+    /// lib_bench_some::some_group::some_func
+    ///   Instructions:                        1353|1353                 (No change)
+    ///   ...
+    ///   ## Function my_crate::decoder::*
+    ///   Instructions:                         800|800                  (No change)
+    ///   ...
+    /// ```
+    pub fn function_metrics<I, T>(&mut self, functions: T) -> &mut Self
+    where
+        I: Into<String>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0
+            .function_metrics
+            .get_or_insert_with(Default::default)
+            .functions
+            .extend(functions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Configure the soft limits for the metrics of the [`Callgrind::function_metrics`]
+    ///
+    /// The soft limits are applied to the metrics of each glob pattern and work the same way as
+    /// the soft limits of the whole benchmark ([`Callgrind::soft_limits`]). There are no default
+    /// soft limits for the metrics of functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default()
+    ///     .function_metrics(["my_crate::decoder::*"])
+    ///     .function_soft_limits([(EventKind::Ir, 5f64)]);
+    /// ```
+    pub fn function_soft_limits<K, T>(&mut self, soft_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        self.0
+            .function_metrics
+            .get_or_insert_with(Default::default)
+            .soft_limits
+            .extend(soft_limits.into_iter().map(|(k, l)| (k.into(), l)));
+        self
+    }
+
+    /// Configure the hard limits for the metrics of the [`Callgrind::function_metrics`]
+    ///
+    /// The hard limits are applied to the metrics of each glob pattern and work the same way as
+    /// the hard limits of the whole benchmark ([`Callgrind::hard_limits`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default()
+    ///     .function_metrics(["my_crate::decoder::*"])
+    ///     .function_hard_limits([(EventKind::Ir, 10_000)]);
+    /// ```
+    pub fn function_hard_limits<K, L, T>(&mut self, hard_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        L: Into<Limit>,
+        T: IntoIterator<Item = (K, L)>,
+    {
+        self.0
+            .function_metrics
+            .get_or_insert_with(Default::default)
+            .hard_limits
+            .extend(hard_limits.into_iter().map(|(k, l)| (k.into(), l.into())));
+        self
+    }
+
+    /// Report the metrics of the named [`Region`]s separately
+    ///
+    /// Callgrind is instructed to dump the collected metrics when entering and leaving the
+    /// function of a [`Region`] (`--dump-before=<function>` and `--dump-after=<function>`). The
+    /// metrics of each region are shown as a separate group below the benchmark headline,
+    /// compared with the same region of the baseline and checked against the limits of the
+    /// [`Region`]. All metrics outside of any region are shown in a separate group and the total
+    /// over all groups is shown as usual.
+    ///
+    /// Note that regions should not be nested and the functions of regions should not be called
+    /// recursively. Otherwise, the metrics are split at the innermost call of the function.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, EventKind, LibraryBenchmarkConfig, Region};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Callgrind::default().regions([
+    ///             Region::new("parse", "my_crate::parse").soft_limits([(EventKind::Ir, 5f64)]),
+    ///             Region::new("serialize", "my_crate::serialize")
+    ///                 .hard_limits([(EventKind::Ir, 10_000)]),
+    ///         ]));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn regions<I, T>(&mut self, regions: T) -> &mut Self
+    where
+        I: Into<__internal::InternalRegion>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0
+            .regions
+            .get_or_insert_with(Default::default)
+            .extend(regions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Option to produce flamegraphs from callgrind output with a [`crate::FlamegraphConfig`]
+    ///
+    /// The flamegraphs are usable but still in an experimental stage. Callgrind lacks the tool like
+    /// `cg_diff` for cachegrind to compare two different profiles. Flamegraphs on the other hand
+    /// can bridge the gap and be [`FlamegraphKind::Differential`] to compare two benchmark runs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// use iai_callgrind::{
+    ///     LibraryBenchmarkConfig, main, FlamegraphConfig, FlamegraphKind, Callgrind
+    /// };
+    ///
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Callgrind::default()
+    ///             .flamegraph(FlamegraphConfig::default()
+    ///                 .kind(FlamegraphKind::Differential)
+    ///             )
+    ///         );
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn flamegraph<T>(&mut self, flamegraph: T) -> &mut Self
+    where
+        T: Into<__internal::InternalFlamegraphConfig>,
+    {
+        self.0.flamegraph_config = Some(__internal::InternalToolFlamegraphConfig::Callgrind(
+            flamegraph.into(),
+        ));
+        self
+    }
+
+    /// Option to export the call graph to a DOT file with a [`crate::CallGraphConfig`]
+    ///
+    /// See [`crate::CallGraphConfig`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// use iai_callgrind::{CallGraphConfig, Callgrind, LibraryBenchmarkConfig, main};
+    ///
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Callgrind::default().call_graph(CallGraphConfig::default()));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn call_graph<T>(&mut self, call_graph: T) -> &mut Self
+    where
+        T: Into<__internal::InternalCallGraphConfig>,
+    {
+        self.0.call_graph_config = Some(call_graph.into());
+        self
+    }
+
+    /// Customize the format of the callgrind output
+    ///
+    /// This option allows customizing the output format of callgrind metrics. It does not set any
+    /// flags for the callgrind execution (i.e. `--branch-sim=yes`) which actually enable the
+    /// collection of these metrics. Consult the docs of [`EventKind`] and [`CallgrindMetrics`] to
+    /// see which flag is necessary to enable the collection of a specific metric. The rules:
+    ///
+    /// 1. A metric is only printed if specified here
+    /// 2. A metric is not printed if not collected by callgrind
+    /// 3. The order matters
+    /// 4. In case of duplicate specifications of the same metric the first one wins.
+    ///
+    /// Callgrind offers a lot of metrics, so the [`CallgrindMetrics`] enum contains groups of
+    /// [`EventKind`]s, to avoid having to specify all [`EventKind`]s one-by-one (although still
+    /// possible with [`CallgrindMetrics::SingleEvent`]).
+    ///
+    /// All command-line arguments of callgrind and which metric they collect are described in full
+    /// detail in the [callgrind
+    /// documentation](https://valgrind.org/docs/manual/cl-manual.html#cl-manual.options).
+    ///
+    /// # Examples
+    ///
+    /// To enable printing all callgrind metrics specify [`CallgrindMetrics::All`]. `All` callgrind
+    /// metrics include the cache misses ([`EventKind::I1mr`], ...). For example in a library
+    /// benchmark:
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, LibraryBenchmarkConfig, OutputFormat, CallgrindMetrics, Callgrind};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default()
+    ///                      .format([CallgrindMetrics::All]));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// The benchmark is executed with the callgrind arguments set by iai-callgrind which don't
+    /// collect any other metrics than cache misses (`--cache-sim=yes`), so the output will look
+    /// like this:
+    ///
+    /// ```text
+    /// file::some_group::printing cache_misses:
+    ///   Instructions:                        1353|1353                 (No change)
+    ///   Dr:                                   255|255                  (No change)
+    ///   Dw:                                   233|233                  (No change)
+    ///   I1mr:                                  54|54                   (No change)
+    ///   D1mr:                                  12|12                   (No change)
+    ///   D1mw:                                   0|0                    (No change)
+    ///   ILmr:                                  53|53                   (No change)
+    ///   DLmr:                                   3|3                    (No change)
+    ///   DLmw:                                   0|0                    (No change)
+    ///   L1 Hits:                             1775|1775                 (No change)
+    ///   LL Hits:                               10|10                   (No change)
+    ///   RAM Hits:                              56|56                   (No change)
+    ///   Total read+write:                    1841|1841                 (No change)
+    ///   Estimated Cycles:                    3785|3785                 (No change)
+    /// ```
+    pub fn format<I, T>(&mut self, callgrind_metrics: T) -> &mut Self
+    where
+        I: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = I>,
+    {
+        let format = self
+            .0
+            .output_format
+            .get_or_insert_with(|| __internal::InternalToolOutputFormat::Callgrind(Vec::new()));
+
+        if let __internal::InternalToolOutputFormat::Callgrind(items) = format {
+            items.extend(callgrind_metrics.into_iter().map(Into::into));
+        }
+
+        self
+    }
+
+    /// Show the `amount` of the hottest instructions with their disassembly
+    ///
+    /// The instructions of the benchmark executable are sorted by their exclusive instruction
+    /// counts ([`EventKind::Ir`]). Hot instructions which are close to each other in the same
+    /// function are shown as a single block including the instructions in between, so for example
+    /// the whole inner loop of a function is visible with the costs of each instruction. Next to
+    /// the instruction counts, the cache misses are shown if they were collected.
+    ///
+    /// This option implies the callgrind argument `--dump-instr=yes`. The disassembly is created
+    /// with `objdump` which needs to be installed. If `objdump` cannot be found, only the
+    /// addresses of the instructions are shown. Since the addresses usually change between
+    /// compilations, there is no comparison with a baseline.
+    ///
+    /// An `amount` of `0` disables the hot instructions. This option can be overridden on the
+    /// command-line with `--callgrind-hot-instructions`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default().hot_instructions(5));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// The output then looks similar to this:
+    ///
+    /// ```text
+    /// lib_bench_some::some_group::some_func
+    ///   Instructions:                        1353|1353                 (No change)
+    ///   ...
+    ///   ## Hot instructions
+    ///   lib_bench_some::some_func
+    ///        Address         Ir       I1mr       D1mr
+    ///   >   0x8a30        200          0          0  add $0x1,%rcx
+    ///   >   0x8a34        200          0          2  add (%rdx,%rcx,8),%rax
+    ///       0x8a38          -          -          -  nop
+    ///   >   0x8a3c        200          0          0  cmp %rcx,%rsi
+    /// ```
+    pub fn hot_instructions(&mut self, amount: usize) -> &mut Self {
+        self.0.hot_instructions = Some(amount);
+        self
+    }
+
+    /// Show the `amount` of the hottest source lines below the callgrind metrics
+    ///
+    /// The source lines are sorted by their exclusive instruction counts ([`EventKind::Ir`]), i.e.
+    /// the costs of the source line itself without the costs of any called functions. Only source
+    /// lines within the project root are shown and all other source lines (for example of the
+    /// standard library or dependencies) are folded into a single entry. Next to the instruction
+    /// counts, the cache misses are shown if they were collected and are non-zero. If a baseline
+    /// is present, the difference to the same source line in the baseline is shown, too.
+    ///
+    /// An `amount` of `0` disables the hot lines. This option can be overridden on the
+    /// command-line with `--callgrind-hot-lines`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default().hot_lines(10));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// The output then looks similar to this:
+    ///
+    /// ```text
+    /// lib_bench_some::some_group::some_func
+    ///   Instructions:                        1353|1353                 (No change)
+    ///   ...
+    ///   ## Hot lines
+    ///   benches/lib_bench_some.rs:10
+    ///                        let sum = (0..n).map(|x| x * 2).sum();
+    ///   Instructions:                         800|800                  (No change)
+    ///   D1mr:                                   2|2                    (No change)
+    ///   Outside of the project root
+    ///   Instructions:                         553|553                  (No change)
+    /// ```
+    pub fn hot_lines(&mut self, amount: usize) -> &mut Self {
+        self.0.hot_lines = Some(amount);
+        self
+    }
+
+    /// Set the metric which is used to fit the complexity of benchmarks with an input size
+    ///
+    /// The benchmarks of a `#[library_benchmark]` function which are annotated with the `size`
+    /// parameter of the `#[benches]` attribute (like in `#[benches::scale(args = [10, 100, 1000],
+    /// size = |n| n)]`) are fitted to the complexity classes `O(1)`, `O(log n)`, `O(n)`, `O(n log
+    /// n)` and `O(n^2)` after the last benchmark of the function has finished. The default metric
+    /// is [`EventKind::Ir`]. If the fitted complexity class changes compared to the baseline, the
+    /// benchmark is considered regressed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, EventKind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default().complexity_metric(EventKind::EstimatedCycles));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn complexity_metric(&mut self, metric: EventKind) -> &mut Self {
+        self.0.complexity_metric = Some(metric);
+        self
+    }
+
+    /// Run the benchmark `runs` times to measure the noise of the metrics
+    ///
+    /// Cache simulation and multi-threaded benchmarks are not always perfectly deterministic. If
+    /// the benchmark is run more than once, the spread of each [`EventKind`] over all runs is
+    /// recorded and stored next to the output files (and with a saved baseline). The soft limits
+    /// ([`Callgrind::soft_limits`]) are then only exceeded by changes larger than the observed
+    /// noise of the new run and the baseline. The metrics shown in the terminal output are the
+    /// metrics of the last run. Values smaller than `2` disable the repeated runs.
+    ///
+    /// This option can be overridden on the command-line with `--repeat`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default().repeat(5));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn repeat(&mut self, runs: usize) -> &mut Self {
+        self.0.repeat = Some(runs);
+        self
+    }
+
+    /// Set the metrics which are normalized by the [`crate::Throughput`] of a benchmark
+    ///
+    /// If a benchmark declares a [`crate::Throughput`] with
+    /// [`crate::LibraryBenchmarkConfig::throughput`] or
+    /// [`crate::BinaryBenchmarkConfig::throughput`], the metrics of the total are divided by the
+    /// amount of bytes or elements and shown as an additional group of metrics, for example the
+    /// instructions per byte. The default metrics are [`EventKind::Ir`] and
+    /// [`EventKind::EstimatedCycles`]. Metrics which are not collected are ignored.
+    ///
+    /// Note the `old` metrics are divided by the current amount of bytes or elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, EventKind, LibraryBenchmarkConfig, Throughput};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .throughput(Throughput::Bytes(1024))
+    ///         .tool(Callgrind::default().throughput_metrics([EventKind::Ir, EventKind::Dr]));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn throughput_metrics<K, T>(&mut self, metrics: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = K>,
+    {
+        self.0
+            .throughput_config
+            .get_or_insert_with(Default::default)
+            .metrics
+            .extend(metrics.into_iter().map(Into::into));
+        self
+    }
+
+    /// Configure the soft limits for the metrics normalized by the [`crate::Throughput`]
+    ///
+    /// The soft limits work the same way as the soft limits of the whole benchmark
+    /// ([`Callgrind::soft_limits`]). There are no default soft limits for the normalized metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default().throughput_soft_limits([(EventKind::Ir, 5f64)]);
+    /// ```
+    pub fn throughput_soft_limits<K, T>(&mut self, soft_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        self.0
+            .throughput_config
+            .get_or_insert_with(Default::default)
+            .soft_limits
+            .extend(soft_limits.into_iter().map(|(k, l)| (k.into(), l)));
+        self
+    }
+
+    /// Configure the hard limits for the metrics normalized by the [`crate::Throughput`]
+    ///
+    /// In contrast to the hard limits of the whole benchmark ([`Callgrind::hard_limits`]), the
+    /// normalized metrics are floats, so a float limit like `2.5` instructions per byte is
+    /// accepted for all metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default().throughput_hard_limits([(EventKind::Ir, 2.5)]);
+    /// ```
+    pub fn throughput_hard_limits<K, L, T>(&mut self, hard_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        L: Into<Limit>,
+        T: IntoIterator<Item = (K, L)>,
+    {
+        self.0
+            .throughput_config
+            .get_or_insert_with(Default::default)
+            .hard_limits
+            .extend(hard_limits.into_iter().map(|(k, l)| (k.into(), l.into())));
+        self
+    }
+
+    /// Include only the processes whose command matches one of the glob patterns
+    ///
+    /// By default, the metrics of all processes (with `--trace-children=yes`) and threads are
+    /// summed up in the total of the benchmark. If commands are selected, only the processes
+    /// whose command matches at least one of the glob patterns count towards the total and all
+    /// other metrics like the regression checks of the total. The command is the path to the
+    /// executable followed by its arguments as recorded by callgrind, so a pattern usually
+    /// starts with a `*`. It's an error if none of the processes matches.
+    ///
+    /// Configuring any of the thread and process options switches on `--separate-threads=yes`
+    /// even if it was switched off with [`Callgrind::args`].
+    ///
+    /// # Examples
+    ///
+    /// Include only the child processes of a binary benchmark running `my-server`:
+    ///
+    /// ```
+    /// use iai_callgrind::Callgrind;
+    ///
+    /// let config =
+    ///     Callgrind::with_args(["--trace-children=yes"]).include_commands(["*my-server*"]);
+    /// ```
+    pub fn include_commands<I, T>(&mut self, commands: T) -> &mut Self
+    where
+        I: Into<String>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0
+            .threads_config
+            .get_or_insert_with(Default::default)
+            .commands
+            .extend(commands.into_iter().map(Into::into));
+        self
+    }
+
+    /// Include only the threads with the given thread numbers
+    ///
+    /// The thread numbers are assigned by valgrind in the order the threads are created, starting
+    /// with `1` for the main thread of each process. Like [`Callgrind::include_commands`], only
+    /// the selected threads count towards the total of the benchmark.
+    ///
+    /// # Examples
+    ///
+    /// Include only the main thread:
+    ///
+    /// ```
+    /// use iai_callgrind::Callgrind;
+    ///
+    /// let config = Callgrind::default().include_threads([1]);
+    /// ```
+    pub fn include_threads<T>(&mut self, threads: T) -> &mut Self
+    where
+        T: IntoIterator<Item = usize>,
+    {
+        self.0
+            .threads_config
+            .get_or_insert_with(Default::default)
+            .threads
+            .extend(threads);
+        self
+    }
+
+    /// Show the `amount` most expensive threads below the callgrind metrics
+    ///
+    /// The metrics of each selected thread are summed up per process and thread, sorted by the
+    /// instructions ([`EventKind::Ir`]) in descending order and shown with the difference to the
+    /// baseline. The threads of the new run and the baseline are matched by their command and
+    /// thread number and processes with the same command in the order of their pids. All threads
+    /// are stored in the summary of the benchmark, regardless of the `amount`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default().show_threads(5));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// The output then looks similar to this:
+    ///
+    /// ```text
+    /// lib_bench_threads::some_group::some_func
+    ///   Instructions:                      492521|492521               (No change)
+    ///   ...
+    ///   ## Threads (sorted by Ir)
+    ///   pid: 3165 thread: 2 target/release/deps/lib_bench_threads-b6d7f8 --iai-run ...
+    ///   Instructions:                      300122|300122               (No change)
+    ///   ...
+    ///   pid: 3165 thread: 1 target/release/deps/lib_bench_threads-b6d7f8 --iai-run ...
+    ///   Instructions:                      192399|192399               (No change)
+    ///   ...
+    /// ```
+    pub fn show_threads(&mut self, amount: usize) -> &mut Self {
+        self.0
+            .threads_config
+            .get_or_insert_with(Default::default)
+            .show = Some(amount);
+        self
+    }
+
+    /// Configure the soft limits for the metrics of each selected thread
+    ///
+    /// The soft limits work the same way as [`Callgrind::soft_limits`] but are applied to each
+    /// thread of each process separately. There are no default soft limits for threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default().thread_soft_limits([(EventKind::Ir, 5f64)]);
+    /// ```
+    pub fn thread_soft_limits<K, T>(&mut self, soft_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        self.0
+            .threads_config
+            .get_or_insert_with(Default::default)
+            .soft_limits
+            .extend(soft_limits.into_iter().map(|(k, l)| (k.into(), l)));
+        self
+    }
+
+    /// Configure the hard limits for the metrics of each selected thread
+    ///
+    /// The hard limits work the same way as [`Callgrind::hard_limits`] but are applied to each
+    /// thread of each process separately. There are no default hard limits for threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Callgrind, EventKind};
+    ///
+    /// let config = Callgrind::default().thread_hard_limits([(EventKind::Ir, 10_000)]);
+    /// ```
+    pub fn thread_hard_limits<K, L, T>(&mut self, hard_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        L: Into<Limit>,
+        T: IntoIterator<Item = (K, L)>,
+    {
+        self.0
+            .threads_config
+            .get_or_insert_with(Default::default)
+            .hard_limits
+            .extend(hard_limits.into_iter().map(|(k, l)| (k.into(), l.into())));
+        self
+    }
+
+    /// Define a custom metric as arithmetic expression over the callgrind metrics
+    ///
+    /// The `expression` consists of [`EventKind`]s (for example `Ir`, `EstimatedCycles` or `Bcm`,
+    /// case-insensitive), integer or float numbers, the operators `+`, `-`, `*`, `/` and
+    /// parentheses. A division by zero results in `0.0`. The custom metrics are calculated from
+    /// the metrics of the total, shown below them in the terminal output with their `name` and
+    /// stored in the summary of the benchmark. A custom metric is not calculated if one of its
+    /// [`EventKind`]s was not collected, so you may need to switch on the collection of the
+    /// metrics for example with `--cache-sim=yes`. Defining a custom metric with the same `name`
+    /// again replaces the expression.
+    ///
+    /// The `name` may consist of ascii alphanumeric characters and `_`. An invalid `name` or
+    /// `expression` is reported as error when running the benchmarks.
+    ///
+    /// Custom metrics can be defined on the command-line with `--callgrind-custom-metrics`, too.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Callgrind::with_args(["--branch-sim=yes"])
+    ///             .custom_metric("branch_miss_rate", "(Bcm + Bim) / (Bc + Bi) * 100")
+    ///         );
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn custom_metric<N, E>(&mut self, name: N, expression: E) -> &mut Self
+    where
+        N: Into<String>,
+        E: Into<String>,
+    {
+        self.0
+            .custom_metrics
+            .get_or_insert_with(Default::default)
+            .metrics
+            .push((name.into(), expression.into()));
+        self
+    }
+
+    /// Configure the soft limits for the [`Callgrind::custom_metric`]s by name
+    ///
+    /// The soft limits work the same way as the soft limits of the whole benchmark
+    /// ([`Callgrind::soft_limits`]). There are no default soft limits for the custom metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::Callgrind;
+    ///
+    /// let config = Callgrind::default()
+    ///     .custom_metric("ipc_proxy", "Ir / EstimatedCycles")
+    ///     .custom_soft_limits([("ipc_proxy", -5f64)]);
+    /// ```
+    pub fn custom_soft_limits<N, T>(&mut self, soft_limits: T) -> &mut Self
+    where
+        N: Into<String>,
+        T: IntoIterator<Item = (N, f64)>,
+    {
+        self.0
+            .custom_metrics
+            .get_or_insert_with(Default::default)
+            .soft_limits
+            .extend(soft_limits.into_iter().map(|(n, l)| (n.into(), l)));
+        self
+    }
+
+    /// Configure the hard limits for the [`Callgrind::custom_metric`]s by name
+    ///
+    /// A custom metric can be an integer or a float depending on its expression, so integer and
+    /// float limits are accepted for all custom metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::Callgrind;
+    ///
+    /// let config = Callgrind::default()
+    ///     .custom_metric("rw", "Dr + Dw")
+    ///     .custom_hard_limits([("rw", 10_000)]);
+    /// ```
+    pub fn custom_hard_limits<N, L, T>(&mut self, hard_limits: T) -> &mut Self
+    where
+        N: Into<String>,
+        L: Into<Limit>,
+        T: IntoIterator<Item = (N, L)>,
+    {
+        self.0
+            .custom_metrics
+            .get_or_insert_with(Default::default)
+            .hard_limits
+            .extend(hard_limits.into_iter().map(|(n, l)| (n.into(), l.into())));
+        self
+    }
+
+    /// Run the cache simulation with the cache hierarchy of a [`CacheModel`]
+    ///
+    /// A cache model is a named preset for the `--I1`, `--D1` and `--LL` command-line arguments of
+    /// callgrind and takes precedence over these arguments. Calling this method multiple times
+    /// with different cache models runs the benchmark once per cache model. The metrics of each
+    /// run are shown separately and additionally side by side at the end of the benchmark output.
+    ///
+    /// This option can be overridden on the command-line with `--cache-model`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, CacheModel, Callgrind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default()
+    ///                      .cache_model(CacheModel::Desktop)
+    ///                      .cache_model(CacheModel::Server)
+    ///                  );
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn cache_model(&mut self, cache_model: CacheModel) -> &mut Self {
+        self.0
+            .cache_models
+            .get_or_insert_with(Vec::new)
+            .push(cache_model);
+        self
+    }
+
+    /// Estimate the cpu cycles with a custom [`CyclesModel`]
+    ///
+    /// The cycles estimated with the `CyclesModel` are reported as [`EventKind::CustomCycles`]
+    /// next to the [`EventKind::EstimatedCycles`] and can be used like any other [`EventKind`],
+    /// for example in the limits of the regression checks or in the [`FlamegraphConfig`]. Setting
+    /// a `CyclesModel` switches on the cache simulation (`--cache-sim=yes`) and the branch
+    /// simulation (`--branch-sim=yes`) if the model has penalties for branch mispredictions. The
+    /// parameters of the model are stored in the summary of the benchmark.
+    ///
+    /// This option can be overridden on the command-line with `--cycles-model`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// use iai_callgrind::{main, Callgrind, CyclesModel, EventKind, LibraryBenchmarkConfig};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///                  .tool(Callgrind::default()
+    ///                      .cycles_model(CyclesModel::default().ll_hits(12).ram_hits(200).bcm(15))
+    ///                      .soft_limits([(EventKind::CustomCycles, 5.0)])
+    ///                  );
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn cycles_model<T>(&mut self, cycles_model: T) -> &mut Self
+    where
+        T: Into<__internal::InternalCyclesModel>,
+    {
+        self.0.cycles_model = Some(cycles_model.into());
+        self
+    }
+}
+
+impl Default for Callgrind {
+    fn default() -> Self {
+        Self(__internal::InternalTool::new(ValgrindTool::Callgrind))
+    }
+}
+
+impl CompareLimit {
+    /// Create a new `CompareLimit` of the `benchmark` relative to the `baseline` benchmark
+    ///
+    /// Both arguments are glob patterns matching the `function::id` of the benchmarks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::CompareLimit;
+    ///
+    /// let limit = CompareLimit::new("optimized::*", "baseline_impl::*");
+    /// ```
+    pub fn new<T, U>(benchmark: T, baseline: U) -> Self
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        Self(__internal::InternalCompareLimit {
+            baseline: baseline.into(),
+            benchmark: benchmark.into(),
+            limits: Vec::default(),
+        })
+    }
+
+    /// Set the maximum percentage of the metrics of the baseline benchmark
+    ///
+    /// A limit of `80f64` for [`EventKind::Ir`] means the benchmark must not use more than 80% of
+    /// the instructions of the baseline benchmark. Limits above `100f64` allow the benchmark to be
+    /// slower than the baseline benchmark by the amount above `100` percent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{CompareLimit, EventKind};
+    ///
+    /// let limit =
+    ///     CompareLimit::new("optimized::*", "baseline_impl::*").limits([(EventKind::Ir, 80f64)]);
+    /// ```
+    pub fn limits<K, T>(&mut self, limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        self.0
+            .limits
+            .extend(limits.into_iter().map(|(k, l)| (k.into(), l)));
+        self
+    }
+}
+
+impl CyclesModel {
+    /// The penalty in cycles of a mispredicted conditional branch ([`EventKind::Bcm`])
+    ///
+    /// The default is `0`. A penalty greater than `0` switches on the branch simulation of
+    /// callgrind (`--branch-sim=yes`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::CyclesModel;
+    ///
+    /// let model = CyclesModel::default().bcm(15);
+    /// ```
+    pub fn bcm(&mut self, penalty: u64) -> &mut Self {
+        self.0.bcm = penalty;
+        self
+    }
+
+    /// The penalty in cycles of a mispredicted indirect branch ([`EventKind::Bim`])
     ///
-    /// The default is `false` and the whole benchmark run fails with a regression error after all
-    /// benchmarks have been run.
+    /// The default is `0`. A penalty greater than `0` switches on the branch simulation of
+    /// callgrind (`--branch-sim=yes`).
     ///
     /// # Examples
     ///
     /// ```
-    /// use iai_callgrind::Callgrind;
+    /// use iai_callgrind::CyclesModel;
     ///
-    /// let config = Callgrind::default().fail_fast(true);
+    /// let model = CyclesModel::default().bim(20);
     /// ```
-    pub fn fail_fast(&mut self, value: bool) -> &mut Self {
-        if let Some(__internal::InternalToolRegressionConfig::Callgrind(config)) =
-            &mut self.0.regression_config
-        {
-            config.fail_fast = Some(value);
-        } else {
-            self.0.regression_config = Some(__internal::InternalToolRegressionConfig::Callgrind(
-                __internal::InternalCallgrindRegressionConfig {
-                    soft_limits: Vec::default(),
-                    hard_limits: Vec::default(),
-                    fail_fast: Some(value),
-                },
-            ));
-        }
+    pub fn bim(&mut self, penalty: u64) -> &mut Self {
+        self.0.bim = penalty;
         self
     }
 
-    /// Option to produce flamegraphs from callgrind output with a [`crate::FlamegraphConfig`]
+    /// The weight in cycles of a L1 hit ([`EventKind::L1hits`])
     ///
-    /// The flamegraphs are usable but still in an experimental stage. Callgrind lacks the tool like
-    /// `cg_diff` for cachegrind to compare two different profiles. Flamegraphs on the other hand
-    /// can bridge the gap and be [`FlamegraphKind::Differential`] to compare two benchmark runs.
+    /// The default is `1`.
     ///
     /// # Examples
     ///
-    /// ```rust
-    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
-    /// # #[library_benchmark]
-    /// # fn some_func() {}
-    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
-    /// use iai_callgrind::{
-    ///     LibraryBenchmarkConfig, main, FlamegraphConfig, FlamegraphKind, Callgrind
-    /// };
+    /// ```
+    /// use iai_callgrind::CyclesModel;
     ///
-    /// # fn main() {
-    /// main!(
-    ///     config = LibraryBenchmarkConfig::default()
-    ///         .tool(Callgrind::default()
-    ///             .flamegraph(FlamegraphConfig::default()
-    ///                 .kind(FlamegraphKind::Differential)
-    ///             )
-    ///         );
-    ///     library_benchmark_groups = some_group
-    /// );
-    /// # }
+    /// let model = CyclesModel::default().l1_hits(2);
     /// ```
-    pub fn flamegraph<T>(&mut self, flamegraph: T) -> &mut Self
-    where
-        T: Into<__internal::InternalFlamegraphConfig>,
-    {
-        self.0.flamegraph_config = Some(__internal::InternalToolFlamegraphConfig::Callgrind(
-            flamegraph.into(),
-        ));
+    pub fn l1_hits(&mut self, weight: u64) -> &mut Self {
+        self.0.l1_hits = weight;
         self
     }
 
-    /// Customize the format of the callgrind output
+    /// The weight in cycles of a last-level cache hit ([`EventKind::LLhits`])
     ///
-    /// This option allows customizing the output format of callgrind metrics. It does not set any
-    /// flags for the callgrind execution (i.e. `--branch-sim=yes`) which actually enable the
-    /// collection of these metrics. Consult the docs of [`EventKind`] and [`CallgrindMetrics`] to
-    /// see which flag is necessary to enable the collection of a specific metric. The rules:
+    /// The default is `5`.
     ///
-    /// 1. A metric is only printed if specified here
-    /// 2. A metric is not printed if not collected by callgrind
-    /// 3. The order matters
-    /// 4. In case of duplicate specifications of the same metric the first one wins.
+    /// # Examples
     ///
-    /// Callgrind offers a lot of metrics, so the [`CallgrindMetrics`] enum contains groups of
-    /// [`EventKind`]s, to avoid having to specify all [`EventKind`]s one-by-one (although still
-    /// possible with [`CallgrindMetrics::SingleEvent`]).
+    /// ```
+    /// use iai_callgrind::CyclesModel;
     ///
-    /// All command-line arguments of callgrind and which metric they collect are described in full
-    /// detail in the [callgrind
-    /// documentation](https://valgrind.org/docs/manual/cl-manual.html#cl-manual.options).
+    /// let model = CyclesModel::default().ll_hits(12);
+    /// ```
+    pub fn ll_hits(&mut self, weight: u64) -> &mut Self {
+        self.0.ll_hits = weight;
+        self
+    }
+
+    /// The weight in cycles of a RAM hit ([`EventKind::RamHits`])
     ///
-    /// # Examples
+    /// The default is `35`.
     ///
-    /// To enable printing all callgrind metrics specify [`CallgrindMetrics::All`]. `All` callgrind
-    /// metrics include the cache misses ([`EventKind::I1mr`], ...). For example in a library
-    /// benchmark:
+    /// # Examples
     ///
-    /// ```rust
-    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
-    /// use iai_callgrind::{main, LibraryBenchmarkConfig, OutputFormat, CallgrindMetrics, Callgrind};
-    /// # #[library_benchmark]
-    /// # fn some_func() {}
-    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
-    /// # fn main() {
-    /// main!(
-    ///     config = LibraryBenchmarkConfig::default()
-    ///                  .tool(Callgrind::default()
-    ///                      .format([CallgrindMetrics::All]));
-    ///     library_benchmark_groups = some_group
-    /// );
-    /// # }
     /// ```
+    /// use iai_callgrind::CyclesModel;
     ///
-    /// The benchmark is executed with the callgrind arguments set by iai-callgrind which don't
-    /// collect any other metrics than cache misses (`--cache-sim=yes`), so the output will look
-    /// like this:
-    ///
-    /// ```text
-    /// file::some_group::printing cache_misses:
-    ///   Instructions:                        1353|1353                 (No change)
-    ///   Dr:                                   255|255                  (No change)
-    ///   Dw:                                   233|233                  (No change)
-    ///   I1mr:                                  54|54                   (No change)
-    ///   D1mr:                                  12|12                   (No change)
-    ///   D1mw:                                   0|0                    (No change)
-    ///   ILmr:                                  53|53                   (No change)
-    ///   DLmr:                                   3|3                    (No change)
-    ///   DLmw:                                   0|0                    (No change)
-    ///   L1 Hits:                             1775|1775                 (No change)
-    ///   LL Hits:                               10|10                   (No change)
-    ///   RAM Hits:                              56|56                   (No change)
-    ///   Total read+write:                    1841|1841                 (No change)
-    ///   Estimated Cycles:                    3785|3785                 (No change)
+    /// let model = CyclesModel::default().ram_hits(200);
     /// ```
-    pub fn format<I, T>(&mut self, callgrind_metrics: T) -> &mut Self
-    where
-        I: Into<CallgrindMetrics>,
-        T: IntoIterator<Item = I>,
-    {
-        let format = self
-            .0
-            .output_format
-            .get_or_insert_with(|| __internal::InternalToolOutputFormat::Callgrind(Vec::new()));
-
-        if let __internal::InternalToolOutputFormat::Callgrind(items) = format {
-            items.extend(callgrind_metrics.into_iter().map(Into::into));
-        }
-
+    pub fn ram_hits(&mut self, weight: u64) -> &mut Self {
+        self.0.ram_hits = weight;
         self
     }
 }
 
-impl Default for Callgrind {
-    fn default() -> Self {
-        Self(__internal::InternalTool::new(ValgrindTool::Callgrind))
-    }
-}
-
 impl Dhat {
     /// Create a new `Callgrind` configuration with initial command-line arguments
     ///
@@ -1336,6 +2534,7 @@ impl Dhat {
                 __internal::InternalDhatRegressionConfig {
                     soft_limits: iter.collect(),
                     hard_limits: Vec::default(),
+                    improvements: Vec::default(),
                     fail_fast: None,
                 },
             ));
@@ -1385,6 +2584,44 @@ impl Dhat {
                 __internal::InternalDhatRegressionConfig {
                     soft_limits: Vec::default(),
                     hard_limits: iter.collect(),
+                    improvements: Vec::default(),
+                    fail_fast: None,
+                },
+            ));
+        }
+        self
+    }
+
+    /// Set the minimum improvements in percent which are expected from the benchmark
+    ///
+    /// Same as [`Callgrind::expected_improvements`] but for [`DhatMetrics`].
+    ///
+    /// # Examples
+    ///
+    /// The total bytes are expected to drop by at least 5%:
+    ///
+    /// ```
+    /// use iai_callgrind::{Dhat, DhatMetric};
+    ///
+    /// let config = Dhat::default().expected_improvements([(DhatMetric::TotalBytes, 5f64)]);
+    /// ```
+    pub fn expected_improvements<K, T>(&mut self, improvements: T) -> &mut Self
+    where
+        K: Into<DhatMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        let iter = improvements.into_iter().map(|(k, l)| (k.into(), l));
+
+        if let Some(__internal::InternalToolRegressionConfig::Dhat(config)) =
+            &mut self.0.regression_config
+        {
+            config.improvements.extend(iter);
+        } else {
+            self.0.regression_config = Some(__internal::InternalToolRegressionConfig::Dhat(
+                __internal::InternalDhatRegressionConfig {
+                    soft_limits: Vec::default(),
+                    hard_limits: Vec::default(),
+                    improvements: iter.collect(),
                     fail_fast: None,
                 },
             ));
@@ -1414,6 +2651,7 @@ impl Dhat {
                 __internal::InternalDhatRegressionConfig {
                     soft_limits: Vec::default(),
                     hard_limits: Vec::default(),
+                    improvements: Vec::default(),
                     fail_fast: Some(value),
                 },
             ));
@@ -1696,6 +2934,88 @@ impl FlamegraphConfig {
         self.0.min_width = Some(min_width);
         self
     }
+
+    /// Strip the hashes of the function names (for example `::h0123456789abcdef`)
+    ///
+    /// The default is `true`.
+    ///
+    /// Depending on the symbol mangling scheme, the demangled function names end with a hash
+    /// which is of no use in a flamegraph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::FlamegraphConfig;
+    ///
+    /// let config = FlamegraphConfig::default().strip_hashes(false);
+    /// ```
+    pub fn strip_hashes(&mut self, value: bool) -> &mut Self {
+        self.0.strip_hashes = Some(value);
+        self
+    }
+
+    /// Collapse the generic arguments of the function names to `<..>`
+    ///
+    /// The default is `false`.
+    ///
+    /// The function names of heavily generic code can get very long, which makes the flamegraph
+    /// hard to read. For example, `core::ptr::drop_in_place<alloc::vec::Vec<u8>>` is shown as
+    /// `core::ptr::drop_in_place<..>`. Consecutive frames which end up with the same name are
+    /// merged into a single frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::FlamegraphConfig;
+    ///
+    /// let config = FlamegraphConfig::default().collapse_generics(true);
+    /// ```
+    pub fn collapse_generics(&mut self, value: bool) -> &mut Self {
+        self.0.collapse_generics = Some(value);
+        self
+    }
+
+    /// Fold the frames of the standard library crates `std`, `core` and `alloc`
+    ///
+    /// The default is `false`.
+    ///
+    /// The frames of a folded crate are shown as a single node named after the crate, for
+    /// example `std::*`. See also [`FlamegraphConfig::fold_crates`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::FlamegraphConfig;
+    ///
+    /// let config = FlamegraphConfig::default().fold_std(true);
+    /// ```
+    pub fn fold_std(&mut self, value: bool) -> &mut Self {
+        self.0.fold_std = Some(value);
+        self
+    }
+
+    /// Fold the frames of the listed crates into a single node per crate
+    ///
+    /// The crate of a function is the first segment of its path, or the crate of the self type
+    /// for trait implementations like `<serde_json::Value as core::fmt::Debug>::fmt`. Dashes in
+    /// the crate names are replaced with underscores. The frames of a folded crate are shown as a
+    /// single node named after the crate, for example `serde_json::*`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::FlamegraphConfig;
+    ///
+    /// let config = FlamegraphConfig::default().fold_crates(["serde", "serde-json"]);
+    /// ```
+    pub fn fold_crates<I, T>(&mut self, crates: T) -> &mut Self
+    where
+        I: Into<String>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0.fold_crates.extend(crates.into_iter().map(Into::into));
+        self
+    }
 }
 
 impl Helgrind {
@@ -2245,3 +3565,73 @@ impl OutputFormat {
         self
     }
 }
+
+impl Region {
+    /// Create a new `Region` with the `name` shown in the output and the `function` glob pattern
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::Region;
+    ///
+    /// let region = Region::new("parse", "my_crate::parse");
+    /// ```
+    pub fn new<N, F>(name: N, function: F) -> Self
+    where
+        N: Into<String>,
+        F: Into<String>,
+    {
+        Self(__internal::InternalRegion {
+            function: function.into(),
+            hard_limits: Vec::default(),
+            name: name.into(),
+            soft_limits: Vec::default(),
+        })
+    }
+
+    /// Configure the soft limits for the metrics of this region
+    ///
+    /// The soft limits work the same way as [`Callgrind::soft_limits`] but there are no default
+    /// soft limits for regions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{EventKind, Region};
+    ///
+    /// let region = Region::new("parse", "my_crate::parse").soft_limits([(EventKind::Ir, 5f64)]);
+    /// ```
+    pub fn soft_limits<K, T>(&mut self, soft_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        T: IntoIterator<Item = (K, f64)>,
+    {
+        self.0
+            .soft_limits
+            .extend(soft_limits.into_iter().map(|(k, l)| (k.into(), l)));
+        self
+    }
+
+    /// Configure the hard limits for the metrics of this region
+    ///
+    /// The hard limits work the same way as [`Callgrind::hard_limits`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{EventKind, Region};
+    ///
+    /// let region = Region::new("parse", "my_crate::parse").hard_limits([(EventKind::Ir, 10_000)]);
+    /// ```
+    pub fn hard_limits<K, L, T>(&mut self, hard_limits: T) -> &mut Self
+    where
+        K: Into<CallgrindMetrics>,
+        L: Into<Limit>,
+        T: IntoIterator<Item = (K, L)>,
+    {
+        self.0
+            .hard_limits
+            .extend(hard_limits.into_iter().map(|(k, l)| (k.into(), l.into())));
+        self
+    }
+}
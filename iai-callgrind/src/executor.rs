@@ -0,0 +1,155 @@
+//! The executors to run `async` library benchmarks
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+
+/// A minimal single-threaded executor which runs the future on the current thread
+///
+/// This is the default executor of `#[library_benchmark(async)]`. It polls the future until it
+/// is ready and parks the current thread in between if the future is pending. There is no
+/// reactor, timer or task spawning, so this executor is only suitable for futures which are woken
+/// by another thread or don't wait on any io at all.
+///
+/// # Examples
+///
+/// ```rust
+/// use iai_callgrind::{Executor, LocalExecutor};
+///
+/// assert_eq!(LocalExecutor.block_on(async { 42 }), 42);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalExecutor;
+
+/// A tokio current-thread runtime as [`Executor`] for `async` library benchmarks
+///
+/// The runtime is built with all drivers (io, time) enabled.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use iai_callgrind::{library_benchmark, TokioExecutor};
+///
+/// async fn my_async_func() -> u64 {
+///     tokio::task::yield_now().await;
+///     42
+/// }
+///
+/// #[library_benchmark(async, executor = TokioExecutor::new())]
+/// async fn bench_async() -> u64 {
+///     std::hint::black_box(my_async_func().await)
+/// }
+/// # fn main() {}
+/// ```
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioExecutor(tokio::runtime::Runtime);
+
+#[derive(Debug)]
+struct ThreadWaker(Thread);
+
+/// An executor drives the future of an `async` library benchmark to completion
+///
+/// The benchmark function annotated with `#[library_benchmark(async)]` is turned into a
+/// synchronous function which passes the future to [`Executor::block_on`] of the executor
+/// specified with the `executor` parameter of the attribute ([`LocalExecutor`] by default). Only
+/// the polls of the future are attributed to the benchmark, so the costs of the executor itself
+/// (setting up a runtime, scheduling, parking, ...) are not part of the metrics.
+///
+/// With the `tokio` feature, this trait is implemented for the [`tokio::runtime::Runtime`]
+/// and there's [`TokioExecutor`] which uses a current-thread runtime.
+///
+/// # Examples
+///
+/// A custom executor wrapping another executor:
+///
+/// ```rust
+/// use std::future::Future;
+///
+/// use iai_callgrind::{Executor, LocalExecutor};
+///
+/// struct MyExecutor;
+///
+/// impl Executor for MyExecutor {
+///     fn block_on<F: Future>(&self, future: F) -> F::Output {
+///         LocalExecutor.block_on(future)
+///     }
+/// }
+///
+/// assert_eq!(MyExecutor.block_on(async { 42 }), 42);
+/// ```
+pub trait Executor {
+    /// Run the `future` to completion on the current thread and return its output
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+}
+
+#[cfg(feature = "tokio")]
+impl TokioExecutor {
+    /// Create a new `TokioExecutor` with a current-thread runtime
+    ///
+    /// # Panics
+    ///
+    /// If the tokio runtime could not be built
+    pub fn new() -> Self {
+        Self(
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Building the tokio current-thread runtime should succeed"),
+        )
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Default for TokioExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Executor for LocalExecutor {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Executor for TokioExecutor {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Executor for tokio::runtime::Runtime {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        Self::block_on(self, future)
+    }
+}
+
+impl<T: Executor + ?Sized> Executor for &T {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        (**self).block_on(future)
+    }
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
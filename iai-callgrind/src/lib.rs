@@ -399,6 +399,8 @@ pub mod client_requests;
 #[cfg(feature = "default")]
 mod common;
 #[cfg(feature = "default")]
+mod executor;
+#[cfg(feature = "default")]
 mod lib_bench;
 #[cfg(feature = "default")]
 mod macros;
@@ -411,20 +413,24 @@ pub use bin_bench::{
 pub use bincode;
 #[cfg(feature = "default")]
 pub use common::{
-    Bbv, Cachegrind, Callgrind, Dhat, Drd, FlamegraphConfig, Helgrind, Massif, Memcheck,
-    OutputFormat,
+    Bbv, Cachegrind, CallGraphConfig, Callgrind, CompareLimit, CyclesModel, Dhat, Drd,
+    FlamegraphConfig, Helgrind, Massif, Memcheck, OutputFormat, Region,
 };
 #[cfg(feature = "client_requests_defs")]
 pub use cty;
+#[cfg(all(feature = "default", feature = "tokio"))]
+pub use executor::TokioExecutor;
+#[cfg(feature = "default")]
+pub use executor::{Executor, LocalExecutor};
 #[cfg(feature = "default")]
 pub use iai_callgrind_macros::{binary_benchmark, library_benchmark};
 // Only add enums here. Do not re-export structs from the runner api directly. See the
 // documentation in `__internal::mod` for more details.
 #[cfg(feature = "default")]
 pub use iai_callgrind_runner::api::{
-    CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DelayKind, DhatMetric, DhatMetrics,
-    Direction, EntryPoint, ErrorMetric, EventKind, ExitWith, FlamegraphKind, Limit, Pipe, Stdin,
-    Stdio, ValgrindTool,
+    CacheModel, CachegrindMetric, CachegrindMetrics, CallgrindMetrics, DelayKind, DhatMetric,
+    DhatMetrics, Direction, EntryPoint, ErrorMetric, EventKind, ExitWith, FlamegraphKind,
+    InstrumentationMode, Limit, Pipe, RegressionPolicy, Stdin, Stdio, Throughput, ValgrindTool,
 };
 #[cfg(feature = "default")]
 pub use lib_bench::LibraryBenchmarkConfig;
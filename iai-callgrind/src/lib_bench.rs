@@ -2,7 +2,7 @@ use std::ffi::OsString;
 
 use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
-use iai_callgrind_runner::api::ValgrindTool;
+use iai_callgrind_runner::api::{InstrumentationMode, RegressionPolicy, Throughput, ValgrindTool};
 
 use crate::__internal;
 
@@ -425,4 +425,187 @@ impl LibraryBenchmarkConfig {
         self.0.output_format = Some(output_format.into());
         self
     }
+
+    /// Set the [`RegressionPolicy`] how failed regression checks of the benchmarks are treated
+    ///
+    /// The default is [`RegressionPolicy::Enforce`]: Any regression fails the benchmark run. With
+    /// [`RegressionPolicy::WarnOnly`] regressions are still checked and reported but don't fail
+    /// the benchmark run. With [`RegressionPolicy::Ignore`] no regression checks are performed at
+    /// all for example for benchmarks which are known to be noisy. The policy can be overwritten
+    /// for all benchmarks on the command-line with `--regression-policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{
+    ///     library_benchmark, library_benchmark_group, main, LibraryBenchmarkConfig,
+    ///     RegressionPolicy,
+    /// };
+    /// use std::hint::black_box;
+    ///
+    /// #[library_benchmark(
+    ///     config = LibraryBenchmarkConfig::default().regression_policy(RegressionPolicy::WarnOnly)
+    /// )]
+    /// fn bench_noisy() -> Vec<u64> {
+    ///     black_box((0..100).collect())
+    /// }
+    ///
+    /// library_benchmark_group!(name = some_group; benchmarks = bench_noisy);
+    /// # fn main() {
+    /// main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn regression_policy(&mut self, regression_policy: RegressionPolicy) -> &mut Self {
+        self.0.regression_policy = Some(regression_policy);
+        self
+    }
+
+    /// Declare the amount of bytes or elements processed by a single run of the benchmark
+    ///
+    /// The callgrind metrics are then additionally shown normalized by this amount, for example
+    /// as instructions per byte. See [`crate::Callgrind::throughput_metrics`] for the metrics and
+    /// [`crate::Callgrind::throughput_soft_limits`] and [`crate::Callgrind::throughput_hard_limits`]
+    /// for regression checks of the normalized metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{
+    ///     library_benchmark, library_benchmark_group, main, LibraryBenchmarkConfig, Throughput,
+    /// };
+    /// use std::hint::black_box;
+    ///
+    /// #[library_benchmark]
+    /// #[bench::kib(
+    ///     args = [vec![0u8; 1024]],
+    ///     config = LibraryBenchmarkConfig::default().throughput(Throughput::Bytes(1024))
+    /// )]
+    /// fn bench_checksum(bytes: Vec<u8>) -> u64 {
+    ///     black_box(bytes.iter().map(|b| u64::from(*b)).sum())
+    /// }
+    ///
+    /// library_benchmark_group!(name = some_group; benchmarks = bench_checksum);
+    /// # fn main() {
+    /// main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn throughput(&mut self, throughput: Throughput) -> &mut Self {
+        self.0.throughput = Some(throughput);
+        self
+    }
+
+    /// Set the [`InstrumentationMode`] of the benchmarks
+    ///
+    /// The default is [`InstrumentationMode::Default`]: The `setup` and `teardown` functions of
+    /// the `#[bench]` and `#[benches]` attributes run in the same process as the benchmark
+    /// function and are excluded from the callgrind metrics only with the help of the
+    /// [`crate::EntryPoint`]. However, their cache effects still have an influence on the metrics
+    /// of the benchmark function.
+    ///
+    /// With [`InstrumentationMode::ClientRequests`], callgrind and cachegrind run with
+    /// `--instr-atstart=no` (`--instr-at-start=no` for cachegrind) and the benchmark harness
+    /// starts the instrumentation with a client request just before the call of the benchmark
+    /// function and stops it right after the call. This way, `setup` and `teardown` are not
+    /// instrumented at all, including their effects on the cache simulation, and the mode works
+    /// the same way for callgrind and cachegrind. This mode requires the `client_requests`
+    /// feature of `iai-callgrind`. Other tools like DHAT or Memcheck are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{
+    ///     library_benchmark, library_benchmark_group, main, InstrumentationMode,
+    ///     LibraryBenchmarkConfig,
+    /// };
+    /// use std::hint::black_box;
+    ///
+    /// fn setup_worst_case(size: usize) -> Vec<u64> {
+    ///     (0..size as u64).rev().collect()
+    /// }
+    ///
+    /// #[library_benchmark(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .instrumentation_mode(InstrumentationMode::ClientRequests)
+    /// )]
+    /// #[bench::worst_case(args = (1000), setup = setup_worst_case)]
+    /// fn bench_sort(mut data: Vec<u64>) -> Vec<u64> {
+    ///     data.sort_unstable();
+    ///     black_box(data)
+    /// }
+    ///
+    /// library_benchmark_group!(name = some_group; benchmarks = bench_sort);
+    /// # fn main() {
+    /// main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn instrumentation_mode(&mut self, instrumentation_mode: InstrumentationMode) -> &mut Self {
+        self.0.instrumentation_mode = Some(instrumentation_mode);
+        self
+    }
+
+    /// Run the benchmark function `warmup` times before the measured iterations
+    ///
+    /// By default, the benchmark function runs exactly once in a fresh process, so the metrics
+    /// reflect a cold cache. The warmup iterations run in the same process as the measured
+    /// iterations (see [`LibraryBenchmarkConfig::iterations`]), so the caches of callgrind's cache
+    /// simulation are warm when the measurement starts. The first warmup iteration is reported
+    /// separately as the cold iteration in the terminal output and the json summary.
+    ///
+    /// Note that `setup` and `teardown` of the `#[bench]` and `#[benches]` attributes run in
+    /// every iteration. This option applies only to callgrind and can't be combined with
+    /// [`InstrumentationMode::ClientRequests`] or callgrind regions. All other tools run the
+    /// benchmark function once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{library_benchmark, library_benchmark_group, main, LibraryBenchmarkConfig};
+    /// use std::hint::black_box;
+    ///
+    /// #[library_benchmark(config = LibraryBenchmarkConfig::default().warmup(1).iterations(3))]
+    /// fn bench_sum() -> u64 {
+    ///     black_box((0..1000u64).sum())
+    /// }
+    ///
+    /// library_benchmark_group!(name = some_group; benchmarks = bench_sum);
+    /// # fn main() {
+    /// main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn warmup(&mut self, warmup: usize) -> &mut Self {
+        self.0.warmup = Some(warmup);
+        self
+    }
+
+    /// Run the benchmark function `iterations` times and report the average of the iterations
+    ///
+    /// The default is a single iteration. Every iteration is dumped separately by callgrind with
+    /// `--dump-after` and the metrics of the benchmark are the average over the measured
+    /// iterations, rounded to the nearest integer. The [`LibraryBenchmarkConfig::warmup`]
+    /// iterations are not part of the average. Note that other outputs like the function-level
+    /// metrics or flamegraphs still cover all iterations.
+    ///
+    /// `iterations` must be greater than `0`. The same restrictions as for
+    /// [`LibraryBenchmarkConfig::warmup`] apply.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{library_benchmark, library_benchmark_group, main, LibraryBenchmarkConfig};
+    /// use std::hint::black_box;
+    ///
+    /// #[library_benchmark(config = LibraryBenchmarkConfig::default().iterations(5))]
+    /// fn bench_sum() -> u64 {
+    ///     black_box((0..1000u64).sum())
+    /// }
+    ///
+    /// library_benchmark_group!(name = some_group; benchmarks = bench_sum);
+    /// # fn main() {
+    /// main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn iterations(&mut self, iterations: usize) -> &mut Self {
+        self.0.iterations = Some(iterations);
+        self
+    }
 }
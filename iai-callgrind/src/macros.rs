@@ -277,6 +277,7 @@ macro_rules! main {
                         $group::__run_setup(false),
                         $group::__run_teardown(false),
                         $group::__compare_by_id(),
+                        $group::__compare_limits(),
                         $group::__BENCHES
                 );
             )+
@@ -419,6 +420,7 @@ macro_rules! main {
                     stringify!($group).to_owned(),
                     $group::__get_config(),
                     $group::__compare_by_id(),
+                    $group::__compare_limits(),
                     $group::__run_setup(false),
                     $group::__run_teardown(false),
                     $group::__BENCHES
@@ -510,7 +512,22 @@ macro_rules! main {
                                             .next()
                                             .and_then(|a| a.parse::<usize>().ok())
                                     );
-                                    $group::__run(group_index, bench_index, iter_index);
+                                    let options = args_iter.collect::<Vec<String>>();
+                                    if options.iter().any(|a| a == "--iai-client-requests") {
+                                        $crate::__internal::lib_bench::enable_client_requests();
+                                    }
+                                    let iterations = std::hint::black_box(
+                                        options
+                                            .iter()
+                                            .find_map(|a| a.strip_prefix("--iai-iterations="))
+                                            .map_or(1, |a| {
+                                                a.parse::<usize>()
+                                                    .expect("Expecting a valid number of iterations")
+                                            })
+                                    );
+                                    for _ in 0..iterations {
+                                        $group::__run(group_index, bench_index, iter_index);
+                                    }
                                 }
                             }
                         }
@@ -557,6 +574,7 @@ macro_rules! main {
 ///
 /// ```rust
 /// # use iai_callgrind::{binary_benchmark, binary_benchmark_group, BinaryBenchmarkGroup, BinaryBenchmarkConfig};
+/// # use iai_callgrind::{CompareLimit, EventKind};
 /// # fn run_setup() {}
 /// # fn run_teardown() {}
 /// # #[binary_benchmark]
@@ -565,6 +583,7 @@ macro_rules! main {
 ///     name = my_group;
 ///     config = BinaryBenchmarkConfig::default();
 ///     compare_by_id = false;
+///     compare_limits = [CompareLimit::new("bench_*", "bench_binary::*").limits([(EventKind::Ir, 80f64)])];
 ///     setup = run_setup();
 ///     teardown = run_teardown();
 ///     benchmarks = bench_binary
@@ -579,6 +598,10 @@ macro_rules! main {
 /// * __`compare_by_id`__ (optional): The default is false. If true, all commands from the functions
 ///   specified in the `benchmarks` argument, are compared with each other as long as the ids (the
 ///   part after the `::` in `#[bench::id(...)]`) match.
+/// * __`compare_limits`__ (optional): A list of [`crate::CompareLimit`]s. The callgrind metrics of
+///   the benchmarks are checked against the metrics of other benchmarks of this group with the same
+///   id. Exceeded limits are reported as regressions. This argument doesn't depend on
+///   `compare_by_id`.
 /// * __`setup`__ (optional): A function which is executed before all benchmarks in this group
 /// * __`teardown`__ (optional): A function which is executed after all benchmarks in this group
 /// * __`benchmarks`__ (mandatory): A `,`-separated list of `#[binary_benchmark]` annotated function
@@ -791,6 +814,7 @@ macro_rules! binary_benchmark_group {
     (
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = $( $function:ident ),+ $(,)*
@@ -805,6 +829,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks =
@@ -820,6 +845,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
     ) => {
@@ -834,6 +860,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = $( $function:ident ),+ $(,)*
@@ -887,6 +914,14 @@ macro_rules! binary_benchmark_group {
                 comp
             }
 
+            pub fn __compare_limits() -> Vec<$crate::__internal::InternalCompareLimit> {
+                let mut limits = Vec::new();
+                $(
+                    limits = $compare_limits.into_iter().map(Into::into).collect();
+                )?
+                limits
+            }
+
             pub fn __get_config() -> Option<$crate::__internal::InternalBinaryBenchmarkConfig> {
                 let mut config = None;
                 $(
@@ -929,6 +964,7 @@ macro_rules! binary_benchmark_group {
     (
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident: &mut BinaryBenchmarkGroup| $body:expr
@@ -942,6 +978,7 @@ macro_rules! binary_benchmark_group {
     (
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident| $body:expr
@@ -956,6 +993,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident|
@@ -972,6 +1010,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident: &mut BinaryBenchmarkGroup|
@@ -988,6 +1027,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident: &mut BinaryBenchmarkGroup| $body:expr
@@ -1041,6 +1081,14 @@ macro_rules! binary_benchmark_group {
                 comp
             }
 
+            pub fn __compare_limits() -> Vec<$crate::__internal::InternalCompareLimit> {
+                let mut limits = Vec::new();
+                $(
+                    limits = $compare_limits.into_iter().map(Into::into).collect();
+                )?
+                limits
+            }
+
             pub fn __run_bench_setup(
                 group_index: usize, bench_index: usize, iter_index: Option<usize>
             ) {
@@ -1130,6 +1178,7 @@ macro_rules! binary_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr; $(;)* )?
         $( teardown = $teardown:expr; $(;)* )?
         benchmarks = |$group:ident| $body:expr
@@ -1138,6 +1187,7 @@ macro_rules! binary_benchmark_group {
             name = $name;
             $( config = $config; )?
             $( compare_by_id = $compare; )?
+            $( compare_limits = $compare_limits; )?
             $( setup = $setup; )?
             $( teardown = $teardown; )?
             benchmarks = |$group: &mut BinaryBenchmarkGroup| $body
@@ -1176,6 +1226,7 @@ macro_rules! binary_benchmark_group {
 ///
 /// ```rust
 /// # use iai_callgrind::{library_benchmark, library_benchmark_group, LibraryBenchmarkConfig};
+/// # use iai_callgrind::{CompareLimit, EventKind};
 /// # #[library_benchmark]
 /// # fn some_func() {}
 /// fn group_setup() {}
@@ -1184,6 +1235,7 @@ macro_rules! binary_benchmark_group {
 ///     name = my_group;
 ///     config = LibraryBenchmarkConfig::default();
 ///     compare_by_id = false;
+///     compare_limits = [CompareLimit::new("some_*", "some_func::*").limits([(EventKind::Ir, 80f64)])];
 ///     setup = group_setup();
 ///     teardown = group_teardown();
 ///     benchmarks = some_func
@@ -1198,6 +1250,10 @@ macro_rules! binary_benchmark_group {
 /// * __`compare_by_id`__ (optional): The default is false. If true, all benches in the benchmark
 ///   functions specified with the `benchmarks` argument, across any benchmark groups, are compared
 ///   with each other as long as the ids (the part after the `::` in `#[bench::id(...)]`) match.
+/// * __`compare_limits`__ (optional): A list of [`crate::CompareLimit`]s. The callgrind metrics of
+///   the benchmarks are checked against the metrics of other benchmarks of this group with the same
+///   id. Exceeded limits are reported as regressions. This argument doesn't depend on
+///   `compare_by_id`.
 /// * __`setup`__ (optional): A setup function or any valid expression which is run before all
 ///   benchmarks of this group
 /// * __`teardown`__ (optional): A teardown function or any valid expression which is run after all
@@ -1209,6 +1265,7 @@ macro_rules! library_benchmark_group {
     (
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr ; $(;)* )?
         $( teardown = $teardown:expr ; $(;)* )?
         benchmarks = $( $function:ident ),+
@@ -1219,6 +1276,7 @@ macro_rules! library_benchmark_group {
         name = $name:ident;
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr ; $(;)* )?
         $( teardown = $teardown:expr ; $(;)* )?
         benchmarks =
@@ -1232,6 +1290,7 @@ macro_rules! library_benchmark_group {
         name = $name:ident; $(;)*
         $( config = $config:expr ; $(;)* )?
         $( compare_by_id = $compare:literal ; $(;)* )?
+        $( compare_limits = $compare_limits:expr ; $(;)* )?
         $( setup = $setup:expr ; $(;)* )?
         $( teardown = $teardown:expr ; $(;)* )?
         benchmarks = $( $function:ident ),+ $(,)*
@@ -1271,6 +1330,15 @@ macro_rules! library_benchmark_group {
                 comp
             }
 
+            #[inline(never)]
+            pub fn __compare_limits() -> Vec<$crate::__internal::InternalCompareLimit> {
+                let mut limits = Vec::new();
+                $(
+                    limits = $compare_limits.into_iter().map(Into::into).collect();
+                )?
+                limits
+            }
+
             #[inline(never)]
             pub fn __run_setup(__run: bool) -> bool {
                 let mut __has_setup = false;
@@ -1,8 +1,32 @@
+// The `#[binary_benchmark]` attribute doesn't accept other attributes on the benchmark function, so
+// the `allow` for the intentionally non-minimal `!true` is scoped to this module instead
+#[allow(clippy::nonminimal_bool)]
+mod parameters {
+    use iai_callgrind::binary_benchmark;
+
+    #[binary_benchmark]
+    #[bench::literals(args = (10, -1.5, true, 'c', "some"))]
+    #[bench::expression(args = (1 + 2, 0.5, !true, 'd', concat!("some", "thing")))]
+    pub fn with_parameters(
+        size: u64,
+        factor: f64,
+        mut flag: bool,
+        character: char,
+        name: &str,
+    ) -> iai_callgrind::Command {
+        flag = !flag;
+        iai_callgrind::Command::new("/just_testing")
+            .arg(format!("{size} {factor} {flag} {character} {name}"))
+            .build()
+    }
+}
+
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 
 use iai_callgrind::{
-    binary_benchmark, binary_benchmark_attribute, Bench, BenchmarkId, BinaryBenchmarkConfig,
-    __internal,
+    __internal, binary_benchmark, binary_benchmark_attribute, Bench, BenchmarkId,
+    BinaryBenchmarkConfig,
 };
 
 static CURRENT: Mutex<String> = Mutex::new(String::new());
@@ -58,6 +82,21 @@ fn with_setup_and_teardown_overwrite() -> iai_callgrind::Command {
     iai_callgrind::Command::new("/just_testing")
 }
 
+static EVALUATED: AtomicUsize = AtomicUsize::new(0);
+
+fn evaluated(value: u64) -> u64 {
+    EVALUATED.fetch_add(1, Ordering::SeqCst);
+    value
+}
+
+#[binary_benchmark]
+#[bench::side_effects(args = (evaluated(5)))]
+fn with_side_effects_in_parameters(size: u64) -> iai_callgrind::Command {
+    iai_callgrind::Command::new("/just_testing")
+        .arg(size.to_string())
+        .build()
+}
+
 #[test]
 fn test_multiple_bench_with_config() {
     let benchmark = binary_benchmark_attribute!(multiple_bench_with_config);
@@ -213,3 +252,49 @@ fn test_with_setup_and_teardown_overwrite() {
     expected.teardown = bench.teardown;
     assert_eq!(bench, &expected);
 }
+
+#[test]
+#[allow(clippy::float_cmp)]
+fn test_with_parameters() {
+    use __internal::InternalMacroParameterValue::*;
+
+    let [literals, expression] = parameters::with_parameters::__BENCHES else {
+        panic!("Expected exactly two benches");
+    };
+
+    assert!(matches!(
+        literals.parameters,
+        [
+            ("size", Int(10)),
+            ("factor", Float(factor)),
+            ("flag", Bool(true)),
+            ("character", String("c")),
+            ("name", String("some"))
+        ] if *factor == -1.5
+    ));
+    assert!(matches!(
+        expression.parameters,
+        [
+            ("size", String("1 + 2")),
+            ("factor", Float(factor)),
+            ("flag", String("! true")),
+            ("character", String("d")),
+            ("name", String("concat! (\"some\", \"thing\")"))
+        ] if *factor == 0.5
+    ));
+}
+
+#[test]
+fn test_with_side_effects_in_parameters() {
+    use __internal::InternalMacroParameterValue::*;
+
+    let [side_effects] = with_side_effects_in_parameters::__BENCHES else {
+        panic!("Expected exactly one bench");
+    };
+
+    assert!(matches!(
+        side_effects.parameters,
+        [("size", String("evaluated(5)"))]
+    ));
+    assert_eq!(EVALUATED.load(Ordering::SeqCst), 0);
+}